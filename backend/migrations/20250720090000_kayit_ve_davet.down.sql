@@ -0,0 +1,3 @@
+-- 20250720090000_kayit_ve_davet.down.sql
+
+DROP TABLE IF EXISTS davetler;
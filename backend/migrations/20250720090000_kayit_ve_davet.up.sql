@@ -0,0 +1,19 @@
+-- 20250720090000_kayit_ve_davet.up.sql
+-- Self-servis kayıt + müşteri içi davet akışı
+
+-- Davetler: portföy yöneticisinin ekip arkadaşlarına verdiği tek kullanımlık token.
+-- Token'ın kendisi DEĞİL, SHA-256 özeti saklanır.
+CREATE TABLE IF NOT EXISTS davetler (
+    id                  UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    musteri_id          UUID NOT NULL REFERENCES musteriler(id) ON DELETE CASCADE,
+    token_hash          TEXT NOT NULL UNIQUE,
+    email               TEXT NULL,              -- doluysa yalnızca bu adres kabul edebilir
+    rol                 TEXT NOT NULL DEFAULT 'user',
+    olusturan_id        UUID NULL REFERENCES kullanicilar(id) ON DELETE SET NULL,
+    son_gecerlilik      TIMESTAMPTZ NOT NULL,
+    kullanildi_tarihi   TIMESTAMPTZ NULL,
+    kullanan_id         UUID NULL REFERENCES kullanicilar(id) ON DELETE SET NULL,
+    olusturma_tarihi    TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_davetler_musteri ON davetler (musteri_id);
@@ -0,0 +1,4 @@
+-- 20250721090000_oturum_ve_refresh_token.down.sql
+
+DROP TABLE IF EXISTS refresh_tokenlar;
+DROP TABLE IF EXISTS oturumlar;
@@ -0,0 +1,26 @@
+-- 20250721090000_oturum_ve_refresh_token.up.sql
+-- Oturumlar + dönen (rotating) refresh token'lar.
+-- Access token (JWT) kısa ömürlüdür ve `sid` claim'i ile bir oturuma bağlıdır;
+-- oturum iptal edilince o oturumun tüm token'ları geçersiz olur.
+
+CREATE TABLE IF NOT EXISTS oturumlar (
+    id                  UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    kullanici_id        UUID NOT NULL REFERENCES kullanicilar(id) ON DELETE CASCADE,
+    olusturma_tarihi    TIMESTAMPTZ NOT NULL DEFAULT now(),
+    iptal_tarihi        TIMESTAMPTZ NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_oturumlar_kullanici ON oturumlar (kullanici_id);
+
+-- Her yenilemede eski token "kullanıldı" işaretlenir, yenisi eklenir.
+-- Kullanılmış bir token tekrar gelirse oturum komple iptal edilir (çalıntı token).
+CREATE TABLE IF NOT EXISTS refresh_tokenlar (
+    id                  UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    oturum_id           UUID NOT NULL REFERENCES oturumlar(id) ON DELETE CASCADE,
+    token_hash          TEXT NOT NULL UNIQUE,
+    son_gecerlilik      TIMESTAMPTZ NOT NULL,
+    kullanildi_tarihi   TIMESTAMPTZ NULL,
+    olusturma_tarihi    TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_refresh_tokenlar_oturum ON refresh_tokenlar (oturum_id);
@@ -0,0 +1,10 @@
+-- 20250722090000_roller.down.sql
+
+ALTER TABLE davetler     DROP CONSTRAINT IF EXISTS davetler_rol_check;
+ALTER TABLE kullanicilar DROP CONSTRAINT IF EXISTS kullanicilar_rol_check;
+
+ALTER TABLE davetler     ALTER COLUMN rol SET DEFAULT 'user';
+ALTER TABLE kullanicilar ALTER COLUMN rol SET DEFAULT 'user';
+
+UPDATE davetler     SET rol = 'user' WHERE rol IN ('planlamaci', 'analist', 'veri_servisi');
+UPDATE kullanicilar SET rol = 'user' WHERE rol IN ('planlamaci', 'analist', 'veri_servisi');
@@ -0,0 +1,14 @@
+-- 20250722090000_roller.up.sql
+-- İnce taneli roller: admin | portfoy_admin | planlamaci | analist | veri_servisi
+-- Eski 'user' rolü müşteri içinde plan yazabiliyordu → planlamaci'ya taşınır.
+
+UPDATE kullanicilar SET rol = 'planlamaci' WHERE rol = 'user';
+UPDATE davetler     SET rol = 'planlamaci' WHERE rol = 'user';
+
+ALTER TABLE kullanicilar ALTER COLUMN rol SET DEFAULT 'analist';
+ALTER TABLE davetler     ALTER COLUMN rol SET DEFAULT 'analist';
+
+ALTER TABLE kullanicilar ADD CONSTRAINT kullanicilar_rol_check
+    CHECK (rol IN ('admin', 'portfoy_admin', 'planlamaci', 'analist', 'veri_servisi'));
+ALTER TABLE davetler ADD CONSTRAINT davetler_rol_check
+    CHECK (rol IN ('portfoy_admin', 'planlamaci', 'analist', 'veri_servisi'));
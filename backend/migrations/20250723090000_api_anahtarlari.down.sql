@@ -0,0 +1,3 @@
+-- 20250723090000_api_anahtarlari.down.sql
+
+DROP TABLE IF EXISTS api_anahtarlari;
@@ -0,0 +1,22 @@
+-- 20250723090000_api_anahtarlari.up.sql
+-- Makineden makineye erişim için müşteri bazlı API anahtarları.
+-- Anahtarın kendisi DEĞİL, SHA-256 özeti saklanır; `onek` yalnızca tanıma amaçlıdır.
+
+CREATE TABLE IF NOT EXISTS api_anahtarlari (
+    id                  UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    musteri_id          UUID NOT NULL REFERENCES musteriler(id) ON DELETE CASCADE,
+    ad                  TEXT NOT NULL,
+    onek                TEXT NOT NULL,
+    anahtar_hash        TEXT NOT NULL UNIQUE,
+    -- 'olcum_yaz' : yalnızca ölçüm aktarımı
+    -- 'analiz_oku': salt okunur analiz (sapma, tarihsel)
+    kapsam              TEXT NOT NULL CHECK (kapsam IN ('olcum_yaz', 'analiz_oku')),
+    santral_idler       UUID[] NOT NULL CHECK (cardinality(santral_idler) > 0),
+    son_gecerlilik      TIMESTAMPTZ NULL,   -- NULL → süresiz
+    son_kullanim        TIMESTAMPTZ NULL,
+    olusturan_id        UUID NULL REFERENCES kullanicilar(id) ON DELETE SET NULL,
+    iptal_tarihi        TIMESTAMPTZ NULL,
+    olusturma_tarihi    TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_api_anahtarlari_musteri ON api_anahtarlari (musteri_id);
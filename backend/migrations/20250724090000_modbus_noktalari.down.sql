@@ -0,0 +1,3 @@
+-- 20250724090000_modbus_noktalari.down.sql
+
+DROP TABLE IF EXISTS modbus_noktalari;
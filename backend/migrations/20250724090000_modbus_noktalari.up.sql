@@ -0,0 +1,25 @@
+-- 20250724090000_modbus_noktalari.up.sql
+-- Santral başına Modbus TCP register haritası.
+-- Bir santralin anlık gücü = aktif noktalarının (ör. inverterler) toplamı.
+-- Okunan ham değer `olcek` ile çarpılarak MW'a çevrilir (kW kaynak → 0.001).
+
+CREATE TABLE IF NOT EXISTS modbus_noktalari (
+    id                  UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    santral_id          UUID NOT NULL REFERENCES santraller(id) ON DELETE CASCADE,
+    host                TEXT NOT NULL,
+    port                INTEGER NOT NULL DEFAULT 502 CHECK (port BETWEEN 1 AND 65535),
+    unit_id             SMALLINT NOT NULL DEFAULT 1 CHECK (unit_id BETWEEN 0 AND 255),
+    register_tipi       TEXT NOT NULL DEFAULT 'holding' CHECK (register_tipi IN ('holding', 'input')),
+    adres               INTEGER NOT NULL CHECK (adres BETWEEN 0 AND 65535),
+    veri_tipi           TEXT NOT NULL DEFAULT 'u16' CHECK (veri_tipi IN ('u16', 'i16', 'u32', 'i32', 'f32')),
+    -- 32 bit tiplerde hangi register önce gelir
+    kelime_sirasi       TEXT NOT NULL DEFAULT 'buyuk_once' CHECK (kelime_sirasi IN ('buyuk_once', 'kucuk_once')),
+    olcek               DOUBLE PRECISION NOT NULL DEFAULT 1.0,
+    aciklama            TEXT NULL,
+    aktif               BOOLEAN NOT NULL DEFAULT TRUE,
+    son_okuma_zamani    TIMESTAMPTZ NULL,
+    son_hata            TEXT NULL,
+    olusturma_tarihi    TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_modbus_noktalari_santral ON modbus_noktalari (santral_id);
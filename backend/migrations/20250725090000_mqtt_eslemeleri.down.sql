@@ -0,0 +1,3 @@
+-- 20250725090000_mqtt_eslemeleri.down.sql
+
+DROP TABLE IF EXISTS mqtt_eslemeleri;
@@ -0,0 +1,20 @@
+-- 20250725090000_mqtt_eslemeleri.up.sql
+-- MQTT telemetri konusu → santral eşlemesi.
+-- `deger_yolu` / `zaman_yolu` JSON Pointer'dır (ör. "/guc_kw", "/ts").
+-- `deger_yolu` NULL ise payload'ın kendisi sayı kabul edilir.
+-- Okunan değer `olcek` ile çarpılarak MW'a çevrilir (kW kaynak → 0.001).
+
+CREATE TABLE IF NOT EXISTS mqtt_eslemeleri (
+    id                  UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    santral_id          UUID NOT NULL REFERENCES santraller(id) ON DELETE CASCADE,
+    konu                TEXT NOT NULL,
+    deger_yolu          TEXT NULL,
+    zaman_yolu          TEXT NULL,
+    olcek               DOUBLE PRECISION NOT NULL DEFAULT 1.0,
+    aktif               BOOLEAN NOT NULL DEFAULT TRUE,
+    olusturma_tarihi    TIMESTAMPTZ NOT NULL DEFAULT now(),
+    -- tek broker: bir konu yalnızca bir santrale ait olabilir
+    CONSTRAINT mqtt_eslemeleri_konu_unique UNIQUE (konu)
+);
+
+CREATE INDEX IF NOT EXISTS idx_mqtt_eslemeleri_santral ON mqtt_eslemeleri (santral_id);
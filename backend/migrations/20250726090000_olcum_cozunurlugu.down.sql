@@ -0,0 +1,3 @@
+-- 20250726090000_olcum_cozunurlugu.down.sql
+
+ALTER TABLE santraller DROP COLUMN IF EXISTS olcum_cozunurluk_sn;
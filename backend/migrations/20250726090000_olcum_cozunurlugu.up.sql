@@ -0,0 +1,8 @@
+-- 20250726090000_olcum_cozunurlugu.up.sql
+-- Santral başına ölçüm örnekleme aralığı (sn).
+-- Tanımlıysa her ölçüm [t, t + aralık) ortalaması kabul edilir;
+-- NULL ise enerji zaman damgalarından trapez integrasyonuyla hesaplanır.
+
+ALTER TABLE santraller
+    ADD COLUMN IF NOT EXISTS olcum_cozunurluk_sn INTEGER NULL
+        CHECK (olcum_cozunurluk_sn BETWEEN 1 AND 3600);
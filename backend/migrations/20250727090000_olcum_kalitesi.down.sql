@@ -0,0 +1,4 @@
+-- 20250727090000_olcum_kalitesi.down.sql
+
+DROP TABLE IF EXISTS olcum_kalite_gunluk;
+ALTER TABLE uretim_olcumleri DROP COLUMN IF EXISTS kalite_bayraklari;
@@ -0,0 +1,20 @@
+-- 20250727090000_olcum_kalitesi.up.sql
+-- Ölçüm veri kalitesi.
+-- - uretim_olcumleri.kalite_bayraklari: okuma bazlı bayraklar
+--   (negatif, kapasite_ustu, donuk, sicrama). NULL → henüz değerlendirilmedi.
+-- - olcum_kalite_gunluk: santral/gün kalite skoru ve sorun listesi.
+
+ALTER TABLE uretim_olcumleri
+    ADD COLUMN IF NOT EXISTS kalite_bayraklari TEXT[] NULL;
+
+CREATE TABLE IF NOT EXISTS olcum_kalite_gunluk (
+    santral_id          UUID NOT NULL REFERENCES santraller(id) ON DELETE CASCADE,
+    gun                 DATE NOT NULL,
+    skor                DOUBLE PRECISION NOT NULL,       -- 0..100
+    kapsama_orani       DOUBLE PRECISION NOT NULL,       -- 0..1
+    n_olcum             INTEGER NOT NULL,
+    n_bayrakli          INTEGER NOT NULL,
+    sorunlar            JSONB NOT NULL DEFAULT '[]'::jsonb,
+    hesaplama_zamani    TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (santral_id, gun)
+);
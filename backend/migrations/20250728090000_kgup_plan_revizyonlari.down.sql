@@ -0,0 +1,6 @@
+-- 20250728090000_kgup_plan_revizyonlari.down.sql
+
+DROP TABLE IF EXISTS kgup_plan_revizyonlari;
+ALTER TABLE kgup_planlari
+    DROP COLUMN IF EXISTS guncelleme_tarihi,
+    DROP COLUMN IF EXISTS revizyon_no;
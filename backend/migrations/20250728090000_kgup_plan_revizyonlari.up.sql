@@ -0,0 +1,32 @@
+-- 20250728090000_kgup_plan_revizyonlari.up.sql
+-- KGÜP plan revizyon geçmişi.
+-- - Her kayıt/geri yükleme yeni bir revizyon satırı ekler; eski değerler silinmez
+-- - kgup_planlari güncel revizyonu taşır (revizyon_no, guncelleme_tarihi)
+-- - Mevcut planlar revizyon 1 olarak aktarılır (yazan bilinmiyor)
+
+ALTER TABLE kgup_planlari
+    ADD COLUMN IF NOT EXISTS revizyon_no       INTEGER NOT NULL DEFAULT 1,
+    ADD COLUMN IF NOT EXISTS guncelleme_tarihi TIMESTAMPTZ NULL;
+
+UPDATE kgup_planlari SET guncelleme_tarihi = olusturma_tarihi WHERE guncelleme_tarihi IS NULL;
+ALTER TABLE kgup_planlari
+    ALTER COLUMN guncelleme_tarihi SET NOT NULL,
+    ALTER COLUMN guncelleme_tarihi SET DEFAULT now();
+
+CREATE TABLE IF NOT EXISTS kgup_plan_revizyonlari (
+    id                  UUID PRIMARY KEY NOT NULL DEFAULT uuid_generate_v4(),
+    plan_id             UUID NOT NULL REFERENCES kgup_planlari(id) ON DELETE CASCADE,
+    revizyon_no         INTEGER NOT NULL,
+    saatlik_plan_mwh    JSONB NOT NULL,
+    yazan_kullanici_id  UUID NULL REFERENCES kullanicilar(id) ON DELETE SET NULL,
+    aciklama            TEXT NULL,
+    -- kayit | geri_yukleme | aktarim
+    kaynak              TEXT NOT NULL DEFAULT 'kayit',
+    olusturma_tarihi    TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (plan_id, revizyon_no)
+);
+
+INSERT INTO kgup_plan_revizyonlari (plan_id, revizyon_no, saatlik_plan_mwh, kaynak, olusturma_tarihi)
+SELECT id, 1, saatlik_plan_mwh, 'aktarim', olusturma_tarihi
+FROM   kgup_planlari
+ON CONFLICT DO NOTHING;
@@ -0,0 +1,3 @@
+-- 20250729090000_planli_kesintiler.down.sql
+
+DROP TABLE IF EXISTS planli_kesintiler;
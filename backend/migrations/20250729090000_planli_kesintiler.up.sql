@@ -0,0 +1,21 @@
+-- 20250729090000_planli_kesintiler.up.sql
+-- Planlı kesinti / kapasite düşümü (bakım, şebeke kısıtı vb.).
+-- - [baslangic, bitis) aralığında santralin kullanılabilir gücü
+--   `kullanilabilir_guc_mw` ile sınırlanır (0 → tam kesinti)
+-- - KGÜP plan doğrulaması saatlik üst sınırı buradan düşer
+
+CREATE TABLE IF NOT EXISTS planli_kesintiler (
+    id                      UUID PRIMARY KEY NOT NULL DEFAULT uuid_generate_v4(),
+    santral_id              UUID NOT NULL REFERENCES santraller(id) ON DELETE CASCADE,
+    baslangic               TIMESTAMPTZ NOT NULL,
+    bitis                   TIMESTAMPTZ NOT NULL,
+    kullanilabilir_guc_mw   DOUBLE PRECISION NOT NULL DEFAULT 0
+                            CHECK (kullanilabilir_guc_mw >= 0),
+    aciklama                TEXT NULL,
+    olusturan_id            UUID NULL REFERENCES kullanicilar(id) ON DELETE SET NULL,
+    olusturma_tarihi        TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CHECK (bitis > baslangic)
+);
+
+CREATE INDEX IF NOT EXISTS planli_kesintiler_santral_idx
+    ON planli_kesintiler (santral_id, baslangic);
@@ -0,0 +1,3 @@
+-- 20250730090000_kgup_kapanis.down.sql
+
+ALTER TABLE kgup_plan_revizyonlari DROP COLUMN IF EXISTS kapanis_gerekcesi;
@@ -0,0 +1,6 @@
+-- 20250730090000_kgup_kapanis.up.sql
+-- Kapı kapanışından sonra yetkili kullanıcının yaptığı düzenlemeler.
+-- kapanis_gerekcesi NULL → revizyon kapanıştan önce / kilitsiz saatlerde yazıldı.
+
+ALTER TABLE kgup_plan_revizyonlari
+    ADD COLUMN IF NOT EXISTS kapanis_gerekcesi TEXT NULL;
@@ -0,0 +1,4 @@
+-- 20250731090000_plan_cozunurlugu.down.sql
+
+ALTER TABLE kgup_plan_revizyonlari DROP COLUMN IF EXISTS cozunurluk_dk;
+ALTER TABLE kgup_planlari DROP COLUMN IF EXISTS cozunurluk_dk;
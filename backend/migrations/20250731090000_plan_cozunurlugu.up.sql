@@ -0,0 +1,12 @@
+-- 20250731090000_plan_cozunurlugu.up.sql
+-- Plan çözünürlüğü (dakika): 60 → saatlik (24 değer), 15 → çeyrek saat (96 değer).
+-- saatlik_plan_mwh dizisinin her elemanı bir periyodun enerjisidir (MWh).
+-- Mevcut planlar saatliktir.
+
+ALTER TABLE kgup_planlari
+    ADD COLUMN IF NOT EXISTS cozunurluk_dk INTEGER NOT NULL DEFAULT 60
+        CHECK (cozunurluk_dk IN (15, 60));
+
+ALTER TABLE kgup_plan_revizyonlari
+    ADD COLUMN IF NOT EXISTS cozunurluk_dk INTEGER NOT NULL DEFAULT 60
+        CHECK (cozunurluk_dk IN (15, 60));
@@ -0,0 +1,7 @@
+-- 20250801090000_santral_pys_kodlari.down.sql
+
+ALTER TABLE santraller
+    DROP CONSTRAINT IF EXISTS santraller_eic_kodu_unique,
+    DROP CONSTRAINT IF EXISTS santraller_eic_kodu_format,
+    DROP COLUMN IF EXISTS uevcb_adi,
+    DROP COLUMN IF EXISTS eic_kodu;
@@ -0,0 +1,13 @@
+-- 20250801090000_santral_pys_kodlari.up.sql
+-- PYS (Piyasa Yönetim Sistemi) yükleme dosyası için santral tanımlayıcıları.
+-- - eic_kodu : UEVÇB'nin 16 karakterlik EIC kodu (ör. 40W000000000123X)
+-- - uevcb_adi: PYS'de kayıtlı UEVÇB adı; NULL → santral adı kullanılır
+
+ALTER TABLE santraller
+    ADD COLUMN IF NOT EXISTS eic_kodu  TEXT NULL,
+    ADD COLUMN IF NOT EXISTS uevcb_adi TEXT NULL;
+
+ALTER TABLE santraller
+    ADD CONSTRAINT santraller_eic_kodu_format
+        CHECK (eic_kodu IS NULL OR eic_kodu ~ '^[0-9A-Z-]{16}$'),
+    ADD CONSTRAINT santraller_eic_kodu_unique UNIQUE (eic_kodu);
@@ -0,0 +1,7 @@
+-- 20250802090000_kgup_plan_onayi.down.sql
+
+DROP TABLE IF EXISTS kgup_plan_durum_gecisleri;
+ALTER TABLE kgup_planlari
+    DROP COLUMN IF EXISTS onayli_revizyon_no,
+    DROP COLUMN IF EXISTS durum;
+ALTER TABLE kgup_plan_revizyonlari DROP COLUMN IF EXISTS durum;
@@ -0,0 +1,33 @@
+-- 20250802090000_kgup_plan_onayi.up.sql
+-- KGÜP plan onay akışı: taslak → onaya_gonderildi → onaylandi → pys_gonderildi
+-- - Durum revizyon başınadır; sapma / PYS son onaylı revizyonu kullanır
+-- - kgup_planlari güncel revizyonun durumunu ve son onaylı revizyon numarasını taşır
+-- - Mevcut planlar onaylı sayılır (davranış değişmesin)
+-- - Her durum geçişi kgup_plan_durum_gecisleri'ne yazılır
+
+ALTER TABLE kgup_plan_revizyonlari
+    ADD COLUMN IF NOT EXISTS durum TEXT NOT NULL DEFAULT 'onaylandi'
+        CONSTRAINT kgup_plan_revizyonlari_durum_check
+        CHECK (durum IN ('taslak', 'onaya_gonderildi', 'onaylandi', 'pys_gonderildi'));
+ALTER TABLE kgup_plan_revizyonlari ALTER COLUMN durum SET DEFAULT 'taslak';
+
+ALTER TABLE kgup_planlari
+    ADD COLUMN IF NOT EXISTS durum TEXT NOT NULL DEFAULT 'onaylandi',
+    ADD COLUMN IF NOT EXISTS onayli_revizyon_no INTEGER NULL;
+UPDATE kgup_planlari SET onayli_revizyon_no = revizyon_no WHERE onayli_revizyon_no IS NULL;
+ALTER TABLE kgup_planlari ALTER COLUMN durum SET DEFAULT 'taslak';
+
+CREATE TABLE IF NOT EXISTS kgup_plan_durum_gecisleri (
+    id              UUID PRIMARY KEY NOT NULL DEFAULT uuid_generate_v4(),
+    plan_id         UUID NOT NULL REFERENCES kgup_planlari(id) ON DELETE CASCADE,
+    revizyon_no     INTEGER NOT NULL,
+    -- NULL: revizyon bu durumla oluşturuldu
+    eski_durum      TEXT NULL,
+    yeni_durum      TEXT NOT NULL,
+    kullanici_id    UUID NULL REFERENCES kullanicilar(id) ON DELETE SET NULL,
+    aciklama        TEXT NULL,
+    olusturma_tarihi TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS kgup_plan_durum_gecisleri_plan_idx
+    ON kgup_plan_durum_gecisleri (plan_id, olusturma_tarihi);
@@ -0,0 +1,3 @@
+-- 20250803090000_piyasa_fiyatlari.down.sql
+
+DROP TABLE IF EXISTS piyasa_fiyatlari;
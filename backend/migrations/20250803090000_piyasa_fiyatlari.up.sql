@@ -0,0 +1,20 @@
+-- 20250803090000_piyasa_fiyatlari.up.sql
+-- Saatlik piyasa fiyatları (tüm müşteriler için ortak).
+-- - saat: teslim saatinin başlangıcı (UTC, saat başı)
+-- - ptf_tl: Piyasa Takas Fiyatı (TL/MWh); smf_tl: Sistem Marjinal Fiyatı (TL/MWh)
+-- - sistem_yonu: enerji_acigi (YAL) | enerji_fazlasi (YAT) | dengede
+-- - SMF ve sistem yönü gün içinde sonradan kesinleşir; boş kalabilir
+-- - kaynak: manuel | epias
+
+CREATE TABLE IF NOT EXISTS piyasa_fiyatlari (
+    saat                TIMESTAMPTZ PRIMARY KEY NOT NULL
+                        CONSTRAINT piyasa_fiyatlari_saat_basi
+                        CHECK (date_trunc('hour', saat) = saat),
+    ptf_tl              NUMERIC(12, 2) NOT NULL,
+    smf_tl              NUMERIC(12, 2) NULL,
+    sistem_yonu         TEXT NULL
+                        CHECK (sistem_yonu IN ('enerji_acigi', 'enerji_fazlasi', 'dengede')),
+    kaynak              TEXT NOT NULL DEFAULT 'manuel',
+    guncelleyen_id      UUID NULL REFERENCES kullanicilar(id) ON DELETE SET NULL,
+    guncelleme_tarihi   TIMESTAMPTZ NOT NULL DEFAULT now()
+);
@@ -0,0 +1,5 @@
+-- 20250804090000_epias.down.sql
+
+DROP TABLE IF EXISTS epias_senkron;
+DROP TABLE IF EXISTS epias_uretim;
+DROP TABLE IF EXISTS epias_eslemeleri;
@@ -0,0 +1,39 @@
+-- 20250804090000_epias.up.sql
+-- EPİAŞ Şeffaflık Platformu senkronu.
+-- - epias_eslemeleri: santral → EPİAŞ kimlikleri
+--     organizasyon_id + uevcb_id : yayımlanan KGÜP (DPP) sorgusu
+--     santral_kodu               : gerçek zamanlı üretim sorgusu (powerPlantId)
+-- - epias_uretim   : yayımlanan saatlik KGÜP ve gerçek zamanlı üretim (MWh)
+-- - epias_senkron  : veri seti başına artımlı geri doldurma imleci
+--     anahtar 'ptf', 'smf', 'kgup:<santral_id>', 'gzu:<santral_id>'
+--     son_saat: bu saate kadar (hariç) veri alındı
+
+CREATE TABLE IF NOT EXISTS epias_eslemeleri (
+    santral_id          UUID PRIMARY KEY REFERENCES santraller(id) ON DELETE CASCADE,
+    organizasyon_id     BIGINT NULL,
+    uevcb_id            BIGINT NULL,
+    santral_kodu        BIGINT NULL,
+    aktif               BOOLEAN NOT NULL DEFAULT TRUE,
+    guncelleme_tarihi   TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CONSTRAINT epias_eslemeleri_dpp_cifti
+        CHECK ((organizasyon_id IS NULL) = (uevcb_id IS NULL))
+);
+
+CREATE TABLE IF NOT EXISTS epias_uretim (
+    santral_id          UUID NOT NULL REFERENCES santraller(id) ON DELETE CASCADE,
+    saat                TIMESTAMPTZ NOT NULL,
+    kgup_mwh            DOUBLE PRECISION NULL,
+    gercek_zamanli_mwh  DOUBLE PRECISION NULL,
+    guncelleme_tarihi   TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (santral_id, saat),
+    CONSTRAINT epias_uretim_saat_basi CHECK (date_trunc('hour', saat) = saat)
+);
+
+CREATE TABLE IF NOT EXISTS epias_senkron (
+    anahtar             TEXT PRIMARY KEY,
+    son_saat            TIMESTAMPTZ NULL,
+    son_deneme          TIMESTAMPTZ NOT NULL DEFAULT now(),
+    son_basari          TIMESTAMPTZ NULL,
+    son_hata            TEXT NULL,
+    ardisik_hata        INTEGER NOT NULL DEFAULT 0
+);
@@ -0,0 +1,4 @@
+-- 20250805090000_dengesizlik_kurallari.down.sql
+
+DROP TABLE IF EXISTS santral_dengesizlik_kurallari;
+DROP TABLE IF EXISTS dengesizlik_kurallari;
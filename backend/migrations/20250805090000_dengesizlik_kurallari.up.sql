@@ -0,0 +1,55 @@
+-- 20250805090000_dengesizlik_kurallari.up.sql
+-- Dengesizlik fiyatlama kural setleri (tüm müşteriler için ortak) ve santral atamaları.
+-- - kod: kural ailesi (ör. standart, epdk_2024); surum: ailede 1'den artan sürüm
+-- - gecerlilik_baslangic: sürümün geçerli olduğu ilk teslim günü (Europe/Istanbul);
+--   bir sürüm, aynı koddaki bir sonraki sürümün başlangıcına kadar geçerlidir
+-- - tolerans_orani: |sapma| ≤ oran × plan kısmı PTF ile fiyatlanır (0..1)
+-- - pozitif_k / negatif_k: pozitif birim fiyat min(PTF, SMF) × (1 − k),
+--   negatif birim fiyat max(PTF, SMF) × (1 + k)
+-- - yekdem_*: YEKDEM kapsamındaki santraller için; NULL → genel değer
+-- - Sürümler değiştirilmez; kural değişikliği yeni sürümle yapılır
+
+CREATE TABLE IF NOT EXISTS dengesizlik_kurallari (
+    id                      UUID PRIMARY KEY NOT NULL,
+    kod                     TEXT NOT NULL
+                            CONSTRAINT dengesizlik_kurallari_kod_format
+                            CHECK (kod ~ '^[a-z0-9_]{1,40}$'),
+    surum                   INTEGER NOT NULL CHECK (surum > 0),
+    ad                      TEXT NOT NULL,
+    gecerlilik_baslangic    DATE NOT NULL,
+    tolerans_orani          DOUBLE PRECISION NOT NULL DEFAULT 0
+                            CHECK (tolerans_orani BETWEEN 0 AND 1),
+    pozitif_k               DOUBLE PRECISION NOT NULL DEFAULT 0
+                            CHECK (pozitif_k BETWEEN 0 AND 1),
+    negatif_k               DOUBLE PRECISION NOT NULL DEFAULT 0
+                            CHECK (negatif_k BETWEEN 0 AND 1),
+    yekdem_tolerans_orani   DOUBLE PRECISION NULL
+                            CHECK (yekdem_tolerans_orani BETWEEN 0 AND 1),
+    yekdem_pozitif_k        DOUBLE PRECISION NULL
+                            CHECK (yekdem_pozitif_k BETWEEN 0 AND 1),
+    yekdem_negatif_k        DOUBLE PRECISION NULL
+                            CHECK (yekdem_negatif_k BETWEEN 0 AND 1),
+    aciklama                TEXT NULL,
+    olusturan_id            UUID NULL REFERENCES kullanicilar(id) ON DELETE SET NULL,
+    olusturma_tarihi        TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CONSTRAINT dengesizlik_kurallari_surum_unique UNIQUE (kod, surum),
+    CONSTRAINT dengesizlik_kurallari_baslangic_unique UNIQUE (kod, gecerlilik_baslangic)
+);
+
+-- Atama yapılmamış santraller ve önceki dönemler bu kodla uzlaştırılır
+-- (min / max kuralı; tolerans ve katsayı yok).
+INSERT INTO dengesizlik_kurallari (id, kod, surum, ad, gecerlilik_baslangic)
+VALUES ('00000000-0000-0000-0000-000000000001', 'standart', 1,
+        'Standart (min / max PTF-SMF)', DATE '2000-01-01')
+ON CONFLICT DO NOTHING;
+
+-- Santral → kural kodu; dönemsel (bir atama bir sonrakinin başlangıcına kadar geçerli).
+CREATE TABLE IF NOT EXISTS santral_dengesizlik_kurallari (
+    santral_id              UUID NOT NULL REFERENCES santraller(id) ON DELETE CASCADE,
+    gecerlilik_baslangic    DATE NOT NULL,
+    kural_kodu              TEXT NOT NULL,
+    yekdem                  BOOLEAN NOT NULL DEFAULT false,
+    guncelleyen_id          UUID NULL REFERENCES kullanicilar(id) ON DELETE SET NULL,
+    guncelleme_tarihi       TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (santral_id, gecerlilik_baslangic)
+);
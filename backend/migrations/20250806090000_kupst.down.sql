@@ -0,0 +1,7 @@
+-- 20250806090000_kupst.down.sql
+
+ALTER TABLE dengesizlik_kurallari
+    DROP COLUMN IF EXISTS yekdem_kupst_tolerans_orani,
+    DROP COLUMN IF EXISTS kupst_fiyat_bazi,
+    DROP COLUMN IF EXISTS kupst_katsayi,
+    DROP COLUMN IF EXISTS kupst_tolerans_orani;
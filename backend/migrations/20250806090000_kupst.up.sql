@@ -0,0 +1,17 @@
+-- 20250806090000_kupst.up.sql
+-- KÜPST (Kesinleşmiş Üretim Planından Sapma Tutarı) parametreleri; dengesizlik kural
+-- setleriyle aynı sürüm / başlangıç günü / santral ataması üzerinden seçilir.
+-- - kupst_tolerans_orani: |gerçekleşen − KGÜP| ≤ oran × KGÜP kısmı cezasız (0..1)
+-- - kupst_katsayi / kupst_fiyat_bazi: birim fiyat = katsayı × baz fiyat
+--   (max_ptf_smf | ptf | smf)
+-- - yekdem_kupst_tolerans_orani: YEKDEM kapsamındaki santraller için; NULL → genel değer
+
+ALTER TABLE dengesizlik_kurallari
+    ADD COLUMN IF NOT EXISTS kupst_tolerans_orani        DOUBLE PRECISION NOT NULL DEFAULT 0.05
+                             CHECK (kupst_tolerans_orani BETWEEN 0 AND 1),
+    ADD COLUMN IF NOT EXISTS kupst_katsayi               DOUBLE PRECISION NOT NULL DEFAULT 0.03
+                             CHECK (kupst_katsayi BETWEEN 0 AND 1),
+    ADD COLUMN IF NOT EXISTS kupst_fiyat_bazi            TEXT NOT NULL DEFAULT 'max_ptf_smf'
+                             CHECK (kupst_fiyat_bazi IN ('max_ptf_smf', 'ptf', 'smf')),
+    ADD COLUMN IF NOT EXISTS yekdem_kupst_tolerans_orani DOUBLE PRECISION NULL
+                             CHECK (yekdem_kupst_tolerans_orani BETWEEN 0 AND 1);
@@ -0,0 +1,4 @@
+-- 20250807090000_durum_gecisi_kapanis_gerekcesi.down.sql
+
+ALTER TABLE kgup_plan_durum_gecisleri
+    DROP COLUMN IF EXISTS kapanis_gerekcesi;
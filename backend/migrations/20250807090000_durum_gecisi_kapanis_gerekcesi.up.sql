@@ -0,0 +1,7 @@
+-- 20250807090000_durum_gecisi_kapanis_gerekcesi.up.sql
+-- Onaylama da kapı kapanışına tabidir: onaylanan revizyon, son onaylı revizyona göre
+-- kilitli periyotları değiştiriyorsa `plan_kilit_as` yetkisi ve gerekçe ister.
+-- Gerekçe geçiş kaydında tutulur; kilit aşılmadıysa NULL.
+
+ALTER TABLE kgup_plan_durum_gecisleri
+    ADD COLUMN IF NOT EXISTS kapanis_gerekcesi TEXT NULL;
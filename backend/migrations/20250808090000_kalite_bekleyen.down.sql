@@ -0,0 +1,3 @@
+-- 20250808090000_kalite_bekleyen.down.sql
+
+DROP INDEX IF EXISTS uretim_olcumleri_kalite_bekleyen_idx;
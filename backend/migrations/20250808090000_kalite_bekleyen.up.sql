@@ -0,0 +1,7 @@
+-- 20250808090000_kalite_bekleyen.up.sql
+-- Kalite değerlendirmesi okuma uçlarından arka plan işine taşındı; iş her turda
+-- henüz bayraklanmamış okumaların günlerini arar.
+
+CREATE INDEX IF NOT EXISTS uretim_olcumleri_kalite_bekleyen_idx
+    ON uretim_olcumleri (santral_id, zaman_utc)
+    WHERE kalite_bayraklari IS NULL;
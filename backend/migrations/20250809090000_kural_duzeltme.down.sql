@@ -0,0 +1,7 @@
+-- 20250809090000_kural_duzeltme.down.sql
+
+ALTER TABLE santral_dengesizlik_kurallari
+    DROP COLUMN IF EXISTS duzeltme_gerekcesi;
+
+ALTER TABLE dengesizlik_kurallari
+    DROP COLUMN IF EXISTS duzeltme_gerekcesi;
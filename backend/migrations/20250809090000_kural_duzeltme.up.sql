@@ -0,0 +1,10 @@
+-- 20250809090000_kural_duzeltme.up.sql
+-- Bugünden önce başlayan kural sürümü / santral ataması uzlaşmış dönemleri yeniden
+-- fiyatlar; yalnızca düzeltme gerekçesiyle kabul edilir ve gerekçe kayıtta tutulur.
+-- İleri tarihli kayıtlarda NULL.
+
+ALTER TABLE dengesizlik_kurallari
+    ADD COLUMN IF NOT EXISTS duzeltme_gerekcesi TEXT NULL;
+
+ALTER TABLE santral_dengesizlik_kurallari
+    ADD COLUMN IF NOT EXISTS duzeltme_gerekcesi TEXT NULL;
@@ -8,24 +8,32 @@ use uuid::Uuid;
 // Argon2/password-hash
 use argon2::{Argon2, PasswordVerifier};
 use password_hash::{PasswordHash, PasswordHasher, SaltString};
-use password_hash::rand_core::OsRng;
+use password_hash::rand_core::{OsRng, RngCore};
+use sha2::{Digest, Sha256};
 
 /// Uygulama Auth yapılandırması.
 #[derive(Debug, Clone)]
 pub struct AuthConfig {
     pub jwt_secret: String,
-    pub jwt_exp_hours: i64,
+    /// Access token (JWT) ömrü — kısa tutulur, yenileme refresh token ile.
+    pub access_exp_minutes: i64,
+    /// Refresh token ömrü.
+    pub refresh_exp_days: i64,
 }
 
 impl AuthConfig {
     pub fn from_env() -> Result<Self> {
         let jwt_secret = env::var("JWT_SECRET")
             .map_err(|_| anyhow!("JWT_SECRET env yok"))?;
-        let jwt_exp_hours: i64 = env::var("JWT_EXP_HOURS")
-            .unwrap_or_else(|_| "24".to_string())
+        let access_exp_minutes: i64 = env::var("JWT_ACCESS_EXP_MINUTES")
+            .unwrap_or_else(|_| "15".to_string())
             .parse()
-            .map_err(|_| anyhow!("JWT_EXP_HOURS sayı değil"))?;
-        Ok(Self { jwt_secret, jwt_exp_hours })
+            .map_err(|_| anyhow!("JWT_ACCESS_EXP_MINUTES sayı değil"))?;
+        let refresh_exp_days: i64 = env::var("JWT_REFRESH_EXP_DAYS")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse()
+            .map_err(|_| anyhow!("JWT_REFRESH_EXP_DAYS sayı değil"))?;
+        Ok(Self { jwt_secret, access_exp_minutes, refresh_exp_days })
     }
 }
 
@@ -34,7 +42,8 @@ impl AuthConfig {
 pub struct Claims {
     pub sub: Uuid,   // user_id
     pub mus: Uuid,   // musteri_id
-    pub rol: String, // "admin" | "user"
+    pub rol: String, // bkz. yetki::Rol
+    pub sid: Uuid,   // oturum_id (iptal kontrolü)
     pub exp: usize,  // unix ts
 }
 
@@ -58,13 +67,32 @@ pub fn hash_password(plain: &str) -> Result<String> {
         .to_string();
     Ok(hashed)
 }
-/// JWT üret.
-pub fn create_jwt(cfg: &AuthConfig, user_id: Uuid, musteri_id: Uuid, rol: &str) -> Result<String> {
-    let exp = (Utc::now() + Duration::hours(cfg.jwt_exp_hours)).timestamp() as usize;
+/// Tek kullanımlık rastgele token (64 hex karakter).
+pub fn generate_token() -> String {
+    let mut buf = [0u8; 32];
+    OsRng.fill_bytes(&mut buf);
+    hex::encode(buf)
+}
+
+/// Token'ın DB'de saklanacak SHA-256 özeti.
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// JWT (access token) üret.
+pub fn create_jwt(
+    cfg: &AuthConfig,
+    user_id: Uuid,
+    musteri_id: Uuid,
+    rol: &str,
+    oturum_id: Uuid,
+) -> Result<String> {
+    let exp = (Utc::now() + Duration::minutes(cfg.access_exp_minutes)).timestamp() as usize;
     let claims = Claims {
         sub: user_id,
         mus: musteri_id,
         rol: rol.to_string(),
+        sid: oturum_id,
         exp,
     };
     let token = encode(
@@ -1,59 +1,198 @@
-use actix_web::{dev::Payload, http::header, Error, FromRequest, HttpRequest};
-use futures::future::{ready, Ready};
+use actix_web::{dev::Payload, http::header, web, Error, FromRequest, HttpRequest};
+use futures::future::LocalBoxFuture;
+use sqlx::PgPool;
 use uuid::Uuid;
 
-use crate::auth::{AuthConfig, decode_jwt, Claims};
+use crate::auth::{AuthConfig, decode_jwt, hash_token, Claims};
+use crate::db;
+use crate::models::DogrulananApiAnahtari;
+use crate::yetki::{ApiKapsam, Rol, Yetki};
 
 #[derive(Debug, Clone)]
 pub struct AuthenticatedUser {
     pub user_id: Uuid,
     pub musteri_id: Uuid,
-    pub rol: String,
+    pub rol: Rol,
+    pub oturum_id: Uuid,
+}
+
+impl AuthenticatedUser {
+    pub fn yetkili(&self, yetki: Yetki) -> bool {
+        self.rol.izinli(yetki)
+    }
 }
 
 impl FromRequest for AuthenticatedUser {
     type Error = Error;
-    type Future = Ready<Result<Self, Error>>;
+    type Future = LocalBoxFuture<'static, Result<Self, Error>>;
 
     fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
-        // Config
-        let auth_cfg = match req.app_data::<actix_web::web::Data<AuthConfig>>() {
-            Some(c) => c.clone(),
-            None => return ready(Err(actix_web::error::ErrorInternalServerError("auth cfg yok"))),
-        };
+        let req = req.clone();
+        Box::pin(async move {
+            // Config + DB
+            let auth_cfg = req
+                .app_data::<web::Data<AuthConfig>>()
+                .ok_or_else(|| actix_web::error::ErrorInternalServerError("auth cfg yok"))?;
+            let pool = req
+                .app_data::<web::Data<PgPool>>()
+                .ok_or_else(|| actix_web::error::ErrorInternalServerError("db pool yok"))?;
 
-        // Authorization header
-        let header_val = match req.headers().get(header::AUTHORIZATION) {
-            Some(v) => v,
-            None => return ready(Err(actix_web::error::ErrorUnauthorized("Bearer yok"))),
-        };
+            // Authorization header
+            let header_val = req
+                .headers()
+                .get(header::AUTHORIZATION)
+                .ok_or_else(|| actix_web::error::ErrorUnauthorized("Bearer yok"))?;
 
-        let header_str = match header_val.to_str() {
-            Ok(s) => s,
-            Err(_) => return ready(Err(actix_web::error::ErrorUnauthorized("geçersiz header"))),
-        };
+            let header_str = header_val
+                .to_str()
+                .map_err(|_| actix_web::error::ErrorUnauthorized("geçersiz header"))?;
 
-        // "Bearer token"
-        let parts: Vec<&str> = header_str.split_whitespace().collect();
-        if parts.len() != 2 || parts[0] != "Bearer" {
-            return ready(Err(actix_web::error::ErrorUnauthorized("format yanlış")));
-        }
-        let token = parts[1];
-
-        // Decode
-        match decode_jwt(&auth_cfg, token) {
-            Ok(data) => {
-                let Claims { sub, mus, rol, .. } = data.claims;
-                ready(Ok(AuthenticatedUser {
-                    user_id: sub,
-                    musteri_id: mus,
-                    rol,
-                }))
+            // "Bearer token"
+            let parts: Vec<&str> = header_str.split_whitespace().collect();
+            if parts.len() != 2 || parts[0] != "Bearer" {
+                return Err(actix_web::error::ErrorUnauthorized("format yanlış"));
             }
-            Err(e) => {
-                log::warn!("JWT decode hata: {e}");
-                ready(Err(actix_web::error::ErrorUnauthorized("token geçersiz")))
+            let token = parts[1];
+
+            // Decode
+            let Claims { sub, mus, rol, sid, .. } = match decode_jwt(auth_cfg, token) {
+                Ok(data) => data.claims,
+                Err(e) => {
+                    log::warn!("JWT decode hata: {e}");
+                    return Err(actix_web::error::ErrorUnauthorized("token geçersiz"));
+                }
+            };
+
+            // Eski / tanınmayan rol → yeniden giriş gerekir
+            let rol = Rol::parse(&rol)
+                .ok_or_else(|| actix_web::error::ErrorUnauthorized("rol tanınmıyor"))?;
+
+            // İptal kontrolü (logout / pasif kullanıcı)
+            match db::oturum_aktif(pool.get_ref(), sid).await {
+                Ok(true) => {}
+                Ok(false) => return Err(actix_web::error::ErrorUnauthorized("oturum sonlandırılmış")),
+                Err(e) => {
+                    log::error!("oturum kontrol hata: {e}");
+                    return Err(actix_web::error::ErrorInternalServerError("db hata"));
+                }
             }
+
+            Ok(AuthenticatedUser {
+                user_id: sub,
+                musteri_id: mus,
+                rol,
+                oturum_id: sid,
+            })
+        })
+    }
+}
+
+/// API anahtarıyla doğrulanmış makine istemcisi.
+#[derive(Debug, Clone)]
+pub struct ApiAnahtariKimligi {
+    pub anahtar_id: Uuid,
+    pub musteri_id: Uuid,
+    pub kapsam: ApiKapsam,
+    pub santral_idler: Vec<Uuid>,
+}
+
+impl ApiAnahtariKimligi {
+    /// Tanınmayan kapsam → `None` (anahtar geçersiz sayılır).
+    fn satirdan(r: DogrulananApiAnahtari) -> Option<Self> {
+        Some(ApiAnahtariKimligi {
+            anahtar_id: r.id,
+            musteri_id: r.musteri_id,
+            kapsam: ApiKapsam::parse(&r.kapsam)?,
+            santral_idler: r.santral_idler,
+        })
+    }
+}
+
+/// İnsan kullanıcı (JWT) ya da makine (API anahtarı).
+///
+/// `X-API-Key` başlığı varsa anahtar yolu, yoksa Bearer JWT denenir.
+#[derive(Debug, Clone)]
+pub enum Istemci {
+    Kullanici(AuthenticatedUser),
+    Anahtar(ApiAnahtariKimligi),
+}
+
+pub const API_KEY_HEADER: &str = "X-API-Key";
+
+impl Istemci {
+    pub fn musteri_id(&self) -> Uuid {
+        match self {
+            Istemci::Kullanici(u) => u.musteri_id,
+            Istemci::Anahtar(k) => k.musteri_id,
+        }
+    }
+
+    pub fn yetkili(&self, yetki: Yetki) -> bool {
+        match self {
+            Istemci::Kullanici(u) => u.yetkili(yetki),
+            Istemci::Anahtar(k) => k.kapsam.yetkiler().contains(&yetki),
+        }
+    }
+
+    /// İşlemi yapan kullanıcı (anahtarda yok).
+    pub fn user_id(&self) -> Option<Uuid> {
+        match self {
+            Istemci::Kullanici(u) => Some(u.user_id),
+            Istemci::Anahtar(_) => None,
+        }
+    }
+
+    /// Santrale erişim: kullanıcıda müşteri sahipliği, anahtarda ayrıca kapsam listesi.
+    pub async fn santral_erisimi(
+        &self,
+        pool: &PgPool,
+        santral_id: Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        match self {
+            Istemci::Kullanici(u) if u.yetkili(Yetki::TumMusteriler) => Ok(true),
+            Istemci::Anahtar(k) if !k.santral_idler.contains(&santral_id) => Ok(false),
+            _ => db::santral_belongs_to_musteri(pool, santral_id, self.musteri_id()).await,
         }
     }
 }
+
+impl FromRequest for Istemci {
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let anahtar = req
+            .headers()
+            .get(API_KEY_HEADER)
+            .map(|v| v.to_str().map(|s| s.trim().to_string()));
+
+        let anahtar = match anahtar {
+            None => {
+                let fut = AuthenticatedUser::from_request(req, payload);
+                return Box::pin(async move { fut.await.map(Istemci::Kullanici) });
+            }
+            Some(Err(_)) => {
+                return Box::pin(async { Err(actix_web::error::ErrorUnauthorized("geçersiz header")) });
+            }
+            Some(Ok(a)) => a,
+        };
+
+        let req = req.clone();
+        Box::pin(async move {
+            let pool = req
+                .app_data::<web::Data<PgPool>>()
+                .ok_or_else(|| actix_web::error::ErrorInternalServerError("db pool yok"))?;
+
+            match db::api_anahtari_dogrula(pool.get_ref(), &hash_token(&anahtar)).await {
+                Ok(Some(satir)) => ApiAnahtariKimligi::satirdan(satir)
+                    .map(Istemci::Anahtar)
+                    .ok_or_else(|| actix_web::error::ErrorUnauthorized("api anahtarı geçersiz")),
+                Ok(None) => Err(actix_web::error::ErrorUnauthorized("api anahtarı geçersiz")),
+                Err(e) => {
+                    log::error!("api anahtarı kontrol hata: {e}");
+                    Err(actix_web::error::ErrorInternalServerError("db hata"))
+                }
+            }
+        })
+    }
+}
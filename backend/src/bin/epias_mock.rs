@@ -0,0 +1,31 @@
+// backend/src/bin/epias_mock.rs
+//
+// EPİAŞ Şeffaflık Platformu taklit sunucusu (bkz. src/epias_mock.rs).
+//   cargo run --bin epias_mock
+//   EPIAS_MOCK_PORT=8090  EPIAS_MOCK_HATA_HER=0
+
+use actix_web::{middleware::Logger, web, App, HttpServer};
+use std::env;
+
+#[path = "../epias_mock.rs"]
+mod epias_mock;
+
+#[actix_web::main]
+async fn main() -> std::io::Result<()> {
+    let _ = env_logger::Builder::from_env(env_logger::Env::new().default_filter_or("info")).try_init();
+
+    let port: u16 = env::var("EPIAS_MOCK_PORT").ok().and_then(|v| v.parse().ok()).unwrap_or(8090);
+    let hata_her: u64 = env::var("EPIAS_MOCK_HATA_HER").ok().and_then(|v| v.parse().ok()).unwrap_or(0);
+    let durum = web::Data::new(epias_mock::MockDurum::new(hata_her));
+
+    println!("EPİAŞ mock: http://127.0.0.1:{port}/electricity-service");
+    HttpServer::new(move || {
+        App::new()
+            .app_data(durum.clone())
+            .wrap(Logger::new("\"%r\" %s %T s"))
+            .configure(epias_mock::yapilandir)
+    })
+    .bind(("127.0.0.1", port))?
+    .run()
+    .await
+}
@@ -1,9 +1,18 @@
 // src/db.rs — derlenebilir, sqlx-query kontrollü sürüm
 // -----------------------------------------------
+use crate::enerji::{self, SaatEnerji, Yontem};
+use crate::kalite::{Degerlendirme, KaliteModu};
+use crate::kgup::{self, PlanDurumu};
+use crate::piyasa_gunu;
 use crate::models::{
-    InputSantral, KgupPlan, KgupPlanInput, Santral, SapmaSaat,
+    ApiAnahtari, Davet, DogrulananApiAnahtari, EpiasEslemesi, EpiasEslemesiInput, EpiasSenkron, EpiasUretim, InputSantral, KaliteGun, KgupPlan, KgupPlanDurumSatiri, KgupPlanInput,
+    KgupDurumGecisi, KgupPlanRevizyonu, OnayliKgupPlan, PlanliKesinti, PlanliKesintiInput,
+    PiyasaFiyati, PysPlanSatiri, DengesizlikKurali, DengesizlikKuraliInput, SantralDengesizlikKurali,
+    SantralDengesizlikKuraliInput,
+    Kullanici, ModbusNoktasi, ModbusNoktasiInput, MqttEslemesi, MqttEslemesiInput, Musteri,
+    Santral, SapmaSaat, saatlik_degerler,
 };
-use bigdecimal::{BigDecimal, ToPrimitive};
+use bigdecimal::BigDecimal;
 use chrono::{DateTime, NaiveDate, Utc};
 use serde_json::json;
 use sqlx::PgPool;
@@ -26,13 +35,14 @@ pub async fn create_santral(
         r#"
         INSERT INTO santraller (
             id, ad, tip, kurulu_guc_mw,
-            koordinat_enlem, koordinat_boylam
+            koordinat_enlem, koordinat_boylam, olcum_cozunurluk_sn,
+            eic_kodu, uevcb_adi
         )
-        VALUES ($1, $2, $3, $4, $5, $6)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
         RETURNING
             id, ad, tip, kurulu_guc_mw,
             koordinat_enlem, koordinat_boylam,
-            musteri_id, olusturma_tarihi
+            musteri_id, olcum_cozunurluk_sn, eic_kodu, uevcb_adi, olusturma_tarihi
         "#,
         new_id,
         data.ad,
@@ -40,6 +50,9 @@ pub async fn create_santral(
         data.kurulu_guc_mw,
         data.koordinat_enlem,
         data.koordinat_boylam,
+        data.olcum_cozunurluk_sn,
+        data.eic_kodu,
+        data.uevcb_adi,
     )
     .fetch_one(pool)
     .await
@@ -52,7 +65,7 @@ pub async fn get_all_santraller(pool: &PgPool) -> Result<Vec<Santral>, sqlx::Err
         r#"
         SELECT id, ad, tip, kurulu_guc_mw,
                koordinat_enlem, koordinat_boylam,
-               musteri_id, olusturma_tarihi
+               musteri_id, olcum_cozunurluk_sn, eic_kodu, uevcb_adi, olusturma_tarihi
         FROM   santraller
         ORDER  BY olusturma_tarihi DESC
         "#
@@ -61,7 +74,8 @@ pub async fn get_all_santraller(pool: &PgPool) -> Result<Vec<Santral>, sqlx::Err
     .await
 }
 
-/// Santrali günceller.
+/// Santrali günceller. Gönderilmeyen `olcum_cozunurluk_sn`, `eic_kodu` ve `uevcb_adi`
+/// kayıtlı değeri korur (frontend formu bu alanları taşımaz); kod ve ad boş metinle silinir.
 pub async fn update_santral_by_id(
     pool: &PgPool,
     santral_id: Uuid,
@@ -75,17 +89,23 @@ pub async fn update_santral_by_id(
             tip             = $2,
             kurulu_guc_mw   = $3,
             koordinat_enlem = $4,
-            koordinat_boylam= $5
-        WHERE id = $6
+            koordinat_boylam= $5,
+            olcum_cozunurluk_sn = COALESCE($6, olcum_cozunurluk_sn),
+            eic_kodu        = NULLIF(COALESCE($7, eic_kodu), ''),
+            uevcb_adi       = NULLIF(COALESCE($8, uevcb_adi), '')
+        WHERE id = $9
         RETURNING id, ad, tip, kurulu_guc_mw,
                   koordinat_enlem, koordinat_boylam,
-                  musteri_id, olusturma_tarihi
+                  musteri_id, olcum_cozunurluk_sn, eic_kodu, uevcb_adi, olusturma_tarihi
         "#,
         data.ad,
         data.tip,
         data.kurulu_guc_mw,
         data.koordinat_enlem,
         data.koordinat_boylam,
+        data.olcum_cozunurluk_sn,
+        data.eic_kodu,
+        data.uevcb_adi,
         santral_id,
     )
     .fetch_one(pool)
@@ -116,7 +136,7 @@ pub async fn get_santral_by_id(
         r#"
         SELECT id, ad, tip, kurulu_guc_mw,
                koordinat_enlem, koordinat_boylam,
-               musteri_id, olusturma_tarihi
+               musteri_id, olcum_cozunurluk_sn, eic_kodu, uevcb_adi, olusturma_tarihi
         FROM   santraller
         WHERE  id = $1
         "#,
@@ -130,34 +150,521 @@ pub async fn get_santral_by_id(
 // KGÜP PLAN
 //-----------------------------------------------------------
 
-/// KGÜP planı ekler veya günceller.
-pub async fn create_or_update_kgup_plan(
+/// Kapanış kilidi ile plan yazımı hatası; her iki durumda da transaction geri alınır.
+#[derive(Debug)]
+pub enum PlanYazHatasi<E> {
+    Db(sqlx::Error),
+    /// Kilit kontrolü yazımı reddetti.
+    Kilit(E),
+}
+
+impl<E> From<sqlx::Error> for PlanYazHatasi<E> {
+    fn from(e: sqlx::Error) -> Self {
+        PlanYazHatasi::Db(e)
+    }
+}
+
+/// KGÜP planı ekler veya günceller; her yazım yeni bir revizyon satırı bırakır.
+/// `kaynak`: kayit | geri_yukleme | aktarim (toplu dosya aktarımı)
+/// `durum`: yeni revizyonun onay durumu (onaylıysa planın onaylı revizyonu olur).
+/// `kilit`: bkz. `kgup_plan_yaz`.
+pub async fn create_or_update_kgup_plan<E>(
     pool: &PgPool,
     santral_id: Uuid,
     plan: KgupPlanInput,
-) -> Result<KgupPlan, sqlx::Error> {
+    yazan_kullanici_id: Uuid,
+    kaynak: &str,
+    durum: PlanDurumu,
+    mut kilit: impl FnMut(Uuid, &KgupPlanInput, Option<OnayliKgupPlan>) -> Result<Option<String>, E>,
+) -> Result<KgupPlan, PlanYazHatasi<E>> {
+    let mut tx = pool.begin().await?;
+    let kayit = kgup_plan_yaz(&mut tx, santral_id, &plan, yazan_kullanici_id, kaynak, durum, &mut kilit).await?;
+    tx.commit().await?;
+    Ok(kayit)
+}
+
+/// Birden çok santral/gün planını tek transaction'da yazar; biri hata verirse hiçbiri kalmaz.
+pub async fn kgup_planlarini_toplu_yaz<E>(
+    pool: &PgPool,
+    planlar: &[(Uuid, KgupPlanInput)],
+    yazan_kullanici_id: Uuid,
+    kaynak: &str,
+    durum: PlanDurumu,
+    mut kilit: impl FnMut(Uuid, &KgupPlanInput, Option<OnayliKgupPlan>) -> Result<Option<String>, E>,
+) -> Result<Vec<KgupPlan>, PlanYazHatasi<E>> {
+    let mut tx = pool.begin().await?;
+    let mut kayitlar = Vec::with_capacity(planlar.len());
+    for (santral_id, plan) in planlar {
+        kayitlar.push(
+            kgup_plan_yaz(&mut tx, *santral_id, plan, yazan_kullanici_id, kaynak, durum, &mut kilit).await?,
+        );
+    }
+    tx.commit().await?;
+    Ok(kayitlar)
+}
+
+/// Plan upsert + revizyon satırı (çağıranın transaction'ı içinde).
+/// `kilit` plan satırı kilitlendikten sonra günün son onaylı revizyonuyla çağrılır; böylece
+/// araya giren onay ya da yazım kapanış karşılaştırmasını bayatlatamaz. Dönen değer
+/// revizyona yazılacak kapanış gerekçesidir; Err yazımı iptal eder.
+async fn kgup_plan_yaz<E>(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    santral_id: Uuid,
+    plan: &KgupPlanInput,
+    yazan_kullanici_id: Uuid,
+    kaynak: &str,
+    durum: PlanDurumu,
+    kilit: &mut impl FnMut(Uuid, &KgupPlanInput, Option<OnayliKgupPlan>) -> Result<Option<String>, E>,
+) -> Result<KgupPlan, PlanYazHatasi<E>> {
+    // Satır kilidi aynı planın eşzamanlı yazımlarını ve onaylarını sıralar.
+    // Plan yoksa kilitlenecek satır da yoktur; karşılaştırma tabanı None en katı durumdur.
+    let onayli = sqlx::query!(
+        r#"
+        SELECT r.revizyon_no      AS "revizyon_no?",
+               r.saatlik_plan_mwh AS "saatlik_plan_mwh?",
+               r.cozunurluk_dk    AS "cozunurluk_dk?"
+        FROM   kgup_planlari p
+        LEFT   JOIN kgup_plan_revizyonlari r
+               ON r.plan_id = p.id AND r.revizyon_no = p.onayli_revizyon_no
+        WHERE  p.santral_id = $1 AND p.plan_tarihi = $2
+        FOR    UPDATE OF p
+        "#,
+        santral_id,
+        plan.plan_tarihi,
+    )
+    .fetch_optional(&mut **tx)
+    .await?
+    .and_then(|o| {
+        Some(OnayliKgupPlan {
+            plan_tarihi: plan.plan_tarihi,
+            revizyon_no: o.revizyon_no?,
+            saatlik_plan_mwh: o.saatlik_plan_mwh?,
+            cozunurluk_dk: o.cozunurluk_dk?,
+        })
+    });
+    let kapanis_gerekcesi = kilit(santral_id, plan, onayli).map_err(PlanYazHatasi::Kilit)?;
+
     let saatlik_plan_json = json!(plan.saatlik_plan_mwh);
 
-    sqlx::query_as!(
+    let kayit = sqlx::query_as!(
         KgupPlan,
         r#"
         INSERT INTO kgup_planlari (
-            santral_id, plan_tarihi, saatlik_plan_mwh
+            santral_id, plan_tarihi, saatlik_plan_mwh, cozunurluk_dk,
+            durum, onayli_revizyon_no
         )
-        VALUES ($1, $2, $3)
+        VALUES ($1, $2, $3, $4, $5, CASE WHEN $6 THEN 1 END)
         ON CONFLICT (santral_id, plan_tarihi)
-        DO UPDATE SET saatlik_plan_mwh = EXCLUDED.saatlik_plan_mwh
+        DO UPDATE SET saatlik_plan_mwh   = EXCLUDED.saatlik_plan_mwh,
+                      cozunurluk_dk      = EXCLUDED.cozunurluk_dk,
+                      revizyon_no        = kgup_planlari.revizyon_no + 1,
+                      guncelleme_tarihi  = now(),
+                      durum              = EXCLUDED.durum,
+                      onayli_revizyon_no = CASE WHEN $6 THEN kgup_planlari.revizyon_no + 1
+                                                ELSE kgup_planlari.onayli_revizyon_no END
         RETURNING id, santral_id, plan_tarihi,
-                  saatlik_plan_mwh, olusturma_tarihi
+                  saatlik_plan_mwh, olusturma_tarihi,
+                  revizyon_no, guncelleme_tarihi, cozunurluk_dk,
+                  durum, onayli_revizyon_no
         "#,
         santral_id,
         plan.plan_tarihi,
         saatlik_plan_json,
+        plan.cozunurluk(),
+        durum.as_str(),
+        durum.onayli(),
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO kgup_plan_revizyonlari (
+            plan_id, revizyon_no, saatlik_plan_mwh,
+            yazan_kullanici_id, aciklama, kaynak, kapanis_gerekcesi, olusturma_tarihi,
+            cozunurluk_dk, durum
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        "#,
+        kayit.id,
+        kayit.revizyon_no,
+        kayit.saatlik_plan_mwh,
+        yazan_kullanici_id,
+        plan.aciklama,
+        kaynak,
+        kapanis_gerekcesi,
+        kayit.guncelleme_tarihi,
+        kayit.cozunurluk_dk,
+        kayit.durum,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO kgup_plan_durum_gecisleri (
+            plan_id, revizyon_no, eski_durum, yeni_durum, kullanici_id, olusturma_tarihi
+        )
+        VALUES ($1, $2, NULL, $3, $4, $5)
+        "#,
+        kayit.id,
+        kayit.revizyon_no,
+        kayit.durum,
+        yazan_kullanici_id,
+        kayit.guncelleme_tarihi,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(kayit)
+}
+
+pub async fn get_kgup_plan(
+    pool: &PgPool,
+    santral_id: Uuid,
+    gun: NaiveDate,
+) -> Result<Option<KgupPlan>, sqlx::Error> {
+    sqlx::query_as!(
+        KgupPlan,
+        r#"
+        SELECT id, santral_id, plan_tarihi, saatlik_plan_mwh, olusturma_tarihi,
+               revizyon_no, guncelleme_tarihi, cozunurluk_dk,
+               durum, onayli_revizyon_no
+        FROM   kgup_planlari
+        WHERE  santral_id = $1 AND plan_tarihi = $2
+        "#,
+        santral_id,
+        gun,
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// [start, end) teslim günlerindeki planlar (tarihe göre).
+pub async fn list_kgup_planlari(
+    pool: &PgPool,
+    santral_id: Uuid,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<Vec<KgupPlan>, sqlx::Error> {
+    sqlx::query_as!(
+        KgupPlan,
+        r#"
+        SELECT id, santral_id, plan_tarihi, saatlik_plan_mwh, olusturma_tarihi,
+               revizyon_no, guncelleme_tarihi, cozunurluk_dk,
+               durum, onayli_revizyon_no
+        FROM   kgup_planlari
+        WHERE  santral_id = $1
+          AND  plan_tarihi >= $2
+          AND  plan_tarihi <  $3
+        ORDER  BY plan_tarihi
+        "#,
+        santral_id,
+        start,
+        end,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Planın tüm revizyonları, yeniden eskiye.
+pub async fn list_kgup_revizyonlari(
+    pool: &PgPool,
+    santral_id: Uuid,
+    gun: NaiveDate,
+) -> Result<Vec<KgupPlanRevizyonu>, sqlx::Error> {
+    sqlx::query_as!(
+        KgupPlanRevizyonu,
+        r#"
+        SELECT r.revizyon_no, r.saatlik_plan_mwh, r.yazan_kullanici_id,
+               k.email AS "yazan_email?",
+               r.aciklama, r.kaynak, r.kapanis_gerekcesi, r.olusturma_tarihi,
+               r.cozunurluk_dk, r.durum
+        FROM   kgup_plan_revizyonlari r
+        JOIN   kgup_planlari p ON p.id = r.plan_id
+        LEFT   JOIN kullanicilar k ON k.id = r.yazan_kullanici_id
+        WHERE  p.santral_id = $1 AND p.plan_tarihi = $2
+        ORDER  BY r.revizyon_no DESC
+        "#,
+        santral_id,
+        gun,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// [start, end) teslim günlerinde santralin son onaylı revizyonları (sapma hesapları).
+pub async fn onayli_kgup_planlari(
+    pool: &PgPool,
+    santral_id: Uuid,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<Vec<OnayliKgupPlan>, sqlx::Error> {
+    sqlx::query_as!(
+        OnayliKgupPlan,
+        r#"
+        SELECT p.plan_tarihi, r.revizyon_no, r.saatlik_plan_mwh, r.cozunurluk_dk
+        FROM   kgup_planlari p
+        JOIN   kgup_plan_revizyonlari r
+               ON r.plan_id = p.id AND r.revizyon_no = p.onayli_revizyon_no
+        WHERE  p.santral_id = $1
+          AND  p.plan_tarihi >= $2
+          AND  p.plan_tarihi <  $3
+        ORDER  BY p.plan_tarihi
+        "#,
+        santral_id,
+        start,
+        end,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Revizyon durumunu `eski`den `yeni`ye geçirir ve geçişi kaydeder.
+/// Geçiş güncel revizyon istiyorsa (bkz. `PlanDurumu::guncel_revizyon_ister`) ve
+/// revizyon artık güncel değilse ya da durum bu arada değiştiyse false döner.
+#[allow(clippy::too_many_arguments)]
+pub async fn kgup_revizyon_durumu_degistir(
+    pool: &PgPool,
+    plan_id: Uuid,
+    revizyon_no: i32,
+    eski: PlanDurumu,
+    yeni: PlanDurumu,
+    kullanici_id: Uuid,
+    aciklama: Option<&str>,
+    kapanis_gerekcesi: Option<&str>,
+) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    // Plan satırı kilidi: aynı anda yeni revizyon yazılmasını bekletir
+    let guncel = sqlx::query_scalar!(
+        "SELECT revizyon_no FROM kgup_planlari WHERE id = $1 FOR UPDATE",
+        plan_id
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    if yeni.guncel_revizyon_ister() && guncel != revizyon_no {
+        return Ok(false);
+    }
+
+    let guncellenen = sqlx::query!(
+        r#"
+        UPDATE kgup_plan_revizyonlari
+        SET    durum = $4
+        WHERE  plan_id = $1 AND revizyon_no = $2 AND durum = $3
+        "#,
+        plan_id,
+        revizyon_no,
+        eski.as_str(),
+        yeni.as_str(),
+    )
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+    if guncellenen == 0 {
+        return Ok(false);
+    }
+
+    sqlx::query!(
+        r#"
+        UPDATE kgup_planlari
+        SET    durum = CASE WHEN revizyon_no = $2 THEN $3 ELSE durum END,
+               onayli_revizyon_no = CASE WHEN $4 THEN GREATEST(COALESCE(onayli_revizyon_no, 0), $2)
+                                         ELSE onayli_revizyon_no END
+        WHERE  id = $1
+        "#,
+        plan_id,
+        revizyon_no,
+        yeni.as_str(),
+        yeni.onayli(),
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO kgup_plan_durum_gecisleri (
+            plan_id, revizyon_no, eski_durum, yeni_durum, kullanici_id, aciklama,
+            kapanis_gerekcesi
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        "#,
+        plan_id,
+        revizyon_no,
+        eski.as_str(),
+        yeni.as_str(),
+        kullanici_id,
+        aciklama,
+        kapanis_gerekcesi,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(true)
+}
+
+/// Planın onay akışı kaydı, eskiden yeniye.
+pub async fn list_kgup_durum_gecisleri(
+    pool: &PgPool,
+    plan_id: Uuid,
+) -> Result<Vec<KgupDurumGecisi>, sqlx::Error> {
+    sqlx::query_as!(
+        KgupDurumGecisi,
+        r#"
+        SELECT g.revizyon_no, g.eski_durum, g.yeni_durum, g.kullanici_id,
+               k.email AS "kullanici_email?",
+               g.aciklama, g.kapanis_gerekcesi, g.olusturma_tarihi
+        FROM   kgup_plan_durum_gecisleri g
+        LEFT   JOIN kullanicilar k ON k.id = g.kullanici_id
+        WHERE  g.plan_id = $1
+        ORDER  BY g.olusturma_tarihi, g.revizyon_no
+        "#,
+        plan_id,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Teslim günü için santrallerin plan durumu. `musteri_id` None → tüm müşteriler.
+pub async fn kgup_plan_durumu(
+    pool: &PgPool,
+    gun: NaiveDate,
+    musteri_id: Option<Uuid>,
+) -> Result<Vec<KgupPlanDurumSatiri>, sqlx::Error> {
+    sqlx::query_as!(
+        KgupPlanDurumSatiri,
+        r#"
+        SELECT s.id               AS "santral_id!",
+               s.ad               AS "santral_ad!",
+               s.tip              AS "tip!",
+               s.musteri_id,
+               p.id               AS "plan_id?",
+               p.saatlik_plan_mwh AS "saatlik_plan_mwh?",
+               p.olusturma_tarihi AS "olusturma_tarihi?",
+               p.revizyon_no      AS "revizyon_no?",
+               p.guncelleme_tarihi AS "guncelleme_tarihi?",
+               p.durum            AS "durum?",
+               p.onayli_revizyon_no
+        FROM   santraller s
+        LEFT   JOIN kgup_planlari p
+               ON p.santral_id = s.id AND p.plan_tarihi = $1
+        WHERE  $2::uuid IS NULL OR s.musteri_id = $2
+        ORDER  BY s.ad
+        "#,
+        gun,
+        musteri_id,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Teslim günü planı olan santraller, PYS kimlikleriyle; plan değerleri son onaylı
+/// revizyondandır (onaylı revizyon yoksa boş). `musteri_id` None → tüm müşteriler.
+pub async fn pys_planlari(
+    pool: &PgPool,
+    gun: NaiveDate,
+    musteri_id: Option<Uuid>,
+) -> Result<Vec<PysPlanSatiri>, sqlx::Error> {
+    sqlx::query_as!(
+        PysPlanSatiri,
+        r#"
+        SELECT s.id               AS "santral_id!",
+               s.ad               AS "santral_ad!",
+               s.eic_kodu,
+               s.uevcb_adi,
+               s.musteri_id,
+               r.revizyon_no      AS "revizyon_no?",
+               r.saatlik_plan_mwh AS "saatlik_plan_mwh?",
+               r.cozunurluk_dk    AS "cozunurluk_dk?"
+        FROM   santraller s
+        JOIN   kgup_planlari p
+               ON p.santral_id = s.id AND p.plan_tarihi = $1
+        LEFT   JOIN kgup_plan_revizyonlari r
+               ON r.plan_id = p.id AND r.revizyon_no = p.onayli_revizyon_no
+        WHERE  $2::uuid IS NULL OR s.musteri_id = $2
+        ORDER  BY s.ad
+        "#,
+        gun,
+        musteri_id,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+//-----------------------------------------------------------
+// PLANLI KESİNTİLER
+//-----------------------------------------------------------
+
+/// Santralin kesintileri; `aralik` verilirse yalnızca [bas, bit) ile çakışanlar.
+pub async fn list_planli_kesintiler(
+    pool: &PgPool,
+    santral_id: Uuid,
+    aralik: Option<(DateTime<Utc>, DateTime<Utc>)>,
+) -> Result<Vec<PlanliKesinti>, sqlx::Error> {
+    let (bas, bit) = aralik.unzip();
+    sqlx::query_as!(
+        PlanliKesinti,
+        r#"
+        SELECT id, santral_id, baslangic, bitis, kullanilabilir_guc_mw,
+               aciklama, olusturan_id, olusturma_tarihi
+        FROM   planli_kesintiler
+        WHERE  santral_id = $1
+          AND  ($2::timestamptz IS NULL OR bitis > $2)
+          AND  ($3::timestamptz IS NULL OR baslangic < $3)
+        ORDER  BY baslangic
+        "#,
+        santral_id,
+        bas,
+        bit,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn create_planli_kesinti(
+    pool: &PgPool,
+    santral_id: Uuid,
+    girdi: PlanliKesintiInput,
+    olusturan_id: Uuid,
+) -> Result<PlanliKesinti, sqlx::Error> {
+    sqlx::query_as!(
+        PlanliKesinti,
+        r#"
+        INSERT INTO planli_kesintiler (
+            santral_id, baslangic, bitis, kullanilabilir_guc_mw, aciklama, olusturan_id
+        )
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING id, santral_id, baslangic, bitis, kullanilabilir_guc_mw,
+                  aciklama, olusturan_id, olusturma_tarihi
+        "#,
+        santral_id,
+        girdi.baslangic,
+        girdi.bitis,
+        girdi.kullanilabilir_guc_mw,
+        girdi.aciklama,
+        olusturan_id,
     )
     .fetch_one(pool)
     .await
 }
 
+/// Silindiyse true.
+pub async fn delete_planli_kesinti(
+    pool: &PgPool,
+    santral_id: Uuid,
+    kesinti_id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    let r = sqlx::query!(
+        "DELETE FROM planli_kesintiler WHERE id = $1 AND santral_id = $2",
+        kesinti_id,
+        santral_id,
+    )
+    .execute(pool)
+    .await?;
+    Ok(r.rows_affected() > 0)
+}
+
 //-----------------------------------------------------------
 // MÜŞTERİYE GÖRE İŞLEMLER
 //-----------------------------------------------------------
@@ -173,12 +680,13 @@ pub async fn create_santral_for_musteri(
         r#"
         INSERT INTO santraller (
             id, ad, tip, kurulu_guc_mw,
-            koordinat_enlem, koordinat_boylam, musteri_id
+            koordinat_enlem, koordinat_boylam, musteri_id, olcum_cozunurluk_sn,
+            eic_kodu, uevcb_adi
         )
-        VALUES ($1,$2,$3,$4,$5,$6,$7)
+        VALUES ($1,$2,$3,$4,$5,$6,$7,$8,NULLIF($9, ''),NULLIF($10, ''))
         RETURNING id, ad, tip, kurulu_guc_mw,
                   koordinat_enlem, koordinat_boylam,
-                  musteri_id, olusturma_tarihi
+                  musteri_id, olcum_cozunurluk_sn, eic_kodu, uevcb_adi, olusturma_tarihi
         "#,
         new_id,
         data.ad,
@@ -187,6 +695,9 @@ pub async fn create_santral_for_musteri(
         data.koordinat_enlem,
         data.koordinat_boylam,
         musteri_id,
+        data.olcum_cozunurluk_sn,
+        data.eic_kodu,
+        data.uevcb_adi,
     )
     .fetch_one(pool)
     .await
@@ -201,7 +712,7 @@ pub async fn get_santraller_by_musteri(
         r#"
         SELECT id, ad, tip, kurulu_guc_mw,
                koordinat_enlem, koordinat_boylam,
-               musteri_id, olusturma_tarihi
+               musteri_id, olcum_cozunurluk_sn, eic_kodu, uevcb_adi, olusturma_tarihi
         FROM   santraller
         WHERE  musteri_id = $1
         ORDER  BY olusturma_tarihi DESC
@@ -233,127 +744,1537 @@ pub async fn santral_belongs_to_musteri(
 }
 
 //-----------------------------------------------------------
-// GERÇEK ZAMANLI ÜRETİM (WebSocket görünümü)
+// KAYIT & DAVET
 //-----------------------------------------------------------
 
-#[derive(Debug, serde::Serialize)]
-pub struct WsSantralUretimRow {
-    pub id: Uuid,
-    pub ad: String,
-    pub kurulu_guc_mw: BigDecimal,
-    pub son_mw: Option<f64>,
-    pub son_ts: Option<DateTime<Utc>>,
-}
-
-pub async fn get_son_uretimler_by_musteri(
+/// Yeni müşteri + ilk portföy yöneticisini tek transaction'da açar.
+/// E-posta zaten kayıtlıysa `kullanicilar_email_key` ihlali döner.
+pub async fn register_musteri_with_admin(
     pool: &PgPool,
-    musteri_id: Uuid,
-) -> Result<Vec<WsSantralUretimRow>, sqlx::Error> {
-    let rows = sqlx::query!(
+    musteri_ad: &str,
+    email: &str,
+    sifre_hash: &str,
+    ad_soyad: Option<&str>,
+) -> Result<(Musteri, Kullanici), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let musteri = sqlx::query_as!(
+        Musteri,
         r#"
-        SELECT
-            s.id,
-            s.ad,
-            s.kurulu_guc_mw,
-            u.guc_mw :: float8 AS "son_mw?",
-            u.zaman_utc        AS "son_ts?"
-        FROM santraller s
-        LEFT JOIN LATERAL (
-            SELECT guc_mw, zaman_utc
-            FROM uretim_olcumleri
-            WHERE santral_id = s.id
-            ORDER BY zaman_utc DESC
-            LIMIT 1
-        ) u ON TRUE
-        WHERE s.musteri_id = $1
-        ORDER BY s.ad
+        INSERT INTO musteriler (ad)
+        VALUES ($1)
+        RETURNING id, ad, aktif, olusturma_tarihi
         "#,
-        musteri_id
+        musteri_ad,
     )
-    .fetch_all(pool)
+    .fetch_one(&mut *tx)
     .await?;
 
-    Ok(rows
-        .into_iter()
-        .map(|r| WsSantralUretimRow {
-            id: r.id,
-            ad: r.ad,
-            kurulu_guc_mw: r.kurulu_guc_mw,
-            son_mw: r.son_mw,
-            son_ts: r.son_ts,
-        })
-        .collect())
+    let kullanici = sqlx::query_as!(
+        Kullanici,
+        r#"
+        INSERT INTO kullanicilar (musteri_id, email, sifre_hash, ad_soyad, rol)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id, musteri_id, email, sifre_hash, ad_soyad,
+                  rol, aktif, olusturma_tarihi
+        "#,
+        musteri.id,
+        email,
+        sifre_hash,
+        ad_soyad,
+        crate::yetki::Rol::PortfoyAdmin.as_str(),
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok((musteri, kullanici))
 }
 
-//-----------------------------------------------------------
-// SAPMA HESAPLARI
-//-----------------------------------------------------------
+/// Davet kaydı oluşturur; düz token çağırana aittir, burada sadece özeti tutulur.
+pub async fn create_davet(
+    pool: &PgPool,
+    musteri_id: Uuid,
+    token_hash: &str,
+    email: Option<&str>,
+    rol: &str,
+    olusturan_id: Uuid,
+    son_gecerlilik: DateTime<Utc>,
+) -> Result<Davet, sqlx::Error> {
+    sqlx::query_as!(
+        Davet,
+        r#"
+        INSERT INTO davetler (
+            musteri_id, token_hash, email, rol, olusturan_id, son_gecerlilik
+        )
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING id, musteri_id, email, rol, son_gecerlilik, olusturma_tarihi
+        "#,
+        musteri_id,
+        token_hash,
+        email,
+        rol,
+        olusturan_id,
+        son_gecerlilik,
+    )
+    .fetch_one(pool)
+    .await
+}
 
-pub async fn sapma_saatlik_gun(
+/// Daveti tüketip yeni kullanıcıyı açar.
+/// Token geçersiz / süresi dolmuş / kullanılmış / e-posta uyuşmuyor /
+/// müşteri pasifse `Ok(None)` döner.
+pub async fn accept_davet(
     pool: &PgPool,
-    santral_id: Uuid,
-    gun: NaiveDate,
-) -> Result<Vec<SapmaSaat>, sqlx::Error> {
-    let rows = sqlx::query!(
+    token_hash: &str,
+    email: &str,
+    sifre_hash: &str,
+    ad_soyad: Option<&str>,
+) -> Result<Option<Kullanici>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    // Token'ı atomik olarak "kullanıldı" işaretle → aynı token iki kez geçemez.
+    let davet = sqlx::query!(
         r#"
-WITH gun_aralik AS (
-  SELECT
-    $2::date AS gun,
-    ($2::date)::timestamptz AS ts_start,
-    ($2::date + INTERVAL '1 day')::timestamptz AS ts_end
-),
-plan AS (
-  SELECT
-    gs.i                                AS saat_index,
-    (p.saatlik_plan_mwh ->> gs.i)::numeric AS plan_mwh
-  FROM kgup_planlari p
-  JOIN gun_aralik ga ON p.plan_tarihi = ga.gun
-  CROSS JOIN generate_series(0,23) AS gs(i)
-  WHERE p.santral_id = $1
-),
-olcum AS (
-  SELECT
-    date_trunc('hour', u.zaman_utc) AS saat_ts,
-    SUM(u.guc_mw * (5.0/60.0))::float8 AS gercek_mwh,
-    COUNT(*)                           AS n_sample,
-    COUNT(*) FILTER (WHERE u.guc_mw IS NOT NULL) AS n_sample_valid
-  FROM uretim_olcumleri u
-  JOIN gun_aralik ga ON u.zaman_utc >= ga.ts_start AND u.zaman_utc < ga.ts_end
-  WHERE u.santral_id = $1
-  GROUP BY 1
-),
-saat_grid AS (
-  SELECT gs AS saat_ts,
-         EXTRACT(HOUR FROM gs)::int AS saat_index
-  FROM generate_series(
-    (SELECT ts_start FROM gun_aralik),
-    (SELECT ts_end   FROM gun_aralik) - INTERVAL '1 hour',
-    INTERVAL '1 hour'
-  ) AS gs
-)
-SELECT
-  sg.saat_index                      AS "saat_index!",
-  sg.saat_ts                         AS "saat_ts!",
-  pl.plan_mwh                        AS "plan_mwh?",
-  ol.gercek_mwh                      AS "gercek_mwh?",
-  ol.n_sample                        AS "n_sample?",
-  ol.n_sample_valid                  AS "n_sample_valid?"
-FROM saat_grid sg
-LEFT JOIN plan  pl ON pl.saat_index = sg.saat_index
-LEFT JOIN olcum ol ON ol.saat_ts    = sg.saat_ts
-ORDER BY sg.saat_index
+        UPDATE davetler d
+        SET    kullanildi_tarihi = now()
+        FROM   musteriler m
+        WHERE  d.token_hash = $1
+          AND  d.kullanildi_tarihi IS NULL
+          AND  d.son_gecerlilik > now()
+          AND  (d.email IS NULL OR lower(d.email) = lower($2))
+          AND  m.id = d.musteri_id
+          AND  m.aktif
+        RETURNING d.id, d.musteri_id, d.rol
         "#,
-        santral_id,
-        gun,
+        token_hash,
+        email,
     )
-    .fetch_all(pool)
+    .fetch_optional(&mut *tx)
     .await?;
 
-    let mut out = Vec::with_capacity(rows.len());
-    for r in rows {
-        let plan_mwh   = r.plan_mwh.and_then(|v| v.to_f64());
-        let gercek_mwh = r.gercek_mwh.and_then(|v| v.to_f64());
-        let sapma_mwh  = match (plan_mwh, gercek_mwh) {
+    let Some(davet) = davet else {
+        return Ok(None);
+    };
+
+    let kullanici = sqlx::query_as!(
+        Kullanici,
+        r#"
+        INSERT INTO kullanicilar (musteri_id, email, sifre_hash, ad_soyad, rol)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id, musteri_id, email, sifre_hash, ad_soyad,
+                  rol, aktif, olusturma_tarihi
+        "#,
+        davet.musteri_id,
+        email,
+        sifre_hash,
+        ad_soyad,
+        davet.rol,
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        "UPDATE davetler SET kullanan_id = $1 WHERE id = $2",
+        kullanici.id,
+        davet.id,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(Some(kullanici))
+}
+
+//-----------------------------------------------------------
+// OTURUM & REFRESH TOKEN
+//-----------------------------------------------------------
+
+/// Yeni oturum + ilk refresh token; oturum id'sini döndürür.
+pub async fn create_oturum(
+    pool: &PgPool,
+    kullanici_id: Uuid,
+    refresh_hash: &str,
+    son_gecerlilik: DateTime<Utc>,
+) -> Result<Uuid, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let oturum_id = sqlx::query_scalar!(
+        "INSERT INTO oturumlar (kullanici_id) VALUES ($1) RETURNING id",
+        kullanici_id,
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO refresh_tokenlar (oturum_id, token_hash, son_gecerlilik)
+        VALUES ($1, $2, $3)
+        "#,
+        oturum_id,
+        refresh_hash,
+        son_gecerlilik,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(oturum_id)
+}
+
+/// Refresh token yenileme sonucu.
+#[derive(Debug)]
+pub enum RefreshSonuc {
+    Yenilendi {
+        oturum_id: Uuid,
+        user_id: Uuid,
+        musteri_id: Uuid,
+        rol: String,
+    },
+    /// Bilinmiyor, süresi dolmuş, oturum iptal ya da kullanıcı / müşteri pasif.
+    Gecersiz,
+    /// Daha önce kullanılmış token tekrar geldi → oturum iptal edildi.
+    TekrarKullanim,
+}
+
+/// Refresh token'ı döndürür: eskiyi "kullanıldı" işaretler, yenisini ekler.
+/// Rol DB'den okunur; rol değişikliği bir sonraki yenilemede token'a yansır.
+pub async fn rotate_refresh_token(
+    pool: &PgPool,
+    eski_hash: &str,
+    yeni_hash: &str,
+    yeni_son_gecerlilik: DateTime<Utc>,
+) -> Result<RefreshSonuc, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let row = sqlx::query!(
+        r#"
+        SELECT r.id, r.oturum_id, r.son_gecerlilik, r.kullanildi_tarihi,
+               o.iptal_tarihi, k.id AS user_id, k.musteri_id, k.rol,
+               (k.aktif AND m.aktif) AS "aktif!"
+        FROM   refresh_tokenlar r
+        JOIN   oturumlar    o ON o.id = r.oturum_id
+        JOIN   kullanicilar k ON k.id = o.kullanici_id
+        JOIN   musteriler   m ON m.id = k.musteri_id
+        WHERE  r.token_hash = $1
+        FOR UPDATE OF r
+        "#,
+        eski_hash,
+    )
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some(row) = row else {
+        return Ok(RefreshSonuc::Gecersiz);
+    };
+
+    if row.kullanildi_tarihi.is_some() {
+        sqlx::query!(
+            "UPDATE oturumlar SET iptal_tarihi = now() WHERE id = $1 AND iptal_tarihi IS NULL",
+            row.oturum_id,
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        return Ok(RefreshSonuc::TekrarKullanim);
+    }
+
+    if row.son_gecerlilik <= Utc::now() || row.iptal_tarihi.is_some() || !row.aktif {
+        return Ok(RefreshSonuc::Gecersiz);
+    }
+
+    sqlx::query!(
+        "UPDATE refresh_tokenlar SET kullanildi_tarihi = now() WHERE id = $1",
+        row.id,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO refresh_tokenlar (oturum_id, token_hash, son_gecerlilik)
+        VALUES ($1, $2, $3)
+        "#,
+        row.oturum_id,
+        yeni_hash,
+        yeni_son_gecerlilik,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(RefreshSonuc::Yenilendi {
+        oturum_id: row.oturum_id,
+        user_id: row.user_id,
+        musteri_id: row.musteri_id,
+        rol: row.rol,
+    })
+}
+
+/// Tek oturumu iptal eder (logout).
+pub async fn revoke_oturum(
+    pool: &PgPool,
+    oturum_id: Uuid,
+    kullanici_id: Uuid,
+) -> Result<u64, sqlx::Error> {
+    let res = sqlx::query!(
+        r#"
+        UPDATE oturumlar SET iptal_tarihi = now()
+        WHERE id = $1 AND kullanici_id = $2 AND iptal_tarihi IS NULL
+        "#,
+        oturum_id,
+        kullanici_id,
+    )
+    .execute(pool)
+    .await?;
+    Ok(res.rows_affected())
+}
+
+/// Refresh token'ın (kullanılmamış, süresi geçmemiş) oturumunu iptal eder; `hepsi` ise
+/// aynı kullanıcının tüm açık oturumlarını. Access token süresi dolmuşken logout için.
+pub async fn revoke_oturum_by_refresh(
+    pool: &PgPool,
+    token_hash: &str,
+    hepsi: bool,
+) -> Result<u64, sqlx::Error> {
+    let res = sqlx::query!(
+        r#"
+        UPDATE oturumlar o SET iptal_tarihi = now()
+        FROM   refresh_tokenlar r
+        JOIN   oturumlar t ON t.id = r.oturum_id
+        WHERE  r.token_hash = $1
+          AND  r.kullanildi_tarihi IS NULL
+          AND  r.son_gecerlilik > now()
+          AND  t.iptal_tarihi IS NULL
+          AND  o.iptal_tarihi IS NULL
+          AND  (o.id = t.id OR ($2 AND o.kullanici_id = t.kullanici_id))
+        "#,
+        token_hash,
+        hepsi,
+    )
+    .execute(pool)
+    .await?;
+    Ok(res.rows_affected())
+}
+
+/// Kullanıcının tüm açık oturumlarını iptal eder.
+pub async fn revoke_kullanici_oturumlari(
+    pool: &PgPool,
+    kullanici_id: Uuid,
+) -> Result<u64, sqlx::Error> {
+    let res = sqlx::query!(
+        r#"
+        UPDATE oturumlar SET iptal_tarihi = now()
+        WHERE kullanici_id = $1 AND iptal_tarihi IS NULL
+        "#,
+        kullanici_id,
+    )
+    .execute(pool)
+    .await?;
+    Ok(res.rows_affected())
+}
+
+/// Oturum hâlâ geçerli mi? (iptal edilmemiş + kullanıcı ve müşterisi aktif)
+/// Her authenticated istekte ve WS yayınında çağrılır.
+pub async fn oturum_aktif(pool: &PgPool, oturum_id: Uuid) -> Result<bool, sqlx::Error> {
+    let aktif = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS(
+            SELECT 1
+            FROM   oturumlar o
+            JOIN   kullanicilar k ON k.id = o.kullanici_id
+            JOIN   musteriler   m ON m.id = k.musteri_id
+            WHERE  o.id = $1
+              AND  o.iptal_tarihi IS NULL
+              AND  k.aktif
+              AND  m.aktif
+        ) AS "aktif!"
+        "#,
+        oturum_id,
+    )
+    .fetch_one(pool)
+    .await?;
+    Ok(aktif)
+}
+
+//-----------------------------------------------------------
+// YÖNETİM: MÜŞTERİ & KULLANICI
+//-----------------------------------------------------------
+
+pub async fn list_musteriler(pool: &PgPool) -> Result<Vec<Musteri>, sqlx::Error> {
+    sqlx::query_as!(
+        Musteri,
+        r#"
+        SELECT id, ad, aktif, olusturma_tarihi
+        FROM   musteriler
+        ORDER  BY ad
+        "#
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn create_musteri(pool: &PgPool, ad: &str) -> Result<Musteri, sqlx::Error> {
+    sqlx::query_as!(
+        Musteri,
+        r#"
+        INSERT INTO musteriler (ad)
+        VALUES ($1)
+        RETURNING id, ad, aktif, olusturma_tarihi
+        "#,
+        ad,
+    )
+    .fetch_one(pool)
+    .await
+}
+
+/// Müşteri adını değiştirir.
+pub async fn rename_musteri(
+    pool: &PgPool,
+    musteri_id: Uuid,
+    ad: &str,
+) -> Result<Musteri, sqlx::Error> {
+    sqlx::query_as!(
+        Musteri,
+        r#"
+        UPDATE musteriler SET ad = $1
+        WHERE id = $2
+        RETURNING id, ad, aktif, olusturma_tarihi
+        "#,
+        ad,
+        musteri_id,
+    )
+    .fetch_one(pool)
+    .await
+}
+
+/// Müşteriyi aktif/pasif yapar. Pasif müşterinin kullanıcıları giriş yapamaz
+/// ve açık oturumları `oturum_aktif` kontrolünde düşer.
+pub async fn set_musteri_aktif(
+    pool: &PgPool,
+    musteri_id: Uuid,
+    aktif: bool,
+) -> Result<Musteri, sqlx::Error> {
+    sqlx::query_as!(
+        Musteri,
+        r#"
+        UPDATE musteriler SET aktif = $1
+        WHERE id = $2
+        RETURNING id, ad, aktif, olusturma_tarihi
+        "#,
+        aktif,
+        musteri_id,
+    )
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn musteri_aktif(pool: &PgPool, musteri_id: Uuid) -> Result<bool, sqlx::Error> {
+    let aktif = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM musteriler WHERE id = $1 AND aktif
+        ) AS "aktif!"
+        "#,
+        musteri_id,
+    )
+    .fetch_one(pool)
+    .await?;
+    Ok(aktif)
+}
+
+/// Kullanıcıları listeler; `musteri_id` verilirse o müşteriyle sınırlar.
+pub async fn list_kullanicilar(
+    pool: &PgPool,
+    musteri_id: Option<Uuid>,
+) -> Result<Vec<Kullanici>, sqlx::Error> {
+    sqlx::query_as!(
+        Kullanici,
+        r#"
+        SELECT id, musteri_id, email, sifre_hash, ad_soyad,
+               rol, aktif, olusturma_tarihi
+        FROM   kullanicilar
+        WHERE  ($1::uuid IS NULL OR musteri_id = $1)
+        ORDER  BY email
+        "#,
+        musteri_id,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn get_kullanici_by_id(
+    pool: &PgPool,
+    kullanici_id: Uuid,
+) -> Result<Kullanici, sqlx::Error> {
+    sqlx::query_as!(
+        Kullanici,
+        r#"
+        SELECT id, musteri_id, email, sifre_hash, ad_soyad,
+               rol, aktif, olusturma_tarihi
+        FROM   kullanicilar
+        WHERE  id = $1
+        "#,
+        kullanici_id,
+    )
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn create_kullanici(
+    pool: &PgPool,
+    musteri_id: Uuid,
+    email: &str,
+    sifre_hash: &str,
+    ad_soyad: Option<&str>,
+    rol: &str,
+) -> Result<Kullanici, sqlx::Error> {
+    sqlx::query_as!(
+        Kullanici,
+        r#"
+        INSERT INTO kullanicilar (musteri_id, email, sifre_hash, ad_soyad, rol)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id, musteri_id, email, sifre_hash, ad_soyad,
+                  rol, aktif, olusturma_tarihi
+        "#,
+        musteri_id,
+        email,
+        sifre_hash,
+        ad_soyad,
+        rol,
+    )
+    .fetch_one(pool)
+    .await
+}
+
+/// Kullanıcıyı aktif/pasif yapar; pasifleştirmede açık oturumlar da iptal edilir.
+pub async fn set_kullanici_aktif(
+    pool: &PgPool,
+    kullanici_id: Uuid,
+    aktif: bool,
+) -> Result<Kullanici, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let kullanici = sqlx::query_as!(
+        Kullanici,
+        r#"
+        UPDATE kullanicilar SET aktif = $1
+        WHERE id = $2
+        RETURNING id, musteri_id, email, sifre_hash, ad_soyad,
+                  rol, aktif, olusturma_tarihi
+        "#,
+        aktif,
+        kullanici_id,
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    if !aktif {
+        sqlx::query!(
+            "UPDATE oturumlar SET iptal_tarihi = now() WHERE kullanici_id = $1 AND iptal_tarihi IS NULL",
+            kullanici_id,
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(kullanici)
+}
+
+/// Rolü değiştirir. Açık access token'lar eski rolü en fazla ömürleri kadar taşır;
+/// refresh'te DB'deki rol okunur.
+pub async fn set_kullanici_rol(
+    pool: &PgPool,
+    kullanici_id: Uuid,
+    rol: &str,
+) -> Result<Kullanici, sqlx::Error> {
+    sqlx::query_as!(
+        Kullanici,
+        r#"
+        UPDATE kullanicilar SET rol = $1
+        WHERE id = $2
+        RETURNING id, musteri_id, email, sifre_hash, ad_soyad,
+                  rol, aktif, olusturma_tarihi
+        "#,
+        rol,
+        kullanici_id,
+    )
+    .fetch_one(pool)
+    .await
+}
+
+/// Şifreyi sıfırlar ve kullanıcının tüm oturumlarını iptal eder.
+pub async fn reset_kullanici_sifre(
+    pool: &PgPool,
+    kullanici_id: Uuid,
+    sifre_hash: &str,
+) -> Result<u64, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let res = sqlx::query!(
+        "UPDATE kullanicilar SET sifre_hash = $1 WHERE id = $2",
+        sifre_hash,
+        kullanici_id,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        "UPDATE oturumlar SET iptal_tarihi = now() WHERE kullanici_id = $1 AND iptal_tarihi IS NULL",
+        kullanici_id,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(res.rows_affected())
+}
+
+//-----------------------------------------------------------
+// API ANAHTARLARI
+//-----------------------------------------------------------
+
+/// Verilen santrallerin hepsi bu müşteriye mi ait?
+pub async fn santraller_musteriye_ait(
+    pool: &PgPool,
+    santral_idler: &[Uuid],
+    musteri_id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    let ait = sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) = cardinality($1::uuid[]) AS "ait!"
+        FROM   santraller
+        WHERE  id = ANY($1) AND musteri_id = $2
+        "#,
+        santral_idler,
+        musteri_id,
+    )
+    .fetch_one(pool)
+    .await?;
+    Ok(ait)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn create_api_anahtari(
+    pool: &PgPool,
+    musteri_id: Uuid,
+    ad: &str,
+    onek: &str,
+    anahtar_hash: &str,
+    kapsam: &str,
+    santral_idler: &[Uuid],
+    son_gecerlilik: Option<DateTime<Utc>>,
+    olusturan_id: Uuid,
+) -> Result<ApiAnahtari, sqlx::Error> {
+    sqlx::query_as!(
+        ApiAnahtari,
+        r#"
+        INSERT INTO api_anahtarlari (
+            musteri_id, ad, onek, anahtar_hash, kapsam,
+            santral_idler, son_gecerlilik, olusturan_id
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING id, musteri_id, ad, onek, kapsam, santral_idler,
+                  son_gecerlilik, son_kullanim, olusturan_id,
+                  iptal_tarihi, olusturma_tarihi
+        "#,
+        musteri_id,
+        ad,
+        onek,
+        anahtar_hash,
+        kapsam,
+        santral_idler,
+        son_gecerlilik,
+        olusturan_id,
+    )
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn list_api_anahtarlari(
+    pool: &PgPool,
+    musteri_id: Uuid,
+) -> Result<Vec<ApiAnahtari>, sqlx::Error> {
+    sqlx::query_as!(
+        ApiAnahtari,
+        r#"
+        SELECT id, musteri_id, ad, onek, kapsam, santral_idler,
+               son_gecerlilik, son_kullanim, olusturan_id,
+               iptal_tarihi, olusturma_tarihi
+        FROM   api_anahtarlari
+        WHERE  musteri_id = $1
+        ORDER  BY olusturma_tarihi DESC
+        "#,
+        musteri_id,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Anahtarı iptal eder; etkilenen satır sayısını döndürür.
+pub async fn revoke_api_anahtari(
+    pool: &PgPool,
+    anahtar_id: Uuid,
+    musteri_id: Uuid,
+) -> Result<u64, sqlx::Error> {
+    let res = sqlx::query!(
+        r#"
+        UPDATE api_anahtarlari SET iptal_tarihi = now()
+        WHERE id = $1 AND musteri_id = $2 AND iptal_tarihi IS NULL
+        "#,
+        anahtar_id,
+        musteri_id,
+    )
+    .execute(pool)
+    .await?;
+    Ok(res.rows_affected())
+}
+
+/// Anahtarı doğrular ve `son_kullanim` damgasını günceller.
+/// İptal, süresi dolmuş, pasif müşteri ya da bilinmeyen anahtar → `None`.
+pub async fn api_anahtari_dogrula(
+    pool: &PgPool,
+    anahtar_hash: &str,
+) -> Result<Option<DogrulananApiAnahtari>, sqlx::Error> {
+    sqlx::query_as!(
+        DogrulananApiAnahtari,
+        r#"
+        UPDATE api_anahtarlari a
+        SET    son_kullanim = now()
+        FROM   musteriler m
+        WHERE  a.anahtar_hash = $1
+          AND  a.iptal_tarihi IS NULL
+          AND  (a.son_gecerlilik IS NULL OR a.son_gecerlilik > now())
+          AND  m.id = a.musteri_id
+          AND  m.aktif
+        RETURNING a.id, a.musteri_id, a.kapsam, a.santral_idler
+        "#,
+        anahtar_hash,
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Belirtilen constraint ihlal edildi mi? (ör. `kullanicilar_email_key`)
+pub fn violates_constraint(err: &sqlx::Error, constraint: &str) -> bool {
+    match err {
+        sqlx::Error::Database(db_err) => db_err.constraint() == Some(constraint),
+        _ => false,
+    }
+}
+
+//-----------------------------------------------------------
+// GERÇEK ZAMANLI ÜRETİM (WebSocket görünümü)
+//-----------------------------------------------------------
+
+#[derive(Debug, serde::Serialize)]
+pub struct WsSantralUretimRow {
+    pub id: Uuid,
+    pub ad: String,
+    pub kurulu_guc_mw: BigDecimal,
+    pub son_mw: Option<f64>,
+    pub son_ts: Option<DateTime<Utc>>,
+}
+
+pub async fn get_son_uretimler_by_musteri(
+    pool: &PgPool,
+    musteri_id: Uuid,
+) -> Result<Vec<WsSantralUretimRow>, sqlx::Error> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT
+            s.id,
+            s.ad,
+            s.kurulu_guc_mw,
+            u.guc_mw :: float8 AS "son_mw?",
+            u.zaman_utc        AS "son_ts?"
+        FROM santraller s
+        LEFT JOIN LATERAL (
+            SELECT guc_mw, zaman_utc
+            FROM uretim_olcumleri
+            WHERE santral_id = s.id
+            ORDER BY zaman_utc DESC
+            LIMIT 1
+        ) u ON TRUE
+        WHERE s.musteri_id = $1
+        ORDER BY s.ad
+        "#,
+        musteri_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| WsSantralUretimRow {
+            id: r.id,
+            ad: r.ad,
+            kurulu_guc_mw: r.kurulu_guc_mw,
+            son_mw: r.son_mw,
+            son_ts: r.son_ts,
+        })
+        .collect())
+}
+
+//-----------------------------------------------------------
+// ÜRETİM ÖLÇÜMÜ AKTARIMI
+//-----------------------------------------------------------
+
+/// Ölçümleri `uretim_olcumleri_unique_ts` üzerinden upsert eder.
+/// (eklenen, güncellenen) sayılarını döndürür.
+/// Çağıran, aynı (santral_id, zaman_utc) çiftinin listede tekrar etmediğini garanti etmeli.
+pub async fn upsert_olcumler(
+    pool: &PgPool,
+    satirlar: &[crate::olcum::OlcumSatiri],
+) -> Result<(u64, u64), sqlx::Error> {
+    if satirlar.is_empty() {
+        return Ok((0, 0));
+    }
+
+    let santral_idler: Vec<Uuid> = satirlar.iter().map(|o| o.santral_id).collect();
+    let zamanlar: Vec<DateTime<Utc>> = satirlar.iter().map(|o| o.zaman_utc).collect();
+    let gucler: Vec<f64> = satirlar.iter().map(|o| o.guc_mw).collect();
+
+    // xmax = 0 → satır yeni eklendi; aksi halde güncellendi
+    let row = sqlx::query!(
+        r#"
+        WITH upsert AS (
+            INSERT INTO uretim_olcumleri (santral_id, zaman_utc, guc_mw)
+            SELECT santral_id, zaman_utc, guc_mw::numeric
+            FROM   UNNEST($1::uuid[], $2::timestamptz[], $3::float8[])
+                   AS t(santral_id, zaman_utc, guc_mw)
+            ON CONFLICT ON CONSTRAINT uretim_olcumleri_unique_ts
+            DO UPDATE SET guc_mw = EXCLUDED.guc_mw,
+                          eklenme_tarihi = now(),
+                          kalite_bayraklari = NULL
+            RETURNING (xmax = 0) AS eklendi
+        )
+        SELECT
+            COUNT(*) FILTER (WHERE eklendi)     AS "eklenen!",
+            COUNT(*) FILTER (WHERE NOT eklendi) AS "guncellenen!"
+        FROM upsert
+        "#,
+        &santral_idler,
+        &zamanlar,
+        &gucler,
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok((row.eklenen as u64, row.guncellenen as u64))
+}
+
+//-----------------------------------------------------------
+// PİYASA FİYATLARI
+//-----------------------------------------------------------
+
+/// Saatlik fiyatları ekler / günceller. Boş SMF ve sistem yönü kayıtlı değeri ezmez.
+/// Dönen: (eklenen, guncellenen)
+pub async fn upsert_piyasa_fiyatlari(
+    pool: &PgPool,
+    satirlar: &[crate::piyasa::FiyatSatiri],
+    kaynak: &str,
+    guncelleyen_id: Option<Uuid>,
+) -> Result<(u64, u64), sqlx::Error> {
+    if satirlar.is_empty() {
+        return Ok((0, 0));
+    }
+
+    let saatler: Vec<DateTime<Utc>> = satirlar.iter().map(|f| f.saat).collect();
+    let ptf: Vec<f64> = satirlar.iter().map(|f| f.ptf_tl).collect();
+    let smf: Vec<Option<f64>> = satirlar.iter().map(|f| f.smf_tl).collect();
+    let yon: Vec<Option<String>> = satirlar
+        .iter()
+        .map(|f| f.sistem_yonu.map(|y| y.as_str().to_string()))
+        .collect();
+
+    let row = sqlx::query!(
+        r#"
+        WITH upsert AS (
+            INSERT INTO piyasa_fiyatlari (saat, ptf_tl, smf_tl, sistem_yonu, kaynak, guncelleyen_id)
+            SELECT saat, ptf_tl::numeric, smf_tl::numeric, sistem_yonu, $5, $6
+            FROM   UNNEST($1::timestamptz[], $2::float8[], $3::float8[], $4::text[])
+                   AS t(saat, ptf_tl, smf_tl, sistem_yonu)
+            ON CONFLICT (saat)
+            DO UPDATE SET ptf_tl            = EXCLUDED.ptf_tl,
+                          smf_tl            = COALESCE(EXCLUDED.smf_tl, piyasa_fiyatlari.smf_tl),
+                          sistem_yonu       = COALESCE(EXCLUDED.sistem_yonu, piyasa_fiyatlari.sistem_yonu),
+                          kaynak            = EXCLUDED.kaynak,
+                          guncelleyen_id    = EXCLUDED.guncelleyen_id,
+                          guncelleme_tarihi = now()
+            RETURNING (xmax = 0) AS eklendi
+        )
+        SELECT
+            COUNT(*) FILTER (WHERE eklendi)     AS "eklenen!",
+            COUNT(*) FILTER (WHERE NOT eklendi) AS "guncellenen!"
+        FROM upsert
+        "#,
+        &saatler,
+        &ptf,
+        &smf as &[Option<f64>],
+        &yon as &[Option<String>],
+        kaynak,
+        guncelleyen_id,
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok((row.eklenen as u64, row.guncellenen as u64))
+}
+
+/// Kayıtlı PTF satırlarına SMF ve sistem yönü yazar; PTF'si olmayan saatler atlanır.
+/// Dönen: güncellenen saatler.
+pub async fn smf_guncelle(
+    pool: &PgPool,
+    saatler: &[DateTime<Utc>],
+    smf: &[f64],
+    yon: &[Option<String>],
+    kaynak: &str,
+) -> Result<Vec<DateTime<Utc>>, sqlx::Error> {
+    if saatler.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    sqlx::query_scalar!(
+        r#"
+        UPDATE piyasa_fiyatlari p
+        SET    smf_tl            = t.smf_tl::numeric,
+               sistem_yonu       = COALESCE(t.sistem_yonu, p.sistem_yonu),
+               kaynak            = $4,
+               guncelleyen_id    = NULL,
+               guncelleme_tarihi = now()
+        FROM   UNNEST($1::timestamptz[], $2::float8[], $3::text[]) AS t(saat, smf_tl, sistem_yonu)
+        WHERE  p.saat = t.saat
+        RETURNING p.saat
+        "#,
+        saatler,
+        smf,
+        yon as &[Option<String>],
+        kaynak,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// [bas, bit) aralığındaki saatlik fiyatlar, saate göre sıralı.
+pub async fn list_piyasa_fiyatlari(
+    pool: &PgPool,
+    bas: DateTime<Utc>,
+    bit: DateTime<Utc>,
+) -> Result<Vec<PiyasaFiyati>, sqlx::Error> {
+    sqlx::query_as!(
+        PiyasaFiyati,
+        r#"
+        SELECT saat, ptf_tl::float8 AS "ptf_tl!", smf_tl::float8 AS smf_tl,
+               sistem_yonu, kaynak, guncelleyen_id, guncelleme_tarihi
+        FROM   piyasa_fiyatlari
+        WHERE  saat >= $1 AND saat < $2
+        ORDER  BY saat
+        "#,
+        bas,
+        bit,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// [bas, bit) için teslim saatine göre fiyat tablosu (finansal hesaplar).
+pub async fn fiyat_tablosu(
+    pool: &PgPool,
+    bas: DateTime<Utc>,
+    bit: DateTime<Utc>,
+) -> Result<crate::piyasa::FiyatTablosu, sqlx::Error> {
+    Ok(crate::piyasa::FiyatTablosu::new(list_piyasa_fiyatlari(pool, bas, bit).await?))
+}
+
+pub async fn delete_piyasa_fiyati(pool: &PgPool, saat: DateTime<Utc>) -> Result<bool, sqlx::Error> {
+    let r = sqlx::query!("DELETE FROM piyasa_fiyatlari WHERE saat = $1", saat)
+        .execute(pool)
+        .await?;
+    Ok(r.rows_affected() > 0)
+}
+
+//-----------------------------------------------------------
+// DENGESİZLİK KURALLARI
+//-----------------------------------------------------------
+
+/// Kural seti sürümleri (kod verilirse yalnız o aile); kod ve başlangıca göre sıralı.
+pub async fn list_dengesizlik_kurallari(
+    pool: &PgPool,
+    kod: Option<&str>,
+) -> Result<Vec<DengesizlikKurali>, sqlx::Error> {
+    sqlx::query_as!(
+        DengesizlikKurali,
+        r#"
+        SELECT id, kod, surum, ad, gecerlilik_baslangic, tolerans_orani, pozitif_k, negatif_k,
+               yekdem_tolerans_orani, yekdem_pozitif_k, yekdem_negatif_k,
+               kupst_tolerans_orani, kupst_katsayi, kupst_fiyat_bazi, yekdem_kupst_tolerans_orani,
+               aciklama, duzeltme_gerekcesi, olusturan_id, olusturma_tarihi
+        FROM   dengesizlik_kurallari
+        WHERE  $1::text IS NULL OR kod = $1
+        ORDER  BY kod, gecerlilik_baslangic
+        "#,
+        kod,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Yeni sürüm ekler; sürüm numarası koddaki en büyük sürüm + 1.
+/// Aynı kodda aynı başlangıç günü → `dengesizlik_kurallari_baslangic_unique`.
+pub async fn create_dengesizlik_kurali(
+    pool: &PgPool,
+    k: &DengesizlikKuraliInput,
+    olusturan_id: Uuid,
+) -> Result<DengesizlikKurali, sqlx::Error> {
+    let kupst = crate::uzlastirma::KupstKurali::default();
+    sqlx::query_as!(
+        DengesizlikKurali,
+        r#"
+        INSERT INTO dengesizlik_kurallari (
+            id, kod, surum, ad, gecerlilik_baslangic, tolerans_orani, pozitif_k, negatif_k,
+            yekdem_tolerans_orani, yekdem_pozitif_k, yekdem_negatif_k,
+            kupst_tolerans_orani, kupst_katsayi, kupst_fiyat_bazi, yekdem_kupst_tolerans_orani,
+            aciklama, duzeltme_gerekcesi, olusturan_id
+        )
+        SELECT $1, $2,
+               COALESCE((SELECT max(surum) FROM dengesizlik_kurallari WHERE kod = $2), 0) + 1,
+               $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17
+        RETURNING id, kod, surum, ad, gecerlilik_baslangic, tolerans_orani, pozitif_k, negatif_k,
+                  yekdem_tolerans_orani, yekdem_pozitif_k, yekdem_negatif_k,
+                  kupst_tolerans_orani, kupst_katsayi, kupst_fiyat_bazi, yekdem_kupst_tolerans_orani,
+                  aciklama, duzeltme_gerekcesi, olusturan_id, olusturma_tarihi
+        "#,
+        Uuid::new_v4(),
+        k.kod,
+        k.ad,
+        k.gecerlilik_baslangic,
+        k.tolerans_orani,
+        k.pozitif_k,
+        k.negatif_k,
+        k.yekdem_tolerans_orani,
+        k.yekdem_pozitif_k,
+        k.yekdem_negatif_k,
+        k.kupst_tolerans_orani.unwrap_or(kupst.tolerans_orani),
+        k.kupst_katsayi.unwrap_or(kupst.katsayi),
+        k.kupst_fiyat_bazi.as_deref().unwrap_or(kupst.fiyat_bazi.as_str()),
+        k.yekdem_kupst_tolerans_orani,
+        k.aciklama,
+        k.duzeltme_gerekcesi,
+        olusturan_id,
+    )
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn list_santral_dengesizlik_kurallari(
+    pool: &PgPool,
+    santral_id: Uuid,
+) -> Result<Vec<SantralDengesizlikKurali>, sqlx::Error> {
+    sqlx::query_as!(
+        SantralDengesizlikKurali,
+        r#"
+        SELECT santral_id, gecerlilik_baslangic, kural_kodu, yekdem, duzeltme_gerekcesi,
+               guncelleyen_id, guncelleme_tarihi
+        FROM   santral_dengesizlik_kurallari
+        WHERE  santral_id = $1
+        ORDER  BY gecerlilik_baslangic
+        "#,
+        santral_id,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Aynı başlangıç gününde atama varsa üzerine yazar.
+pub async fn put_santral_dengesizlik_kurali(
+    pool: &PgPool,
+    santral_id: Uuid,
+    a: &SantralDengesizlikKuraliInput,
+    guncelleyen_id: Uuid,
+) -> Result<SantralDengesizlikKurali, sqlx::Error> {
+    sqlx::query_as!(
+        SantralDengesizlikKurali,
+        r#"
+        INSERT INTO santral_dengesizlik_kurallari (
+            santral_id, gecerlilik_baslangic, kural_kodu, yekdem, duzeltme_gerekcesi, guncelleyen_id
+        )
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (santral_id, gecerlilik_baslangic)
+        DO UPDATE SET kural_kodu         = EXCLUDED.kural_kodu,
+                      yekdem             = EXCLUDED.yekdem,
+                      duzeltme_gerekcesi = EXCLUDED.duzeltme_gerekcesi,
+                      guncelleyen_id     = EXCLUDED.guncelleyen_id,
+                      guncelleme_tarihi  = now()
+        RETURNING santral_id, gecerlilik_baslangic, kural_kodu, yekdem, duzeltme_gerekcesi,
+                  guncelleyen_id, guncelleme_tarihi
+        "#,
+        santral_id,
+        a.gecerlilik_baslangic,
+        a.kural_kodu,
+        a.yekdem,
+        a.duzeltme_gerekcesi,
+        guncelleyen_id,
+    )
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn delete_santral_dengesizlik_kurali(
+    pool: &PgPool,
+    santral_id: Uuid,
+    gun: NaiveDate,
+) -> Result<bool, sqlx::Error> {
+    let r = sqlx::query!(
+        "DELETE FROM santral_dengesizlik_kurallari WHERE santral_id = $1 AND gecerlilik_baslangic = $2",
+        santral_id,
+        gun,
+    )
+    .execute(pool)
+    .await?;
+    Ok(r.rows_affected() > 0)
+}
+
+/// Santralin tüm atamaları ve kural sürümleri (finansal hesaplar).
+pub async fn kural_takvimi(
+    pool: &PgPool,
+    santral_id: Uuid,
+) -> Result<crate::uzlastirma::KuralTakvimi, sqlx::Error> {
+    let atamalar = list_santral_dengesizlik_kurallari(pool, santral_id).await?;
+    let surumler = list_dengesizlik_kurallari(pool, None).await?;
+    Ok(crate::uzlastirma::KuralTakvimi::new(surumler, &atamalar))
+}
+
+//-----------------------------------------------------------
+// MODBUS NOKTALARI
+//-----------------------------------------------------------
+
+pub async fn list_modbus_noktalari(
+    pool: &PgPool,
+    santral_id: Uuid,
+) -> Result<Vec<ModbusNoktasi>, sqlx::Error> {
+    sqlx::query_as!(
+        ModbusNoktasi,
+        r#"
+        SELECT id, santral_id, host, port, unit_id, register_tipi, adres,
+               veri_tipi, kelime_sirasi, olcek, aciklama, aktif,
+               son_okuma_zamani, son_hata, olusturma_tarihi
+        FROM   modbus_noktalari
+        WHERE  santral_id = $1
+        ORDER  BY host, port, unit_id, adres
+        "#,
+        santral_id,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Santralin register haritasını komple değiştirir (tek transaction).
+pub async fn replace_modbus_noktalari(
+    pool: &PgPool,
+    santral_id: Uuid,
+    noktalar: Vec<ModbusNoktasiInput>,
+) -> Result<Vec<ModbusNoktasi>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query!("DELETE FROM modbus_noktalari WHERE santral_id = $1", santral_id)
+        .execute(&mut *tx)
+        .await?;
+
+    let mut out = Vec::with_capacity(noktalar.len());
+    for n in noktalar {
+        let row = sqlx::query_as!(
+            ModbusNoktasi,
+            r#"
+            INSERT INTO modbus_noktalari (
+                santral_id, host, port, unit_id, register_tipi, adres,
+                veri_tipi, kelime_sirasi, olcek, aciklama, aktif
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            RETURNING id, santral_id, host, port, unit_id, register_tipi, adres,
+                      veri_tipi, kelime_sirasi, olcek, aciklama, aktif,
+                      son_okuma_zamani, son_hata, olusturma_tarihi
+            "#,
+            santral_id,
+            n.host,
+            n.port.unwrap_or(502),
+            n.unit_id.unwrap_or(1),
+            n.register_tipi.unwrap_or_else(|| "holding".into()),
+            n.adres,
+            n.veri_tipi.unwrap_or_else(|| "u16".into()),
+            n.kelime_sirasi.unwrap_or_else(|| "buyuk_once".into()),
+            n.olcek.unwrap_or(1.0),
+            n.aciklama,
+            n.aktif.unwrap_or(true),
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        out.push(row);
+    }
+
+    tx.commit().await?;
+    Ok(out)
+}
+
+/// Poller için: aktif müşterilerin aktif noktaları.
+pub async fn list_aktif_modbus_noktalari(pool: &PgPool) -> Result<Vec<ModbusNoktasi>, sqlx::Error> {
+    sqlx::query_as!(
+        ModbusNoktasi,
+        r#"
+        SELECT n.id, n.santral_id, n.host, n.port, n.unit_id, n.register_tipi, n.adres,
+               n.veri_tipi, n.kelime_sirasi, n.olcek, n.aciklama, n.aktif,
+               n.son_okuma_zamani, n.son_hata, n.olusturma_tarihi
+        FROM   modbus_noktalari n
+        JOIN   santraller s ON s.id = n.santral_id
+        JOIN   musteriler m ON m.id = s.musteri_id
+        WHERE  n.aktif AND m.aktif
+        ORDER  BY n.host, n.port, n.unit_id, n.adres
+        "#
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Okuma sonucunu noktaya yazar (`hata` None → başarılı okuma).
+pub async fn modbus_durum_guncelle(
+    pool: &PgPool,
+    nokta_id: Uuid,
+    zaman: DateTime<Utc>,
+    hata: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE modbus_noktalari
+        SET    son_okuma_zamani = CASE WHEN $3::text IS NULL THEN $2 ELSE son_okuma_zamani END,
+               son_hata = $3
+        WHERE  id = $1
+        "#,
+        nokta_id,
+        zaman,
+        hata,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+//-----------------------------------------------------------
+// MQTT EŞLEMELERİ
+//-----------------------------------------------------------
+
+pub async fn list_mqtt_eslemeleri(
+    pool: &PgPool,
+    santral_id: Uuid,
+) -> Result<Vec<MqttEslemesi>, sqlx::Error> {
+    sqlx::query_as!(
+        MqttEslemesi,
+        r#"
+        SELECT id, santral_id, konu, deger_yolu, zaman_yolu, olcek, aktif, olusturma_tarihi
+        FROM   mqtt_eslemeleri
+        WHERE  santral_id = $1
+        ORDER  BY konu
+        "#,
+        santral_id,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Santralin konu eşlemelerini komple değiştirir (tek transaction).
+pub async fn replace_mqtt_eslemeleri(
+    pool: &PgPool,
+    santral_id: Uuid,
+    eslemeler: Vec<MqttEslemesiInput>,
+) -> Result<Vec<MqttEslemesi>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query!("DELETE FROM mqtt_eslemeleri WHERE santral_id = $1", santral_id)
+        .execute(&mut *tx)
+        .await?;
+
+    let mut out = Vec::with_capacity(eslemeler.len());
+    for e in eslemeler {
+        let row = sqlx::query_as!(
+            MqttEslemesi,
+            r#"
+            INSERT INTO mqtt_eslemeleri (santral_id, konu, deger_yolu, zaman_yolu, olcek, aktif)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id, santral_id, konu, deger_yolu, zaman_yolu, olcek, aktif, olusturma_tarihi
+            "#,
+            santral_id,
+            e.konu.trim(),
+            e.deger_yolu,
+            e.zaman_yolu,
+            e.olcek.unwrap_or(1.0),
+            e.aktif.unwrap_or(true),
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        out.push(row);
+    }
+
+    tx.commit().await?;
+    Ok(out)
+}
+
+/// Abone için: aktif müşterilerin aktif eşlemeleri.
+pub async fn list_aktif_mqtt_eslemeleri(pool: &PgPool) -> Result<Vec<MqttEslemesi>, sqlx::Error> {
+    sqlx::query_as!(
+        MqttEslemesi,
+        r#"
+        SELECT e.id, e.santral_id, e.konu, e.deger_yolu, e.zaman_yolu, e.olcek, e.aktif,
+               e.olusturma_tarihi
+        FROM   mqtt_eslemeleri e
+        JOIN   santraller s ON s.id = e.santral_id
+        JOIN   musteriler m ON m.id = s.musteri_id
+        WHERE  e.aktif AND m.aktif
+        "#
+    )
+    .fetch_all(pool)
+    .await
+}
+
+//-----------------------------------------------------------
+// EPİAŞ
+//-----------------------------------------------------------
+
+pub async fn get_epias_eslemesi(
+    pool: &PgPool,
+    santral_id: Uuid,
+) -> Result<Option<EpiasEslemesi>, sqlx::Error> {
+    sqlx::query_as!(
+        EpiasEslemesi,
+        r#"
+        SELECT santral_id, organizasyon_id, uevcb_id, santral_kodu, aktif, guncelleme_tarihi
+        FROM   epias_eslemeleri
+        WHERE  santral_id = $1
+        "#,
+        santral_id,
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn put_epias_eslemesi(
+    pool: &PgPool,
+    santral_id: Uuid,
+    e: &EpiasEslemesiInput,
+) -> Result<EpiasEslemesi, sqlx::Error> {
+    sqlx::query_as!(
+        EpiasEslemesi,
+        r#"
+        INSERT INTO epias_eslemeleri (santral_id, organizasyon_id, uevcb_id, santral_kodu, aktif)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (santral_id)
+        DO UPDATE SET organizasyon_id   = EXCLUDED.organizasyon_id,
+                      uevcb_id          = EXCLUDED.uevcb_id,
+                      santral_kodu      = EXCLUDED.santral_kodu,
+                      aktif             = EXCLUDED.aktif,
+                      guncelleme_tarihi = now()
+        RETURNING santral_id, organizasyon_id, uevcb_id, santral_kodu, aktif, guncelleme_tarihi
+        "#,
+        santral_id,
+        e.organizasyon_id,
+        e.uevcb_id,
+        e.santral_kodu,
+        e.aktif.unwrap_or(true),
+    )
+    .fetch_one(pool)
+    .await
+}
+
+/// Senkron için: aktif müşterilerin aktif eşlemeleri.
+pub async fn list_aktif_epias_eslemeleri(pool: &PgPool) -> Result<Vec<EpiasEslemesi>, sqlx::Error> {
+    sqlx::query_as!(
+        EpiasEslemesi,
+        r#"
+        SELECT e.santral_id, e.organizasyon_id, e.uevcb_id, e.santral_kodu, e.aktif,
+               e.guncelleme_tarihi
+        FROM   epias_eslemeleri e
+        JOIN   santraller s ON s.id = e.santral_id
+        JOIN   musteriler m ON m.id = s.musteri_id
+        WHERE  e.aktif AND m.aktif
+        ORDER  BY e.santral_id
+        "#
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Yayımlanan KGÜP değerlerini yazar (gerçek zamanlı üretim korunur).
+pub async fn upsert_epias_kgup(
+    pool: &PgPool,
+    santral_id: Uuid,
+    saatler: &[DateTime<Utc>],
+    mwh: &[f64],
+) -> Result<u64, sqlx::Error> {
+    let r = sqlx::query!(
+        r#"
+        INSERT INTO epias_uretim (santral_id, saat, kgup_mwh)
+        SELECT $1, saat, mwh
+        FROM   UNNEST($2::timestamptz[], $3::float8[]) AS t(saat, mwh)
+        ON CONFLICT (santral_id, saat)
+        DO UPDATE SET kgup_mwh = EXCLUDED.kgup_mwh, guncelleme_tarihi = now()
+        "#,
+        santral_id,
+        saatler,
+        mwh,
+    )
+    .execute(pool)
+    .await?;
+    Ok(r.rows_affected())
+}
+
+/// Gerçek zamanlı üretimi yazar (KGÜP korunur).
+pub async fn upsert_epias_gercek_zamanli(
+    pool: &PgPool,
+    santral_id: Uuid,
+    saatler: &[DateTime<Utc>],
+    mwh: &[f64],
+) -> Result<u64, sqlx::Error> {
+    let r = sqlx::query!(
+        r#"
+        INSERT INTO epias_uretim (santral_id, saat, gercek_zamanli_mwh)
+        SELECT $1, saat, mwh
+        FROM   UNNEST($2::timestamptz[], $3::float8[]) AS t(saat, mwh)
+        ON CONFLICT (santral_id, saat)
+        DO UPDATE SET gercek_zamanli_mwh = EXCLUDED.gercek_zamanli_mwh, guncelleme_tarihi = now()
+        "#,
+        santral_id,
+        saatler,
+        mwh,
+    )
+    .execute(pool)
+    .await?;
+    Ok(r.rows_affected())
+}
+
+pub async fn list_epias_uretim(
+    pool: &PgPool,
+    santral_id: Uuid,
+    bas: DateTime<Utc>,
+    bit: DateTime<Utc>,
+) -> Result<Vec<EpiasUretim>, sqlx::Error> {
+    sqlx::query_as!(
+        EpiasUretim,
+        r#"
+        SELECT santral_id, saat, kgup_mwh, gercek_zamanli_mwh, guncelleme_tarihi
+        FROM   epias_uretim
+        WHERE  santral_id = $1 AND saat >= $2 AND saat < $3
+        ORDER  BY saat
+        "#,
+        santral_id,
+        bas,
+        bit,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn get_epias_senkron(pool: &PgPool, anahtar: &str) -> Result<Option<EpiasSenkron>, sqlx::Error> {
+    sqlx::query_as!(
+        EpiasSenkron,
+        r#"
+        SELECT anahtar, son_saat, son_deneme, son_basari, son_hata, ardisik_hata
+        FROM   epias_senkron
+        WHERE  anahtar = $1
+        "#,
+        anahtar,
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn list_epias_senkron(pool: &PgPool) -> Result<Vec<EpiasSenkron>, sqlx::Error> {
+    sqlx::query_as!(
+        EpiasSenkron,
+        r#"
+        SELECT anahtar, son_saat, son_deneme, son_basari, son_hata, ardisik_hata
+        FROM   epias_senkron
+        ORDER  BY anahtar
+        "#
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Başarılı parça: imleç yalnızca ileri gider.
+pub async fn epias_senkron_basari(
+    pool: &PgPool,
+    anahtar: &str,
+    son_saat: Option<DateTime<Utc>>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO epias_senkron (anahtar, son_saat, son_basari)
+        VALUES ($1, $2, now())
+        ON CONFLICT (anahtar)
+        DO UPDATE SET son_saat     = GREATEST(epias_senkron.son_saat, EXCLUDED.son_saat),
+                      son_deneme   = now(),
+                      son_basari   = now(),
+                      son_hata     = NULL,
+                      ardisik_hata = 0
+        "#,
+        anahtar,
+        son_saat,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn epias_senkron_hata(pool: &PgPool, anahtar: &str, hata: &str) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO epias_senkron (anahtar, son_hata, ardisik_hata)
+        VALUES ($1, $2, 1)
+        ON CONFLICT (anahtar)
+        DO UPDATE SET son_deneme   = now(),
+                      son_hata     = EXCLUDED.son_hata,
+                      ardisik_hata = epias_senkron.ardisik_hata + 1
+        "#,
+        anahtar,
+        hata,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+//-----------------------------------------------------------
+// SAPMA HESAPLARI
+//-----------------------------------------------------------
+
+/// Teslim günü için periyot bazlı plan / gerçekleşen / sapma.
+/// Plan son onaylı revizyondur (taslaklar sayılmaz); kayıtlı çözünürlüğünden
+/// `cozunurluk_dk`ya çevrilir (bkz. `kgup::donustur`). Kullanılan revizyon no'su da döner.
+pub async fn sapma_saatlik_gun(
+    pool: &PgPool,
+    santral_id: Uuid,
+    gun: NaiveDate,
+    cozunurluk_dk: i32,
+    kalite_modu: KaliteModu,
+) -> Result<(Option<i32>, Vec<SapmaSaat>), sqlx::Error> {
+    let onayli = onayli_kgup_planlari(pool, santral_id, gun, gun + chrono::Duration::days(1))
+        .await?
+        .pop();
+    let revizyon_no = onayli.as_ref().map(|p| p.revizyon_no);
+    let plan = onayli.map(|p| {
+        kgup::donustur(&saatlik_degerler(&p.saatlik_plan_mwh), p.cozunurluk_dk, cozunurluk_dk)
+    });
+
+    let adet = kgup::periyot_sayisi(gun, cozunurluk_dk);
+    let ts_start = piyasa_gunu::gun_baslangici(gun);
+    let periyot = chrono::Duration::minutes(cozunurluk_dk as i64);
+    let enerji = periyot_gercek(pool, santral_id, ts_start, periyot, adet, kalite_modu).await?;
+
+    let mut out = Vec::with_capacity(adet);
+    for (i, e) in enerji.into_iter().enumerate() {
+        let saat_ts    = kgup::periyot_baslangici(gun, cozunurluk_dk, i);
+        let plan_mwh   = plan.as_ref().and_then(|p| p.get(i).copied().flatten());
+        let gercek_mwh = e.mwh;
+        let sapma_mwh  = match (plan_mwh, gercek_mwh) {
             (Some(p), Some(g)) => Some(g - p),
             _ => None,
         };
@@ -363,77 +2284,263 @@ ORDER BY sg.saat_index
         };
 
         out.push(SapmaSaat {
-            saat: r.saat_index,
-            saat_ts: r.saat_ts,
+            saat: i as i32,
+            saat_ts,
+            saat_ts_yerel: piyasa_gunu::yerel(saat_ts),
             plan_mwh,
             gercek_mwh,
             sapma_mwh,
             sapma_oran,
+            kapsama_orani: e.kapsama_orani,
+            n_olcum: e.n_olcum,
         });
     }
-    Ok(out)
+    Ok((revizyon_no, out))
 }
 
 //-----------------------------------------------------------
 // PLAN VS GERÇEK — TARİH ARALIĞI
 //-----------------------------------------------------------
 
+/// [start, end) teslim günleri boyunca periyot bazlı (başlangıç, plan, gerçekleşen).
+/// Plan her gün için son onaylı revizyondur.
 pub async fn plan_gercek_aralik(
     pool: &PgPool,
     santral_id: Uuid,
     start: NaiveDate,
     end: NaiveDate, // exclusive
-) -> Result<Vec<(DateTime<Utc>, Option<f64>, Option<f64>)>, sqlx::Error> {
+    cozunurluk_dk: i32,
+    kalite_modu: KaliteModu,
+) -> Result<Vec<(DateTime<Utc>, Option<f64>, SaatEnerji)>, sqlx::Error> {
+    let planlar: std::collections::HashMap<NaiveDate, Vec<Option<f64>>> =
+        onayli_kgup_planlari(pool, santral_id, start, end)
+            .await?
+            .into_iter()
+            .map(|p| {
+                let degerler = saatlik_degerler(&p.saatlik_plan_mwh);
+                (p.plan_tarihi, kgup::donustur(&degerler, p.cozunurluk_dk, cozunurluk_dk))
+            })
+            .collect();
+
+    // Teslim günleri ardışık: her günün periyotları bir sonrakinin başına kadar uzanır
+    let mut periyotlar: Vec<(DateTime<Utc>, Option<f64>)> = Vec::new();
+    for gun in start.iter_days().take_while(|g| *g < end) {
+        let plan = planlar.get(&gun);
+        for i in 0..kgup::periyot_sayisi(gun, cozunurluk_dk) {
+            let plan_mwh = plan.and_then(|p| p.get(i).copied().flatten());
+            periyotlar.push((kgup::periyot_baslangici(gun, cozunurluk_dk, i), plan_mwh));
+        }
+    }
+
+    let ts_start = piyasa_gunu::gun_baslangici(start);
+    let periyot = chrono::Duration::minutes(cozunurluk_dk as i64);
+    let enerji =
+        periyot_gercek(pool, santral_id, ts_start, periyot, periyotlar.len(), kalite_modu).await?;
+
+    Ok(periyotlar
+        .into_iter()
+        .zip(enerji)
+        .map(|((ts, plan_mwh), e)| (ts, plan_mwh, e))
+        .collect())
+}
+
+//-----------------------------------------------------------
+// ÖLÇÜMDEN PERİYOT ENERJİSİ
+//-----------------------------------------------------------
+
+/// Santralin ölçümlerini okuyup `ts_start`tan itibaren `periyot` uzunluğunda
+/// `adet` periyodun enerjisine çevirir (yöntem santralin `olcum_cozunurluk_sn`
+/// ayarına göre, bkz. `enerji`). Bayraklı okumalar `kalite_modu`na göre ele alınır
+/// (bkz. `kalite`).
+pub async fn periyot_gercek(
+    pool: &PgPool,
+    santral_id: Uuid,
+    ts_start: DateTime<Utc>,
+    periyot: chrono::Duration,
+    adet: usize,
+    kalite_modu: KaliteModu,
+) -> Result<Vec<SaatEnerji>, sqlx::Error> {
+    let (_, cozunurluk) = santral_olcum_ayarlari(pool, santral_id).await?;
+    let yontem = Yontem::santral_icin(cozunurluk);
+
+    let ts_end = ts_start + periyot * adet as i32;
+    let pay = yontem.kenar_payi();
+    let ornekler = olcum_ornekleri(pool, santral_id, ts_start - pay, ts_end + pay).await?;
+    let ornekler = crate::kalite::uygula(ornekler, kalite_modu);
+
+    Ok(enerji::periyot_enerji(&ornekler, ts_start, periyot, adet, yontem))
+}
+
+/// Santralin kurulu gücü (MW) ve ölçüm çözünürlüğü (sn).
+pub async fn santral_olcum_ayarlari(
+    pool: &PgPool,
+    santral_id: Uuid,
+) -> Result<(f64, Option<i32>), sqlx::Error> {
+    let r = sqlx::query!(
+        r#"
+        SELECT kurulu_guc_mw::float8 AS "kurulu_guc_mw!", olcum_cozunurluk_sn
+        FROM   santraller
+        WHERE  id = $1
+        "#,
+        santral_id
+    )
+    .fetch_one(pool)
+    .await?;
+    Ok((r.kurulu_guc_mw, r.olcum_cozunurluk_sn))
+}
+
+/// [bas, bit) aralığındaki okumalar, zamana göre sıralı: (zaman, güç, bayraklı mı).
+pub async fn olcum_ornekleri(
+    pool: &PgPool,
+    santral_id: Uuid,
+    bas: DateTime<Utc>,
+    bit: DateTime<Utc>,
+) -> Result<Vec<(DateTime<Utc>, f64, bool)>, sqlx::Error> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT zaman_utc,
+               guc_mw::float8 AS "guc_mw!",
+               COALESCE(cardinality(kalite_bayraklari) > 0, FALSE) AS "bayrakli!"
+        FROM   uretim_olcumleri
+        WHERE  santral_id = $1
+          AND  zaman_utc >= $2
+          AND  zaman_utc <  $3
+        ORDER  BY zaman_utc
+        "#,
+        santral_id,
+        bas,
+        bit,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(|r| (r.zaman_utc, r.guc_mw, r.bayrakli)).collect())
+}
+
+//-----------------------------------------------------------
+// VERİ KALİTESİ
+//-----------------------------------------------------------
+
+/// Değerlendirilmesi gereken (santral, teslim günü) çiftleri, eskiden yeniye:
+/// bayraklanmamış okuması olan günler ve gün bitmeden hesaplanmış günlük skorlar
+/// (kapsama geçen süreye göre olduğundan gün kapanana kadar yenilenir).
+pub async fn degerlendirilecek_kalite_gunleri(
+    pool: &PgPool,
+    limit: i64,
+) -> Result<Vec<(Uuid, NaiveDate)>, sqlx::Error> {
     let rows = sqlx::query!(
         r#"
-WITH params AS (
-  SELECT $1::uuid AS santral_id,
-         $2::date AS start_gun,
-         $3::date AS end_gun
-),
-ts_serie AS (
-  SELECT generate_series(start_gun::timestamptz,
-                         (end_gun::timestamptz - INTERVAL '1 hour'),
-                         INTERVAL '1 hour') AS saat_ts
-  FROM params
-),
-plan_expanded AS (
-  SELECT
-    ts.saat_ts,
-    (kp.saatlik_plan_mwh ->> EXTRACT(HOUR FROM ts.saat_ts)::int)::float8 AS plan_mwh
-  FROM ts_serie ts
-  LEFT JOIN kgup_planlari kp
-    ON kp.santral_id = (SELECT santral_id FROM params)
-   AND kp.plan_tarihi = (ts.saat_ts AT TIME ZONE 'UTC')::date
-),
-olcum AS (
-  SELECT
-    date_trunc('hour', u.zaman_utc) AS saat_ts,
-    SUM(u.guc_mw * (5.0/60.0))::float8 AS gercek_mwh
-  FROM uretim_olcumleri u
-  WHERE u.santral_id = (SELECT santral_id FROM params)
-    AND u.zaman_utc >= (SELECT start_gun::timestamptz FROM params)
-    AND u.zaman_utc <  (SELECT end_gun::timestamptz   FROM params)
-  GROUP BY 1
-)
-SELECT
-  ts.saat_ts    AS "saat_ts!",
-  pl.plan_mwh   AS "plan_mwh",
-  ol.gercek_mwh AS "gercek_mwh"
-FROM ts_serie ts
-LEFT JOIN plan_expanded pl USING (saat_ts)
-LEFT JOIN olcum         ol USING (saat_ts)
-ORDER BY ts.saat_ts
+        SELECT santral_id AS "santral_id!", gun AS "gun!"
+        FROM (
+            SELECT DISTINCT santral_id, (zaman_utc AT TIME ZONE $2)::date AS gun
+            FROM   uretim_olcumleri
+            WHERE  kalite_bayraklari IS NULL
+              AND  zaman_utc <= now()
+            UNION
+            SELECT santral_id, gun
+            FROM   olcum_kalite_gunluk
+            WHERE  hesaplama_zamani < ((gun + 1)::timestamp AT TIME ZONE $2)
+        ) t
+        ORDER  BY gun, santral_id
+        LIMIT  $1
+        "#,
+        limit,
+        piyasa_gunu::PIYASA_TZ_ADI,
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().map(|r| (r.santral_id, r.gun)).collect())
+}
+
+/// [start, end) günlerinin saklı kalite değerlendirmeleri.
+pub async fn list_kalite_gunleri(
+    pool: &PgPool,
+    santral_id: Uuid,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<Vec<KaliteGun>, sqlx::Error> {
+    sqlx::query_as!(
+        KaliteGun,
+        r#"
+        SELECT santral_id, gun, skor, kapsama_orani, n_olcum, n_bayrakli,
+               sorunlar, hesaplama_zamani
+        FROM   olcum_kalite_gunluk
+        WHERE  santral_id = $1
+          AND  gun >= $2
+          AND  gun <  $3
+        ORDER  BY gun
         "#,
         santral_id,
         start,
         end,
     )
     .fetch_all(pool)
+    .await
+}
+
+/// Okuma bayraklarını yazar (bayraksız okumalar boş diziye çekilir).
+pub async fn kalite_bayraklarini_yaz(
+    pool: &PgPool,
+    santral_id: Uuid,
+    bayraklar: &[(DateTime<Utc>, Vec<crate::kalite::Bayrak>)],
+) -> Result<(), sqlx::Error> {
+    if bayraklar.is_empty() {
+        return Ok(());
+    }
+    let zamanlar: Vec<DateTime<Utc>> = bayraklar.iter().map(|(t, _)| *t).collect();
+    let listeler: Vec<String> = bayraklar
+        .iter()
+        .map(|(_, b)| b.iter().map(|x| x.as_str()).collect::<Vec<_>>().join(","))
+        .collect();
+
+    sqlx::query!(
+        r#"
+        UPDATE uretim_olcumleri u
+        SET    kalite_bayraklari = string_to_array(t.liste, ',')
+        FROM   UNNEST($2::timestamptz[], $3::text[]) AS t(zaman_utc, liste)
+        WHERE  u.santral_id = $1
+          AND  u.zaman_utc  = t.zaman_utc
+        "#,
+        santral_id,
+        &zamanlar,
+        &listeler,
+    )
+    .execute(pool)
     .await?;
+    Ok(())
+}
 
-    Ok(rows
-        .into_iter()
-        .map(|r| (r.saat_ts, r.plan_mwh, r.gercek_mwh))
-        .collect())
+pub async fn kalite_gun_kaydet(
+    pool: &PgPool,
+    santral_id: Uuid,
+    gun: NaiveDate,
+    d: &Degerlendirme,
+) -> Result<KaliteGun, sqlx::Error> {
+    sqlx::query_as!(
+        KaliteGun,
+        r#"
+        INSERT INTO olcum_kalite_gunluk (
+            santral_id, gun, skor, kapsama_orani, n_olcum, n_bayrakli, sorunlar
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        ON CONFLICT (santral_id, gun)
+        DO UPDATE SET skor             = EXCLUDED.skor,
+                      kapsama_orani    = EXCLUDED.kapsama_orani,
+                      n_olcum          = EXCLUDED.n_olcum,
+                      n_bayrakli       = EXCLUDED.n_bayrakli,
+                      sorunlar         = EXCLUDED.sorunlar,
+                      hesaplama_zamani = now()
+        RETURNING santral_id, gun, skor, kapsama_orani, n_olcum, n_bayrakli,
+                  sorunlar, hesaplama_zamani
+        "#,
+        santral_id,
+        gun,
+        d.skor,
+        d.kapsama_orani,
+        d.n_olcum as i32,
+        d.n_bayrakli as i32,
+        json!(d.sorunlar),
+    )
+    .fetch_one(pool)
+    .await
 }
@@ -0,0 +1,345 @@
+// backend/src/enerji.rs
+//
+// Güç ölçümlerinden (MW) saatlik enerjiye (MWh) geçiş.
+// - Santralde `olcum_cozunurluk_sn` tanımlıysa her örnek [t, t + çözünürlük)
+//   aralığının ortalama gücü sayılır (SCADA periyot ortalaması)
+// - Tanımlı değilse gerçek zaman damgaları arasında trapez integrasyonu yapılır;
+//   köprülenebilecek en uzun aralık örneklerin medyan aralığının BOSLUK_CARPANI katıdır
+//   (en az MAX_BOSLUK, en çok EN_UZUN_KOPRU). Daha uzun aralıklar veri boşluğudur,
+//   enterpolasyon yapılmaz; 30 / 60 dk örneklenen veri de böylece köprülenir
+// - Periyot sınırını aşan aralıklar iki periyoda bölünür (periyot: 60 ya da 15 dk)
+//
+// Kapsama oranı = ölçümle örtülen süre / periyot süresi. Boşluklu periyotlarda enerji
+// yalnızca örtülen kısım için hesaplanır; tamamlanmaz, oran ayrıca raporlanır.
+
+use chrono::{DateTime, Duration, Utc};
+
+/// Trapez integrasyonunda her zaman köprülenen süre (sık örneklenen veri için sınır).
+pub const MAX_BOSLUK: Duration = Duration::minutes(15);
+/// Seyrek örneklenen veride köprünün üst sınırı.
+pub const EN_UZUN_KOPRU: Duration = Duration::hours(2);
+/// Köprü = medyan örnek aralığı × bu katsayı; tek eksik örnek (2×) boşluk sayılır.
+pub const BOSLUK_CARPANI: f64 = 1.5;
+
+/// Periyot (saat / çeyrek saat) bazlı entegrasyon sonucu.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SaatEnerji {
+    /// Örtülen süre yoksa None.
+    pub mwh: Option<f64>,
+    /// 0..1
+    pub kapsama_orani: f64,
+    /// Periyot içine düşen örnek sayısı.
+    pub n_olcum: i64,
+}
+
+/// Entegrasyon yöntemi.
+#[derive(Debug, Clone, Copy)]
+pub enum Yontem {
+    /// Her örnek kendisinden sonraki `cozunurluk` süresinin ortalamasıdır.
+    Periyot { cozunurluk: Duration },
+    /// Komşu örnekler arasında doğrusal güç; `max_bosluk`tan uzun aralık boşluktur.
+    Trapez { max_bosluk: Duration },
+}
+
+impl Yontem {
+    /// Santral ayarından yöntem seçer.
+    pub fn santral_icin(olcum_cozunurluk_sn: Option<i32>) -> Self {
+        match olcum_cozunurluk_sn {
+            Some(sn) if sn > 0 => Yontem::Periyot { cozunurluk: Duration::seconds(sn as i64) },
+            _ => Yontem::Trapez { max_bosluk: MAX_BOSLUK },
+        }
+    }
+
+    /// Aralık kenarlarını doğru hesaplamak için dışarıdan okunması gereken pay.
+    /// Trapezde köprü örneklerden belirlendiği için olası en uzun köprü kadardır.
+    pub fn kenar_payi(&self) -> Duration {
+        match *self {
+            Yontem::Periyot { cozunurluk } => cozunurluk,
+            Yontem::Trapez { max_bosluk } => max_bosluk.max(EN_UZUN_KOPRU),
+        }
+    }
+
+    /// Trapez köprüsünü örneklerin medyan aralığına göre genişletir
+    /// (bkz. BOSLUK_CARPANI); periyot yöntemi olduğu gibi kalır.
+    pub fn orneklere_gore(self, ornekler: &[(DateTime<Utc>, f64)]) -> Self {
+        let Yontem::Trapez { max_bosluk } = self else {
+            return self;
+        };
+        let mut araliklar: Vec<i64> = ornekler
+            .windows(2)
+            .map(|w| (w[1].0 - w[0].0).num_milliseconds())
+            .filter(|ms| *ms > 0)
+            .collect();
+        if araliklar.is_empty() {
+            return self;
+        }
+        let orta = araliklar.len() / 2;
+        let medyan = *araliklar.select_nth_unstable(orta).1;
+        let kopru = Duration::milliseconds((medyan as f64 * BOSLUK_CARPANI) as i64);
+        Yontem::Trapez { max_bosluk: kopru.clamp(max_bosluk, EN_UZUN_KOPRU.max(max_bosluk)) }
+    }
+}
+
+fn saniye(d: Duration) -> f64 {
+    d.num_milliseconds() as f64 / 1000.0
+}
+
+struct Kova {
+    mwh: f64,
+    sn: f64,
+    n: i64,
+}
+
+/// `baslangic`tan itibaren `saat_sayisi` saatlik kovaya enerji dağıtır.
+/// `ornekler` zamana göre sıralı olmalıdır; kenar payı kadar dış örnek içerebilir.
+pub fn saatlik_enerji(
+    ornekler: &[(DateTime<Utc>, f64)],
+    baslangic: DateTime<Utc>,
+    saat_sayisi: usize,
+    yontem: Yontem,
+) -> Vec<SaatEnerji> {
+    periyot_enerji(ornekler, baslangic, Duration::hours(1), saat_sayisi, yontem)
+}
+
+/// `baslangic`tan itibaren `periyot` uzunluğunda `adet` kovaya enerji dağıtır.
+pub fn periyot_enerji(
+    ornekler: &[(DateTime<Utc>, f64)],
+    baslangic: DateTime<Utc>,
+    periyot: Duration,
+    adet: usize,
+    yontem: Yontem,
+) -> Vec<SaatEnerji> {
+    let yontem = yontem.orneklere_gore(ornekler);
+    let mut kovalar: Vec<Kova> = (0..adet).map(|_| Kova { mwh: 0.0, sn: 0.0, n: 0 }).collect();
+    let periyot_ms = periyot.num_milliseconds().max(1);
+    let bitis = baslangic + periyot * adet as i32;
+
+    for &(t, _) in ornekler {
+        if t >= baslangic && t < bitis {
+            kovalar[((t - baslangic).num_milliseconds() / periyot_ms) as usize].n += 1;
+        }
+    }
+
+    for (i, &(t0, p0)) in ornekler.iter().enumerate() {
+        let sonraki = ornekler.get(i + 1).copied();
+        match yontem {
+            Yontem::Periyot { cozunurluk } => {
+                // Düzensiz/çakışan örneklerde sonraki örnekte kes (çift sayım olmasın)
+                let t1 = sonraki.map_or(t0 + cozunurluk, |(t, _)| (t0 + cozunurluk).min(t));
+                parca_ekle(&mut kovalar, baslangic, periyot, t0, t1, p0, p0);
+            }
+            Yontem::Trapez { max_bosluk } => {
+                if let Some((t1, p1)) = sonraki
+                    && t1 - t0 <= max_bosluk
+                {
+                    parca_ekle(&mut kovalar, baslangic, periyot, t0, t1, p0, p1);
+                }
+            }
+        }
+    }
+
+    let periyot_sn = saniye(periyot);
+    kovalar
+        .into_iter()
+        .map(|k| SaatEnerji {
+            mwh: (k.sn > 0.0).then_some(k.mwh),
+            kapsama_orani: (k.sn / periyot_sn).min(1.0),
+            n_olcum: k.n,
+        })
+        .collect()
+}
+
+/// [a, b) aralığında gücü pa → pb doğrusal kabul edip periyot kovalarına böler.
+fn parca_ekle(
+    kovalar: &mut [Kova],
+    baslangic: DateTime<Utc>,
+    periyot: Duration,
+    a: DateTime<Utc>,
+    b: DateTime<Utc>,
+    pa: f64,
+    pb: f64,
+) {
+    let toplam = saniye(b - a);
+    if toplam <= 0.0 {
+        return;
+    }
+    let guc = |t: DateTime<Utc>| pa + (pb - pa) * saniye(t - a) / toplam;
+    let periyot_ms = periyot.num_milliseconds().max(1);
+
+    let mut bas = a;
+    while bas < b {
+        let sira = (bas - baslangic).num_milliseconds().div_euclid(periyot_ms);
+        let periyot_sonu = baslangic + Duration::milliseconds((sira + 1) * periyot_ms);
+        let son = periyot_sonu.min(b);
+        if sira >= 0
+            && let Some(k) = kovalar.get_mut(sira as usize)
+        {
+            let sn = saniye(son - bas);
+            k.mwh += (guc(bas) + guc(son)) / 2.0 * sn / 3600.0;
+            k.sn += sn;
+        }
+        bas = son;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn t0() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2025-03-10T10:00:00Z").unwrap().with_timezone(&Utc)
+    }
+
+    fn trapez() -> Yontem {
+        Yontem::santral_icin(None)
+    }
+
+    /// `bas`tan `adim` dakikada bir sabit güç, [bas, bas + sure_dk] uçları dahil.
+    fn sabit(bas: DateTime<Utc>, adim: i64, sure_dk: i64, mw: f64) -> Vec<(DateTime<Utc>, f64)> {
+        (0..=sure_dk / adim).map(|i| (bas + Duration::minutes(i * adim), mw)).collect()
+    }
+
+    fn yakin(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn sabit_guc_her_ornekleme_araliginda_tam_saat() {
+        for adim in [1, 10, 15, 30, 60] {
+            // bir saat önce ve sonra: kenarlar kapansın
+            let o = sabit(t0() - Duration::hours(1), adim, 180, 6.0);
+            let e = saatlik_enerji(&o, t0(), 1, trapez());
+            assert_eq!(e[0].mwh.map(|m| yakin(m, 6.0)), Some(true), "{adim} dk: {:?}", e[0]);
+            assert!(yakin(e[0].kapsama_orani, 1.0), "{adim} dk");
+            assert_eq!(e[0].n_olcum, 60 / adim, "{adim} dk");
+        }
+    }
+
+    #[test]
+    fn kopru_medyan_araliga_gore() {
+        let kopru = |adim: i64| match trapez().orneklere_gore(&sabit(t0(), adim, 600, 1.0)) {
+            Yontem::Trapez { max_bosluk } => max_bosluk,
+            Yontem::Periyot { .. } => unreachable!(),
+        };
+        assert_eq!(kopru(1), MAX_BOSLUK);
+        assert_eq!(kopru(15), Duration::seconds(15 * 90));
+        assert_eq!(kopru(60), Duration::minutes(90));
+        assert_eq!(kopru(120), EN_UZUN_KOPRU);
+        // tek örnek: aralık yok, varsayılan kalır
+        assert!(matches!(
+            trapez().orneklere_gore(&[(t0(), 1.0)]),
+            Yontem::Trapez { max_bosluk } if max_bosluk == MAX_BOSLUK
+        ));
+    }
+
+    #[test]
+    fn duzensiz_ornekler() {
+        // 0 → 12 MW doğrusal rampa, düzensiz damgalar: trapez tam doğru
+        let dk = [0, 3, 11, 12, 25, 31, 44, 52, 60];
+        let o: Vec<_> = dk.iter().map(|&d| (t0() + Duration::minutes(d), d as f64 / 5.0)).collect();
+        let e = saatlik_enerji(&o, t0(), 1, trapez());
+        assert!(yakin(e[0].mwh.unwrap(), 6.0), "{:?}", e[0]);
+        assert!(yakin(e[0].kapsama_orani, 1.0));
+        assert_eq!(e[0].n_olcum, 8);
+    }
+
+    #[test]
+    fn bosluk_koprulenmez() {
+        // 1 dk veri, 10:20–10:50 arası yok: 30 dk > 15 dk köprü
+        let o: Vec<_> = sabit(t0(), 1, 60, 6.0)
+            .into_iter()
+            .filter(|(t, _)| *t <= t0() + Duration::minutes(20) || *t >= t0() + Duration::minutes(50))
+            .collect();
+        let e = saatlik_enerji(&o, t0(), 1, trapez());
+        assert!(yakin(e[0].kapsama_orani, 0.5), "{:?}", e[0]);
+        // enerji tamamlanmaz: yalnızca örtülen yarım saat
+        assert!(yakin(e[0].mwh.unwrap(), 3.0));
+    }
+
+    #[test]
+    fn saatlik_veride_eksik_ornek_bosluktur() {
+        // 60 dk veri, 12:00 okuması yok: 11:00–13:00 köprülenmez
+        let o: Vec<_> = sabit(t0(), 60, 240, 6.0)
+            .into_iter()
+            .filter(|(t, _)| *t != t0() + Duration::hours(2))
+            .collect();
+        let e = saatlik_enerji(&o, t0(), 4, trapez());
+        assert_eq!(e[0].mwh.map(|m| yakin(m, 6.0)), Some(true));
+        assert_eq!((e[1].mwh, e[2].mwh), (None, None));
+        assert_eq!(e[3].mwh.map(|m| yakin(m, 6.0)), Some(true));
+    }
+
+    #[test]
+    fn saat_siniri_bolunur() {
+        // 10:50'de 0 MW, 11:05'te 15 MW: 10. saate 10 dk (0→10), 11. saate 5 dk (10→15)
+        let o = vec![(t0() + Duration::minutes(50), 0.0), (t0() + Duration::minutes(65), 15.0)];
+        let e = saatlik_enerji(&o, t0(), 2, trapez());
+        assert!(yakin(e[0].mwh.unwrap(), 5.0 * 10.0 / 60.0), "{e:?}");
+        assert!(yakin(e[1].mwh.unwrap(), 12.5 * 5.0 / 60.0), "{e:?}");
+        assert!(yakin(e[0].kapsama_orani, 10.0 / 60.0));
+        assert!(yakin(e[1].kapsama_orani, 5.0 / 60.0));
+        assert_eq!((e[0].n_olcum, e[1].n_olcum), (1, 1));
+    }
+
+    #[test]
+    fn ceyrek_saat_kovalari() {
+        let o = sabit(t0(), 1, 60, 4.0);
+        let e = periyot_enerji(&o, t0(), Duration::minutes(15), 4, trapez());
+        assert_eq!(e.len(), 4);
+        for k in &e {
+            assert!(yakin(k.mwh.unwrap(), 1.0), "{k:?}");
+            assert!(yakin(k.kapsama_orani, 1.0));
+        }
+    }
+
+    #[test]
+    fn periyot_yontemi_ortalama_ve_kesme() {
+        let yontem = Yontem::santral_icin(Some(15 * 60));
+        // 10:00 ve 10:15 tam, 10:20 okuması 10:15'inkini keser (çift sayım yok), 10:45 yok
+        let o = vec![
+            (t0(), 8.0),
+            (t0() + Duration::minutes(15), 4.0),
+            (t0() + Duration::minutes(20), 4.0),
+        ];
+        let e = saatlik_enerji(&o, t0(), 1, yontem);
+        // 15 dk × 8 + 5 dk × 4 + 15 dk × 4
+        assert!(yakin(e[0].mwh.unwrap(), (15.0 * 8.0 + 20.0 * 4.0) / 60.0), "{:?}", e[0]);
+        assert!(yakin(e[0].kapsama_orani, 35.0 / 60.0));
+    }
+
+    #[test]
+    fn ornek_yoksa_bos() {
+        let e = saatlik_enerji(&[], t0(), 3, trapez());
+        assert!(e.iter().all(|k| k.mwh.is_none() && k.kapsama_orani == 0.0 && k.n_olcum == 0));
+    }
+
+    #[test]
+    fn ceyrek_ve_saat_kovalari_ayni_enerji() {
+        // 1 dk, düzensiz güç: çeyrek kovaların dörtlü toplamı saat kovasına eşit
+        let o: Vec<_> = (0..=240)
+            .map(|i| (t0() + Duration::minutes(i), 10.0 + (i as f64 / 7.0).sin() * 5.0))
+            .collect();
+        let saat = saatlik_enerji(&o, t0(), 4, trapez());
+        let ceyrek = periyot_enerji(&o, t0(), Duration::minutes(15), 16, trapez());
+        let ceyrek_mwh: Vec<Option<f64>> = ceyrek.iter().map(|k| k.mwh).collect();
+        let toplanan = crate::kgup::donustur(&ceyrek_mwh, 15, 60);
+        for (a, b) in toplanan.iter().zip(&saat) {
+            assert!((a.unwrap() - b.mwh.unwrap()).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn yaz_saati_gununde_92_ve_100_periyot() {
+        for (gun, adet) in [((2015, 3, 29), 92), ((2015, 11, 8), 100)] {
+            let gun = chrono::NaiveDate::from_ymd_opt(gun.0, gun.1, gun.2).unwrap();
+            let bas = crate::piyasa_gunu::gun_baslangici(gun);
+            assert_eq!(crate::kgup::periyot_sayisi(gun, 15), adet);
+            let o = sabit(bas - Duration::minutes(15), 1, 15 * (adet as i64 + 2), 4.0);
+            let e = periyot_enerji(&o, bas, Duration::minutes(15), adet, trapez());
+            assert_eq!(e.len(), adet);
+            assert!(e.iter().all(|k| (k.mwh.unwrap() - 1.0).abs() < 1e-9), "{gun}");
+            let gun_toplami: f64 = e.iter().filter_map(|k| k.mwh).sum();
+            assert!((gun_toplami - adet as f64).abs() < 1e-6);
+        }
+    }
+}
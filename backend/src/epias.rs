@@ -0,0 +1,718 @@
+// backend/src/epias.rs
+//
+// EPİAŞ Şeffaflık Platformu istemcisi ve zamanlanmış senkron.
+// - PTF (GÖP), SMF ve sistem yönü → piyasa_fiyatlari (kaynak "epias")
+// - Santral başına yayımlanan KGÜP (DPP) ve gerçek zamanlı üretim → epias_uretim
+//   (kimlikler `epias_eslemeleri`nde; PUT /api/santral/{id}/epias)
+// - Her veri seti `epias_senkron`daki imleçten devam eder; ilk çalıştırmada
+//   EPIAS_BASLANGIC_GUN gün geriden başlar, EPIAS_PARCA_GUN'lük parçalarla ilerler
+// - Ağ hatası, 429 ve 5xx üstel beklemeyle EPIAS_DENEME kez denenir; 401'de TGT yenilenir
+// - Henüz yayımlanmamış saatler (boş / yarım cevap) imleci ilerletmez;
+//   iki günden eski boş parça atlanır (santral o dönemde yayın yapmamış olabilir)
+//
+// Kimlik: EPIAS_KULLANICI / EPIAS_SIFRE verilirse EPIAS_GIRIS_URL'den TGT alınır ve
+// her isteğe `TGT` başlığı eklenir (2 saat geçerli; 90 dakikada bir yenilenir).
+//
+// Ortam değişkenleri:
+//   EPIAS_AKTIF=true                (zamanlanmış senkron; varsayılan kapalı)
+//   EPIAS_TEMEL_URL=https://seffaflik.epias.com.tr/electricity-service
+//   EPIAS_GIRIS_URL=https://giris.epias.com.tr/cas/v1/tickets
+//   EPIAS_KULLANICI / EPIAS_SIFRE
+//   EPIAS_ARALIK_DAKIKA=60  EPIAS_BASLANGIC_GUN=30  EPIAS_PARCA_GUN=7
+//   EPIAS_DENEME=4  EPIAS_BEKLEME_MS=500  EPIAS_TIMEOUT_SN=30
+//
+// Yerel deneme: `cargo run --bin epias_mock` (127.0.0.1:8090) ve
+//   EPIAS_TEMEL_URL=http://127.0.0.1:8090/electricity-service
+//   EPIAS_GIRIS_URL=http://127.0.0.1:8090/cas/v1/tickets  EPIAS_KULLANICI=mock  EPIAS_SIFRE=mock
+
+use std::collections::HashMap;
+use std::env;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, FixedOffset, NaiveDate, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db;
+use crate::piyasa::{FiyatSatiri, SistemYonu};
+use crate::piyasa_gunu;
+
+/// TGT bu süreden eskiyse yeniden alınır (platform 2 saat geçerli sayar).
+const TGT_OMRU: Duration = Duration::from_secs(90 * 60);
+/// Bir turda veri seti başına en fazla parça (geri doldurma sonraki turlarda sürer).
+const TUR_BASINA_PARCA: usize = 60;
+
+const PTF_YOLU: &str = "/v1/markets/dam/data/mcp";
+const SMF_YOLU: &str = "/v1/markets/bpm/data/system-marginal-price";
+const YON_YOLU: &str = "/v1/markets/bpm/data/system-direction";
+const KGUP_YOLU: &str = "/v1/generation/data/dpp";
+const GZU_YOLU: &str = "/v1/generation/data/realtime-generation";
+
+#[derive(Debug, Clone)]
+pub struct EpiasConfig {
+    pub aktif: bool,
+    pub temel_url: String,
+    pub giris_url: String,
+    pub kullanici: Option<String>,
+    pub sifre: Option<String>,
+    pub aralik_dakika: u64,
+    pub baslangic_gun: i64,
+    pub parca_gun: i64,
+    pub deneme: u32,
+    pub bekleme_ms: u64,
+    pub timeout_sn: u64,
+}
+
+fn env_sayi<T: std::str::FromStr>(ad: &str, varsayilan: T) -> T {
+    env::var(ad).ok().and_then(|v| v.trim().parse().ok()).unwrap_or(varsayilan)
+}
+
+impl EpiasConfig {
+    pub fn from_env() -> Self {
+        let aktif = env::var("EPIAS_AKTIF")
+            .map(|v| matches!(v.trim(), "1" | "true" | "evet"))
+            .unwrap_or(false);
+        Self {
+            aktif,
+            temel_url: env::var("EPIAS_TEMEL_URL")
+                .unwrap_or_else(|_| "https://seffaflik.epias.com.tr/electricity-service".into())
+                .trim_end_matches('/')
+                .to_string(),
+            giris_url: env::var("EPIAS_GIRIS_URL")
+                .unwrap_or_else(|_| "https://giris.epias.com.tr/cas/v1/tickets".into()),
+            kullanici: env::var("EPIAS_KULLANICI").ok().filter(|v| !v.is_empty()),
+            sifre: env::var("EPIAS_SIFRE").ok().filter(|v| !v.is_empty()),
+            aralik_dakika: env_sayi("EPIAS_ARALIK_DAKIKA", 60u64).max(1),
+            baslangic_gun: env_sayi("EPIAS_BASLANGIC_GUN", 30i64).max(1),
+            parca_gun: env_sayi("EPIAS_PARCA_GUN", 7i64).clamp(1, 31),
+            deneme: env_sayi("EPIAS_DENEME", 4u32).max(1),
+            bekleme_ms: env_sayi("EPIAS_BEKLEME_MS", 500u64),
+            timeout_sn: env_sayi("EPIAS_TIMEOUT_SN", 30u64).max(1),
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// İSTEMCİ
+// -----------------------------------------------------------------------------
+
+#[derive(Debug)]
+pub enum EpiasHata {
+    Ag(reqwest::Error),
+    /// HTTP durum kodu + gövdenin başı.
+    Durum(u16, String),
+    Cevap(String),
+    Db(sqlx::Error),
+}
+
+impl EpiasHata {
+    /// Ağ hatası, 429 ve 5xx geçici sayılır.
+    fn gecici(&self) -> bool {
+        match self {
+            EpiasHata::Ag(_) => true,
+            EpiasHata::Durum(kod, _) => *kod == 429 || *kod >= 500,
+            EpiasHata::Cevap(_) | EpiasHata::Db(_) => false,
+        }
+    }
+}
+
+impl fmt::Display for EpiasHata {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EpiasHata::Ag(e) => write!(f, "ağ hatası: {e}"),
+            EpiasHata::Durum(kod, govde) => write!(f, "HTTP {kod}: {govde}"),
+            EpiasHata::Cevap(e) => write!(f, "cevap çözülemedi: {e}"),
+            EpiasHata::Db(e) => write!(f, "veritabanı: {e}"),
+        }
+    }
+}
+
+impl From<sqlx::Error> for EpiasHata {
+    fn from(e: sqlx::Error) -> Self {
+        EpiasHata::Db(e)
+    }
+}
+
+#[derive(Deserialize)]
+struct Cevap<T> {
+    items: Vec<T>,
+}
+
+#[derive(Deserialize)]
+struct PtfKalemi {
+    date: DateTime<FixedOffset>,
+    price: f64,
+}
+
+#[derive(Deserialize)]
+struct SmfKalemi {
+    date: DateTime<FixedOffset>,
+    #[serde(rename = "systemMarginalPrice")]
+    smf: Option<f64>,
+}
+
+#[derive(Deserialize)]
+struct YonKalemi {
+    date: DateTime<FixedOffset>,
+    #[serde(rename = "systemDirection")]
+    yon: String,
+}
+
+#[derive(Deserialize)]
+struct KgupKalemi {
+    date: DateTime<FixedOffset>,
+    toplam: f64,
+}
+
+#[derive(Deserialize)]
+struct GzuKalemi {
+    date: DateTime<FixedOffset>,
+    total: f64,
+}
+
+/// Platformun sistem yönü etiketi ("Enerji Açığı", "Enerji Fazlası", "Dengede").
+fn yon_coz(s: &str) -> Option<SistemYonu> {
+    let s = s.to_lowercase();
+    if s.contains("aç") || s.contains("acig") || s.contains("deficit") {
+        Some(SistemYonu::EnerjiAcigi)
+    } else if s.contains("fazla") || s.contains("surplus") {
+        Some(SistemYonu::EnerjiFazlasi)
+    } else if s.contains("denge") || s.contains("balance") {
+        Some(SistemYonu::Dengede)
+    } else {
+        None
+    }
+}
+
+/// [bas, bit) teslim günleri için istek gövdesi (platformda bitiş günü dahildir).
+fn tarih_araligi(bas: NaiveDate, bit: NaiveDate) -> serde_json::Value {
+    let son = bit.pred_opt().unwrap_or(bas).max(bas);
+    serde_json::json!({
+        "startDate": piyasa_gunu::yerel(piyasa_gunu::gun_baslangici(bas)).to_rfc3339(),
+        "endDate": piyasa_gunu::yerel(piyasa_gunu::gun_baslangici(son)).to_rfc3339(),
+    })
+}
+
+pub struct EpiasIstemci {
+    http: reqwest::Client,
+    cfg: EpiasConfig,
+    tgt: tokio::sync::Mutex<Option<(String, Instant)>>,
+}
+
+impl EpiasIstemci {
+    pub fn new(cfg: EpiasConfig) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(cfg.timeout_sn))
+            .build()
+            .unwrap_or_default();
+        Self { http, cfg, tgt: tokio::sync::Mutex::new(None) }
+    }
+
+    pub fn cfg(&self) -> &EpiasConfig {
+        &self.cfg
+    }
+
+    /// Geçerli TGT; kullanıcı tanımlı değilse `None` (kimliksiz istek).
+    async fn tgt(&self) -> Result<Option<String>, EpiasHata> {
+        let (Some(kullanici), Some(sifre)) = (&self.cfg.kullanici, &self.cfg.sifre) else {
+            return Ok(None);
+        };
+        let mut tgt = self.tgt.lock().await;
+        if let Some((t, alinma)) = tgt.as_ref()
+            && alinma.elapsed() < TGT_OMRU
+        {
+            return Ok(Some(t.clone()));
+        }
+
+        let r = self
+            .http
+            .post(&self.cfg.giris_url)
+            .header(reqwest::header::ACCEPT, "text/plain")
+            .form(&[("username", kullanici.as_str()), ("password", sifre.as_str())])
+            .send()
+            .await
+            .map_err(EpiasHata::Ag)?;
+        let kod = r.status();
+        let govde = r.text().await.map_err(EpiasHata::Ag)?;
+        if !kod.is_success() {
+            return Err(EpiasHata::Durum(kod.as_u16(), govde.chars().take(200).collect()));
+        }
+        let t = govde.trim().to_string();
+        if t.is_empty() {
+            return Err(EpiasHata::Cevap("boş TGT".into()));
+        }
+        *tgt = Some((t.clone(), Instant::now()));
+        Ok(Some(t))
+    }
+
+    async fn tek_istek<T: DeserializeOwned>(
+        &self,
+        yol: &str,
+        govde: &serde_json::Value,
+    ) -> Result<Vec<T>, EpiasHata> {
+        let mut istek = self.http.post(format!("{}{}", self.cfg.temel_url, yol)).json(govde);
+        if let Some(t) = self.tgt().await? {
+            istek = istek.header("TGT", t);
+        }
+        let r = istek.send().await.map_err(EpiasHata::Ag)?;
+        let kod = r.status();
+        if !kod.is_success() {
+            let metin = r.text().await.unwrap_or_default();
+            return Err(EpiasHata::Durum(kod.as_u16(), metin.chars().take(200).collect()));
+        }
+        r.json::<Cevap<T>>()
+            .await
+            .map(|c| c.items)
+            .map_err(|e| EpiasHata::Cevap(e.to_string()))
+    }
+
+    /// Geçici hatalarda üstel beklemeyle yeniden dener; 401'de TGT bir kez yenilenir.
+    async fn istek<T: DeserializeOwned>(
+        &self,
+        yol: &str,
+        govde: serde_json::Value,
+    ) -> Result<Vec<T>, EpiasHata> {
+        let mut bekleme = Duration::from_millis(self.cfg.bekleme_ms);
+        let mut deneme = 0;
+        let mut tgt_yenilendi = false;
+        loop {
+            let hata = match self.tek_istek(yol, &govde).await {
+                Ok(v) => return Ok(v),
+                Err(h) => h,
+            };
+            if matches!(hata, EpiasHata::Durum(401, _)) && self.cfg.kullanici.is_some() && !tgt_yenilendi {
+                tgt_yenilendi = true;
+                *self.tgt.lock().await = None;
+                continue;
+            }
+            deneme += 1;
+            if !hata.gecici() || deneme >= self.cfg.deneme {
+                return Err(hata);
+            }
+            log::warn!("EPİAŞ {yol} deneme {deneme}/{} başarısız: {hata}", self.cfg.deneme);
+            tokio::time::sleep(bekleme).await;
+            bekleme *= 2;
+        }
+    }
+
+    /// Saatlik PTF (TL/MWh), [bas, bit) teslim günleri.
+    pub async fn ptf(&self, bas: NaiveDate, bit: NaiveDate) -> Result<Vec<(DateTime<Utc>, f64)>, EpiasHata> {
+        let k: Vec<PtfKalemi> = self.istek(PTF_YOLU, tarih_araligi(bas, bit)).await?;
+        Ok(k.into_iter().map(|k| (k.date.with_timezone(&Utc), k.price)).collect())
+    }
+
+    /// Saatlik SMF (TL/MWh); henüz kesinleşmemiş saatler atlanır.
+    pub async fn smf(&self, bas: NaiveDate, bit: NaiveDate) -> Result<Vec<(DateTime<Utc>, f64)>, EpiasHata> {
+        let k: Vec<SmfKalemi> = self.istek(SMF_YOLU, tarih_araligi(bas, bit)).await?;
+        Ok(k.into_iter()
+            .filter_map(|k| Some((k.date.with_timezone(&Utc), k.smf?)))
+            .collect())
+    }
+
+    pub async fn sistem_yonu(
+        &self,
+        bas: NaiveDate,
+        bit: NaiveDate,
+    ) -> Result<Vec<(DateTime<Utc>, SistemYonu)>, EpiasHata> {
+        let k: Vec<YonKalemi> = self.istek(YON_YOLU, tarih_araligi(bas, bit)).await?;
+        Ok(k.into_iter()
+            .filter_map(|k| Some((k.date.with_timezone(&Utc), yon_coz(&k.yon)?)))
+            .collect())
+    }
+
+    /// UEVÇB'nin yayımlanan saatlik KGÜP'ü (MWh).
+    pub async fn kgup(
+        &self,
+        organizasyon_id: i64,
+        uevcb_id: i64,
+        bas: NaiveDate,
+        bit: NaiveDate,
+    ) -> Result<Vec<(DateTime<Utc>, f64)>, EpiasHata> {
+        let mut govde = tarih_araligi(bas, bit);
+        govde["region"] = "TR1".into();
+        govde["organizationId"] = organizasyon_id.into();
+        govde["uevcbId"] = uevcb_id.into();
+        let k: Vec<KgupKalemi> = self.istek(KGUP_YOLU, govde).await?;
+        Ok(k.into_iter().map(|k| (k.date.with_timezone(&Utc), k.toplam)).collect())
+    }
+
+    /// Santralin saatlik gerçek zamanlı üretimi (MWh).
+    pub async fn gercek_zamanli(
+        &self,
+        santral_kodu: i64,
+        bas: NaiveDate,
+        bit: NaiveDate,
+    ) -> Result<Vec<(DateTime<Utc>, f64)>, EpiasHata> {
+        let mut govde = tarih_araligi(bas, bit);
+        govde["powerPlantId"] = santral_kodu.into();
+        let k: Vec<GzuKalemi> = self.istek(GZU_YOLU, govde).await?;
+        Ok(k.into_iter().map(|k| (k.date.with_timezone(&Utc), k.total)).collect())
+    }
+}
+
+// -----------------------------------------------------------------------------
+// SENKRON
+// -----------------------------------------------------------------------------
+
+#[derive(Debug, Clone)]
+enum VeriSeti {
+    Ptf,
+    Smf,
+    Kgup { santral_id: Uuid, organizasyon_id: i64, uevcb_id: i64 },
+    GercekZamanli { santral_id: Uuid, santral_kodu: i64 },
+}
+
+impl VeriSeti {
+    fn anahtar(&self) -> String {
+        match self {
+            VeriSeti::Ptf => "ptf".into(),
+            VeriSeti::Smf => "smf".into(),
+            VeriSeti::Kgup { santral_id, .. } => format!("kgup:{santral_id}"),
+            VeriSeti::GercekZamanli { santral_id, .. } => format!("gzu:{santral_id}"),
+        }
+    }
+
+    /// Hariç bitiş günü: PTF ve KGÜP gün öncesinde yayımlanır, yarını da kapsar.
+    fn hedef(&self, bugun: NaiveDate) -> NaiveDate {
+        match self {
+            VeriSeti::Ptf | VeriSeti::Kgup { .. } => bugun + chrono::Days::new(2),
+            VeriSeti::Smf | VeriSeti::GercekZamanli { .. } => bugun + chrono::Days::new(1),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VeriSetiOzeti {
+    pub anahtar: String,
+    pub parca: usize,
+    pub yazilan: u64,
+    /// Tur sonundaki imleç (bu saate kadar alındı).
+    pub son_saat: Option<DateTime<Utc>>,
+    pub hata: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TurOzeti {
+    pub baslangic: DateTime<Utc>,
+    pub bitis: DateTime<Utc>,
+    pub veri_setleri: Vec<VeriSetiOzeti>,
+}
+
+/// İstemci + tur kilidi + son tur özeti (zamanlayıcı ve admin ucu paylaşır).
+pub struct Epias {
+    pub istemci: EpiasIstemci,
+    calisiyor: tokio::sync::Mutex<()>,
+    son_tur: Mutex<Option<TurOzeti>>,
+}
+
+/// GET /api/admin/epias/durum cevabı.
+#[derive(Debug, Serialize)]
+pub struct EpiasDurumu {
+    pub aktif: bool,
+    pub temel_url: String,
+    pub kimlikli: bool,
+    pub aralik_dakika: u64,
+    pub son_tur: Option<TurOzeti>,
+    pub senkronlar: Vec<crate::models::EpiasSenkron>,
+}
+
+impl Epias {
+    pub fn new(cfg: EpiasConfig) -> Self {
+        Self {
+            istemci: EpiasIstemci::new(cfg),
+            calisiyor: tokio::sync::Mutex::new(()),
+            son_tur: Mutex::new(None),
+        }
+    }
+
+    pub fn son_tur(&self) -> Option<TurOzeti> {
+        self.son_tur.lock().ok().and_then(|s| s.clone())
+    }
+
+    pub async fn durum(&self, pool: &PgPool) -> Result<EpiasDurumu, sqlx::Error> {
+        let cfg = self.istemci.cfg();
+        Ok(EpiasDurumu {
+            aktif: cfg.aktif,
+            temel_url: cfg.temel_url.clone(),
+            kimlikli: cfg.kullanici.is_some(),
+            aralik_dakika: cfg.aralik_dakika,
+            son_tur: self.son_tur(),
+            senkronlar: db::list_epias_senkron(pool).await?,
+        })
+    }
+
+    /// Başka tur sürüyorsa `None`.
+    pub async fn tur_dene(&self, pool: &PgPool) -> Option<TurOzeti> {
+        let _kilit = self.calisiyor.try_lock().ok()?;
+        Some(self.tur_calistir(pool).await)
+    }
+
+    async fn tur(&self, pool: &PgPool) -> TurOzeti {
+        let _kilit = self.calisiyor.lock().await;
+        self.tur_calistir(pool).await
+    }
+
+    async fn tur_calistir(&self, pool: &PgPool) -> TurOzeti {
+        let baslangic = Utc::now();
+        // Fiyat önce: SMF yalnızca PTF'si kayıtlı saatlere yazılır
+        let mut setler = vec![VeriSeti::Ptf, VeriSeti::Smf];
+        match db::list_aktif_epias_eslemeleri(pool).await {
+            Ok(eslemeler) => {
+                for e in eslemeler {
+                    if let (Some(organizasyon_id), Some(uevcb_id)) = (e.organizasyon_id, e.uevcb_id) {
+                        setler.push(VeriSeti::Kgup { santral_id: e.santral_id, organizasyon_id, uevcb_id });
+                    }
+                    if let Some(santral_kodu) = e.santral_kodu {
+                        setler.push(VeriSeti::GercekZamanli { santral_id: e.santral_id, santral_kodu });
+                    }
+                }
+            }
+            Err(e) => log::error!("EPİAŞ eşleme okuma hata: {e}"),
+        }
+
+        let mut veri_setleri = Vec::with_capacity(setler.len());
+        for vs in &setler {
+            let ozet = self.veri_seti(pool, vs).await;
+            if let Some(h) = &ozet.hata {
+                log::warn!("EPİAŞ {} senkron hata: {h}", ozet.anahtar);
+            }
+            veri_setleri.push(ozet);
+        }
+
+        let ozet = TurOzeti { baslangic, bitis: Utc::now(), veri_setleri };
+        if let Ok(mut s) = self.son_tur.lock() {
+            *s = Some(ozet.clone());
+        }
+        ozet
+    }
+
+    /// İmleçten hedefe kadar parça parça ilerler; yarım kalan parçada durur.
+    async fn veri_seti(&self, pool: &PgPool, vs: &VeriSeti) -> VeriSetiOzeti {
+        let anahtar = vs.anahtar();
+        let mut ozet = VeriSetiOzeti { anahtar: anahtar.clone(), parca: 0, yazilan: 0, son_saat: None, hata: None };
+
+        let bugun = piyasa_gunu::bugun();
+        let hedef = vs.hedef(bugun);
+        let imlec = match db::get_epias_senkron(pool, &anahtar).await {
+            Ok(s) => s.and_then(|s| s.son_saat),
+            Err(e) => {
+                ozet.hata = Some(e.to_string());
+                return ozet;
+            }
+        };
+        ozet.son_saat = imlec;
+        let mut bas = imlec
+            .map(piyasa_gunu::piyasa_gunu)
+            .unwrap_or(bugun - chrono::Days::new(self.istemci.cfg.baslangic_gun as u64));
+
+        for _ in 0..TUR_BASINA_PARCA {
+            if bas >= hedef {
+                break;
+            }
+            let bit = (bas + chrono::Days::new(self.istemci.cfg.parca_gun as u64)).min(hedef);
+            let (son, yazilan) = match self.parca(pool, vs, bas, bit).await {
+                Ok(r) => r,
+                Err(h) => {
+                    let mesaj = h.to_string();
+                    if let Err(e) = db::epias_senkron_hata(pool, &anahtar, &mesaj).await {
+                        log::error!("EPİAŞ senkron durumu yazılamadı: {e}");
+                    }
+                    ozet.hata = Some(mesaj);
+                    break;
+                }
+            };
+
+            let parca_sonu = piyasa_gunu::gun_baslangici(bit);
+            let yeni = match son {
+                Some(t) => Some(t + chrono::Duration::hours(1)),
+                // Eski dönem boşsa yayın yoktur; yeni dönem boşsa henüz yayımlanmamıştır
+                None if bit < bugun - chrono::Days::new(1) => Some(parca_sonu),
+                None => None,
+            };
+            if let Err(e) = db::epias_senkron_basari(pool, &anahtar, yeni).await {
+                ozet.hata = Some(e.to_string());
+                break;
+            }
+            ozet.parca += 1;
+            ozet.yazilan += yazilan;
+            if yeni.is_some() {
+                ozet.son_saat = yeni.max(ozet.son_saat);
+            }
+            if yeni.is_none_or(|t| t < parca_sonu) {
+                break;
+            }
+            bas = bit;
+        }
+        ozet
+    }
+
+    /// Tek parça: indir ve yaz. Dönen: (yazılan son saat, yazılan satır).
+    async fn parca(
+        &self,
+        pool: &PgPool,
+        vs: &VeriSeti,
+        bas: NaiveDate,
+        bit: NaiveDate,
+    ) -> Result<(Option<DateTime<Utc>>, u64), EpiasHata> {
+        let alt = piyasa_gunu::gun_baslangici(bas);
+        let ust = piyasa_gunu::gun_baslangici(bit);
+        let aralikta = |t: &DateTime<Utc>| *t >= alt && *t < ust;
+
+        match vs {
+            VeriSeti::Ptf => {
+                let satirlar: Vec<FiyatSatiri> = self
+                    .istemci
+                    .ptf(bas, bit)
+                    .await?
+                    .into_iter()
+                    .filter(|(t, _)| aralikta(t))
+                    .map(|(saat, ptf_tl)| FiyatSatiri { saat, ptf_tl, smf_tl: None, sistem_yonu: None })
+                    .collect();
+                let son = satirlar.iter().map(|f| f.saat).max();
+                let (eklenen, guncellenen) = db::upsert_piyasa_fiyatlari(pool, &satirlar, "epias", None).await?;
+                Ok((son, eklenen + guncellenen))
+            }
+            VeriSeti::Smf => {
+                let yonler: HashMap<DateTime<Utc>, SistemYonu> =
+                    self.istemci.sistem_yonu(bas, bit).await?.into_iter().collect();
+                let smf: Vec<(DateTime<Utc>, f64)> =
+                    self.istemci.smf(bas, bit).await?.into_iter().filter(|(t, _)| aralikta(t)).collect();
+                let saatler: Vec<DateTime<Utc>> = smf.iter().map(|(t, _)| *t).collect();
+                let degerler: Vec<f64> = smf.iter().map(|(_, v)| *v).collect();
+                let yon: Vec<Option<String>> = saatler
+                    .iter()
+                    .map(|t| yonler.get(t).map(|y| y.as_str().to_string()))
+                    .collect();
+                let yazilan = db::smf_guncelle(pool, &saatler, &degerler, &yon, "epias").await?;
+                Ok((yazilan.iter().max().copied(), yazilan.len() as u64))
+            }
+            VeriSeti::Kgup { santral_id, organizasyon_id, uevcb_id } => {
+                let k: Vec<(DateTime<Utc>, f64)> = self
+                    .istemci
+                    .kgup(*organizasyon_id, *uevcb_id, bas, bit)
+                    .await?
+                    .into_iter()
+                    .filter(|(t, _)| aralikta(t))
+                    .collect();
+                let (saatler, mwh): (Vec<_>, Vec<_>) = k.into_iter().unzip();
+                let yazilan = db::upsert_epias_kgup(pool, *santral_id, &saatler, &mwh).await?;
+                Ok((saatler.iter().max().copied(), yazilan))
+            }
+            VeriSeti::GercekZamanli { santral_id, santral_kodu } => {
+                let k: Vec<(DateTime<Utc>, f64)> = self
+                    .istemci
+                    .gercek_zamanli(*santral_kodu, bas, bit)
+                    .await?
+                    .into_iter()
+                    .filter(|(t, _)| aralikta(t))
+                    .collect();
+                let (saatler, mwh): (Vec<_>, Vec<_>) = k.into_iter().unzip();
+                let yazilan = db::upsert_epias_gercek_zamanli(pool, *santral_id, &saatler, &mwh).await?;
+                Ok((saatler.iter().max().copied(), yazilan))
+            }
+        }
+    }
+}
+
+/// Zamanlanmış senkron görevi (EPIAS_AKTIF=true ise).
+pub fn baslat(pool: PgPool, epias: Arc<Epias>) {
+    actix_web::rt::spawn(async move {
+        let dakika = epias.istemci.cfg().aralik_dakika;
+        log::info!("EPİAŞ senkronu başladı ({} dk, {})", dakika, epias.istemci.cfg().temel_url);
+        let mut interval = tokio::time::interval(Duration::from_secs(dakika * 60));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            interval.tick().await;
+            let ozet = epias.tur(&pool).await;
+            let yazilan: u64 = ozet.veri_setleri.iter().map(|v| v.yazilan).sum();
+            let hatali = ozet.veri_setleri.iter().filter(|v| v.hata.is_some()).count();
+            log::info!("EPİAŞ turu bitti: {yazilan} satır, {hatali} hatalı veri seti");
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{web, App, HttpServer};
+
+    use crate::epias_mock::MockDurum;
+
+    /// Mock sunucuyu rastgele portta başlatır, temel adresini döner.
+    fn mock_sunucu(hata_her: u64) -> String {
+        let durum = web::Data::new(MockDurum::new(hata_her));
+        let srv = HttpServer::new(move || {
+            App::new().app_data(durum.clone()).configure(crate::epias_mock::yapilandir)
+        })
+        .workers(1)
+        .bind(("127.0.0.1", 0))
+        .expect("mock bind");
+        let port = srv.addrs()[0].port();
+        actix_web::rt::spawn(srv.run());
+        format!("http://127.0.0.1:{port}")
+    }
+
+    fn cfg(adres: &str, kimlikli: bool) -> EpiasConfig {
+        EpiasConfig {
+            aktif: false,
+            temel_url: format!("{adres}/electricity-service"),
+            giris_url: format!("{adres}/cas/v1/tickets"),
+            kullanici: kimlikli.then(|| "mock".into()),
+            sifre: kimlikli.then(|| "mock".into()),
+            aralik_dakika: 60,
+            baslangic_gun: 30,
+            parca_gun: 7,
+            deneme: 4,
+            bekleme_ms: 10,
+            timeout_sn: 5,
+        }
+    }
+
+    fn gun() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 8, 1).unwrap()
+    }
+
+    #[actix_web::test]
+    async fn ptf_gun_basina_24_saat() {
+        let istemci = EpiasIstemci::new(cfg(&mock_sunucu(0), true));
+        let ptf = istemci.ptf(gun(), gun() + chrono::Days::new(2)).await.unwrap();
+        assert_eq!(ptf.len(), 48);
+        assert_eq!(ptf[0].0, piyasa_gunu::gun_baslangici(gun()));
+        assert_eq!(ptf[0].1, crate::epias_mock::ptf(gun(), 0));
+        assert!(ptf.windows(2).all(|w| w[1].0 - w[0].0 == chrono::Duration::hours(1)));
+    }
+
+    #[actix_web::test]
+    async fn gecici_hata_yeniden_denenir() {
+        // Her ikinci istek 503: ilk deneme başarılı, ikinci çağrı bir kez yeniden dener
+        let istemci = EpiasIstemci::new(cfg(&mock_sunucu(2), true));
+        let yon = istemci.sistem_yonu(gun(), gun() + chrono::Days::new(1)).await.unwrap();
+        let smf = istemci.smf(gun(), gun() + chrono::Days::new(1)).await.unwrap();
+        assert_eq!(yon.len(), 24);
+        assert_eq!(smf.len(), 24);
+        assert!(yon.iter().any(|(_, y)| *y == SistemYonu::EnerjiAcigi));
+        assert!(yon.iter().any(|(_, y)| *y == SistemYonu::EnerjiFazlasi));
+    }
+
+    #[actix_web::test]
+    async fn kimliksiz_istek_reddedilir() {
+        let istemci = EpiasIstemci::new(cfg(&mock_sunucu(0), false));
+        match istemci.ptf(gun(), gun() + chrono::Days::new(1)).await {
+            Err(EpiasHata::Durum(401, _)) => {}
+            r => panic!("401 bekleniyordu: {r:?}"),
+        }
+    }
+
+    #[test]
+    fn sistem_yonu_etiketleri() {
+        assert_eq!(yon_coz("Enerji Açığı"), Some(SistemYonu::EnerjiAcigi));
+        assert_eq!(yon_coz("ENERJİ AÇIĞI"), Some(SistemYonu::EnerjiAcigi));
+        assert_eq!(yon_coz("ENERJİ FAZLASI"), Some(SistemYonu::EnerjiFazlasi));
+        assert_eq!(yon_coz("Dengede"), Some(SistemYonu::Dengede));
+        assert_eq!(yon_coz("?"), None);
+    }
+}
@@ -0,0 +1,197 @@
+// backend/src/epias_mock.rs
+//
+// EPİAŞ Şeffaflık Platformu yerel taklidi (geliştirme ve `epias` testleri).
+// `cargo run --bin epias_mock` → 127.0.0.1:8090 (EPIAS_MOCK_PORT)
+// - POST /cas/v1/tickets (username, password) → 201 + TGT
+// - POST /electricity-service/v1/... → { "items": [...] }; `TGT` başlığı zorunlu (yoksa 401)
+// - Değerler teslim günü ve saatten türetilir (deterministik)
+// - PTF ve KGÜP yarının sonuna kadar, SMF / sistem yönü / gerçek zamanlı üretim
+//   içinde bulunulan saatten öncesine kadar yayımlanmış sayılır
+// - EPIAS_MOCK_HATA_HER=N: her N. veri isteği 503 döner (yeniden deneme denemesi)
+//
+// Bağımsız derlenir (bin hedefi `#[path]` ile içerir): yalnızca dış crate'lere dayanır.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveDate, NaiveTime, TimeZone, Timelike, Utc};
+use chrono_tz::Europe::Istanbul;
+use serde::Deserialize;
+use serde_json::json;
+
+pub const TGT: &str = "TGT-1-mock";
+
+pub struct MockDurum {
+    hata_her: u64,
+    istek: AtomicU64,
+}
+
+impl MockDurum {
+    pub fn new(hata_her: u64) -> Self {
+        Self { hata_her, istek: AtomicU64::new(0) }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Istek {
+    start_date: DateTime<FixedOffset>,
+    end_date: DateTime<FixedOffset>,
+    #[serde(default)]
+    uevcb_id: Option<i64>,
+    #[serde(default)]
+    power_plant_id: Option<i64>,
+}
+
+/// Sahte PTF: gün ve saate göre artan basamaklar.
+pub fn ptf(gun: NaiveDate, saat: u32) -> f64 {
+    1500.0 + 100.0 * saat as f64 + gun.day() as f64
+}
+
+/// Çift saatler enerji açığı (SMF > PTF), tek saatler fazlası, 12:00 dengede.
+fn smf_ve_yon(gun: NaiveDate, saat: u32) -> (f64, &'static str) {
+    let p = ptf(gun, saat);
+    match saat {
+        12 => (p, "Dengede"),
+        s if s.is_multiple_of(2) => ((p * 1.1 * 100.0).round() / 100.0, "Enerji Açığı"),
+        _ => ((p * 0.9 * 100.0).round() / 100.0, "Enerji Fazlası"),
+    }
+}
+
+/// Gündüz çan eğrisi; UEVÇB / santral kimliği ölçeği belirler.
+fn uretim(kimlik: i64, saat: u32) -> f64 {
+    let kurulu = (kimlik.rem_euclid(50) + 10) as f64;
+    let x = (saat as f64 - 12.5) / 4.0;
+    (kurulu * (-x * x).exp() * 1000.0).round() / 1000.0
+}
+
+/// İstek aralığındaki (bitiş günü dahil) yerel saat başları, `yayin` öncesi.
+fn saatler(i: &Istek, yayin: DateTime<Utc>) -> Vec<(NaiveDate, u32, DateTime<FixedOffset>)> {
+    let bas = i.start_date.with_timezone(&Istanbul).date_naive();
+    let son = i.end_date.with_timezone(&Istanbul).date_naive();
+    let mut out = Vec::new();
+    let mut gun = bas;
+    while gun <= son {
+        let mut t = Istanbul
+            .from_local_datetime(&gun.and_time(NaiveTime::MIN))
+            .earliest()
+            .map(|t| t.with_timezone(&Utc));
+        while let Some(u) = t {
+            let yerel = u.with_timezone(&Istanbul);
+            if yerel.date_naive() != gun || u >= yayin {
+                break;
+            }
+            out.push((gun, yerel.hour(), yerel.fixed_offset()));
+            t = Some(u + Duration::hours(1));
+        }
+        gun = match gun.succ_opt() {
+            Some(g) => g,
+            None => break,
+        };
+    }
+    out
+}
+
+fn yarin_sonu() -> DateTime<Utc> {
+    let gun = Utc::now().with_timezone(&Istanbul).date_naive() + chrono::Days::new(2);
+    Istanbul
+        .from_local_datetime(&gun.and_time(NaiveTime::MIN))
+        .earliest()
+        .map(|t| t.with_timezone(&Utc))
+        .unwrap_or_else(Utc::now)
+}
+
+fn saat_basi() -> DateTime<Utc> {
+    let n = Utc::now();
+    DateTime::from_timestamp(n.timestamp() - n.timestamp().rem_euclid(3600), 0).unwrap_or(n)
+}
+
+/// TGT ve hata enjeksiyonu kontrolü.
+fn kontrol(req: &HttpRequest, durum: &MockDurum) -> Option<HttpResponse> {
+    if req.headers().get("TGT").and_then(|v| v.to_str().ok()) != Some(TGT) {
+        return Some(HttpResponse::Unauthorized().body("TGT gecersiz"));
+    }
+    let n = durum.istek.fetch_add(1, Ordering::Relaxed) + 1;
+    if durum.hata_her > 0 && n.is_multiple_of(durum.hata_her) {
+        return Some(HttpResponse::ServiceUnavailable().body("gecici hata"));
+    }
+    None
+}
+
+async fn tgt(form: web::Form<std::collections::HashMap<String, String>>) -> HttpResponse {
+    if form.get("username").is_none_or(|u| u.is_empty()) || form.get("password").is_none() {
+        return HttpResponse::Unauthorized().body("kimlik eksik");
+    }
+    HttpResponse::Created().content_type("text/plain").body(TGT)
+}
+
+async fn mcp(req: HttpRequest, d: web::Data<MockDurum>, i: web::Json<Istek>) -> HttpResponse {
+    if let Some(r) = kontrol(&req, &d) {
+        return r;
+    }
+    let items: Vec<_> = saatler(&i, yarin_sonu())
+        .into_iter()
+        .map(|(g, s, t)| json!({ "date": t.to_rfc3339(), "hour": format!("{s:02}:00"), "price": ptf(g, s) }))
+        .collect();
+    HttpResponse::Ok().json(json!({ "items": items }))
+}
+
+async fn smf(req: HttpRequest, d: web::Data<MockDurum>, i: web::Json<Istek>) -> HttpResponse {
+    if let Some(r) = kontrol(&req, &d) {
+        return r;
+    }
+    let items: Vec<_> = saatler(&i, saat_basi())
+        .into_iter()
+        .map(|(g, s, t)| json!({ "date": t.to_rfc3339(), "hour": format!("{s:02}:00"), "systemMarginalPrice": smf_ve_yon(g, s).0 }))
+        .collect();
+    HttpResponse::Ok().json(json!({ "items": items }))
+}
+
+async fn yon(req: HttpRequest, d: web::Data<MockDurum>, i: web::Json<Istek>) -> HttpResponse {
+    if let Some(r) = kontrol(&req, &d) {
+        return r;
+    }
+    let items: Vec<_> = saatler(&i, saat_basi())
+        .into_iter()
+        .map(|(g, s, t)| json!({ "date": t.to_rfc3339(), "hour": format!("{s:02}:00"), "systemDirection": smf_ve_yon(g, s).1 }))
+        .collect();
+    HttpResponse::Ok().json(json!({ "items": items }))
+}
+
+async fn dpp(req: HttpRequest, d: web::Data<MockDurum>, i: web::Json<Istek>) -> HttpResponse {
+    if let Some(r) = kontrol(&req, &d) {
+        return r;
+    }
+    let kimlik = i.uevcb_id.unwrap_or(0);
+    let items: Vec<_> = saatler(&i, yarin_sonu())
+        .into_iter()
+        .map(|(_, s, t)| json!({ "date": t.to_rfc3339(), "time": format!("{s:02}:00"), "toplam": uretim(kimlik, s) }))
+        .collect();
+    HttpResponse::Ok().json(json!({ "items": items }))
+}
+
+async fn gercek_zamanli(req: HttpRequest, d: web::Data<MockDurum>, i: web::Json<Istek>) -> HttpResponse {
+    if let Some(r) = kontrol(&req, &d) {
+        return r;
+    }
+    let kimlik = i.power_plant_id.unwrap_or(0);
+    let items: Vec<_> = saatler(&i, saat_basi())
+        .into_iter()
+        .map(|(_, s, t)| {
+            let v = (uretim(kimlik, s) * 0.95 * 1000.0).round() / 1000.0;
+            json!({ "date": t.to_rfc3339(), "hour": format!("{s:02}:00"), "total": v })
+        })
+        .collect();
+    HttpResponse::Ok().json(json!({ "items": items }))
+}
+
+pub fn yapilandir(cfg: &mut web::ServiceConfig) {
+    cfg.route("/cas/v1/tickets", web::post().to(tgt)).service(
+        web::scope("/electricity-service/v1")
+            .route("/markets/dam/data/mcp", web::post().to(mcp))
+            .route("/markets/bpm/data/system-marginal-price", web::post().to(smf))
+            .route("/markets/bpm/data/system-direction", web::post().to(yon))
+            .route("/generation/data/dpp", web::post().to(dpp))
+            .route("/generation/data/realtime-generation", web::post().to(gercek_zamanli)),
+    );
+}
@@ -3,22 +3,53 @@
 //! HTTP handler fonksiyonları.
 //!
 //! - Santral CRUD
-//! - Dengesizlik Hesabı
-//! - KGÜP Plan Kaydetme
-//! - Auth: Login (Bearer) & Whoami
+//! - Dengesizlik Hesabı (fiyatı verilmeyen saatler kayıtlı PTF / SMF ile) ve santral için
+//!   kayıtlı plan / gerçekleşen / fiyattan otomatik dengesizlik maliyeti
+//! - KÜPST (plandan sapma tutarı): santral için saatlik ve aylık toplamlar
+//! - Piyasa fiyatları (saatlik PTF / SMF / sistem yönü): listeleme, toplu yükleme, silme, eksik raporu
+//! - Dengesizlik kural setleri (tolerans bandı, k katsayıları, YEKDEM; sürümlü) ve santral atamaları
+//! - EPİAŞ Şeffaflık Platformu: santral kimlikleri, yayımlanan üretim, senkron durumu / tetikleme
+//! - KGÜP Plan Kaydetme (doğrulamalı, kapı kapanışı kilitli), okuma, revizyon geçmişi,
+//!   onay akışı (taslak → onaya_gonderildi → onaylandi → pys_gonderildi)
+//!   ve portföy durumu, PYS dosyası (CSV / XML) dışa aktarımı, toplu CSV / XLSX aktarımı
+//! - Planlı kesintiler
+//! - Üretim ölçümü aktarımı (JSON / CSV), veri kalitesi
+//! - Modbus register haritası, MQTT konu eşlemeleri ve abone durumu
+//! - Auth: Login (Bearer) & Whoami, Kayıt & Davet
+//! - Yönetim: Müşteri & Kullanıcı, API anahtarları
 
-use actix_web::{delete, get, post, put, web, Error, HttpResponse, Responder};
+use actix_web::{delete, get, post, put, web, Error, HttpRequest, HttpResponse, Responder};
+use futures::StreamExt;
 use sqlx::PgPool;
 use uuid::Uuid;
-use chrono::NaiveDate;
+use chrono::{Datelike, Duration, Utc};
 use crate::models::SapmaGunResponse;
 
 use crate::db;
+use crate::epias::Epias;
+use crate::kalite::{self, KaliteModu};
+use crate::kgup::{self, KapanisKurallari, PlanDurumu};
+use crate::modbus;
+use crate::piyasa;
+use crate::piyasa_gunu;
+use crate::plan_aktarim;
+use crate::pys;
+use crate::uzlastirma;
+use crate::mqtt::MqttDurum;
 use crate::models::{
-    DengesizlikInput, DengesizlikOutput, InputSantral, KgupPlanInput, Kullanici,
+    DengesizlikInput, DengesizlikKuraliInput, DengesizlikOutput, DengesizlikRaporu, DengesizlikSaat,
+    DengesizlikToplami, KupstAy, KupstRaporu, KupstSaat, KupstToplami, SantralDengesizlikKuraliInput,
+    EpiasEslemesiInput, InputSantral, KgupDurumGecisiInput, KgupKapanisDurumu,
+    KgupPlanDetay, KgupPlanDurumu,
+    KgupPlanInput, KgupPlanRevizyonu, KgupPortfoyResponse, KgupRevizyonDetay, KgupRevizyonFarki,
+    KgupRevizyonGecmisi, KgupSaatFarki, Kullanici, ModbusNoktasiInput, MqttEslemesiInput,
+    OnayliKgupPlan, PlanliKesinti, PlanliKesintiInput, saatlik_degerler,
 };
-use crate::auth::{create_jwt, verify_password, AuthConfig};
-use crate::auth_mw::AuthenticatedUser;
+use crate::auth::{
+    create_jwt, generate_token, hash_password, hash_token, verify_password, AuthConfig,
+};
+use crate::auth_mw::{AuthenticatedUser, Istemci};
+use crate::yetki::{gerek, yetki_yok, ApiKapsam, IstemciYetkili, Rol, Yetki, Yetkili};
 
 // -----------------------------------------------------------------------------
 // AUTH
@@ -33,6 +64,8 @@ pub struct LoginRequest {
 #[derive(serde::Serialize)]
 pub struct LoginResponse {
     pub access_token: String,
+    pub refresh_token: String,
+    pub expires_in: i64, // access token ömrü (sn)
     pub user_id: Uuid,
     pub musteri_id: Uuid,
     pub rol: String,
@@ -91,14 +124,43 @@ pub async fn login_handler(
     return Ok(HttpResponse::Unauthorized().body("Giriş bilgileri hatalı."));
     }
 
+    // Müşteri pasifse tüm kullanıcıları bloklanır
+    match db::musteri_aktif(pool.get_ref(), user.musteri_id).await {
+        Ok(true) => {}
+        Ok(false) => return Ok(HttpResponse::Unauthorized().body("Hesap pasif.")),
+        Err(e) => {
+            log::error!("DB login musteri kontrol hata: {e}");
+            return Err(actix_web::error::ErrorInternalServerError("db hata"));
+        }
+    }
+
+    // Oturum + refresh token aç
+    let refresh_token = generate_token();
+    let oturum_id = match db::create_oturum(
+        pool.get_ref(),
+        user.id,
+        &hash_token(&refresh_token),
+        Utc::now() + Duration::days(auth_cfg.refresh_exp_days),
+    )
+    .await
+    {
+        Ok(id) => id,
+        Err(e) => {
+            log::error!("oturum açılamadı: {e}");
+            return Err(actix_web::error::ErrorInternalServerError("db hata"));
+        }
+    };
+
     // JWT üret
-    let token = create_jwt(&auth_cfg, user.id, user.musteri_id, &user.rol).map_err(|e| {
+    let token = create_jwt(&auth_cfg, user.id, user.musteri_id, &user.rol, oturum_id).map_err(|e| {
         log::error!("JWT üretilemedi: {e}");
         actix_web::error::ErrorInternalServerError("jwt hata")
     })?;
 
     let resp = LoginResponse {
         access_token: token,
+        refresh_token,
+        expires_in: auth_cfg.access_exp_minutes * 60,
         user_id: user.id,
         musteri_id: user.musteri_id,
         rol: user.rol,
@@ -107,6 +169,111 @@ pub async fn login_handler(
     Ok(HttpResponse::Ok().json(resp))
 }
 
+#[derive(serde::Deserialize)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
+}
+
+/// POST /auth/refresh
+///
+/// Body: { "refresh_token": "..." }
+///
+/// Refresh token tek kullanımlıktır; her çağrıda yenisi döner.
+pub async fn refresh_handler(
+    pool: web::Data<PgPool>,
+    auth_cfg: web::Data<AuthConfig>,
+    form: web::Json<RefreshRequest>,
+) -> HttpResponse {
+    let yeni_refresh = generate_token();
+    let sonuc = db::rotate_refresh_token(
+        pool.get_ref(),
+        &hash_token(form.refresh_token.trim()),
+        &hash_token(&yeni_refresh),
+        Utc::now() + Duration::days(auth_cfg.refresh_exp_days),
+    )
+    .await;
+
+    let (oturum_id, user_id, musteri_id, rol) = match sonuc {
+        Ok(db::RefreshSonuc::Yenilendi { oturum_id, user_id, musteri_id, rol }) => {
+            (oturum_id, user_id, musteri_id, rol)
+        }
+        Ok(db::RefreshSonuc::TekrarKullanim) => {
+            log::warn!("refresh token tekrar kullanıldı, oturum iptal edildi");
+            return HttpResponse::Unauthorized().body("Oturum sonlandırıldı.");
+        }
+        Ok(db::RefreshSonuc::Gecersiz) => {
+            return HttpResponse::Unauthorized().body("Refresh token geçersiz.");
+        }
+        Err(e) => {
+            log::error!("refresh DB hata: {e}");
+            return HttpResponse::InternalServerError().finish();
+        }
+    };
+
+    let token = match create_jwt(&auth_cfg, user_id, musteri_id, &rol, oturum_id) {
+        Ok(t) => t,
+        Err(e) => {
+            log::error!("JWT üretilemedi: {e}");
+            return HttpResponse::InternalServerError().finish();
+        }
+    };
+
+    HttpResponse::Ok().json(LoginResponse {
+        access_token: token,
+        refresh_token: yeni_refresh,
+        expires_in: auth_cfg.access_exp_minutes * 60,
+        user_id,
+        musteri_id,
+        rol,
+    })
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct LogoutQuery {
+    pub hepsi: Option<bool>, // true → kullanıcının tüm oturumları
+}
+
+#[derive(serde::Deserialize)]
+pub struct LogoutRequest {
+    pub refresh_token: String,
+}
+
+/// POST /auth/logout[?hepsi=true]
+///
+/// Body: { "refresh_token": "..." }  ya da  Authorization: Bearer <token>
+///
+/// Refresh token ile çağrı access token'ın süresi dolmuş olsa da oturumu kapatır.
+pub async fn logout_handler(
+    pool: web::Data<PgPool>,
+    user: Option<AuthenticatedUser>,
+    q: web::Query<LogoutQuery>,
+    form: Option<web::Json<LogoutRequest>>,
+) -> HttpResponse {
+    let hepsi = q.hepsi.unwrap_or(false);
+    let res = match (form, user) {
+        (Some(form), _) => {
+            let token_hash = hash_token(form.refresh_token.trim());
+            match db::revoke_oturum_by_refresh(pool.get_ref(), &token_hash, hepsi).await {
+                Ok(0) => return HttpResponse::Unauthorized().body("Refresh token geçersiz."),
+                r => r,
+            }
+        }
+        (None, Some(user)) if hepsi => {
+            db::revoke_kullanici_oturumlari(pool.get_ref(), user.user_id).await
+        }
+        (None, Some(user)) => db::revoke_oturum(pool.get_ref(), user.oturum_id, user.user_id).await,
+        (None, None) => return HttpResponse::Unauthorized().body("Oturum bilgisi yok."),
+    };
+
+    match res {
+        Ok(n) => HttpResponse::Ok().json(serde_json::json!({"status":"success","iptal_edilen":n})),
+        Err(e) => {
+            log::error!("logout DB hata: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
 /// GET /auth/whoami
 ///
 /// Authorization: Bearer <token>
@@ -115,263 +282,3085 @@ pub async fn whoami(user: AuthenticatedUser) -> HttpResponse {
         "user_id": user.user_id,
         "musteri_id": user.musteri_id,
         "rol": user.rol,
+        "yetkiler": user.rol.yetkiler(),
     }))
 }
 
 // -----------------------------------------------------------------------------
-// SANTRAL CRUD
+// KAYIT & DAVET
 // -----------------------------------------------------------------------------
 
-#[post("/api/santral")]
-pub async fn create_santral_handler(
+const MIN_SIFRE_UZUNLUK: usize = 8;
+const DAVET_VARSAYILAN_SAAT: i64 = 72;
+
+#[derive(serde::Deserialize)]
+pub struct RegisterRequest {
+    pub musteri_ad: String,
+    pub email: String,
+    pub sifre: String,
+    pub ad_soyad: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct DavetRequest {
+    pub email: Option<String>,
+    pub rol: Option<Rol>,              // varsayılan "analist"
+    pub gecerlilik_saat: Option<i64>,  // varsayılan 72
+}
+
+#[derive(serde::Serialize)]
+pub struct DavetResponse {
+    pub davet: crate::models::Davet,
+    pub token: String, // sadece bu cevapta görünür
+}
+
+#[derive(serde::Deserialize)]
+pub struct DavetKabulRequest {
+    pub token: String,
+    pub email: String,
+    pub sifre: String,
+    pub ad_soyad: Option<String>,
+}
+
+fn bad_request(msg: &str) -> HttpResponse {
+    HttpResponse::BadRequest().json(serde_json::json!({"status":"error","message":msg}))
+}
+
+/// Kayıt / davet girdilerinin ortak kontrolü.
+fn validate_email_sifre(email: &str, sifre: &str) -> Result<(), HttpResponse> {
+    if !email.contains('@') {
+        return Err(bad_request("Geçerli bir e-posta girin."));
+    }
+    if sifre.chars().count() < MIN_SIFRE_UZUNLUK {
+        return Err(bad_request("Şifre en az 8 karakter olmalı."));
+    }
+    Ok(())
+}
+
+/// POST /auth/register
+///
+/// Body: { "musteri_ad": "...", "email": "...", "sifre": "...", "ad_soyad": "..." }
+///
+/// Yeni müşteri (portföy) açar; kaydolan kişi `portfoy_admin` olur.
+pub async fn register_handler(
     pool: web::Data<PgPool>,
-    user: AuthenticatedUser,
-    yeni_santral: web::Json<InputSantral>,
-) -> impl Responder {
-    let data = yeni_santral.into_inner();
-    let musteri_id = user.musteri_id; // admin de kendi default musteri'sine ekliyor (şimdilik)
+    form: web::Json<RegisterRequest>,
+) -> HttpResponse {
+    let form = form.into_inner();
+    let musteri_ad = form.musteri_ad.trim();
+    let email = form.email.trim();
 
-    match db::create_santral_for_musteri(pool.get_ref(), musteri_id, data).await {
-        Ok(santral) => HttpResponse::Ok().json(santral),
+    if musteri_ad.is_empty() {
+        return bad_request("Müşteri adı boş olamaz.");
+    }
+    if let Err(resp) = validate_email_sifre(email, &form.sifre) {
+        return resp;
+    }
+
+    let sifre_hash = match hash_password(&form.sifre) {
+        Ok(h) => h,
         Err(e) => {
-            eprintln!("Santral oluşturulurken hata oluştu: {:?}", e);
+            log::error!("register hash hata: {e}");
+            return HttpResponse::InternalServerError().finish();
+        }
+    };
+
+    match db::register_musteri_with_admin(
+        pool.get_ref(),
+        musteri_ad,
+        email,
+        &sifre_hash,
+        form.ad_soyad.as_deref(),
+    )
+    .await
+    {
+        Ok((musteri, kullanici)) => HttpResponse::Created().json(serde_json::json!({
+            "musteri": musteri,
+            "kullanici": kullanici,
+        })),
+        Err(e) if db::violates_constraint(&e, "kullanicilar_email_key") => {
+            HttpResponse::Conflict().json(serde_json::json!({"status":"error","message":"Bu e-posta zaten kayıtlı."}))
+        }
+        Err(e) => {
+            log::error!("register DB hata: {e}");
             HttpResponse::InternalServerError().finish()
         }
     }
 }
 
-#[get("/api/santraller")]
-pub async fn get_all_santraller_handler(
+/// POST /auth/davet
+///
+/// Authorization: Bearer <token> (yetki: kullanici_yonet)
+///
+/// Kendi müşterisine tek kullanımlık davet token'ı üretir.
+pub async fn create_davet_handler(
     pool: web::Data<PgPool>,
-    user: AuthenticatedUser,
-) -> impl Responder {
-    if user.rol == "admin" {
-        match db::get_all_santraller(pool.get_ref()).await {
-            Ok(santraller) => HttpResponse::Ok().json(santraller),
-            Err(e) => {
-                eprintln!("Santraller listelenirken hata oluştu: {:?}", e);
-                HttpResponse::InternalServerError().finish()
-            }
+    user: Yetkili<gerek::KullaniciYonet>,
+    form: web::Json<DavetRequest>,
+) -> HttpResponse {
+    let form = form.into_inner();
+    let rol = form.rol.unwrap_or(Rol::Analist);
+    // platform admin rolü davetle verilemez
+    if rol == Rol::Admin {
+        return bad_request("Geçersiz rol.");
+    }
+    let saat = form.gecerlilik_saat.unwrap_or(DAVET_VARSAYILAN_SAAT);
+    if !(1..=24 * 30).contains(&saat) {
+        return bad_request("gecerlilik_saat 1..720 aralığında olmalı.");
+    }
+    let email = form.email.as_deref().map(str::trim).filter(|e| !e.is_empty());
+
+    let token = generate_token();
+    let son_gecerlilik = Utc::now() + Duration::hours(saat);
+
+    match db::create_davet(
+        pool.get_ref(),
+        user.musteri_id,
+        &hash_token(&token),
+        email,
+        rol.as_str(),
+        user.user_id,
+        son_gecerlilik,
+    )
+    .await
+    {
+        Ok(davet) => HttpResponse::Created().json(DavetResponse { davet, token }),
+        Err(e) => {
+            log::error!("davet DB hata: {e}");
+            HttpResponse::InternalServerError().finish()
         }
-    } else {
-        match db::get_santraller_by_musteri(pool.get_ref(), user.musteri_id).await {
-            Ok(santraller) => HttpResponse::Ok().json(santraller),
-            Err(e) => {
-                eprintln!("Müşteri santralleri listelenirken hata oluştu: {:?}", e);
-                HttpResponse::InternalServerError().finish()
-            }
+    }
+}
+
+/// POST /auth/davet/kabul
+///
+/// Body: { "token": "...", "email": "...", "sifre": "...", "ad_soyad": "..." }
+pub async fn accept_davet_handler(
+    pool: web::Data<PgPool>,
+    form: web::Json<DavetKabulRequest>,
+) -> HttpResponse {
+    let form = form.into_inner();
+    let email = form.email.trim();
+
+    if let Err(resp) = validate_email_sifre(email, &form.sifre) {
+        return resp;
+    }
+
+    let sifre_hash = match hash_password(&form.sifre) {
+        Ok(h) => h,
+        Err(e) => {
+            log::error!("davet kabul hash hata: {e}");
+            return HttpResponse::InternalServerError().finish();
+        }
+    };
+
+    match db::accept_davet(
+        pool.get_ref(),
+        &hash_token(form.token.trim()),
+        email,
+        &sifre_hash,
+        form.ad_soyad.as_deref(),
+    )
+    .await
+    {
+        Ok(Some(kullanici)) => HttpResponse::Created().json(kullanici),
+        // generic mesaj → hangi koşulun tutmadığını sızdırma
+        Ok(None) => bad_request("Davet geçersiz veya süresi dolmuş."),
+        Err(e) if db::violates_constraint(&e, "kullanicilar_email_key") => {
+            HttpResponse::Conflict().json(serde_json::json!({"status":"error","message":"Bu e-posta zaten kayıtlı."}))
+        }
+        Err(e) => {
+            log::error!("davet kabul DB hata: {e}");
+            HttpResponse::InternalServerError().finish()
         }
     }
 }
 
-#[delete("/api/santral/{id}")]
-pub async fn delete_santral_handler(
+// -----------------------------------------------------------------------------
+// YÖNETİM: MÜŞTERİ & KULLANICI
+// -----------------------------------------------------------------------------
+//
+// Yetki:
+//   - musteri_yonet                 : müşteri CRUD (platform admin)
+//   - kullanici_yonet               : kendi müşterisinin kullanıcıları
+//   - kullanici_yonet + tum_musteriler : tüm kullanıcılar
+
+fn forbidden() -> HttpResponse {
+    HttpResponse::Forbidden().json(serde_json::json!({"status":"error","message":"Yetkin yok."}))
+}
+
+fn not_found(msg: &str) -> HttpResponse {
+    HttpResponse::NotFound().json(serde_json::json!({"status":"error","message":msg}))
+}
+
+/// Oturumdaki kullanıcı, `hedef_musteri_id` müşterisinin kullanıcılarını yönetebilir mi?
+fn kullanici_yonetebilir(user: &AuthenticatedUser, hedef_musteri_id: Uuid) -> bool {
+    user.yetkili(Yetki::KullaniciYonet)
+        && (user.yetkili(Yetki::TumMusteriler) || user.musteri_id == hedef_musteri_id)
+}
+
+/// Oturumdaki kullanıcı bu rolü verebilir mi? (admin rolünü yalnızca admin verir)
+fn rol_atanabilir(user: &AuthenticatedUser, rol: Rol) -> bool {
+    rol != Rol::Admin || user.rol == Rol::Admin
+}
+
+/// Platform admin hesabına (rol, aktiflik, şifre) yalnızca admin dokunabilir;
+/// tüm müşterilere erişim yalnızca admin rolünde olduğundan eksik yetki olarak o bildirilir.
+fn admin_hedefi(user: &AuthenticatedUser, hedef: &Kullanici) -> Result<(), HttpResponse> {
+    if Rol::parse(&hedef.rol) == Some(Rol::Admin) && user.rol != Rol::Admin {
+        return Err(yetki_yok(Yetki::TumMusteriler));
+    }
+    Ok(())
+}
+
+/// Hedef kullanıcıyı çek + yetki kontrolü.
+async fn yonetilen_kullanici(
+    pool: &PgPool,
+    user: &AuthenticatedUser,
+    kullanici_id: Uuid,
+) -> Result<Kullanici, HttpResponse> {
+    match db::get_kullanici_by_id(pool, kullanici_id).await {
+        Ok(k) if kullanici_yonetebilir(user, k.musteri_id) => Ok(k),
+        // başka müşterinin kullanıcısı → varlığını sızdırma
+        Ok(_) | Err(sqlx::Error::RowNotFound) => Err(not_found("Kullanıcı bulunamadı.")),
+        Err(e) => {
+            log::error!("kullanici getir DB hata: {e}");
+            Err(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct MusteriInput {
+    pub ad: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct AktifInput {
+    pub aktif: bool,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct KullaniciListeQuery {
+    pub musteri_id: Option<Uuid>, // sadece admin için anlamlı
+}
+
+#[derive(serde::Deserialize)]
+pub struct KullaniciCreateInput {
+    pub musteri_id: Option<Uuid>, // yoksa oturumdaki müşteri
+    pub email: String,
+    pub sifre: String,
+    pub ad_soyad: Option<String>,
+    pub rol: Option<Rol>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct RolInput {
+    pub rol: Rol,
+}
+
+#[derive(serde::Deserialize)]
+pub struct SifreResetInput {
+    pub yeni_sifre: String,
+}
+
+#[get("/api/admin/musteriler")]
+pub async fn list_musteriler_handler(
     pool: web::Data<PgPool>,
-    user: AuthenticatedUser,
-    id: web::Path<Uuid>,
-) -> impl Responder {
-    let santral_id = id.into_inner();
+    _user: Yetkili<gerek::MusteriYonet>,
+) -> HttpResponse {
+    match db::list_musteriler(pool.get_ref()).await {
+        Ok(list) => HttpResponse::Ok().json(list),
+        Err(e) => {
+            log::error!("musteri listele hata: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
 
-    // admin → doğrudan sil
-    if user.rol == "admin" {
-        match db::delete_santral_by_id(pool.get_ref(), santral_id).await {
-            Ok(rows) if rows > 0 => {
-                return HttpResponse::Ok().json(serde_json::json!({"status":"success"}));
-            }
-            Ok(_) => {
-                return HttpResponse::NotFound().json(serde_json::json!({"status":"error","message":"Santral bulunamadı."}));
-            }
-            Err(e) => {
-                eprintln!("Santral sil hata: {e:?}");
-                return HttpResponse::InternalServerError().finish();
-            }
+#[post("/api/admin/musteriler")]
+pub async fn create_musteri_handler(
+    pool: web::Data<PgPool>,
+    _user: Yetkili<gerek::MusteriYonet>,
+    body: web::Json<MusteriInput>,
+) -> HttpResponse {
+    let ad = body.ad.trim();
+    if ad.is_empty() {
+        return bad_request("Müşteri adı boş olamaz.");
+    }
+    match db::create_musteri(pool.get_ref(), ad).await {
+        Ok(m) => HttpResponse::Created().json(m),
+        Err(e) => {
+            log::error!("musteri oluştur hata: {e}");
+            HttpResponse::InternalServerError().finish()
         }
     }
+}
 
-    // user → sahiplik kontrolü
-    match db::santral_belongs_to_musteri(pool.get_ref(), santral_id, user.musteri_id).await {
-        Ok(true) => {}
-        Ok(false) => {
-            return HttpResponse::Forbidden().json(serde_json::json!({"status":"error","message":"Yetkin yok."}));
+#[put("/api/admin/musteri/{id}")]
+pub async fn rename_musteri_handler(
+    pool: web::Data<PgPool>,
+    _user: Yetkili<gerek::MusteriYonet>,
+    id: web::Path<Uuid>,
+    body: web::Json<MusteriInput>,
+) -> HttpResponse {
+    let ad = body.ad.trim();
+    if ad.is_empty() {
+        return bad_request("Müşteri adı boş olamaz.");
+    }
+    match db::rename_musteri(pool.get_ref(), id.into_inner(), ad).await {
+        Ok(m) => HttpResponse::Ok().json(m),
+        Err(sqlx::Error::RowNotFound) => not_found("Müşteri bulunamadı."),
+        Err(e) => {
+            log::error!("musteri güncelle hata: {e}");
+            HttpResponse::InternalServerError().finish()
         }
+    }
+}
+
+#[put("/api/admin/musteri/{id}/aktif")]
+pub async fn set_musteri_aktif_handler(
+    pool: web::Data<PgPool>,
+    user: Yetkili<gerek::MusteriYonet>,
+    id: web::Path<Uuid>,
+    body: web::Json<AktifInput>,
+) -> HttpResponse {
+    let musteri_id = id.into_inner();
+    if musteri_id == user.musteri_id && !body.aktif {
+        return bad_request("Kendi müşterini pasifleştiremezsin.");
+    }
+    match db::set_musteri_aktif(pool.get_ref(), musteri_id, body.aktif).await {
+        Ok(m) => HttpResponse::Ok().json(m),
+        Err(sqlx::Error::RowNotFound) => not_found("Müşteri bulunamadı."),
         Err(e) => {
-            eprintln!("Sahiplik kontrol hata: {e:?}");
-            return HttpResponse::InternalServerError().finish();
+            log::error!("musteri aktif hata: {e}");
+            HttpResponse::InternalServerError().finish()
         }
     }
+}
 
-    // sahip → sil
-    match db::delete_santral_by_id(pool.get_ref(), santral_id).await {
-        Ok(rows) if rows > 0 => HttpResponse::Ok().json(serde_json::json!({"status":"success"})),
-        Ok(_) => HttpResponse::NotFound().json(serde_json::json!({"status":"error","message":"Santral bulunamadı."})),
+#[get("/api/admin/kullanicilar")]
+pub async fn list_kullanicilar_handler(
+    pool: web::Data<PgPool>,
+    user: Yetkili<gerek::KullaniciYonet>,
+    q: web::Query<KullaniciListeQuery>,
+) -> HttpResponse {
+    let filtre = if user.yetkili(Yetki::TumMusteriler) {
+        q.musteri_id
+    } else {
+        Some(user.musteri_id)
+    };
+    match db::list_kullanicilar(pool.get_ref(), filtre).await {
+        Ok(list) => HttpResponse::Ok().json(list),
         Err(e) => {
-            eprintln!("Santral sil hata: {e:?}");
+            log::error!("kullanici listele hata: {e}");
             HttpResponse::InternalServerError().finish()
         }
     }
 }
 
-#[put("/api/santral/{id}")]
-pub async fn update_santral_handler(
+#[post("/api/admin/kullanicilar")]
+pub async fn create_kullanici_handler(
+    pool: web::Data<PgPool>,
+    user: Yetkili<gerek::KullaniciYonet>,
+    body: web::Json<KullaniciCreateInput>,
+) -> HttpResponse {
+    let body = body.into_inner();
+    let musteri_id = body.musteri_id.unwrap_or(user.musteri_id);
+    if !kullanici_yonetebilir(&user, musteri_id) {
+        return yetki_yok(Yetki::TumMusteriler);
+    }
+    let rol = body.rol.unwrap_or(Rol::Analist);
+    if !rol_atanabilir(&user, rol) {
+        return bad_request("Geçersiz rol.");
+    }
+    let email = body.email.trim();
+    if let Err(resp) = validate_email_sifre(email, &body.sifre) {
+        return resp;
+    }
+
+    let sifre_hash = match hash_password(&body.sifre) {
+        Ok(h) => h,
+        Err(e) => {
+            log::error!("kullanici oluştur hash hata: {e}");
+            return HttpResponse::InternalServerError().finish();
+        }
+    };
+
+    match db::create_kullanici(
+        pool.get_ref(),
+        musteri_id,
+        email,
+        &sifre_hash,
+        body.ad_soyad.as_deref(),
+        rol.as_str(),
+    )
+    .await
+    {
+        Ok(k) => HttpResponse::Created().json(k),
+        Err(e) if db::violates_constraint(&e, "kullanicilar_email_key") => {
+            HttpResponse::Conflict().json(serde_json::json!({"status":"error","message":"Bu e-posta zaten kayıtlı."}))
+        }
+        Err(e) if db::violates_constraint(&e, "kullanicilar_musteri_id_fkey") => {
+            not_found("Müşteri bulunamadı.")
+        }
+        Err(e) => {
+            log::error!("kullanici oluştur DB hata: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[put("/api/admin/kullanici/{id}/aktif")]
+pub async fn set_kullanici_aktif_handler(
+    pool: web::Data<PgPool>,
+    user: Yetkili<gerek::KullaniciYonet>,
+    id: web::Path<Uuid>,
+    body: web::Json<AktifInput>,
+) -> HttpResponse {
+    let kullanici_id = id.into_inner();
+    if kullanici_id == user.user_id {
+        return bad_request("Kendi hesabını değiştiremezsin.");
+    }
+    let hedef = match yonetilen_kullanici(pool.get_ref(), &user, kullanici_id).await {
+        Ok(k) => k,
+        Err(resp) => return resp,
+    };
+    // portföy yöneticisi platform admin'ini pasifleştiremez (oturumlarını da düşürür)
+    if let Err(resp) = admin_hedefi(&user, &hedef) {
+        return resp;
+    }
+    match db::set_kullanici_aktif(pool.get_ref(), kullanici_id, body.aktif).await {
+        Ok(k) => HttpResponse::Ok().json(k),
+        Err(sqlx::Error::RowNotFound) => not_found("Kullanıcı bulunamadı."),
+        Err(e) => {
+            log::error!("kullanici aktif hata: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[put("/api/admin/kullanici/{id}/rol")]
+pub async fn set_kullanici_rol_handler(
+    pool: web::Data<PgPool>,
+    user: Yetkili<gerek::KullaniciYonet>,
+    id: web::Path<Uuid>,
+    body: web::Json<RolInput>,
+) -> HttpResponse {
+    let kullanici_id = id.into_inner();
+    if kullanici_id == user.user_id {
+        return bad_request("Kendi hesabını değiştiremezsin.");
+    }
+    let hedef = match yonetilen_kullanici(pool.get_ref(), &user, kullanici_id).await {
+        Ok(k) => k,
+        Err(resp) => return resp,
+    };
+    // portföy yöneticisi platform admin'ine dokunamaz
+    if let Err(resp) = admin_hedefi(&user, &hedef) {
+        return resp;
+    }
+    if !rol_atanabilir(&user, body.rol) {
+        return bad_request("Geçersiz rol.");
+    }
+    match db::set_kullanici_rol(pool.get_ref(), kullanici_id, body.rol.as_str()).await {
+        Ok(k) => HttpResponse::Ok().json(k),
+        Err(sqlx::Error::RowNotFound) => not_found("Kullanıcı bulunamadı."),
+        Err(e) => {
+            log::error!("kullanici rol hata: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[post("/api/admin/kullanici/{id}/sifre")]
+pub async fn reset_kullanici_sifre_handler(
+    pool: web::Data<PgPool>,
+    user: Yetkili<gerek::KullaniciYonet>,
+    id: web::Path<Uuid>,
+    body: web::Json<SifreResetInput>,
+) -> HttpResponse {
+    let kullanici_id = id.into_inner();
+    let hedef = match yonetilen_kullanici(pool.get_ref(), &user, kullanici_id).await {
+        Ok(k) => k,
+        Err(resp) => return resp,
+    };
+    if let Err(resp) = admin_hedefi(&user, &hedef) {
+        return resp;
+    }
+    if let Err(resp) = validate_email_sifre(&hedef.email, &body.yeni_sifre) {
+        return resp;
+    }
+
+    let sifre_hash = match hash_password(&body.yeni_sifre) {
+        Ok(h) => h,
+        Err(e) => {
+            log::error!("sifre reset hash hata: {e}");
+            return HttpResponse::InternalServerError().finish();
+        }
+    };
+
+    match db::reset_kullanici_sifre(pool.get_ref(), kullanici_id, &sifre_hash).await {
+        Ok(_) => HttpResponse::Ok().json(serde_json::json!({"status":"success"})),
+        Err(e) => {
+            log::error!("sifre reset DB hata: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// API ANAHTARLARI
+// -----------------------------------------------------------------------------
+//
+// Anahtar sadece oluşturma cevabında bir kez görünür; istemci `X-API-Key`
+// başlığıyla gönderir.
+
+const API_ANAHTARI_ONEK_UZUNLUK: usize = 8;
+
+#[derive(Debug, serde::Deserialize)]
+pub struct ApiAnahtariInput {
+    pub ad: String,
+    pub kapsam: ApiKapsam,
+    pub santral_idler: Vec<Uuid>,
+    pub gecerlilik_gun: Option<i64>, // yoksa süresiz
+}
+
+#[derive(serde::Serialize)]
+pub struct ApiAnahtariResponse {
+    pub anahtar: crate::models::ApiAnahtari,
+    pub api_key: String, // sadece bu cevapta görünür
+}
+
+#[post("/api/api-anahtarlari")]
+pub async fn create_api_anahtari_handler(
+    pool: web::Data<PgPool>,
+    user: Yetkili<gerek::ApiAnahtariYonet>,
+    body: web::Json<ApiAnahtariInput>,
+) -> HttpResponse {
+    let body = body.into_inner();
+    let ad = body.ad.trim();
+    if ad.is_empty() {
+        return bad_request("Anahtar adı boş olamaz.");
+    }
+
+    let mut santral_idler = body.santral_idler;
+    santral_idler.sort();
+    santral_idler.dedup();
+    if santral_idler.is_empty() {
+        return bad_request("En az bir santral seçilmeli.");
+    }
+    match db::santraller_musteriye_ait(pool.get_ref(), &santral_idler, user.musteri_id).await {
+        Ok(true) => {}
+        Ok(false) => return forbidden(),
+        Err(e) => {
+            log::error!("api anahtarı santral kontrol hata: {e}");
+            return HttpResponse::InternalServerError().finish();
+        }
+    }
+
+    let son_gecerlilik = match body.gecerlilik_gun {
+        Some(gun) if !(1..=3650).contains(&gun) => {
+            return bad_request("gecerlilik_gun 1..3650 aralığında olmalı.");
+        }
+        Some(gun) => Some(Utc::now() + Duration::days(gun)),
+        None => None,
+    };
+
+    let api_key = generate_token();
+    let onek = &api_key[..API_ANAHTARI_ONEK_UZUNLUK];
+
+    match db::create_api_anahtari(
+        pool.get_ref(),
+        user.musteri_id,
+        ad,
+        onek,
+        &hash_token(&api_key),
+        body.kapsam.as_str(),
+        &santral_idler,
+        son_gecerlilik,
+        user.user_id,
+    )
+    .await
+    {
+        Ok(anahtar) => HttpResponse::Created().json(ApiAnahtariResponse { anahtar, api_key }),
+        Err(e) => {
+            log::error!("api anahtarı oluştur hata: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[get("/api/api-anahtarlari")]
+pub async fn list_api_anahtarlari_handler(
+    pool: web::Data<PgPool>,
+    user: Yetkili<gerek::ApiAnahtariYonet>,
+) -> HttpResponse {
+    match db::list_api_anahtarlari(pool.get_ref(), user.musteri_id).await {
+        Ok(list) => HttpResponse::Ok().json(list),
+        Err(e) => {
+            log::error!("api anahtarı listele hata: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[delete("/api/api-anahtari/{id}")]
+pub async fn revoke_api_anahtari_handler(
+    pool: web::Data<PgPool>,
+    user: Yetkili<gerek::ApiAnahtariYonet>,
+    id: web::Path<Uuid>,
+) -> HttpResponse {
+    match db::revoke_api_anahtari(pool.get_ref(), id.into_inner(), user.musteri_id).await {
+        Ok(rows) if rows > 0 => HttpResponse::Ok().json(serde_json::json!({"status":"success"})),
+        Ok(_) => not_found("Anahtar bulunamadı."),
+        Err(e) => {
+            log::error!("api anahtarı iptal hata: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// SANTRAL CRUD
+// -----------------------------------------------------------------------------
+
+fn validate_santral(data: &InputSantral) -> Result<(), HttpResponse> {
+    if !data.olcum_cozunurluk_sn.is_none_or(|sn| (1..=3600).contains(&sn)) {
+        return Err(bad_request("olcum_cozunurluk_sn 1-3600 sn arası olmalı."));
+    }
+    if let Some(eic) = &data.eic_kodu
+        && !eic.is_empty()
+        && !pys::eic_gecerli(eic)
+    {
+        return Err(bad_request("eic_kodu 16 karakterlik geçerli bir EIC kodu olmalı (0-9, A-Z, '-')."));
+    }
+    Ok(())
+}
+
+fn eic_cakismasi() -> HttpResponse {
+    HttpResponse::Conflict().json(serde_json::json!({
+        "status": "error",
+        "message": "EIC kodu başka bir santralde kayıtlı."
+    }))
+}
+
+#[post("/api/santral")]
+pub async fn create_santral_handler(
+    pool: web::Data<PgPool>,
+    user: Yetkili<gerek::SantralYaz>,
+    yeni_santral: web::Json<InputSantral>,
+) -> impl Responder {
+    let data = yeni_santral.into_inner();
+    if let Err(resp) = validate_santral(&data) {
+        return resp;
+    }
+    let musteri_id = user.musteri_id; // admin de kendi default musteri'sine ekliyor (şimdilik)
+
+    match db::create_santral_for_musteri(pool.get_ref(), musteri_id, data).await {
+        Ok(santral) => HttpResponse::Ok().json(santral),
+        Err(e) if db::violates_constraint(&e, "santraller_eic_kodu_unique") => eic_cakismasi(),
+        Err(e) => {
+            eprintln!("Santral oluşturulurken hata oluştu: {:?}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[get("/api/santraller")]
+pub async fn get_all_santraller_handler(
+    pool: web::Data<PgPool>,
+    user: Yetkili<gerek::SantralOku>,
+) -> impl Responder {
+    if user.yetkili(Yetki::TumMusteriler) {
+        match db::get_all_santraller(pool.get_ref()).await {
+            Ok(santraller) => HttpResponse::Ok().json(santraller),
+            Err(e) => {
+                eprintln!("Santraller listelenirken hata oluştu: {:?}", e);
+                HttpResponse::InternalServerError().finish()
+            }
+        }
+    } else {
+        match db::get_santraller_by_musteri(pool.get_ref(), user.musteri_id).await {
+            Ok(santraller) => HttpResponse::Ok().json(santraller),
+            Err(e) => {
+                eprintln!("Müşteri santralleri listelenirken hata oluştu: {:?}", e);
+                HttpResponse::InternalServerError().finish()
+            }
+        }
+    }
+}
+
+#[delete("/api/santral/{id}")]
+pub async fn delete_santral_handler(
+    pool: web::Data<PgPool>,
+    user: Yetkili<gerek::SantralSil>,
+    id: web::Path<Uuid>,
+) -> impl Responder {
+    let santral_id = id.into_inner();
+
+    // tüm müşteriler yetkisi → doğrudan sil
+    if user.yetkili(Yetki::TumMusteriler) {
+        match db::delete_santral_by_id(pool.get_ref(), santral_id).await {
+            Ok(rows) if rows > 0 => {
+                return HttpResponse::Ok().json(serde_json::json!({"status":"success"}));
+            }
+            Ok(_) => {
+                return HttpResponse::NotFound().json(serde_json::json!({"status":"error","message":"Santral bulunamadı."}));
+            }
+            Err(e) => {
+                eprintln!("Santral sil hata: {e:?}");
+                return HttpResponse::InternalServerError().finish();
+            }
+        }
+    }
+
+    // diğerleri → sahiplik kontrolü
+    match db::santral_belongs_to_musteri(pool.get_ref(), santral_id, user.musteri_id).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return HttpResponse::Forbidden().json(serde_json::json!({"status":"error","message":"Yetkin yok."}));
+        }
+        Err(e) => {
+            eprintln!("Sahiplik kontrol hata: {e:?}");
+            return HttpResponse::InternalServerError().finish();
+        }
+    }
+
+    // sahip → sil
+    match db::delete_santral_by_id(pool.get_ref(), santral_id).await {
+        Ok(rows) if rows > 0 => HttpResponse::Ok().json(serde_json::json!({"status":"success"})),
+        Ok(_) => HttpResponse::NotFound().json(serde_json::json!({"status":"error","message":"Santral bulunamadı."})),
+        Err(e) => {
+            eprintln!("Santral sil hata: {e:?}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[put("/api/santral/{id}")]
+pub async fn update_santral_handler(
+    pool: web::Data<PgPool>,
+    user: Yetkili<gerek::SantralYaz>,
+    id: web::Path<Uuid>,
+    santral_data: web::Json<InputSantral>,
+) -> impl Responder {
+    let santral_id = id.into_inner();
+
+    if !user.yetkili(Yetki::TumMusteriler) {
+        match db::santral_belongs_to_musteri(pool.get_ref(), santral_id, user.musteri_id).await {
+            Ok(true) => {}
+            Ok(false) => {
+                return HttpResponse::Forbidden().json(serde_json::json!({"status":"error","message":"Yetkin yok."}));
+            }
+            Err(e) => {
+                eprintln!("Sahiplik kontrol hata: {e:?}");
+                return HttpResponse::InternalServerError().finish();
+            }
+        }
+    }
+
+    let data = santral_data.into_inner();
+    if let Err(resp) = validate_santral(&data) {
+        return resp;
+    }
+    match db::update_santral_by_id(pool.get_ref(), santral_id, data).await {
+        Ok(santral) => HttpResponse::Ok().json(santral),
+        Err(sqlx::Error::RowNotFound) => HttpResponse::NotFound().json(serde_json::json!({"status":"error","message":"Santral bulunamadı."})),
+        Err(e) if db::violates_constraint(&e, "santraller_eic_kodu_unique") => eic_cakismasi(),
+        Err(e) => {
+            eprintln!("Update santral hata: {e:?}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[get("/api/santral/{id}")]
+pub async fn get_santral_by_id_handler(
+    pool: web::Data<PgPool>,
+    user: Yetkili<gerek::SantralOku>,
+    id: web::Path<Uuid>,
+) -> impl Responder {
+    let santral_id = id.into_inner();
+
+    if !user.yetkili(Yetki::TumMusteriler) {
+        match db::santral_belongs_to_musteri(pool.get_ref(), santral_id, user.musteri_id).await {
+            Ok(true) => {}
+            Ok(false) => {
+                return HttpResponse::Forbidden().json(serde_json::json!({"status":"error","message":"Yetkin yok."}));
+            }
+            Err(e) => {
+                eprintln!("Sahiplik kontrol hata: {e:?}");
+                return HttpResponse::InternalServerError().finish();
+            }
+        }
+    }
+
+    match db::get_santral_by_id(pool.get_ref(), santral_id).await {
+        Ok(santral) => HttpResponse::Ok().json(santral),
+        Err(sqlx::Error::RowNotFound) => HttpResponse::NotFound().json(serde_json::json!({"status":"error","message":"Santral bulunamadı."})),
+        Err(e) => {
+            eprintln!("Get santral hata: {e:?}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+
+// -----------------------------------------------------------------------------
+// MODBUS REGISTER HARİTASI
+// -----------------------------------------------------------------------------
+
+/// Santral sahipliği (TumMusteriler yetkisi yoksa). Hata durumunda hazır cevap döner.
+async fn santral_sahipligi(
+    pool: &PgPool,
+    user: &AuthenticatedUser,
+    santral_id: Uuid,
+) -> Result<(), HttpResponse> {
+    if user.yetkili(Yetki::TumMusteriler) {
+        return Ok(());
+    }
+    match db::santral_belongs_to_musteri(pool, santral_id, user.musteri_id).await {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(forbidden()),
+        Err(e) => {
+            log::error!("Sahiplik kontrol hata: {e:?}");
+            Err(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+fn validate_modbus_noktasi(i: usize, n: &ModbusNoktasiInput) -> Result<(), String> {
+    let secim = |alan: &str, deger: &Option<String>, izinli: &[&str]| match deger {
+        Some(v) if !izinli.contains(&v.as_str()) => {
+            Err(format!("{i}. nokta: {alan} şunlardan biri olmalı: {}", izinli.join(", ")))
+        }
+        _ => Ok(()),
+    };
+    if n.host.trim().is_empty() {
+        return Err(format!("{i}. nokta: host boş olamaz"));
+    }
+    if !n.port.is_none_or(|p| (1..=65535).contains(&p)) {
+        return Err(format!("{i}. nokta: port 1-65535 arası olmalı"));
+    }
+    if !n.unit_id.is_none_or(|u| (0..=255).contains(&u)) {
+        return Err(format!("{i}. nokta: unit_id 0-255 arası olmalı"));
+    }
+    let adet = modbus::kelime_sayisi(n.veri_tipi.as_deref().unwrap_or("u16")) as i32;
+    if n.adres < 0 || n.adres + adet - 1 > 65535 {
+        return Err(format!("{i}. nokta: adres 0-65535 arası olmalı"));
+    }
+    if !n.olcek.is_none_or(f64::is_finite) {
+        return Err(format!("{i}. nokta: olcek sayı olmalı"));
+    }
+    secim("register_tipi", &n.register_tipi, &modbus::REGISTER_TIPLERI)?;
+    secim("veri_tipi", &n.veri_tipi, &modbus::VERI_TIPLERI)?;
+    secim("kelime_sirasi", &n.kelime_sirasi, &modbus::KELIME_SIRALARI)?;
+    Ok(())
+}
+
+#[get("/api/santral/{id}/modbus")]
+pub async fn get_modbus_noktalari_handler(
+    pool: web::Data<PgPool>,
+    user: Yetkili<gerek::SantralOku>,
+    id: web::Path<Uuid>,
+) -> impl Responder {
+    let santral_id = id.into_inner();
+    if let Err(resp) = santral_sahipligi(pool.get_ref(), &user, santral_id).await {
+        return resp;
+    }
+
+    match db::list_modbus_noktalari(pool.get_ref(), santral_id).await {
+        Ok(noktalar) => HttpResponse::Ok().json(noktalar),
+        Err(e) => {
+            log::error!("Modbus nokta listesi hata: {e:?}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+/// Register haritasını komple değiştirir (boş dizi → santral poller dışında kalır).
+/// Poller, verilen host/port'a deployment ağının içinden bağlandığından haritayı
+/// yalnızca platform yöneticisi tanımlar.
+#[put("/api/santral/{id}/modbus")]
+pub async fn put_modbus_noktalari_handler(
+    pool: web::Data<PgPool>,
+    user: Yetkili<gerek::MusteriYonet>,
+    id: web::Path<Uuid>,
+    body: web::Json<Vec<ModbusNoktasiInput>>,
+) -> impl Responder {
+    let santral_id = id.into_inner();
+    if let Err(resp) = santral_sahipligi(pool.get_ref(), &user, santral_id).await {
+        return resp;
+    }
+
+    let noktalar = body.into_inner();
+    for (i, n) in noktalar.iter().enumerate() {
+        if let Err(msg) = validate_modbus_noktasi(i + 1, n) {
+            return bad_request(&msg);
+        }
+    }
+
+    match db::replace_modbus_noktalari(pool.get_ref(), santral_id, noktalar).await {
+        Ok(kayitlar) => HttpResponse::Ok().json(kayitlar),
+        Err(e) if db::violates_constraint(&e, "modbus_noktalari_santral_id_fkey") => {
+            not_found("Santral bulunamadı.")
+        }
+        Err(e) => {
+            log::error!("Modbus nokta kaydetme hata: {e:?}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// MQTT KONU EŞLEMELERİ
+// -----------------------------------------------------------------------------
+
+fn validate_mqtt_eslemesi(i: usize, e: &MqttEslemesiInput) -> Result<(), String> {
+    let konu = e.konu.trim();
+    if konu.is_empty() {
+        return Err(format!("{i}. eşleme: konu boş olamaz"));
+    }
+    if konu.contains(['+', '#']) {
+        return Err(format!("{i}. eşleme: konu joker (+, #) içeremez"));
+    }
+    for (alan, yol) in [("deger_yolu", &e.deger_yolu), ("zaman_yolu", &e.zaman_yolu)] {
+        if let Some(y) = yol
+            && !y.starts_with('/')
+        {
+            return Err(format!("{i}. eşleme: {alan} JSON Pointer olmalı (ör. /guc_kw)"));
+        }
+    }
+    if !e.olcek.is_none_or(f64::is_finite) {
+        return Err(format!("{i}. eşleme: olcek sayı olmalı"));
+    }
+    Ok(())
+}
+
+#[get("/api/santral/{id}/mqtt")]
+pub async fn get_mqtt_eslemeleri_handler(
+    pool: web::Data<PgPool>,
+    user: Yetkili<gerek::SantralOku>,
+    id: web::Path<Uuid>,
+) -> impl Responder {
+    let santral_id = id.into_inner();
+    if let Err(resp) = santral_sahipligi(pool.get_ref(), &user, santral_id).await {
+        return resp;
+    }
+
+    match db::list_mqtt_eslemeleri(pool.get_ref(), santral_id).await {
+        Ok(eslemeler) => HttpResponse::Ok().json(eslemeler),
+        Err(e) => {
+            log::error!("MQTT eşleme listesi hata: {e:?}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+/// Konu eşlemelerini komple değiştirir. Abone değişikliği en geç bir dakikada görür.
+/// Broker tüm müşterilerce ortak olduğundan eşlemeyi yalnızca platform yöneticisi yapar;
+/// aksi halde bir müşteri başka müşterinin konusunu kendi santraline bağlayabilir.
+#[put("/api/santral/{id}/mqtt")]
+pub async fn put_mqtt_eslemeleri_handler(
+    pool: web::Data<PgPool>,
+    user: Yetkili<gerek::MusteriYonet>,
+    id: web::Path<Uuid>,
+    body: web::Json<Vec<MqttEslemesiInput>>,
+) -> impl Responder {
+    let santral_id = id.into_inner();
+    if let Err(resp) = santral_sahipligi(pool.get_ref(), &user, santral_id).await {
+        return resp;
+    }
+
+    let eslemeler = body.into_inner();
+    for (i, e) in eslemeler.iter().enumerate() {
+        if let Err(msg) = validate_mqtt_eslemesi(i + 1, e) {
+            return bad_request(&msg);
+        }
+    }
+
+    match db::replace_mqtt_eslemeleri(pool.get_ref(), santral_id, eslemeler).await {
+        Ok(kayitlar) => HttpResponse::Ok().json(kayitlar),
+        Err(e) if db::violates_constraint(&e, "mqtt_eslemeleri_konu_unique") => {
+            HttpResponse::Conflict().json(serde_json::json!({
+                "status": "error",
+                "message": "Konu başka bir santrale eşlenmiş."
+            }))
+        }
+        Err(e) if db::violates_constraint(&e, "mqtt_eslemeleri_santral_id_fkey") => {
+            not_found("Santral bulunamadı.")
+        }
+        Err(e) => {
+            log::error!("MQTT eşleme kaydetme hata: {e:?}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// DENGESİZLİK HESAPLAMA
+// -----------------------------------------------------------------------------
+
+#[derive(Debug, serde::Deserialize)]
+pub struct DengesizlikKuralQuery {
+    pub kural: Option<String>, // varsayılan: standart
+    #[serde(default)]
+    pub yekdem: bool,
+}
+
+/// Girdide verilmeyen PTF / SMF, `saat` üzerinden kayıtlı fiyatlardan tamamlanır.
+/// Satır, `kural` kodunun teslim gününde (saat yoksa bugün) geçerli sürümüyle fiyatlanır.
+#[post("/api/hesapla/dengesizlik")]
+pub async fn dengesizlik_hesapla_handler(
+    pool: web::Data<PgPool>,
+    _istemci: IstemciYetkili<gerek::PlanOku>,
+    q: web::Query<DengesizlikKuralQuery>,
+    inputs: web::Json<Vec<DengesizlikInput>>,
+) -> HttpResponse {
+    let kod = q.kural.as_deref().unwrap_or(uzlastirma::VARSAYILAN_KOD);
+    let surumler = match db::list_dengesizlik_kurallari(pool.get_ref(), Some(kod)).await {
+        Ok(s) if s.is_empty() => return bad_request(&format!("Dengesizlik kuralı bulunamadı: {kod}")),
+        Ok(s) => s,
+        Err(e) => {
+            log::error!("dengesizlik kural okuma DB hata: {e}");
+            return HttpResponse::InternalServerError().finish();
+        }
+    };
+    let takvim = uzlastirma::KuralTakvimi::sabit(surumler, kod, q.yekdem);
+
+    let fiyat_gerekli: Vec<chrono::DateTime<Utc>> = inputs
+        .iter()
+        .filter(|i| i.ptf_tl.is_none() || i.smf_tl.is_none())
+        .filter_map(|i| i.saat)
+        .collect();
+    let tablo = match (fiyat_gerekli.iter().min(), fiyat_gerekli.iter().max()) {
+        (Some(bas), Some(bit)) => {
+            if *bit - *bas > Duration::days(366) {
+                return bad_request("Fiyat aranan saatler 366 günden uzun bir aralığa yayılamaz.");
+            }
+            match db::fiyat_tablosu(pool.get_ref(), *bas - Duration::hours(1), *bit + Duration::hours(1)).await {
+                Ok(t) => t,
+                Err(e) => {
+                    log::error!("dengesizlik fiyat okuma DB hata: {e}");
+                    return HttpResponse::InternalServerError().finish();
+                }
+            }
+        }
+        _ => piyasa::FiyatTablosu::default(),
+    };
+
+    let mut fiyatlar = Vec::with_capacity(inputs.len());
+    for (i, input) in inputs.iter().enumerate() {
+        let gun = input.saat.map_or_else(piyasa_gunu::bugun, piyasa_gunu::piyasa_gunu);
+        let Some(cozum) = takvim.coz(gun) else {
+            return bad_request(&format!("{}. satır: {gun} için {kod} kuralının geçerli sürümü yok.", i + 1));
+        };
+        let kayitli = input.saat.and_then(|t| tablo.saat(t));
+        let ptf = input.ptf_tl.or(kayitli.map(|f| f.ptf_tl));
+        let smf = input.smf_tl.or(kayitli.and_then(|f| f.smf_tl));
+        match (ptf, smf) {
+            (Some(ptf), Some(smf)) => fiyatlar.push((cozum, ptf, smf)),
+            _ if input.saat.is_none() => {
+                return bad_request(&format!(
+                    "{}. satır: ptf_tl ve smf_tl ya da fiyat aranacak saat verilmeli.",
+                    i + 1
+                ));
+            }
+            _ => {
+                return bad_request(&format!(
+                    "{}. satır: {} için kayıtlı PTF / SMF bulunamadı.",
+                    i + 1,
+                    piyasa_gunu::yerel(input.saat.unwrap_or_default()).to_rfc3339()
+                ));
+            }
+        }
+    }
+
+    let outputs: Vec<DengesizlikOutput> = inputs
+        .iter()
+        .zip(fiyatlar)
+        .map(|(input, (cozum, ptf_tl, smf_tl))| {
+            cozum.cikti(&cozum.uzlastir(input.tahmini_uretim_mwh, input.gerceklesen_uretim_mwh, ptf_tl, smf_tl))
+        })
+        .collect();
+
+    HttpResponse::Ok().json(outputs)
+}
+
+/// Uzlaştırma raporlarının girdisi: periyot bazlı onaylı plan + gerçekleşen, saatlik
+/// fiyatlar ve santralin kural takvimi. Kalite modu saklı bayrakları kullanır; okuma
+/// ucu değerlendirme yazmaz (bkz. kalite.rs).
+struct UzlastirmaVerisi {
+    periyotlar: Vec<(chrono::DateTime<Utc>, Option<f64>, crate::enerji::SaatEnerji)>,
+    fiyatlar: piyasa::FiyatTablosu,
+    takvim: uzlastirma::KuralTakvimi,
+}
+
+async fn uzlastirma_verisi(
+    pool: &PgPool,
+    santral_id: Uuid,
+    start: chrono::NaiveDate,
+    end: chrono::NaiveDate,
+    cozunurluk_dk: i32,
+    kalite_modu: KaliteModu,
+) -> Result<UzlastirmaVerisi, HttpResponse> {
+    let db_hatasi = |ne: &str, e: sqlx::Error| {
+        log::error!("uzlaştırma {ne} DB hata: {e}");
+        HttpResponse::InternalServerError().finish()
+    };
+
+    let periyotlar = db::plan_gercek_aralik(pool, santral_id, start, end, cozunurluk_dk, kalite_modu)
+        .await
+        .map_err(|e| db_hatasi("plan / gerçek", e))?;
+    let fiyatlar = db::fiyat_tablosu(pool, piyasa_gunu::gun_baslangici(start), piyasa_gunu::gun_baslangici(end))
+        .await
+        .map_err(|e| db_hatasi("fiyat", e))?;
+    let takvim = db::kural_takvimi(pool, santral_id)
+        .await
+        .map_err(|e| db_hatasi("kural", e))?;
+    Ok(UzlastirmaVerisi { periyotlar, fiyatlar, takvim })
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct DengesizlikAralikQuery {
+    pub start: Option<chrono::NaiveDate>, // varsayılan: dün (Europe/Istanbul)
+    pub end: Option<chrono::NaiveDate>,   // hariç; varsayılan: start + 1 gün
+}
+
+/// Santralin son onaylı KGÜP planı, ölçümden gerçekleşen ve kayıtlı PTF / SMF ile
+/// periyot bazlı dengesizlik tutarı ve maliyeti. 15 dk periyotlar saatin fiyatını alır;
+/// her periyot, teslim gününde santrale atanmış kuralın o gün geçerli sürümüyle fiyatlanır.
+#[get("/api/santral/{id}/dengesizlik")]
+pub async fn santral_dengesizlik_handler(
+    pool: web::Data<PgPool>,
+    istemci: IstemciYetkili<gerek::PlanOku>,
+    path: web::Path<Uuid>,
+    q: web::Query<DengesizlikAralikQuery>,
+    k: web::Query<KaliteQuery>,
+    c: web::Query<CozunurlukQuery>,
+) -> HttpResponse {
+    let santral_id = path.into_inner();
+    if let Err(resp) = istemci_santral_erisimi(pool.get_ref(), &istemci, santral_id).await {
+        return resp;
+    }
+    let cozunurluk_dk = match cozunurluk_sec(c.cozunurluk_dk) {
+        Ok(dk) => dk,
+        Err(resp) => return resp,
+    };
+
+    let start = q.start.unwrap_or_else(|| piyasa_gunu::bugun() - Duration::days(1));
+    let end = q.end.unwrap_or(start + Duration::days(1));
+    if end <= start {
+        return bad_request("end, start'tan sonra olmalı.");
+    }
+    if (end - start).num_days() > 31 {
+        return bad_request("Tarih aralığı 31 günden uzun olamaz.");
+    }
+
+    let UzlastirmaVerisi { periyotlar, fiyatlar, takvim } =
+        match uzlastirma_verisi(pool.get_ref(), santral_id, start, end, cozunurluk_dk, k.kalite.unwrap_or_default()).await {
+            Ok(v) => v,
+            Err(resp) => return resp,
+        };
+
+    let mut toplam = DengesizlikToplami::default();
+    let mut rows = Vec::with_capacity(periyotlar.len());
+    for (ts, plan_mwh, enerji) in periyotlar {
+        let fiyat = fiyatlar.saat(ts);
+        let ptf_tl = fiyat.map(|f| f.ptf_tl);
+        let smf_tl = fiyat.and_then(|f| f.smf_tl);
+
+        let cozum = takvim.coz(piyasa_gunu::piyasa_gunu(ts));
+
+        let (uzlasma, eksik) = match (plan_mwh, enerji.mwh, ptf_tl, smf_tl, cozum) {
+            (None, ..) => (None, Some("plan_yok")),
+            (_, None, ..) => (None, Some("olcum_yok")),
+            (_, _, None, ..) | (_, _, _, None, _) => (None, Some("fiyat_yok")),
+            (Some(p), Some(g), Some(ptf), Some(smf), Some(c)) => (Some((c, c.uzlastir(p, g, ptf, smf))), None),
+            _ => (None, Some("kural_yok")),
+        };
+
+        match &uzlasma {
+            Some((_, u)) => {
+                toplam.plan_mwh += u.plan_mwh;
+                toplam.gercek_mwh += u.gercek_mwh;
+                toplam.dengesizlik_mwh += u.miktar_mwh;
+                if u.miktar_mwh > 0.0 {
+                    toplam.pozitif_dengesizlik_mwh += u.miktar_mwh;
+                } else {
+                    toplam.negatif_dengesizlik_mwh += u.miktar_mwh;
+                }
+                toplam.dengesizlik_tutari_tl += u.tutar_tl;
+                toplam.maliyet_tl += u.maliyet_tl;
+                toplam.hesaplanan_periyot += 1;
+            }
+            None => toplam.eksik_periyot += 1,
+        }
+        let sonuc = uzlasma.as_ref().map(|(c, u)| c.cikti(u));
+        let maliyet_tl = uzlasma.map(|(_, u)| u.maliyet_tl);
+
+        rows.push(DengesizlikSaat {
+            ts_utc: ts,
+            ts_yerel: piyasa_gunu::yerel(ts),
+            plan_mwh,
+            gercek_mwh: enerji.mwh,
+            ptf_tl,
+            smf_tl,
+            kapsama_orani: enerji.kapsama_orani,
+            sonuc,
+            maliyet_tl,
+            eksik,
+        });
+    }
+
+    HttpResponse::Ok().json(DengesizlikRaporu {
+        santral_id,
+        start,
+        end,
+        cozunurluk_dk,
+        rows,
+        toplam,
+    })
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct KupstAralikQuery {
+    pub start: Option<chrono::NaiveDate>, // varsayılan: bu ayın ilk günü (Europe/Istanbul)
+    pub end: Option<chrono::NaiveDate>,   // hariç; varsayılan: start + 1 ay
+}
+
+fn kupst_topla(t: &mut KupstToplami, plan_mwh: f64, gercek_mwh: f64, c: &uzlastirma::Kupst) {
+    t.plan_mwh += plan_mwh;
+    t.gercek_mwh += gercek_mwh;
+    t.mutlak_sapma_mwh += c.sapma_mwh.abs();
+    t.cezali_mwh += c.cezali_mwh;
+    t.tutar_tl += c.tutar_tl;
+    t.hesaplanan_saat += 1;
+}
+
+/// KÜPST: son onaylı KGÜP, ölçümden saatlik gerçekleşen ve kayıtlı PTF / SMF ile saatlik
+/// ve teslim ayı bazında tutar. Tolerans ve birim fiyat, teslim gününde santrale atanmış
+/// kural setinin o gün geçerli sürümünden gelir.
+#[get("/api/santral/{id}/kupst")]
+pub async fn santral_kupst_handler(
+    pool: web::Data<PgPool>,
+    istemci: IstemciYetkili<gerek::PlanOku>,
+    path: web::Path<Uuid>,
+    q: web::Query<KupstAralikQuery>,
+    k: web::Query<KaliteQuery>,
+) -> HttpResponse {
+    let santral_id = path.into_inner();
+    if let Err(resp) = istemci_santral_erisimi(pool.get_ref(), &istemci, santral_id).await {
+        return resp;
+    }
+
+    let bugun = piyasa_gunu::bugun();
+    let start = q.start.unwrap_or_else(|| bugun.with_day(1).unwrap_or(bugun));
+    let end = q
+        .end
+        .unwrap_or_else(|| start.checked_add_months(chrono::Months::new(1)).unwrap_or(start + Duration::days(1)));
+    if end <= start {
+        return bad_request("end, start'tan sonra olmalı.");
+    }
+    if (end - start).num_days() > 366 {
+        return bad_request("Tarih aralığı 366 günden uzun olamaz.");
+    }
+
+    let UzlastirmaVerisi { periyotlar, fiyatlar, takvim } =
+        match uzlastirma_verisi(pool.get_ref(), santral_id, start, end, 60, k.kalite.unwrap_or_default()).await {
+            Ok(v) => v,
+            Err(resp) => return resp,
+        };
+
+    let mut toplam = KupstToplami::default();
+    let mut aylar: Vec<KupstAy> = Vec::new();
+    let mut rows = Vec::with_capacity(periyotlar.len());
+    for (ts, plan_mwh, enerji) in periyotlar {
+        let fiyat = fiyatlar.saat(ts);
+        let ptf_tl = fiyat.map(|f| f.ptf_tl);
+        let smf_tl = fiyat.and_then(|f| f.smf_tl);
+        let gun = piyasa_gunu::piyasa_gunu(ts);
+        let cozum = takvim.coz(gun);
+
+        let (kupst, eksik) = match (plan_mwh, enerji.mwh, ptf_tl, smf_tl, cozum) {
+            (None, ..) => (None, Some("plan_yok")),
+            (_, None, ..) => (None, Some("olcum_yok")),
+            (_, _, None, ..) | (_, _, _, None, _) => (None, Some("fiyat_yok")),
+            (Some(p), Some(g), Some(ptf), Some(smf), Some(c)) => (Some((p, g, c.kupst.hesapla(p, g, ptf, smf))), None),
+            _ => (None, Some("kural_yok")),
+        };
+
+        let ay = gun.format("%Y-%m").to_string();
+        if aylar.last().is_none_or(|a| a.ay != ay) {
+            aylar.push(KupstAy { ay, toplam: KupstToplami::default() });
+        }
+        if let Some(a) = aylar.last_mut() {
+            match &kupst {
+                Some((p, g, c)) => {
+                    kupst_topla(&mut toplam, *p, *g, c);
+                    kupst_topla(&mut a.toplam, *p, *g, c);
+                }
+                None => {
+                    toplam.eksik_saat += 1;
+                    a.toplam.eksik_saat += 1;
+                }
+            }
+        }
+
+        let c = kupst.map(|(_, _, c)| c);
+        rows.push(KupstSaat {
+            ts_utc: ts,
+            ts_yerel: piyasa_gunu::yerel(ts),
+            plan_mwh,
+            gercek_mwh: enerji.mwh,
+            ptf_tl,
+            smf_tl,
+            kapsama_orani: enerji.kapsama_orani,
+            kural_kodu: cozum.map(|c| c.surum.kod.clone()),
+            kural_surumu: cozum.map(|c| c.surum.surum),
+            sapma_mwh: c.map(|c| c.sapma_mwh),
+            tolerans_mwh: c.map(|c| c.tolerans_mwh),
+            cezali_mwh: c.map(|c| c.cezali_mwh),
+            birim_fiyat_tl: c.map(|c| c.birim_fiyat_tl),
+            tutar_tl: c.map(|c| c.tutar_tl),
+            eksik,
+        });
+    }
+
+    HttpResponse::Ok().json(KupstRaporu {
+        santral_id,
+        start,
+        end,
+        rows,
+        aylar,
+        toplam,
+    })
+}
+
+// -----------------------------------------------------------------------------
+// PİYASA FİYATLARI
+// -----------------------------------------------------------------------------
+// POST /api/piyasa/fiyatlar
+//   Content-Type: application/json → [{ "saat", "ptf_tl", "smf_tl"?, "sistem_yonu"? }, ...]
+//   Content-Type: text/csv         → saat,ptf_tl[,smf_tl][,sistem_yonu] (başlıklı)
+//
+// Fiyatlar müşteriden bağımsızdır: okuma plan_oku, yazma fiyat_yaz ister.
+
+const FIYAT_MAX_GOVDE: usize = 8 * 1024 * 1024; // 8 MiB
+
+#[derive(Debug, serde::Deserialize)]
+pub struct FiyatAralikQuery {
+    pub start: Option<chrono::NaiveDate>, // varsayılan: bugün (Europe/Istanbul)
+    pub end: Option<chrono::NaiveDate>,   // hariç; varsayılan: start + 1 gün
+}
+
+impl FiyatAralikQuery {
+    fn aralik(&self) -> Result<(chrono::NaiveDate, chrono::NaiveDate), HttpResponse> {
+        let start = self.start.unwrap_or_else(piyasa_gunu::bugun);
+        let end = self.end.unwrap_or(start + Duration::days(1));
+        if end <= start {
+            return Err(bad_request("end, start'tan sonra olmalı."));
+        }
+        if (end - start).num_days() > 366 {
+            return Err(bad_request("Tarih aralığı 366 günden uzun olamaz."));
+        }
+        Ok((start, end))
+    }
+}
+
+#[get("/api/piyasa/fiyatlar")]
+pub async fn list_piyasa_fiyatlari_handler(
+    pool: web::Data<PgPool>,
+    _istemci: IstemciYetkili<gerek::PlanOku>,
+    q: web::Query<FiyatAralikQuery>,
+) -> HttpResponse {
+    let (start, end) = match q.aralik() {
+        Ok(a) => a,
+        Err(resp) => return resp,
+    };
+
+    match db::list_piyasa_fiyatlari(
+        pool.get_ref(),
+        piyasa_gunu::gun_baslangici(start),
+        piyasa_gunu::gun_baslangici(end),
+    )
+    .await
+    {
+        Ok(fiyatlar) => HttpResponse::Ok().json(
+            fiyatlar
+                .into_iter()
+                .map(piyasa::FiyatDetay::from)
+                .collect::<Vec<_>>(),
+        ),
+        Err(e) => {
+            log::error!("piyasa fiyat listeleme hata: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+/// Eksik PTF saatleri ve PTF'si olup SMF / sistem yönü henüz gelmemiş saatler.
+#[get("/api/piyasa/fiyatlar/eksikler")]
+pub async fn piyasa_fiyat_eksikleri_handler(
+    pool: web::Data<PgPool>,
+    _istemci: IstemciYetkili<gerek::PlanOku>,
+    q: web::Query<FiyatAralikQuery>,
+) -> HttpResponse {
+    let (start, end) = match q.aralik() {
+        Ok(a) => a,
+        Err(resp) => return resp,
+    };
+
+    match db::list_piyasa_fiyatlari(
+        pool.get_ref(),
+        piyasa_gunu::gun_baslangici(start),
+        piyasa_gunu::gun_baslangici(end),
+    )
+    .await
+    {
+        Ok(fiyatlar) => HttpResponse::Ok().json(piyasa::eksik_raporu(start, end, &fiyatlar)),
+        Err(e) => {
+            log::error!("piyasa fiyat eksik raporu hata: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[post("/api/piyasa/fiyatlar")]
+pub async fn piyasa_fiyat_aktar_handler(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    user: Yetkili<gerek::FiyatYaz>,
+    payload: web::Payload,
+) -> HttpResponse {
+    let govde = match govde_oku(payload, FIYAT_MAX_GOVDE).await {
+        Ok(b) => b,
+        Err(resp) => return resp,
+    };
+
+    let content_type = req
+        .headers()
+        .get(actix_web::http::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("application/json");
+
+    let ayristirma = if content_type.starts_with("text/csv") {
+        piyasa::parse_csv(&govde)
+    } else {
+        piyasa::parse_json(&govde)
+    };
+    let mut ayristirma = match ayristirma {
+        Ok(a) => a,
+        Err(msg) => return bad_request(&msg),
+    };
+    let toplam = ayristirma.toplam();
+    ayristirma.tekrarlari_ayikla();
+
+    let satirlar: Vec<piyasa::FiyatSatiri> =
+        ayristirma.satirlar.into_iter().map(|(_, f)| f).collect();
+    let (eklenen, guncellenen) =
+        match db::upsert_piyasa_fiyatlari(pool.get_ref(), &satirlar, "manuel", Some(user.user_id)).await {
+            Ok(r) => r,
+            Err(e) => {
+                log::error!("piyasa fiyat upsert DB hata: {e}");
+                return HttpResponse::InternalServerError().finish();
+            }
+        };
+
+    HttpResponse::Ok().json(crate::olcum::AktarimOzeti {
+        toplam,
+        eklenen,
+        guncellenen,
+        reddedilen: ayristirma.hatalar.len(),
+        hatalar: ayristirma.hatalar,
+        uyarilar: ayristirma.uyarilar,
+    })
+}
+
+#[delete("/api/piyasa/fiyatlar/{saat}")]
+pub async fn delete_piyasa_fiyati_handler(
+    pool: web::Data<PgPool>,
+    _user: Yetkili<gerek::FiyatYaz>,
+    path: web::Path<String>,
+) -> HttpResponse {
+    let saat = match chrono::DateTime::parse_from_rfc3339(&path.into_inner()) {
+        Ok(t) => t.with_timezone(&Utc),
+        Err(_) => return bad_request("saat RFC 3339 biçiminde olmalı."),
+    };
+
+    match db::delete_piyasa_fiyati(pool.get_ref(), saat).await {
+        Ok(true) => HttpResponse::NoContent().finish(),
+        Ok(false) => not_found("Bu saat için fiyat bulunamadı."),
+        Err(e) => {
+            log::error!("piyasa fiyat silme hata: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// DENGESİZLİK KURALLARI
+// -----------------------------------------------------------------------------
+// Fiyatlama kural setleri tüm müşterilerce ortaktır ve sürümlenir; santral atamaları
+// dönemseldir. Çözüm ve fiyatlama `uzlastirma` modülünde.
+
+#[derive(Debug, serde::Deserialize)]
+pub struct KuralKoduQuery {
+    pub kod: Option<String>,
+}
+
+fn validate_dengesizlik_kurali(k: &DengesizlikKuraliInput) -> Result<(), String> {
+    if k.kod.is_empty()
+        || k.kod.len() > 40
+        || !k.kod.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+    {
+        return Err("kod 1-40 karakter; küçük harf, rakam ve _ olmalı.".into());
+    }
+    if k.ad.trim().is_empty() {
+        return Err("ad boş olamaz.".into());
+    }
+    let oranlar = [
+        ("tolerans_orani", Some(k.tolerans_orani)),
+        ("pozitif_k", Some(k.pozitif_k)),
+        ("negatif_k", Some(k.negatif_k)),
+        ("yekdem_tolerans_orani", k.yekdem_tolerans_orani),
+        ("yekdem_pozitif_k", k.yekdem_pozitif_k),
+        ("yekdem_negatif_k", k.yekdem_negatif_k),
+        ("kupst_tolerans_orani", k.kupst_tolerans_orani),
+        ("kupst_katsayi", k.kupst_katsayi),
+        ("yekdem_kupst_tolerans_orani", k.yekdem_kupst_tolerans_orani),
+    ];
+    for (alan, deger) in oranlar {
+        if let Some(v) = deger
+            && !(0.0..=1.0).contains(&v)
+        {
+            return Err(format!("{alan} 0 ile 1 arasında olmalı."));
+        }
+    }
+    if let Some(b) = &k.kupst_fiyat_bazi
+        && uzlastirma::FiyatBazi::parse(b).is_none()
+    {
+        return Err("kupst_fiyat_bazi max_ptf_smf, ptf ya da smf olmalı.".into());
+    }
+    Ok(())
+}
+
+/// Bugünden önce başlayan kayıt uzlaşmış dönemleri yeniden fiyatlar; yalnızca
+/// `duzeltme_gerekcesi` ile kabul edilir. Kaydedilecek gerekçeyi döner (ileri tarihliyse None).
+fn gecmis_duzeltmesi(
+    baslangic: chrono::NaiveDate,
+    gerekce: Option<&str>,
+    ne: &str,
+) -> Result<Option<String>, HttpResponse> {
+    if baslangic >= piyasa_gunu::bugun() {
+        return Ok(None);
+    }
+    match gerekce.map(str::trim).filter(|g| !g.is_empty()) {
+        Some(g) => {
+            log::warn!("geçmişe dönük {ne} düzeltmesi: başlangıç {baslangic}, gerekçe: {g}");
+            Ok(Some(g.to_string()))
+        }
+        None => Err(bad_request(
+            "gecerlilik_baslangic bugünden önce; geçmişe dönük düzeltme için duzeltme_gerekcesi zorunlu.",
+        )),
+    }
+}
+
+#[get("/api/piyasa/dengesizlik-kurallari")]
+pub async fn list_dengesizlik_kurallari_handler(
+    pool: web::Data<PgPool>,
+    _istemci: IstemciYetkili<gerek::PlanOku>,
+    q: web::Query<KuralKoduQuery>,
+) -> HttpResponse {
+    match db::list_dengesizlik_kurallari(pool.get_ref(), q.kod.as_deref()).await {
+        Ok(kurallar) => HttpResponse::Ok().json(kurallar),
+        Err(e) => {
+            log::error!("dengesizlik kural listeleme hata: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+/// Yeni kural sürümü; mevcut sürümler değiştirilmez. Aynı kodun önceki sürümü
+/// bu sürümün başlangıç gününe kadar geçerli kalır.
+#[post("/api/piyasa/dengesizlik-kurallari")]
+pub async fn create_dengesizlik_kurali_handler(
+    pool: web::Data<PgPool>,
+    user: Yetkili<gerek::FiyatYaz>,
+    body: web::Json<DengesizlikKuraliInput>,
+) -> HttpResponse {
+    let mut k = body.into_inner();
+    if let Err(msg) = validate_dengesizlik_kurali(&k) {
+        return bad_request(&msg);
+    }
+    k.duzeltme_gerekcesi =
+        match gecmis_duzeltmesi(k.gecerlilik_baslangic, k.duzeltme_gerekcesi.as_deref(), "kural sürümü") {
+            Ok(g) => g,
+            Err(resp) => return resp,
+        };
+
+    match db::create_dengesizlik_kurali(pool.get_ref(), &k, user.user_id).await {
+        Ok(kayit) => HttpResponse::Created().json(kayit),
+        Err(e)
+            if db::violates_constraint(&e, "dengesizlik_kurallari_baslangic_unique")
+                || db::violates_constraint(&e, "dengesizlik_kurallari_surum_unique") =>
+        {
+            HttpResponse::Conflict().json(serde_json::json!({
+                "status": "error",
+                "message": "Bu kod için aynı günde başlayan bir sürüm zaten var."
+            }))
+        }
+        Err(e) => {
+            log::error!("dengesizlik kural kaydetme hata: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+/// Santralin dönemsel kural atamaları (atama yoksa `standart`).
+#[get("/api/santral/{id}/dengesizlik-kurallari")]
+pub async fn list_santral_dengesizlik_kurallari_handler(
+    pool: web::Data<PgPool>,
+    user: Yetkili<gerek::SantralOku>,
+    id: web::Path<Uuid>,
+) -> HttpResponse {
+    let santral_id = id.into_inner();
+    if let Err(resp) = santral_sahipligi(pool.get_ref(), &user, santral_id).await {
+        return resp;
+    }
+
+    match db::list_santral_dengesizlik_kurallari(pool.get_ref(), santral_id).await {
+        Ok(atamalar) => HttpResponse::Ok().json(atamalar),
+        Err(e) => {
+            log::error!("santral kural ataması listeleme hata: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+/// `gecerlilik_baslangic` gününden itibaren (bir sonraki atamaya kadar) kural kodu ve
+/// YEKDEM durumu; aynı günde atama varsa üzerine yazar.
+#[put("/api/santral/{id}/dengesizlik-kurallari")]
+pub async fn put_santral_dengesizlik_kurali_handler(
+    pool: web::Data<PgPool>,
+    user: Yetkili<gerek::SantralYaz>,
+    id: web::Path<Uuid>,
+    body: web::Json<SantralDengesizlikKuraliInput>,
+) -> HttpResponse {
+    let santral_id = id.into_inner();
+    if let Err(resp) = santral_sahipligi(pool.get_ref(), &user, santral_id).await {
+        return resp;
+    }
+
+    let mut a = body.into_inner();
+    a.duzeltme_gerekcesi =
+        match gecmis_duzeltmesi(a.gecerlilik_baslangic, a.duzeltme_gerekcesi.as_deref(), "santral kural ataması") {
+            Ok(g) => g,
+            Err(resp) => return resp,
+        };
+    match db::list_dengesizlik_kurallari(pool.get_ref(), Some(&a.kural_kodu)).await {
+        Ok(s) if s.is_empty() => return bad_request(&format!("Dengesizlik kuralı bulunamadı: {}", a.kural_kodu)),
+        Ok(_) => {}
+        Err(e) => {
+            log::error!("dengesizlik kural okuma DB hata: {e}");
+            return HttpResponse::InternalServerError().finish();
+        }
+    }
+
+    match db::put_santral_dengesizlik_kurali(pool.get_ref(), santral_id, &a, user.user_id).await {
+        Ok(kayit) => HttpResponse::Ok().json(kayit),
+        Err(e) if db::violates_constraint(&e, "santral_dengesizlik_kurallari_santral_id_fkey") => {
+            not_found("Santral bulunamadı.")
+        }
+        Err(e) => {
+            log::error!("santral kural ataması kaydetme hata: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+/// Yalnızca henüz başlamamış (bugün ya da sonrası) atama silinir; başlamış atama
+/// aynı güne gerekçeli düzeltme olarak yeniden yazılır (bkz. `gecmis_duzeltmesi`).
+#[delete("/api/santral/{id}/dengesizlik-kurallari/{gun}")]
+pub async fn delete_santral_dengesizlik_kurali_handler(
+    pool: web::Data<PgPool>,
+    user: Yetkili<gerek::SantralYaz>,
+    path: web::Path<(Uuid, chrono::NaiveDate)>,
+) -> HttpResponse {
+    let (santral_id, gun) = path.into_inner();
+    if let Err(resp) = santral_sahipligi(pool.get_ref(), &user, santral_id).await {
+        return resp;
+    }
+    if gun < piyasa_gunu::bugun() {
+        return HttpResponse::Conflict().json(serde_json::json!({
+            "status": "error",
+            "message": "Geçmişte başlamış atama silinemez; aynı güne duzeltme_gerekcesi ile yeni atama yazın.",
+        }));
+    }
+
+    match db::delete_santral_dengesizlik_kurali(pool.get_ref(), santral_id, gun).await {
+        Ok(true) => HttpResponse::NoContent().finish(),
+        Ok(false) => not_found("Bu gün için kural ataması bulunamadı."),
+        Err(e) => {
+            log::error!("santral kural ataması silme hata: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// EPİAŞ ŞEFFAFLIK PLATFORMU
+// -----------------------------------------------------------------------------
+// Fiyat ve yayımlanan üretim senkronu `epias` modülünde; burada santral kimlikleri,
+// senkron durumu ve elle tetikleme.
+
+#[get("/api/santral/{id}/epias")]
+pub async fn get_epias_eslemesi_handler(
+    pool: web::Data<PgPool>,
+    user: Yetkili<gerek::SantralOku>,
+    id: web::Path<Uuid>,
+) -> HttpResponse {
+    let santral_id = id.into_inner();
+    if let Err(resp) = santral_sahipligi(pool.get_ref(), &user, santral_id).await {
+        return resp;
+    }
+
+    match db::get_epias_eslemesi(pool.get_ref(), santral_id).await {
+        Ok(Some(e)) => HttpResponse::Ok().json(e),
+        Ok(None) => not_found("Santral için EPİAŞ eşlemesi yok."),
+        Err(e) => {
+            log::error!("EPİAŞ eşleme okuma hata: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+/// Santralin EPİAŞ kimliklerini yazar; senkron bir sonraki turda kullanır.
+#[put("/api/santral/{id}/epias")]
+pub async fn put_epias_eslemesi_handler(
+    pool: web::Data<PgPool>,
+    user: Yetkili<gerek::SantralYaz>,
+    id: web::Path<Uuid>,
+    body: web::Json<EpiasEslemesiInput>,
+) -> HttpResponse {
+    let santral_id = id.into_inner();
+    if let Err(resp) = santral_sahipligi(pool.get_ref(), &user, santral_id).await {
+        return resp;
+    }
+
+    let e = body.into_inner();
+    if e.organizasyon_id.is_some() != e.uevcb_id.is_some() {
+        return bad_request("organizasyon_id ve uevcb_id birlikte verilmeli.");
+    }
+    if [e.organizasyon_id, e.uevcb_id, e.santral_kodu].iter().flatten().any(|k| *k <= 0) {
+        return bad_request("EPİAŞ kimlikleri pozitif tam sayı olmalı.");
+    }
+
+    match db::put_epias_eslemesi(pool.get_ref(), santral_id, &e).await {
+        Ok(kayit) => HttpResponse::Ok().json(kayit),
+        Err(e) if db::violates_constraint(&e, "epias_eslemeleri_santral_id_fkey") => {
+            not_found("Santral bulunamadı.")
+        }
+        Err(e) => {
+            log::error!("EPİAŞ eşleme kaydetme hata: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+/// Yayımlanan saatlik KGÜP ve gerçek zamanlı üretim.
+#[get("/api/santral/{id}/epias/uretim")]
+pub async fn epias_uretim_handler(
+    pool: web::Data<PgPool>,
+    istemci: IstemciYetkili<gerek::PlanOku>,
+    path: web::Path<Uuid>,
+    q: web::Query<FiyatAralikQuery>,
+) -> HttpResponse {
+    let santral_id = path.into_inner();
+    if let Err(resp) = istemci_santral_erisimi(pool.get_ref(), &istemci, santral_id).await {
+        return resp;
+    }
+    let (start, end) = match q.aralik() {
+        Ok(a) => a,
+        Err(resp) => return resp,
+    };
+
+    match db::list_epias_uretim(
+        pool.get_ref(),
+        santral_id,
+        piyasa_gunu::gun_baslangici(start),
+        piyasa_gunu::gun_baslangici(end),
+    )
+    .await
+    {
+        Ok(satirlar) => HttpResponse::Ok().json(satirlar),
+        Err(e) => {
+            log::error!("EPİAŞ üretim listeleme hata: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+/// Veri seti imleçleri, son hatalar ve son tur özeti.
+#[get("/api/admin/epias/durum")]
+pub async fn epias_durum_handler(
+    pool: web::Data<PgPool>,
+    _user: Yetkili<gerek::FiyatYaz>,
+    epias: web::Data<Epias>,
+) -> HttpResponse {
+    match epias.durum(pool.get_ref()).await {
+        Ok(d) => HttpResponse::Ok().json(d),
+        Err(e) => {
+            log::error!("EPİAŞ durum okuma hata: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+/// Zamanlayıcıyı beklemeden bir senkron turu çalıştırır (EPIAS_AKTIF kapalıyken de).
+#[post("/api/admin/epias/senkron")]
+pub async fn epias_senkron_handler(
+    pool: web::Data<PgPool>,
+    _user: Yetkili<gerek::FiyatYaz>,
+    epias: web::Data<Epias>,
+) -> HttpResponse {
+    match epias.tur_dene(pool.get_ref()).await {
+        Some(ozet) => HttpResponse::Ok().json(ozet),
+        None => HttpResponse::Conflict().json(serde_json::json!({
+            "status": "error",
+            "message": "Bir EPİAŞ senkron turu zaten sürüyor."
+        })),
+    }
+}
+
+// -----------------------------------------------------------------------------
+// KGÜP PLAN
+// -----------------------------------------------------------------------------
+
+#[post("/api/santral/{id}/kgupplan")]
+pub async fn create_or_update_kgup_plan_handler(
+    pool: web::Data<PgPool>,
+    kurallar: web::Data<KapanisKurallari>,
+    user: Yetkili<gerek::PlanYaz>,
+    id: web::Path<Uuid>,
+    body: web::Json<KgupPlanInput>,
+) -> impl Responder {
+
+    let santral_id = id.into_inner();
+
+    if !user.yetkili(Yetki::TumMusteriler) {
+        match db::santral_belongs_to_musteri(pool.get_ref(), santral_id, user.musteri_id).await {
+            Ok(true) => {}
+            Ok(false) => {
+                return HttpResponse::Forbidden().json(serde_json::json!({"status":"error","message":"Yetkin yok."}));
+            }
+            Err(e) => {
+                eprintln!("Sahiplik kontrol hata: {e:?}");
+                return HttpResponse::InternalServerError().finish();
+            }
+        }
+    }
+
+    let plan = body.into_inner();
+    if let Err(resp) = plan_dogrulamasi(pool.get_ref(), santral_id, &plan).await {
+        return resp;
+    }
+
+    let durum = yeni_revizyon_durumu(&user, plan.taslak);
+    let kilit = kapanis_kilidi(&kurallar, &user);
+    let kayit = db::create_or_update_kgup_plan(pool.get_ref(), santral_id, plan, user.user_id, "kayit", durum, kilit)
+        .await;
+    match kayit {
+        Ok(plan) => HttpResponse::Ok().json(plan),
+        Err(db::PlanYazHatasi::Kilit(resp)) => resp,
+        Err(db::PlanYazHatasi::Db(e)) => {
+            eprintln!("KGÜP Planı kaydedilirken hata oluştu: {:?}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+/// Yeni revizyonun başlangıç durumu: `plan_onayla` yetkisi olanın kaydı onaylı
+/// (taslak istenmedikçe), diğerlerininki taslak.
+fn yeni_revizyon_durumu(user: &AuthenticatedUser, taslak: bool) -> PlanDurumu {
+    if user.yetkili(Yetki::PlanOnayla) && !taslak {
+        PlanDurumu::Onaylandi
+    } else {
+        PlanDurumu::Taslak
+    }
+}
+
+/// Tek plan yazımının kapanış kilidi (bkz. `db::kgup_plan_yaz`): kapısı kapanmış saatleri
+/// değiştiren yazımı 409 ile durdurur. `plan_kilit_as` yetkisi olan kullanıcı gerekçe vererek
+/// geçebilir; kilit aşılmadıysa gerekçe yazılmaz (revizyonda yalnızca gerçek kilit aşımları görünsün).
+fn kapanis_kilidi<'a>(
+    kurallar: &'a KapanisKurallari,
+    user: &'a AuthenticatedUser,
+) -> impl FnMut(Uuid, &KgupPlanInput, Option<OnayliKgupPlan>) -> Result<Option<String>, HttpResponse> + 'a {
+    move |santral_id, plan, onayli| {
+        let dk = plan.cozunurluk();
+        let mevcut = onayli_degerleri(onayli, dk);
+        kilit_kontrolu(
+            kurallar,
+            user,
+            santral_id,
+            plan.plan_tarihi,
+            dk,
+            mevcut.as_deref(),
+            &plan.saatlik_plan_mwh,
+            plan.kapanis_gerekcesi.as_deref(),
+        )
+    }
+}
+
+/// Onaylı revizyonun `dk` çözünürlüğündeki değerleri.
+fn onayli_degerleri(onayli: Option<OnayliKgupPlan>, dk: i32) -> Option<Vec<Option<f64>>> {
+    onayli.map(|p| kgup::donustur(&saatlik_degerler(&p.saatlik_plan_mwh), p.cozunurluk_dk, dk))
+}
+
+/// Kapanış kontrolünün karşılaştırma tabanı: günün son onaylı revizyonu, `dk` çözünürlüğünde.
+/// Taslaklar sayılmaz; kapanıştan önce yazılmış bir taslak kilitli saatleri gevşetemez.
+async fn onayli_plan_degerleri(
+    pool: &PgPool,
+    santral_id: Uuid,
+    gun: chrono::NaiveDate,
+    dk: i32,
+) -> Result<Option<Vec<Option<f64>>>, HttpResponse> {
+    match db::onayli_kgup_planlari(pool, santral_id, gun, gun + Duration::days(1)).await {
+        Ok(mut p) => Ok(onayli_degerleri(p.pop(), dk)),
+        Err(e) => {
+            log::error!("KGÜP onaylı plan okuma hata: {e}");
+            Err(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+/// `yeni` değerlerin `mevcut`a göre kilitli periyot değiştirip değiştirmediğine bakar.
+/// Değiştirmiyorsa Ok(None); `plan_kilit_as` + gerekçe ile aşıldıysa Ok(gerekçe); aksi halde 409.
+#[allow(clippy::too_many_arguments)]
+fn kilit_kontrolu(
+    kurallar: &KapanisKurallari,
+    user: &AuthenticatedUser,
+    santral_id: Uuid,
+    gun: chrono::NaiveDate,
+    dk: i32,
+    mevcut: Option<&[Option<f64>]>,
+    yeni: &[f64],
+    gerekce: Option<&str>,
+) -> Result<Option<String>, HttpResponse> {
+    let kilitli = kurallar.kilitli_degisiklikler(gun, dk, mevcut, yeni, Utc::now());
+    if kilitli.is_empty() {
+        return Ok(None);
+    }
+
+    let gerekce = gerekce.map(str::trim).filter(|g| !g.is_empty());
+    let kilit_asabilir = user.yetkili(Yetki::PlanKilitAs);
+    if kilit_asabilir && let Some(g) = gerekce {
+        log::warn!(
+            "KGÜP kapanış sonrası düzenleme: santral {santral_id}, gün {gun}, periyotlar ({dk} dk) {kilitli:?}, kullanıcı {}, gerekçe: {g}",
+            user.user_id
+        );
+        return Ok(Some(g.to_string()));
+    }
+
+    let message = if kilit_asabilir {
+        "Kapı kapandı; kilitli saatleri değiştirmek için kapanis_gerekcesi zorunlu."
+    } else {
+        "Kapı kapandı; bu saatler artık değiştirilemez."
+    };
+    Err(HttpResponse::Conflict().json(serde_json::json!({
+        "status": "error",
+        "message": message,
+        "kod": "kapanis_gecti",
+        "gun_oncesi_kapanis": piyasa_gunu::yerel(kurallar.gun_oncesi_kapanisi(gun)),
+        "cozunurluk_dk": dk,
+        "kilitli_saatler": kilitli,
+        "kilit_asabilir": kilit_asabilir,
+    })))
+}
+
+/// Planın saatlik sınırları için santral bağlamı: kurulu güç ve günün kesintileri.
+async fn plan_siniri(
+    pool: &PgPool,
+    santral_id: Uuid,
+    gun: chrono::NaiveDate,
+) -> Result<(f64, Vec<PlanliKesinti>), HttpResponse> {
+    let kurulu_guc_mw = match db::santral_olcum_ayarlari(pool, santral_id).await {
+        Ok((kurulu, _)) => kurulu,
+        Err(sqlx::Error::RowNotFound) => return Err(not_found("Santral bulunamadı.")),
+        Err(e) => {
+            log::error!("santral okuma hata: {e}");
+            return Err(HttpResponse::InternalServerError().finish());
+        }
+    };
+    let aralik = (
+        piyasa_gunu::gun_baslangici(gun),
+        piyasa_gunu::gun_baslangici(gun + Duration::days(1)),
+    );
+    match db::list_planli_kesintiler(pool, santral_id, Some(aralik)).await {
+        Ok(kesintiler) => Ok((kurulu_guc_mw, kesintiler)),
+        Err(e) => {
+            log::error!("planlı kesinti okuma hata: {e}");
+            Err(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+/// `cozunurluk_dk` sorgu/girdi değeri: yoksa 60, yalnızca 60 ya da 15.
+fn cozunurluk_sec(dk: Option<i32>) -> Result<i32, HttpResponse> {
+    match dk.unwrap_or(kgup::VARSAYILAN_COZUNURLUK_DK) {
+        dk if kgup::cozunurluk_gecerli(dk) => Ok(dk),
+        _ => Err(bad_request("cozunurluk_dk 60 ya da 15 olmalı.")),
+    }
+}
+
+/// Plan doğrulamadan geçmezse periyot bazlı hata listesiyle 400 döner.
+async fn plan_dogrulamasi(
+    pool: &PgPool,
+    santral_id: Uuid,
+    plan: &KgupPlanInput,
+) -> Result<(), HttpResponse> {
+    let dk = cozunurluk_sec(plan.cozunurluk_dk)?;
+    let (kurulu_guc_mw, kesintiler) = plan_siniri(pool, santral_id, plan.plan_tarihi).await?;
+    let hatalar =
+        kgup::dogrula(plan.plan_tarihi, dk, &plan.saatlik_plan_mwh, kurulu_guc_mw, &kesintiler);
+    if hatalar.is_empty() {
+        return Ok(());
+    }
+    Err(HttpResponse::BadRequest().json(serde_json::json!({
+        "status": "error",
+        "message": "Plan doğrulamadan geçmedi.",
+        "hatalar": hatalar,
+    })))
+}
+
+/// Kaydetmeden doğrulama: hatalar ve periyot üst sınırları.
+#[post("/api/santral/{id}/kgupplan/dogrula")]
+pub async fn kgup_plan_dogrula_handler(
+    pool: web::Data<PgPool>,
+    user: Yetkili<gerek::PlanYaz>,
+    id: web::Path<Uuid>,
+    body: web::Json<KgupPlanInput>,
+) -> HttpResponse {
+    let santral_id = id.into_inner();
+    if let Err(resp) = santral_sahipligi(pool.get_ref(), &user, santral_id).await {
+        return resp;
+    }
+    let dk = match cozunurluk_sec(body.cozunurluk_dk) {
+        Ok(dk) => dk,
+        Err(resp) => return resp,
+    };
+    let (kurulu_guc_mw, kesintiler) =
+        match plan_siniri(pool.get_ref(), santral_id, body.plan_tarihi).await {
+            Ok(r) => r,
+            Err(resp) => return resp,
+        };
+
+    let gun = body.plan_tarihi;
+    let hatalar = kgup::dogrula(gun, dk, &body.saatlik_plan_mwh, kurulu_guc_mw, &kesintiler);
+    HttpResponse::Ok().json(serde_json::json!({
+        "gecerli": hatalar.is_empty(),
+        "hatalar": hatalar,
+        "cozunurluk_dk": dk,
+        "saatlik_limit_mwh": kgup::periyot_limitleri(gun, dk, kurulu_guc_mw, &kesintiler),
+    }))
+}
+
+/// İstemcinin santrale erişimi yoksa hazır cevap döner.
+async fn istemci_santral_erisimi(
+    pool: &PgPool,
+    istemci: &Istemci,
+    santral_id: Uuid,
+) -> Result<(), HttpResponse> {
+    match istemci.santral_erisimi(pool, santral_id).await {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(forbidden()),
+        Err(e) => {
+            log::error!("sahiplik kontrol hata: {e}");
+            Err(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct CozunurlukQuery {
+    pub cozunurluk_dk: Option<i32>, // 60 (varsayılan) | 15
+}
+
+#[get("/api/santral/{id}/kgupplan/{tarih}")]
+pub async fn get_kgup_plan_handler(
+    pool: web::Data<PgPool>,
+    istemci: IstemciYetkili<gerek::PlanOku>,
+    path: web::Path<(Uuid, chrono::NaiveDate)>,
+    c: web::Query<CozunurlukQuery>,
+) -> HttpResponse {
+    let (santral_id, gun) = path.into_inner();
+    if let Err(resp) = istemci_santral_erisimi(pool.get_ref(), &istemci, santral_id).await {
+        return resp;
+    }
+    let dk = match cozunurluk_sec(c.cozunurluk_dk) {
+        Ok(dk) => dk,
+        Err(resp) => return resp,
+    };
+
+    match db::get_kgup_plan(pool.get_ref(), santral_id, gun).await {
+        Ok(Some(plan)) => HttpResponse::Ok().json(KgupPlanDetay::cozunurlukte(plan, dk)),
+        Ok(None) => not_found("Bu gün için plan yok."),
+        Err(e) => {
+            log::error!("KGÜP plan okuma hata: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct PlanAralikQuery {
+    pub start: Option<chrono::NaiveDate>, // varsayılan: bugün (Europe/Istanbul)
+    pub end: Option<chrono::NaiveDate>,   // hariç; varsayılan: start + 7 gün
+}
+
+#[get("/api/santral/{id}/kgupplan")]
+pub async fn list_kgup_planlari_handler(
+    pool: web::Data<PgPool>,
+    istemci: IstemciYetkili<gerek::PlanOku>,
+    path: web::Path<Uuid>,
+    q: web::Query<PlanAralikQuery>,
+    c: web::Query<CozunurlukQuery>,
+) -> HttpResponse {
+    let santral_id = path.into_inner();
+    if let Err(resp) = istemci_santral_erisimi(pool.get_ref(), &istemci, santral_id).await {
+        return resp;
+    }
+    let dk = match cozunurluk_sec(c.cozunurluk_dk) {
+        Ok(dk) => dk,
+        Err(resp) => return resp,
+    };
+
+    let start = q.start.unwrap_or_else(piyasa_gunu::bugun);
+    let end = q.end.unwrap_or(start + Duration::days(7));
+    if end <= start {
+        return bad_request("end, start'tan sonra olmalı.");
+    }
+    if (end - start).num_days() > 366 {
+        return bad_request("Tarih aralığı 366 günden uzun olamaz.");
+    }
+
+    match db::list_kgup_planlari(pool.get_ref(), santral_id, start, end).await {
+        Ok(planlar) => HttpResponse::Ok().json(
+            planlar
+                .into_iter()
+                .map(|p| KgupPlanDetay::cozunurlukte(p, dk))
+                .collect::<Vec<_>>(),
+        ),
+        Err(e) => {
+            log::error!("KGÜP plan listeleme hata: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+/// Planın revizyonlarını okur; plan yoksa hazır 404 döner.
+async fn plan_revizyonlari(
+    pool: &PgPool,
+    santral_id: Uuid,
+    gun: chrono::NaiveDate,
+) -> Result<Vec<KgupPlanRevizyonu>, HttpResponse> {
+    match db::list_kgup_revizyonlari(pool, santral_id, gun).await {
+        Ok(r) if r.is_empty() => Err(not_found("Bu gün için plan yok.")),
+        Ok(r) => Ok(r),
+        Err(e) => {
+            log::error!("KGÜP revizyon okuma hata: {e}");
+            Err(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+/// Plan revizyon geçmişi (kim, ne zaman, hangi not), yeniden eskiye.
+#[get("/api/santral/{id}/kgupplan/{tarih}/revizyonlar")]
+pub async fn kgup_revizyonlari_handler(
+    pool: web::Data<PgPool>,
+    istemci: IstemciYetkili<gerek::PlanOku>,
+    path: web::Path<(Uuid, chrono::NaiveDate)>,
+) -> HttpResponse {
+    let (santral_id, gun) = path.into_inner();
+    if let Err(resp) = istemci_santral_erisimi(pool.get_ref(), &istemci, santral_id).await {
+        return resp;
+    }
+    let revizyonlar = match plan_revizyonlari(pool.get_ref(), santral_id, gun).await {
+        Ok(r) => r,
+        Err(resp) => return resp,
+    };
+
+    let guncel_revizyon = revizyonlar[0].revizyon_no;
+    let onayli_revizyon_no = revizyonlar
+        .iter()
+        .find(|r| PlanDurumu::parse(&r.durum).is_some_and(|d| d.onayli()))
+        .map(|r| r.revizyon_no);
+    HttpResponse::Ok().json(KgupRevizyonGecmisi {
+        santral_id,
+        plan_tarihi: gun,
+        guncel_revizyon,
+        onayli_revizyon_no,
+        revizyonlar: revizyonlar
+            .into_iter()
+            .map(|r| {
+                let saatlik = saatlik_degerler(&r.saatlik_plan_mwh);
+                KgupRevizyonDetay {
+                    revizyon_no: r.revizyon_no,
+                    guncel: r.revizyon_no == guncel_revizyon,
+                    cozunurluk_dk: r.cozunurluk_dk,
+                    toplam_mwh: saatlik.iter().flatten().sum(),
+                    saatlik_plan_mwh: saatlik,
+                    yazan_kullanici_id: r.yazan_kullanici_id,
+                    yazan_email: r.yazan_email,
+                    aciklama: r.aciklama,
+                    kaynak: r.kaynak,
+                    kapanis_gerekcesi: r.kapanis_gerekcesi,
+                    olusturma_tarihi: r.olusturma_tarihi,
+                    durum: r.durum,
+                }
+            })
+            .collect(),
+    })
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct RevizyonFarkQuery {
+    pub a: Option<i32>, // varsayılan: b - 1
+    pub b: Option<i32>, // varsayılan: güncel revizyon
+}
+
+/// İki revizyon arasındaki periyot periyot fark (b − a), `cozunurluk_dk` çözünürlüğünde.
+#[get("/api/santral/{id}/kgupplan/{tarih}/revizyonlar/fark")]
+pub async fn kgup_revizyon_farki_handler(
+    pool: web::Data<PgPool>,
+    istemci: IstemciYetkili<gerek::PlanOku>,
+    path: web::Path<(Uuid, chrono::NaiveDate)>,
+    q: web::Query<RevizyonFarkQuery>,
+    c: web::Query<CozunurlukQuery>,
+) -> HttpResponse {
+    let (santral_id, gun) = path.into_inner();
+    if let Err(resp) = istemci_santral_erisimi(pool.get_ref(), &istemci, santral_id).await {
+        return resp;
+    }
+    let dk = match cozunurluk_sec(c.cozunurluk_dk) {
+        Ok(dk) => dk,
+        Err(resp) => return resp,
+    };
+    let revizyonlar = match plan_revizyonlari(pool.get_ref(), santral_id, gun).await {
+        Ok(r) => r,
+        Err(resp) => return resp,
+    };
+
+    let b = q.b.unwrap_or(revizyonlar[0].revizyon_no);
+    let a = q.a.unwrap_or(b - 1);
+    let bul = |no: i32| {
+        revizyonlar
+            .iter()
+            .find(|r| r.revizyon_no == no)
+            .map(|r| kgup::donustur(&saatlik_degerler(&r.saatlik_plan_mwh), r.cozunurluk_dk, dk))
+    };
+    let (Some(plan_a), Some(plan_b)) = (bul(a), bul(b)) else {
+        return not_found(&format!("Revizyon bulunamadı (a={a}, b={b})."));
+    };
+
+    let saatler: Vec<KgupSaatFarki> = (0..plan_a.len().max(plan_b.len()))
+        .map(|saat| {
+            let a_mwh = plan_a.get(saat).copied().flatten();
+            let b_mwh = plan_b.get(saat).copied().flatten();
+            let fark_mwh = a_mwh.zip(b_mwh).map(|(x, y)| y - x);
+            KgupSaatFarki {
+                saat,
+                ts_yerel: piyasa_gunu::yerel(kgup::periyot_baslangici(gun, dk, saat)),
+                a_mwh,
+                b_mwh,
+                fark_mwh,
+                degisti: fark_mwh.map_or(a_mwh.is_some() != b_mwh.is_some(), |f| f.abs() > 1e-9),
+            }
+        })
+        .collect();
+
+    let toplam_a_mwh: f64 = plan_a.iter().flatten().sum();
+    let toplam_b_mwh: f64 = plan_b.iter().flatten().sum();
+    HttpResponse::Ok().json(KgupRevizyonFarki {
+        santral_id,
+        plan_tarihi: gun,
+        a,
+        b,
+        cozunurluk_dk: dk,
+        toplam_a_mwh,
+        toplam_b_mwh,
+        toplam_fark_mwh: toplam_b_mwh - toplam_a_mwh,
+        degisen_saat_sayisi: saatler.iter().filter(|s| s.degisti).count(),
+        saatler,
+    })
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct GeriYukleInput {
+    pub aciklama: Option<String>,
+    pub kapanis_gerekcesi: Option<String>,
+    #[serde(default)]
+    pub taslak: bool,
+}
+
+/// Eski bir revizyonu yeni revizyon olarak geri yükler; geçmiş silinmez.
+#[post("/api/santral/{id}/kgupplan/{tarih}/revizyonlar/{no}/geri-yukle")]
+pub async fn kgup_revizyon_geri_yukle_handler(
+    pool: web::Data<PgPool>,
+    kurallar: web::Data<KapanisKurallari>,
+    user: Yetkili<gerek::PlanYaz>,
+    path: web::Path<(Uuid, chrono::NaiveDate, i32)>,
+    body: Option<web::Json<GeriYukleInput>>,
+) -> HttpResponse {
+    let (santral_id, gun, no) = path.into_inner();
+    if let Err(resp) = santral_sahipligi(pool.get_ref(), &user, santral_id).await {
+        return resp;
+    }
+    let revizyonlar = match plan_revizyonlari(pool.get_ref(), santral_id, gun).await {
+        Ok(r) => r,
+        Err(resp) => return resp,
+    };
+
+    if revizyonlar[0].revizyon_no == no {
+        return bad_request("Bu revizyon zaten güncel.");
+    }
+    let Some(eski) = revizyonlar.iter().find(|r| r.revizyon_no == no) else {
+        return not_found("Revizyon bulunamadı.");
+    };
+    let Some(saatlik_plan_mwh) = saatlik_degerler(&eski.saatlik_plan_mwh)
+        .into_iter()
+        .collect::<Option<Vec<f64>>>()
+    else {
+        return bad_request("Revizyon sayı olmayan periyot değeri içeriyor; geri yüklenemez.");
+    };
+
+    let girdi = body.map(|b| b.into_inner());
+    let (aciklama, kapanis_gerekcesi, taslak) =
+        girdi.map_or((None, None, false), |g| (g.aciklama, g.kapanis_gerekcesi, g.taslak));
+    let plan = KgupPlanInput {
+        plan_tarihi: gun,
+        saatlik_plan_mwh,
+        cozunurluk_dk: Some(eski.cozunurluk_dk),
+        aciklama: Some(aciklama.unwrap_or_else(|| format!("Revizyon {no} geri yüklendi."))),
+        kapanis_gerekcesi,
+        taslak,
+    };
+    // Eski revizyon bugünkü kesintilere göre artık geçersiz olabilir
+    if let Err(resp) = plan_dogrulamasi(pool.get_ref(), santral_id, &plan).await {
+        return resp;
+    }
+
+    let durum = yeni_revizyon_durumu(&user, plan.taslak);
+    let kilit = kapanis_kilidi(&kurallar, &user);
+    match db::create_or_update_kgup_plan(pool.get_ref(), santral_id, plan, user.user_id, "geri_yukleme", durum, kilit)
+        .await
+    {
+        Ok(plan) => {
+            let dk = plan.cozunurluk_dk;
+            HttpResponse::Ok().json(KgupPlanDetay::cozunurlukte(plan, dk))
+        }
+        Err(db::PlanYazHatasi::Kilit(resp)) => resp,
+        Err(db::PlanYazHatasi::Db(e)) => {
+            log::error!("KGÜP revizyon geri yükleme hata: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+/// Revizyonun onay durumunu değiştirir (taslak → onaya_gonderildi → onaylandi → pys_gonderildi).
+/// Geçişin istediği yetki `PlanDurumu::gecis_yetkisi`; yoksa 403 + eksik_yetki.
+#[post("/api/santral/{id}/kgupplan/{tarih}/revizyonlar/{no}/durum")]
+pub async fn kgup_revizyon_durumu_handler(
+    pool: web::Data<PgPool>,
+    kurallar: web::Data<KapanisKurallari>,
+    user: Yetkili<gerek::PlanYaz>,
+    path: web::Path<(Uuid, chrono::NaiveDate, i32)>,
+    body: web::Json<KgupDurumGecisiInput>,
+) -> HttpResponse {
+    let (santral_id, gun, no) = path.into_inner();
+    if let Err(resp) = santral_sahipligi(pool.get_ref(), &user, santral_id).await {
+        return resp;
+    }
+    let girdi = body.into_inner();
+    let Some(yeni) = PlanDurumu::parse(&girdi.durum) else {
+        return bad_request("durum taslak, onaya_gonderildi, onaylandi ya da pys_gonderildi olmalı.");
+    };
+    let plan = match db::get_kgup_plan(pool.get_ref(), santral_id, gun).await {
+        Ok(Some(p)) => p,
+        Ok(None) => return not_found("Bu gün için plan yok."),
+        Err(e) => {
+            log::error!("KGÜP plan okuma hata: {e}");
+            return HttpResponse::InternalServerError().finish();
+        }
+    };
+    let revizyonlar = match plan_revizyonlari(pool.get_ref(), santral_id, gun).await {
+        Ok(r) => r,
+        Err(resp) => return resp,
+    };
+    let Some(revizyon) = revizyonlar.iter().find(|r| r.revizyon_no == no) else {
+        return not_found("Revizyon bulunamadı.");
+    };
+    let Some(eski) = PlanDurumu::parse(&revizyon.durum) else {
+        log::error!("KGÜP revizyon durumu tanınmıyor: {}", revizyon.durum);
+        return HttpResponse::InternalServerError().finish();
+    };
+
+    let Some(gereken) = eski.gecis_yetkisi(yeni) else {
+        return HttpResponse::Conflict().json(serde_json::json!({
+            "status": "error",
+            "message": format!("{} durumundan {} durumuna geçilemez.", eski.as_str(), yeni.as_str()),
+            "durum": eski,
+        }));
+    };
+    if !user.yetkili(gereken) {
+        return yetki_yok(gereken);
+    }
+    if yeni.guncel_revizyon_ister() && no != plan.revizyon_no {
+        return HttpResponse::Conflict().json(serde_json::json!({
+            "status": "error",
+            "message": "Yalnızca güncel revizyonun durumu değiştirilebilir.",
+            "guncel_revizyon": plan.revizyon_no,
+        }));
+    }
+
+    // Onaylanan revizyon sapma / PYS tabanı olur; kapanmış periyotları son onaylıya göre
+    // değiştiriyorsa yazımdaki kilidin aynısı uygulanır
+    let mut kapanis_gerekcesi = None;
+    if yeni == PlanDurumu::Onaylandi {
+        let Some(degerler) = saatlik_degerler(&revizyon.saatlik_plan_mwh)
+            .into_iter()
+            .collect::<Option<Vec<f64>>>()
+        else {
+            return bad_request("Revizyon sayı olmayan periyot değeri içeriyor; onaylanamaz.");
+        };
+        let dk = revizyon.cozunurluk_dk;
+        let mevcut = match onayli_plan_degerleri(pool.get_ref(), santral_id, gun, dk).await {
+            Ok(m) => m,
+            Err(resp) => return resp,
+        };
+        kapanis_gerekcesi = match kilit_kontrolu(
+            &kurallar,
+            &user,
+            santral_id,
+            gun,
+            dk,
+            mevcut.as_deref(),
+            &degerler,
+            girdi.kapanis_gerekcesi.as_deref(),
+        ) {
+            Ok(g) => g,
+            Err(resp) => return resp,
+        };
+    }
+
+    let aciklama = girdi.aciklama.as_deref().map(str::trim).filter(|a| !a.is_empty());
+    let sonuc = db::kgup_revizyon_durumu_degistir(
+        pool.get_ref(),
+        plan.id,
+        no,
+        eski,
+        yeni,
+        user.user_id,
+        aciklama,
+        kapanis_gerekcesi.as_deref(),
+    )
+    .await;
+    match sonuc {
+        Ok(true) => HttpResponse::Ok().json(serde_json::json!({
+            "status": "success",
+            "revizyon_no": no,
+            "eski_durum": eski,
+            "durum": yeni,
+        })),
+        Ok(false) => HttpResponse::Conflict().json(serde_json::json!({
+            "status": "error",
+            "message": "Plan bu arada değişti; yeniden deneyin.",
+        })),
+        Err(e) => {
+            log::error!("KGÜP revizyon durum geçişi hata: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+/// Planın onay akışı kaydı (kim, hangi revizyonu, hangi duruma), eskiden yeniye.
+#[get("/api/santral/{id}/kgupplan/{tarih}/onay-gecmisi")]
+pub async fn kgup_onay_gecmisi_handler(
+    pool: web::Data<PgPool>,
+    istemci: IstemciYetkili<gerek::PlanOku>,
+    path: web::Path<(Uuid, chrono::NaiveDate)>,
+) -> HttpResponse {
+    let (santral_id, gun) = path.into_inner();
+    if let Err(resp) = istemci_santral_erisimi(pool.get_ref(), &istemci, santral_id).await {
+        return resp;
+    }
+    let plan = match db::get_kgup_plan(pool.get_ref(), santral_id, gun).await {
+        Ok(Some(p)) => p,
+        Ok(None) => return not_found("Bu gün için plan yok."),
+        Err(e) => {
+            log::error!("KGÜP plan okuma hata: {e}");
+            return HttpResponse::InternalServerError().finish();
+        }
+    };
+    match db::list_kgup_durum_gecisleri(pool.get_ref(), plan.id).await {
+        Ok(gecisler) => HttpResponse::Ok().json(serde_json::json!({
+            "santral_id": santral_id,
+            "plan_tarihi": gun,
+            "durum": plan.durum,
+            "guncel_revizyon": plan.revizyon_no,
+            "onayli_revizyon_no": plan.onayli_revizyon_no,
+            "gecisler": gecisler,
+        })),
+        Err(e) => {
+            log::error!("KGÜP onay geçmişi hata: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// PLANLI KESİNTİLER
+// -----------------------------------------------------------------------------
+
+#[get("/api/santral/{id}/kesintiler")]
+pub async fn list_planli_kesintiler_handler(
+    pool: web::Data<PgPool>,
+    istemci: IstemciYetkili<gerek::SantralOku>,
+    id: web::Path<Uuid>,
+) -> HttpResponse {
+    let santral_id = id.into_inner();
+    if let Err(resp) = istemci_santral_erisimi(pool.get_ref(), &istemci, santral_id).await {
+        return resp;
+    }
+    match db::list_planli_kesintiler(pool.get_ref(), santral_id, None).await {
+        Ok(k) => HttpResponse::Ok().json(k),
+        Err(e) => {
+            log::error!("planlı kesinti listeleme hata: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[post("/api/santral/{id}/kesintiler")]
+pub async fn create_planli_kesinti_handler(
+    pool: web::Data<PgPool>,
+    user: Yetkili<gerek::PlanYaz>,
+    id: web::Path<Uuid>,
+    body: web::Json<PlanliKesintiInput>,
+) -> HttpResponse {
+    let santral_id = id.into_inner();
+    if let Err(resp) = santral_sahipligi(pool.get_ref(), &user, santral_id).await {
+        return resp;
+    }
+    let girdi = body.into_inner();
+    if girdi.bitis <= girdi.baslangic {
+        return bad_request("bitis, baslangic'tan sonra olmalı.");
+    }
+    if !girdi.kullanilabilir_guc_mw.is_finite() || girdi.kullanilabilir_guc_mw < 0.0 {
+        return bad_request("kullanilabilir_guc_mw negatif olmayan bir sayı olmalı.");
+    }
+
+    match db::create_planli_kesinti(pool.get_ref(), santral_id, girdi, user.user_id).await {
+        Ok(k) => HttpResponse::Ok().json(k),
+        Err(e) => {
+            log::error!("planlı kesinti ekleme hata: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[delete("/api/santral/{id}/kesintiler/{kesinti_id}")]
+pub async fn delete_planli_kesinti_handler(
+    pool: web::Data<PgPool>,
+    user: Yetkili<gerek::PlanYaz>,
+    path: web::Path<(Uuid, Uuid)>,
+) -> HttpResponse {
+    let (santral_id, kesinti_id) = path.into_inner();
+    if let Err(resp) = santral_sahipligi(pool.get_ref(), &user, santral_id).await {
+        return resp;
+    }
+    match db::delete_planli_kesinti(pool.get_ref(), santral_id, kesinti_id).await {
+        Ok(true) => HttpResponse::Ok().json(serde_json::json!({"status":"success"})),
+        Ok(false) => not_found("Kesinti bulunamadı."),
+        Err(e) => {
+            log::error!("planlı kesinti silme hata: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct PortfoyQuery {
+    pub gun: Option<chrono::NaiveDate>, // varsayılan: yarın (Europe/Istanbul)
+}
+
+/// Portföy: teslim günü için hangi santrallerin planı var / yok.
+#[get("/api/kgupplan/durum")]
+pub async fn kgup_portfoy_durumu_handler(
+    pool: web::Data<PgPool>,
+    istemci: IstemciYetkili<gerek::PlanOku>,
+    q: web::Query<PortfoyQuery>,
+) -> HttpResponse {
+    let gun = q.gun.unwrap_or_else(|| piyasa_gunu::bugun() + Duration::days(1));
+
+    let musteri_filtresi = match &*istemci {
+        Istemci::Kullanici(u) if u.yetkili(Yetki::TumMusteriler) => None,
+        i => Some(i.musteri_id()),
+    };
+    let satirlar = match db::kgup_plan_durumu(pool.get_ref(), gun, musteri_filtresi).await {
+        Ok(r) => r,
+        Err(e) => {
+            log::error!("KGÜP portföy durumu hata: {e}");
+            return HttpResponse::InternalServerError().finish();
+        }
+    };
+
+    let santraller: Vec<KgupPlanDurumu> = satirlar
+        .into_iter()
+        .filter(|r| match &*istemci {
+            Istemci::Anahtar(k) => k.santral_idler.contains(&r.santral_id),
+            Istemci::Kullanici(_) => true,
+        })
+        .map(|r| KgupPlanDurumu {
+            santral_id: r.santral_id,
+            santral_ad: r.santral_ad,
+            tip: r.tip,
+            plan_var: r.plan_id.is_some(),
+            plan_id: r.plan_id,
+            toplam_mwh: r.saatlik_plan_mwh.as_ref().and_then(|v| v.as_array()).map(|a| {
+                a.iter().filter_map(|x| x.as_f64()).sum()
+            }),
+            olusturma_tarihi: r.olusturma_tarihi,
+            revizyon_no: r.revizyon_no,
+            guncelleme_tarihi: r.guncelleme_tarihi,
+            durum: r.durum,
+            onayli_revizyon_no: r.onayli_revizyon_no,
+        })
+        .collect();
+
+    let plani_olan = santraller.iter().filter(|s| s.plan_var).count();
+    HttpResponse::Ok().json(KgupPortfoyResponse {
+        gun,
+        santral_sayisi: santraller.len(),
+        plani_olan,
+        plani_olmayan: santraller.len() - plani_olan,
+        santraller,
+    })
+}
+
+/// Teslim günü için kapı kapanışı: gün öncesi kapanış anı ve kapanmış saatler.
+#[get("/api/kgupplan/kapanis")]
+pub async fn kgup_kapanis_handler(
+    kurallar: web::Data<KapanisKurallari>,
+    _istemci: IstemciYetkili<gerek::PlanOku>,
+    q: web::Query<PortfoyQuery>,
+    c: web::Query<CozunurlukQuery>,
+) -> HttpResponse {
+    let gun = q.gun.unwrap_or_else(|| piyasa_gunu::bugun() + Duration::days(1));
+    let dk = match cozunurluk_sec(c.cozunurluk_dk) {
+        Ok(dk) => dk,
+        Err(resp) => return resp,
+    };
+    let simdi = Utc::now();
+    let gun_oncesi = kurallar.gun_oncesi_kapanisi(gun);
+    let kapali_saatler = kurallar.kapali_periyotlar(gun, dk, simdi);
+
+    HttpResponse::Ok().json(KgupKapanisDurumu {
+        gun,
+        gun_oncesi_kapanis: piyasa_gunu::yerel(gun_oncesi),
+        gun_oncesi_kapandi: simdi >= gun_oncesi,
+        cozunurluk_dk: dk,
+        gun_ici_guncelleme: kurallar.gun_ici_guncelleme,
+        gun_ici_kapanis_dk: kurallar.gun_ici_kapanis_dk,
+        acik_saat_sayisi: kgup::periyot_sayisi(gun, dk) - kapali_saatler.len(),
+        kapali_saatler,
+    })
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct PysQuery {
+    pub gun: Option<chrono::NaiveDate>, // varsayılan: yarın (Europe/Istanbul)
+    pub format: Option<String>,         // csv (varsayılan) | xml
+    pub santral_id: Option<String>,     // virgülle ayrılmış liste; yoksa erişilebilen tüm santraller
+}
+
+/// Teslim günü planlarını PYS yükleme dosyası (CSV / XML) olarak indir.
+#[get("/api/kgupplan/pys")]
+pub async fn kgup_pys_disa_aktar_handler(
     pool: web::Data<PgPool>,
-    user: AuthenticatedUser,
-    id: web::Path<Uuid>,
-    santral_data: web::Json<InputSantral>,
-) -> impl Responder {
-    let santral_id = id.into_inner();
+    istemci: IstemciYetkili<gerek::PlanOku>,
+    q: web::Query<PysQuery>,
+    c: web::Query<CozunurlukQuery>,
+) -> HttpResponse {
+    let gun = q.gun.unwrap_or_else(|| piyasa_gunu::bugun() + Duration::days(1));
+    let dk = match cozunurluk_sec(c.cozunurluk_dk) {
+        Ok(dk) => dk,
+        Err(resp) => return resp,
+    };
+    let xml = match q.format.as_deref().unwrap_or("csv") {
+        "csv" => false,
+        "xml" => true,
+        _ => return bad_request("format csv ya da xml olmalı."),
+    };
 
-    if user.rol != "admin" {
-        match db::santral_belongs_to_musteri(pool.get_ref(), santral_id, user.musteri_id).await {
-            Ok(true) => {}
-            Ok(false) => {
-                return HttpResponse::Forbidden().json(serde_json::json!({"status":"error","message":"Yetkin yok."}));
+    let mut istenen: Option<Vec<Uuid>> = None;
+    if let Some(liste) = q.santral_id.as_deref().filter(|s| !s.trim().is_empty()) {
+        let mut idler = Vec::new();
+        for parca in liste.split(',') {
+            match parca.trim().parse::<Uuid>() {
+                Ok(id) => idler.push(id),
+                Err(_) => return bad_request(&format!("Geçersiz santral_id: {}", parca.trim())),
             }
-            Err(e) => {
-                eprintln!("Sahiplik kontrol hata: {e:?}");
-                return HttpResponse::InternalServerError().finish();
+        }
+        idler.sort();
+        idler.dedup();
+        for id in &idler {
+            if let Err(resp) = istemci_santral_erisimi(pool.get_ref(), &istemci, *id).await {
+                return resp;
             }
         }
+        istenen = Some(idler);
     }
 
-    let data = santral_data.into_inner();
-    match db::update_santral_by_id(pool.get_ref(), santral_id, data).await {
-        Ok(santral) => HttpResponse::Ok().json(santral),
-        Err(sqlx::Error::RowNotFound) => HttpResponse::NotFound().json(serde_json::json!({"status":"error","message":"Santral bulunamadı."})),
+    let musteri_filtresi = match &*istemci {
+        Istemci::Kullanici(u) if u.yetkili(Yetki::TumMusteriler) => None,
+        i => Some(i.musteri_id()),
+    };
+    let satirlar = match db::pys_planlari(pool.get_ref(), gun, musteri_filtresi).await {
+        Ok(r) => r,
         Err(e) => {
-            eprintln!("Update santral hata: {e:?}");
-            HttpResponse::InternalServerError().finish()
+            log::error!("PYS plan sorgu hata: {e}");
+            return HttpResponse::InternalServerError().finish();
+        }
+    };
+    let satirlar: Vec<_> = satirlar
+        .into_iter()
+        .filter(|r| match &*istemci {
+            Istemci::Anahtar(k) => k.santral_idler.contains(&r.santral_id),
+            Istemci::Kullanici(_) => true,
+        })
+        .filter(|r| istenen.as_ref().is_none_or(|ids| ids.contains(&r.santral_id)))
+        .collect();
+
+    if let Some(ids) = &istenen {
+        let plansiz: Vec<Uuid> = ids
+            .iter()
+            .filter(|id| !satirlar.iter().any(|r| r.santral_id == **id))
+            .copied()
+            .collect();
+        if !plansiz.is_empty() {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "status": "error",
+                "message": "Teslim günü için planı olmayan santraller var.",
+                "santral_idler": plansiz,
+            }));
         }
     }
-}
+    if satirlar.is_empty() {
+        return not_found("Teslim günü için plan bulunamadı.");
+    }
 
-#[get("/api/santral/{id}")]
-pub async fn get_santral_by_id_handler(
-    pool: web::Data<PgPool>,
-    user: AuthenticatedUser,
-    id: web::Path<Uuid>,
-) -> impl Responder {
-    let santral_id = id.into_inner();
+    let mut planlar = Vec::with_capacity(satirlar.len());
+    let mut eksikler = Vec::new();
+    for r in satirlar {
+        // Yalnızca son onaylı revizyon gönderilir
+        let degerler = match (&r.saatlik_plan_mwh, r.cozunurluk_dk) {
+            (Some(v), Some(kayit_dk)) => kgup::donustur(&saatlik_degerler(v), kayit_dk, dk),
+            _ => Vec::new(),
+        };
+        let tam = degerler.len() == kgup::periyot_sayisi(gun, dk) && degerler.iter().all(Option::is_some);
+        match (&r.eic_kodu, tam) {
+            (Some(eic), true) => planlar.push(pys::PysPlan {
+                santral_id: r.santral_id,
+                eic_kodu: eic.clone(),
+                uevcb_adi: r.uevcb_adi.unwrap_or_else(|| r.santral_ad.clone()),
+                degerler: degerler.into_iter().flatten().collect(),
+            }),
+            (eic, _) => eksikler.push(serde_json::json!({
+                "santral_id": r.santral_id,
+                "santral_ad": r.santral_ad,
+                "sebep": if eic.is_none() {
+                    "eic_kodu_yok"
+                } else if r.revizyon_no.is_none() {
+                    "onayli_revizyon_yok"
+                } else {
+                    "plan_eksik"
+                },
+            })),
+        }
+    }
+    if !eksikler.is_empty() {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "status": "error",
+            "message": "Bazı santraller PYS dosyasına yazılamıyor (EIC kodu, onaylı revizyon ya da plan eksik).",
+            "santraller": eksikler,
+        }));
+    }
 
-    if user.rol != "admin" {
-        match db::santral_belongs_to_musteri(pool.get_ref(), santral_id, user.musteri_id).await {
-            Ok(true) => {}
-            Ok(false) => {
-                return HttpResponse::Forbidden().json(serde_json::json!({"status":"error","message":"Yetkin yok."}));
-            }
+    let (govde, tur, uzanti) = if xml {
+        (pys::xml(gun, dk, &planlar).into_bytes(), "application/xml; charset=utf-8", "xml")
+    } else {
+        match pys::csv(gun, dk, &planlar) {
+            Ok(b) => (b, "text/csv; charset=utf-8", "csv"),
             Err(e) => {
-                eprintln!("Sahiplik kontrol hata: {e:?}");
+                log::error!("PYS CSV yazma hata: {e}");
                 return HttpResponse::InternalServerError().finish();
             }
         }
-    }
-
-    match db::get_santral_by_id(pool.get_ref(), santral_id).await {
-        Ok(santral) => HttpResponse::Ok().json(santral),
-        Err(sqlx::Error::RowNotFound) => HttpResponse::NotFound().json(serde_json::json!({"status":"error","message":"Santral bulunamadı."})),
-        Err(e) => {
-            eprintln!("Get santral hata: {e:?}");
-            HttpResponse::InternalServerError().finish()
-        }
-    }
+    };
+    HttpResponse::Ok()
+        .content_type(tur)
+        .insert_header((
+            "Content-Disposition",
+            format!("attachment; filename=\"kgup_pys_{gun}.{uzanti}\""),
+        ))
+        .body(govde)
 }
 
-
 // -----------------------------------------------------------------------------
-// DENGESİZLİK HESAPLAMA
+// KGÜP TOPLU PLAN AKTARIMI
 // -----------------------------------------------------------------------------
+// POST /api/kgupplan/aktar?onizleme=true&cozunurluk_dk=60&aciklama=...&kapanis_gerekcesi=...
+//   Content-Type: text/csv                                                      → CSV
+//   Content-Type: application/vnd.openxmlformats-officedocument.spreadsheetml.sheet → XLSX
+//
+// onizleme=true yalnızca mevcut planlarla karşılaştırır. Aksi halde tek bir hata
+// bile varsa hiçbir plan yazılmaz (400); hata yoksa değişen planlar tek
+// transaction'da yazılır. Dosya biçimi için bkz. plan_aktarim.rs.
 
-#[post("/api/hesapla/dengesizlik")]
-pub async fn dengesizlik_hesapla_handler(
-    inputs: web::Json<Vec<DengesizlikInput>>,
-) -> impl Responder {
-    let outputs: Vec<DengesizlikOutput> = inputs
-        .iter()
-        .map(|input| {
-            let dengesizlik_miktari = input.gerceklesen_uretim_mwh - input.tahmini_uretim_mwh;
-            let (dengesizlik_tipi, dengesizlik_tutari, aciklama) = if dengesizlik_miktari > 0.0 {
-                let fiyat = input.ptf_tl.min(input.smf_tl);
-                (
-                    "Pozitif Dengesizlik (Fazla Üretim)".to_string(),
-                    dengesizlik_miktari * fiyat,
-                    format!(
-                        "Sistem, fazla ürettiğiniz {:.2} MWh enerjiyi, düşük olan {:.2} TL fiyattan satın aldı.",
-                        dengesizlik_miktari, fiyat
-                    ),
-                )
-            } else if dengesizlik_miktari < 0.0 {
-                let fiyat = input.ptf_tl.max(input.smf_tl);
-                (
-                    "Negatif Dengesizlik (Eksik Üretim)".to_string(),
-                    dengesizlik_miktari * fiyat,
-                    format!(
-                        "Sistem, eksik ürettiğiniz {:.2} MWh enerjiyi, yüksek olan {:.2} TL fiyattan adınıza satın aldı.",
-                        dengesizlik_miktari.abs(),
-                        fiyat
-                    ),
-                )
-            } else {
-                (
-                    "Dengede".to_string(),
-                    0.0,
-                    "Santral üretim tahmini ile tam dengededir.".to_string(),
-                )
-            };
-
-            DengesizlikOutput {
-                tahmini_uretim_mwh: input.tahmini_uretim_mwh,
-                gerceklesen_uretim_mwh: input.gerceklesen_uretim_mwh,
-                ptf_tl: input.ptf_tl,
-                smf_tl: input.smf_tl,
-                dengesizlik_miktari_mwh: dengesizlik_miktari,
-                dengesizlik_tutari_tl: dengesizlik_tutari,
-                dengesizlik_tipi,
-                aciklama,
-            }
-        })
-        .collect();
+const PLAN_AKTARIM_MAX_GOVDE: usize = 8 * 1024 * 1024; // 8 MiB
 
-    HttpResponse::Ok().json(outputs)
+#[derive(Debug, serde::Deserialize)]
+pub struct PlanAktarimQuery {
+    #[serde(default)]
+    pub onizleme: bool,
+    pub cozunurluk_dk: Option<i32>, // dosyadaki tüm planlar için; varsayılan 60
+    pub aciklama: Option<String>,
+    pub kapanis_gerekcesi: Option<String>,
+    #[serde(default)]
+    pub taslak: bool,
 }
 
-// -----------------------------------------------------------------------------
-// KGÜP PLAN
-// -----------------------------------------------------------------------------
-
-#[post("/api/santral/{id}/kgupplan")]
-pub async fn create_or_update_kgup_plan_handler(
+#[post("/api/kgupplan/aktar")]
+pub async fn kgup_plan_aktar_handler(
+    req: HttpRequest,
     pool: web::Data<PgPool>,
-    user: AuthenticatedUser,
-    id: web::Path<Uuid>,
-    body: web::Json<KgupPlanInput>,
-) -> impl Responder {
+    kurallar: web::Data<KapanisKurallari>,
+    user: Yetkili<gerek::PlanYaz>,
+    q: web::Query<PlanAktarimQuery>,
+    payload: web::Payload,
+) -> HttpResponse {
+    let dk = match cozunurluk_sec(q.cozunurluk_dk) {
+        Ok(dk) => dk,
+        Err(resp) => return resp,
+    };
+    let govde = match govde_oku(payload, PLAN_AKTARIM_MAX_GOVDE).await {
+        Ok(b) => b,
+        Err(resp) => return resp,
+    };
+    let content_type = req
+        .headers()
+        .get(actix_web::http::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+    // XLSX bir ZIP arşividir; tür başlığı genel gönderilmişse imzadan anlaşılır
+    let ayristirma = if content_type.starts_with("application/vnd.openxmlformats")
+        || govde.starts_with(b"PK\x03\x04")
+    {
+        plan_aktarim::parse_xlsx(&govde)
+    } else {
+        plan_aktarim::parse_csv(&govde)
+    };
+    let ayristirma = match ayristirma {
+        Ok(a) => a,
+        Err(msg) => return bad_request(&msg),
+    };
 
-    let santral_id = id.into_inner();
+    let gerekce = q.kapanis_gerekcesi.as_deref().map(str::trim).filter(|g| !g.is_empty());
+    let kilit_asabilir = user.yetkili(Yetki::PlanKilitAs);
+    let kilit_hatasi = |kilitli: &[usize]| {
+        if kilitli.is_empty() || (kilit_asabilir && gerekce.is_some()) {
+            return None;
+        }
+        let neden = if kilit_asabilir { "kapanis_gerekcesi zorunlu" } else { "artık değiştirilemez" };
+        let periyotlar: Vec<String> = kilitli.iter().map(|i| (i + 1).to_string()).collect();
+        Some(format!("kapı kapandı; kilitli periyotlar ({dk} dk) {} {neden}", periyotlar.join(", ")))
+    };
+    let simdi = Utc::now();
+    let mut hatalar = ayristirma.hatalar;
+    let mut erisim: std::collections::HashMap<Uuid, bool> = std::collections::HashMap::new();
+    let mut karsilastirmalar = Vec::new();
+    let mut yazilacak = Vec::new();
 
-    if user.rol != "admin" {
-        match db::santral_belongs_to_musteri(pool.get_ref(), santral_id, user.musteri_id).await {
-            Ok(true) => {}
-            Ok(false) => {
-                return HttpResponse::Forbidden().json(serde_json::json!({"status":"error","message":"Yetkin yok."}));
+    for p in ayristirma.planlar {
+        let satir_hatasi = |hata: String| crate::olcum::SatirHatasi { satir: p.satir, hata };
+
+        let erisebilir = match erisim.get(&p.santral_id) {
+            Some(ok) => *ok,
+            None => {
+                let ok = if user.yetkili(Yetki::TumMusteriler) {
+                    Ok(true)
+                } else {
+                    db::santral_belongs_to_musteri(pool.get_ref(), p.santral_id, user.musteri_id).await
+                };
+                match ok {
+                    Ok(ok) => *erisim.entry(p.santral_id).or_insert(ok),
+                    Err(e) => {
+                        log::error!("plan aktarım sahiplik kontrol hata: {e}");
+                        return HttpResponse::InternalServerError().finish();
+                    }
+                }
             }
+        };
+        if !erisebilir {
+            hatalar.push(satir_hatasi("santral bulunamadı ya da erişim yok".into()));
+            continue;
+        }
+
+        let (kurulu_guc_mw, kesintiler) =
+            match plan_siniri(pool.get_ref(), p.santral_id, p.plan_tarihi).await {
+                Ok(r) => r,
+                Err(resp) if resp.status() == actix_web::http::StatusCode::NOT_FOUND => {
+                    hatalar.push(satir_hatasi("santral bulunamadı ya da erişim yok".into()));
+                    continue;
+                }
+                Err(resp) => return resp,
+            };
+        let plan_hatalari = kgup::dogrula(p.plan_tarihi, dk, &p.degerler, kurulu_guc_mw, &kesintiler);
+        if !plan_hatalari.is_empty() {
+            hatalar.extend(plan_hatalari.into_iter().map(|h| satir_hatasi(h.mesaj)));
+            continue;
+        }
+
+        let mevcut = match db::get_kgup_plan(pool.get_ref(), p.santral_id, p.plan_tarihi).await {
+            Ok(m) => m,
             Err(e) => {
-                eprintln!("Sahiplik kontrol hata: {e:?}");
+                log::error!("KGÜP plan okuma hata: {e}");
                 return HttpResponse::InternalServerError().finish();
             }
+        };
+        let mevcut_degerler = mevcut
+            .as_ref()
+            .map(|m| kgup::donustur(&saatlik_degerler(&m.saatlik_plan_mwh), m.cozunurluk_dk, dk));
+        // Kilit son onaylı revizyona göre; karşılaştırma özeti güncel revizyona göre
+        let onayli = match onayli_plan_degerleri(pool.get_ref(), p.santral_id, p.plan_tarihi, dk).await {
+            Ok(o) => o,
+            Err(resp) => return resp,
+        };
+
+        let kilitli = kurallar.kilitli_degisiklikler(p.plan_tarihi, dk, onayli.as_deref(), &p.degerler, simdi);
+        if let Some(hata) = kilit_hatasi(&kilitli) {
+            hatalar.push(satir_hatasi(hata));
+            continue;
+        }
+
+        let degisen_periyot_sayisi = match &mevcut_degerler {
+            Some(m) => (0..p.degerler.len().max(m.len()))
+                .filter(|&i| match (m.get(i).copied().flatten(), p.degerler.get(i)) {
+                    (Some(a), Some(b)) => (a - b).abs() > kgup::LIMIT_TOLERANSI_MWH,
+                    (None, None) => false,
+                    _ => true,
+                })
+                .count(),
+            None => p.degerler.len(),
+        };
+        let durum = match &mevcut {
+            None => "yeni",
+            Some(m) if degisen_periyot_sayisi > 0 || m.cozunurluk_dk != dk => "degisiyor",
+            Some(_) => "ayni",
+        };
+        if durum != "ayni" {
+            yazilacak.push((
+                karsilastirmalar.len(),
+                p.santral_id,
+                KgupPlanInput {
+                    plan_tarihi: p.plan_tarihi,
+                    saatlik_plan_mwh: p.degerler.clone(),
+                    cozunurluk_dk: Some(dk),
+                    aciklama: q.aciklama.clone(),
+                    kapanis_gerekcesi: (!kilitli.is_empty()).then(|| gerekce.unwrap_or_default().to_string()),
+                    taslak: q.taslak,
+                },
+            ));
         }
+        karsilastirmalar.push(plan_aktarim::PlanKarsilastirmasi {
+            satir: p.satir,
+            santral_id: p.santral_id,
+            plan_tarihi: p.plan_tarihi,
+            durum,
+            mevcut_revizyon_no: mevcut.as_ref().map(|m| m.revizyon_no),
+            mevcut_toplam_mwh: mevcut_degerler.map(|m| m.iter().flatten().sum()),
+            yeni_toplam_mwh: p.degerler.iter().sum(),
+            degisen_periyot_sayisi,
+            revizyon_no: None,
+        });
     }
+    hatalar.sort_by_key(|h| h.satir);
 
-    match db::create_or_update_kgup_plan(pool.get_ref(), santral_id, body.into_inner()).await {
-        Ok(plan) => HttpResponse::Ok().json(plan),
-        Err(e) => {
-            eprintln!("KGÜP Planı kaydedilirken hata oluştu: {:?}", e);
-            HttpResponse::InternalServerError().finish()
+    let mut yaz = !q.onizleme && hatalar.is_empty() && !yazilacak.is_empty();
+    if yaz {
+        let (siralar, planlar): (Vec<usize>, Vec<(Uuid, KgupPlanInput)>) =
+            yazilacak.into_iter().map(|(i, s, p)| (i, (s, p))).unzip();
+        let durum = yeni_revizyon_durumu(&user, q.taslak);
+        // Önizlemedeki kilit kontrolü yazım anında, plan satırları kilitliyken tekrarlanır
+        let kilit = |santral_id: Uuid, plan: &KgupPlanInput, onayli| {
+            let mevcut = onayli_degerleri(onayli, dk);
+            let kilitli =
+                kurallar.kilitli_degisiklikler(plan.plan_tarihi, dk, mevcut.as_deref(), &plan.saatlik_plan_mwh, Utc::now());
+            match kilit_hatasi(&kilitli) {
+                Some(hata) => Err((santral_id, plan.plan_tarihi, hata)),
+                None => Ok((!kilitli.is_empty()).then(|| gerekce.unwrap_or_default().to_string())),
+            }
+        };
+        match db::kgup_planlarini_toplu_yaz(pool.get_ref(), &planlar, user.user_id, "aktarim", durum, kilit).await {
+            Ok(kayitlar) => {
+                for (i, k) in siralar.into_iter().zip(kayitlar) {
+                    karsilastirmalar[i].revizyon_no = Some(k.revizyon_no);
+                }
+            }
+            Err(db::PlanYazHatasi::Kilit((santral_id, gun, hata))) => {
+                yaz = false;
+                let satir = karsilastirmalar
+                    .iter()
+                    .find(|k| k.santral_id == santral_id && k.plan_tarihi == gun)
+                    .map_or(0, |k| k.satir);
+                hatalar.push(crate::olcum::SatirHatasi { satir, hata });
+            }
+            Err(db::PlanYazHatasi::Db(e)) => {
+                log::error!("KGÜP toplu aktarım DB hata: {e}");
+                return HttpResponse::InternalServerError().finish();
+            }
         }
     }
+
+    let say = |d: &str| karsilastirmalar.iter().filter(|k| k.durum == d).count();
+    let ozet = plan_aktarim::PlanAktarimOzeti {
+        onizleme: q.onizleme,
+        yazildi: yaz,
+        cozunurluk_dk: dk,
+        plan_sayisi: karsilastirmalar.len(),
+        yeni: say("yeni"),
+        degisen: say("degisiyor"),
+        ayni: say("ayni"),
+        reddedilen: hatalar.len(),
+        planlar: karsilastirmalar,
+        hatalar,
+    };
+    if !q.onizleme && ozet.reddedilen > 0 {
+        return HttpResponse::BadRequest().json(ozet);
+    }
+    HttpResponse::Ok().json(ozet)
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct KaliteQuery {
+    pub kalite: Option<KaliteModu>, // ham (varsayılan) | haric | doldur
 }
 
 #[derive(Debug, serde::Deserialize)]
 pub struct SapmaQuery {
-    pub gun: Option<String>, // YYYY-MM-DD (opsiyonel; yoksa bugün UTC)
+    pub gun: Option<String>, // YYYY-MM-DD (opsiyonel; yoksa bugün, Europe/Istanbul)
 }
 
 // -----------------------------------------------------------------------------
@@ -380,16 +3369,22 @@ pub struct SapmaQuery {
 // GET /api/santral/{id}/sapma/{gun}
 // örn: /api/santral/ab3d.../sapma/2025-07-17
 //
-// Yetki:
-//   - admin: istediği santrali sorabilir
-//   - user : sadece kendi portföyündeki santrali sorabilir
+// Yetki: plan_oku
+//   - tum_musteriler: istediği santrali sorabilir
+//   - diğerleri     : sadece kendi portföyündeki santrali sorabilir
 #[get("/api/santral/{id}/sapma/{gun}")]
 pub async fn sapma_gun_handler(
     pool: web::Data<PgPool>,
-    user: AuthenticatedUser,           // bearer -> musteri_id, rol
-    path: web::Path<(Uuid, String)>,   // (santral_id, gun_str)
+    istemci: IstemciYetkili<gerek::PlanOku>, // bearer ya da X-API-Key
+    path: web::Path<(Uuid, String)>,         // (santral_id, gun_str)
+    q: web::Query<KaliteQuery>,
+    c: web::Query<CozunurlukQuery>,
 ) -> Result<HttpResponse, Error> {
     let (santral_id, gun_str) = path.into_inner();
+    let cozunurluk_dk = match cozunurluk_sec(c.cozunurluk_dk) {
+        Ok(dk) => dk,
+        Err(resp) => return Ok(resp),
+    };
 
     // Tarih parse
     let gun = match chrono::NaiveDate::parse_from_str(&gun_str, "%Y-%m-%d") {
@@ -399,22 +3394,23 @@ pub async fn sapma_gun_handler(
         }
     };
 
-    // user ise santral sahiplik kontrolü
-    if user.rol != "admin" {
-        match db::santral_belongs_to_musteri(pool.get_ref(), santral_id, user.musteri_id).await {
-            Ok(true) => {}
-            Ok(false) => {
-                return Ok(HttpResponse::Forbidden().body("Bu santrale erişim yetkin yok."));
-            }
-            Err(e) => {
-                log::error!("sahiplik kontrol hata: {e}");
-                return Ok(HttpResponse::InternalServerError().finish());
-            }
+    // santral erişim kontrolü (sahiplik / anahtar kapsamı)
+    match istemci.santral_erisimi(pool.get_ref(), santral_id).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return Ok(HttpResponse::Forbidden().body("Bu santrale erişim yetkin yok."));
+        }
+        Err(e) => {
+            log::error!("sahiplik kontrol hata: {e}");
+            return Ok(HttpResponse::InternalServerError().finish());
         }
     }
 
+    // Bayraklı okumalar saklı bayraklara göre dışlanır/doldurulur (bkz. kalite.rs)
+    let kalite_modu = q.kalite.unwrap_or_default();
+
     // Veriyi çek
-    let rows = match db::sapma_saatlik_gun(pool.get_ref(), santral_id, gun).await {
+    let (plan_revizyon_no, rows) = match db::sapma_saatlik_gun(pool.get_ref(), santral_id, gun, cozunurluk_dk, kalite_modu).await {
         Ok(r) => r,
         Err(e) => {
             log::error!("sapma_saatlik_gun DB hata: {e}");
@@ -438,10 +3434,10 @@ pub async fn sapma_gun_handler(
             toplam_gercek += g;
             has_gercek = true;
         }
-        if let (Some(p), Some(g)) = (r.plan_mwh, r.gercek_mwh) {
-            if p > 0.0 {
-                toplam_abs += (p - g).abs();
-            }
+        if let (Some(p), Some(g)) = (r.plan_mwh, r.gercek_mwh)
+            && p > 0.0
+        {
+            toplam_abs += (p - g).abs();
         }
     }
 
@@ -460,6 +3456,8 @@ pub async fn sapma_gun_handler(
     let resp = SapmaGunResponse {
         santral_id,
         gun,
+        cozunurluk_dk,
+        plan_revizyon_no,
         rows,
         toplam_plan_mwh: if has_plan { Some(toplam_plan) } else { None },
         toplam_gercek_mwh: if has_gercek { Some(toplam_gercek) } else { None },
@@ -473,43 +3471,40 @@ pub async fn sapma_gun_handler(
 #[get("/api/santral/{id}/tarihsel")]
 pub async fn plan_gercek_tarihsel_handler(
     pool: web::Data<PgPool>,
-    user: AuthenticatedUser,
+    istemci: IstemciYetkili<gerek::PlanOku>,
     path: web::Path<Uuid>,
     q: web::Query<std::collections::HashMap<String,String>>,
 ) -> HttpResponse {
     let santral_id = path.into_inner();
 
-    // User yetki kontrolü (admin her şeyi görür; user sadece kendi)
-    if user.rol != "admin" {
-        match db::santral_belongs_to_musteri(pool.get_ref(), santral_id, user.musteri_id).await {
-            Ok(true) => {}
-            Ok(false) => {
-                return HttpResponse::Forbidden().body("Yetkin yok.");
-            }
-            Err(e) => {
-                eprintln!("sahiplik kontrolü hata: {e}");
-                return HttpResponse::InternalServerError().finish();
-            }
+    // Sahiplik kontrolü (tum_musteriler her şeyi görür; diğerleri / anahtarlar sadece kendi)
+    match istemci.santral_erisimi(pool.get_ref(), santral_id).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return HttpResponse::Forbidden().body("Yetkin yok.");
+        }
+        Err(e) => {
+            eprintln!("sahiplik kontrolü hata: {e}");
+            return HttpResponse::InternalServerError().finish();
         }
     }
 
     // Paramları çek
-    let start_str = q.get("start").map(String::as_str).unwrap_or_else(|| {
-        // default: bugün
-        // TR timezone’a girmiyoruz; UTC date
-        let today = chrono::Utc::now().date_naive();
-        Box::leak(Box::new(today.to_string()))
-    });
-    let end_str = q.get("end").map(String::as_str).unwrap_or(start_str); // yoksa tek gün
-
-    // parse
-    let start = match chrono::NaiveDate::parse_from_str(start_str, "%Y-%m-%d") {
-        Ok(d) => d,
-        Err(_) => return HttpResponse::BadRequest().body("start format YYYY-MM-DD olmalı"),
+    let parse_gun = |s: &str| chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d");
+    let start = match q.get("start") {
+        Some(s) => match parse_gun(s) {
+            Ok(d) => d,
+            Err(_) => return HttpResponse::BadRequest().body("start format YYYY-MM-DD olmalı"),
+        },
+        // default: bugün (teslim günü, Europe/Istanbul)
+        None => piyasa_gunu::bugun(),
     };
-    let mut end = match chrono::NaiveDate::parse_from_str(end_str, "%Y-%m-%d") {
-        Ok(d) => d,
-        Err(_) => return HttpResponse::BadRequest().body("end format YYYY-MM-DD olmalı"),
+    let mut end = match q.get("end") {
+        Some(s) => match parse_gun(s) {
+            Ok(d) => d,
+            Err(_) => return HttpResponse::BadRequest().body("end format YYYY-MM-DD olmalı"),
+        },
+        None => start, // yoksa tek gün
     };
     if end <= start {
         // tek gün durumu: end = start + 1
@@ -521,8 +3516,21 @@ pub async fn plan_gercek_tarihsel_handler(
         return HttpResponse::BadRequest().body("Tarih aralığı 31 günden uzun olamaz.");
     }
 
+    let kalite_modu = match q.get("kalite").map(String::as_str) {
+        None => KaliteModu::Ham,
+        Some(k) => match KaliteModu::parse(k) {
+            Some(m) => m,
+            None => return HttpResponse::BadRequest().body("kalite ham, haric ya da doldur olmalı"),
+        },
+    };
+    let cozunurluk_dk = match q.get("cozunurluk_dk").map(|v| v.parse::<i32>()) {
+        None => kgup::VARSAYILAN_COZUNURLUK_DK,
+        Some(Ok(dk)) if kgup::cozunurluk_gecerli(dk) => dk,
+        Some(_) => return HttpResponse::BadRequest().body("cozunurluk_dk 60 ya da 15 olmalı"),
+    };
+
     // DB çağrısı
-    let rows = match db::plan_gercek_aralik(pool.get_ref(), santral_id, start, end).await {
+    let rows = match db::plan_gercek_aralik(pool.get_ref(), santral_id, start, end, cozunurluk_dk, kalite_modu).await {
         Ok(r) => r,
         Err(e) => {
             eprintln!("plan_gercek_aralik DB hata: {e}");
@@ -540,7 +3548,8 @@ pub async fn plan_gercek_tarihsel_handler(
 
     let mut out_rows = Vec::with_capacity(rows.len());
 
-    for (ts, plan, gercek) in rows {
+    for (ts, plan, enerji) in rows {
+        let gercek = enerji.mwh;
         let sapma_opt = match (plan, gercek) {
             (Some(p), Some(a)) => {
                 toplam_plan += p;
@@ -565,9 +3574,11 @@ pub async fn plan_gercek_tarihsel_handler(
 
         out_rows.push(crate::models::PlanGercekSaat {
             ts_utc: ts,
+            ts_yerel: piyasa_gunu::yerel(ts),
             plan_mwh: plan,
             gercek_mwh: gercek,
             sapma_mwh: sapma_opt,
+            kapsama_orani: enerji.kapsama_orani,
         });
     }
 
@@ -577,6 +3588,7 @@ pub async fn plan_gercek_tarihsel_handler(
         santral_id,
         start,
         end,
+        cozunurluk_dk,
         rows: out_rows,
         toplam_plan_mwh: Some(toplam_plan),
         toplam_gercek_mwh: Some(toplam_gercek),
@@ -586,3 +3598,189 @@ pub async fn plan_gercek_tarihsel_handler(
 
     HttpResponse::Ok().json(resp)
 }
+
+// -----------------------------------------------------------------------------
+// VERİ KALİTESİ
+// -----------------------------------------------------------------------------
+
+#[derive(Debug, serde::Deserialize)]
+pub struct KaliteAralikQuery {
+    pub start: Option<chrono::NaiveDate>, // varsayılan: bugün (Europe/Istanbul)
+    pub end: Option<chrono::NaiveDate>,   // hariç; varsayılan: start + 1 gün
+}
+
+/// Gün bazında saklı kalite skoru ve sorunlar (arka plan işi ya da
+/// `kalite/degerlendir` yazar). Henüz değerlendirilmemiş gün listede yer almaz.
+#[get("/api/santral/{id}/kalite")]
+pub async fn kalite_gunleri_handler(
+    pool: web::Data<PgPool>,
+    istemci: IstemciYetkili<gerek::SantralOku>,
+    path: web::Path<Uuid>,
+    q: web::Query<KaliteAralikQuery>,
+) -> HttpResponse {
+    let santral_id = path.into_inner();
+
+    match istemci.santral_erisimi(pool.get_ref(), santral_id).await {
+        Ok(true) => {}
+        Ok(false) => return forbidden(),
+        Err(e) => {
+            log::error!("sahiplik kontrol hata: {e}");
+            return HttpResponse::InternalServerError().finish();
+        }
+    }
+
+    let start = q.start.unwrap_or_else(piyasa_gunu::bugun);
+    let end = q.end.filter(|e| *e > start).unwrap_or(start + Duration::days(1));
+    if (end - start).num_days() > 31 {
+        return bad_request("Tarih aralığı 31 günden uzun olamaz.");
+    }
+
+    match db::list_kalite_gunleri(pool.get_ref(), santral_id, start, end).await {
+        Ok(gunler) => HttpResponse::Ok().json(gunler),
+        Err(e) => {
+            log::error!("kalite günleri DB hata: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+/// Aralığı hemen değerlendirir: okuma bayraklarını ve günlük skorları yeniden yazar.
+#[post("/api/santral/{id}/kalite/degerlendir")]
+pub async fn kalite_degerlendir_handler(
+    pool: web::Data<PgPool>,
+    istemci: IstemciYetkili<gerek::OlcumYaz>,
+    path: web::Path<Uuid>,
+    q: web::Query<KaliteAralikQuery>,
+) -> HttpResponse {
+    let santral_id = path.into_inner();
+    if let Err(resp) = istemci_santral_erisimi(pool.get_ref(), &istemci, santral_id).await {
+        return resp;
+    }
+
+    let start = q.start.unwrap_or_else(piyasa_gunu::bugun);
+    let end = q.end.filter(|e| *e > start).unwrap_or(start + Duration::days(1));
+    if (end - start).num_days() > 31 {
+        return bad_request("Tarih aralığı 31 günden uzun olamaz.");
+    }
+
+    match kalite::aralik_degerlendir(pool.get_ref(), santral_id, start, end).await {
+        Ok(gunler) => HttpResponse::Ok().json(gunler),
+        Err(e) => {
+            log::error!("kalite değerlendirme DB hata: {e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// ÜRETİM ÖLÇÜMÜ AKTARIMI
+// -----------------------------------------------------------------------------
+// POST /api/olcumler
+//   Content-Type: application/json → [{ "santral_id", "zaman_utc", "guc_mw" }, ...]
+//   Content-Type: text/csv         → santral_id,zaman_utc,guc_mw (başlıklı)
+//
+// Yetki: olcum_yaz (kullanıcı ya da X-API-Key). Santral başına erişim kontrolü
+// yapılır; erişilemeyen santralin satırları reddedilir.
+
+const OLCUM_MAX_GOVDE: usize = 16 * 1024 * 1024; // 16 MiB
+
+/// Gövdeyi limitle oku.
+async fn govde_oku(mut payload: web::Payload, limit: usize) -> Result<web::BytesMut, HttpResponse> {
+    let mut buf = web::BytesMut::new();
+    while let Some(chunk) = payload.next().await {
+        let chunk = chunk.map_err(|_| bad_request("Gövde okunamadı."))?;
+        if buf.len() + chunk.len() > limit {
+            return Err(HttpResponse::PayloadTooLarge().json(serde_json::json!({"status":"error","message":"Gövde çok büyük."})));
+        }
+        buf.extend_from_slice(&chunk);
+    }
+    Ok(buf)
+}
+
+#[post("/api/olcumler")]
+pub async fn olcum_aktar_handler(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    istemci: IstemciYetkili<gerek::OlcumYaz>,
+    payload: web::Payload,
+) -> HttpResponse {
+    let govde = match govde_oku(payload, OLCUM_MAX_GOVDE).await {
+        Ok(b) => b,
+        Err(resp) => return resp,
+    };
+
+    let content_type = req
+        .headers()
+        .get(actix_web::http::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("application/json");
+
+    let ayristirma = if content_type.starts_with("text/csv") {
+        crate::olcum::parse_csv(&govde)
+    } else {
+        crate::olcum::parse_json(&govde)
+    };
+    let mut ayristirma = match ayristirma {
+        Ok(a) => a,
+        Err(msg) => return bad_request(&msg),
+    };
+    let toplam = ayristirma.toplam();
+    ayristirma.tekrarlari_ayikla();
+
+    // Santral erişimi (her santral için bir kez)
+    let mut erisim: std::collections::HashMap<Uuid, bool> = std::collections::HashMap::new();
+    for (_, o) in &ayristirma.satirlar {
+        if erisim.contains_key(&o.santral_id) {
+            continue;
+        }
+        match istemci.santral_erisimi(pool.get_ref(), o.santral_id).await {
+            Ok(ok) => {
+                erisim.insert(o.santral_id, ok);
+            }
+            Err(e) => {
+                log::error!("olcum aktar sahiplik kontrol hata: {e}");
+                return HttpResponse::InternalServerError().finish();
+            }
+        }
+    }
+
+    let mut hatalar = ayristirma.hatalar;
+    let mut yazilacak = Vec::with_capacity(ayristirma.satirlar.len());
+    for (satir, o) in ayristirma.satirlar {
+        if erisim.get(&o.santral_id).copied().unwrap_or(false) {
+            yazilacak.push(o);
+        } else {
+            hatalar.push(crate::olcum::SatirHatasi {
+                satir,
+                hata: "santral bulunamadı ya da erişim yok".into(),
+            });
+        }
+    }
+    hatalar.sort_by_key(|h| h.satir);
+
+    let (eklenen, guncellenen) = match db::upsert_olcumler(pool.get_ref(), &yazilacak).await {
+        Ok(r) => r,
+        Err(e) => {
+            log::error!("olcum upsert DB hata: {e}");
+            return HttpResponse::InternalServerError().finish();
+        }
+    };
+
+    HttpResponse::Ok().json(crate::olcum::AktarimOzeti {
+        toplam,
+        eklenen,
+        guncellenen,
+        reddedilen: hatalar.len(),
+        hatalar,
+        uyarilar: ayristirma.uyarilar,
+    })
+}
+
+/// MQTT abonesinin sayaçları: gecikme, düşürülen mesajlar.
+#[get("/api/admin/mqtt/durum")]
+pub async fn mqtt_durum_handler(
+    _user: Yetkili<gerek::MusteriYonet>,
+    durum: web::Data<MqttDurum>,
+) -> impl Responder {
+    HttpResponse::Ok().json(durum.ozet())
+}
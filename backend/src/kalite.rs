@@ -0,0 +1,540 @@
+// backend/src/kalite.rs
+//
+// Ölçüm veri kalitesi.
+// - Okuma bayrakları: negatif, kapasite_ustu, donuk (donmuş değer), sicrama
+// - Eksik aralıklar: beklenen örnekleme aralığından uzun boşluklar (gün bazında sorun olarak)
+// - Günlük skor = 100 × kapsama oranı × bayraksız okuma oranı
+// - Gün = teslim günü (Europe/Istanbul)
+//
+// Donmuş inverter tam "düz" bir gün üretir; sıfırdan farklı aynı değerin
+// DONUK_MIN_SURE boyunca tekrarlanması bu yüzden bayraklanır. Gece sıfırları
+// (GES) donuk sayılmaz.
+//
+// Sapma/tarihsel hesapları `KaliteModu` ile bayraklı okumaları dışlayabilir
+// (haric) ya da komşu temiz okumalardan doğrusal doldurabilir (doldur).
+//
+// Değerlendirme (bayrak + günlük skor yazımı) okuma uçlarında yapılmaz:
+// - Arka plan işi her KALITE_ARALIK_DAKIKA'da bayraklanmamış okuması olan günleri
+//   ve gün bitmeden skorlanmış günleri yeniden değerlendirir (tur başına KALITE_TUR_GUN)
+// - POST /api/santral/{id}/kalite/degerlendir aralığı hemen değerlendirir (olcum_yaz)
+// - Okuma uçları saklı bayrakları kullanır; henüz değerlendirilmemiş okuma temiz sayılır
+//
+// Ortam değişkenleri:
+//   KALITE_AKTIF=true  KALITE_ARALIK_DAKIKA=5  KALITE_TUR_GUN=100
+
+use std::env;
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db;
+use crate::enerji::{self, Yontem};
+use crate::models::KaliteGun;
+use crate::piyasa_gunu;
+
+/// Kurulu gücün bu katını aşan okuma kapasite üstüdür.
+pub const KAPASITE_TOLERANSI: f64 = 1.05;
+/// Kurulu gücün bu oranından daha negatif okuma bayraklanır (gece iç tüketimi normaldir).
+pub const NEGATIF_TOLERANSI: f64 = 0.01;
+/// Her iki komşusundan kurulu gücün bu oranı kadar aynı yönde sapan okuma sıçramadır.
+pub const SICRAMA_ORANI: f64 = 0.5;
+/// Aynı (sıfır olmayan) değerin donuk sayılması için en kısa süre.
+pub const DONUK_MIN_SURE: Duration = Duration::minutes(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Bayrak {
+    Negatif,
+    KapasiteUstu,
+    Donuk,
+    Sicrama,
+}
+
+impl Bayrak {
+    pub const HEPSI: [Bayrak; 4] = [Bayrak::Negatif, Bayrak::KapasiteUstu, Bayrak::Donuk, Bayrak::Sicrama];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Bayrak::Negatif => "negatif",
+            Bayrak::KapasiteUstu => "kapasite_ustu",
+            Bayrak::Donuk => "donuk",
+            Bayrak::Sicrama => "sicrama",
+        }
+    }
+}
+
+/// Arka plan değerlendirmesinin ayarları.
+#[derive(Debug, Clone)]
+pub struct KaliteConfig {
+    pub aktif: bool,
+    pub aralik_dakika: u64,
+    /// Bir turda değerlendirilecek en fazla (santral, gün).
+    pub tur_gun: i64,
+}
+
+impl KaliteConfig {
+    pub fn from_env() -> Self {
+        let aktif = env::var("KALITE_AKTIF")
+            .map(|v| matches!(v.trim(), "1" | "true" | "evet"))
+            .unwrap_or(true);
+        let aralik_dakika = env::var("KALITE_ARALIK_DAKIKA")
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(5u64)
+            .max(1);
+        let tur_gun = env::var("KALITE_TUR_GUN")
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(100i64)
+            .max(1);
+        Self { aktif, aralik_dakika, tur_gun }
+    }
+}
+
+/// Sapma/enerji hesaplarında bayraklı okumaların ele alınışı.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KaliteModu {
+    /// Tüm okumalar olduğu gibi.
+    #[default]
+    Ham,
+    /// Bayraklı okumalar yok sayılır (boşluk olur).
+    Haric,
+    /// Bayraklı okumalar komşu temiz okumalardan doğrusal doldurulur.
+    Doldur,
+}
+
+impl KaliteModu {
+    pub fn parse(s: &str) -> Option<KaliteModu> {
+        match s {
+            "ham" => Some(KaliteModu::Ham),
+            "haric" => Some(KaliteModu::Haric),
+            "doldur" => Some(KaliteModu::Doldur),
+            _ => None,
+        }
+    }
+}
+
+/// Gün içindeki tek sorun aralığı.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KaliteSorunu {
+    /// "eksik" ya da bayrak adı
+    pub tip: String,
+    pub bas: DateTime<Utc>,
+    pub bit: DateTime<Utc>,
+    /// Aralıktaki okuma sayısı (eksik için 0)
+    pub adet: usize,
+}
+
+/// Bir günün değerlendirmesi.
+#[derive(Debug, Default)]
+pub struct Degerlendirme {
+    /// Gün içindeki okumalar ve bayrakları
+    pub bayraklar: Vec<(DateTime<Utc>, Vec<Bayrak>)>,
+    pub sorunlar: Vec<KaliteSorunu>,
+    pub kapsama_orani: f64,
+    pub n_olcum: usize,
+    pub n_bayrakli: usize,
+    pub skor: f64,
+}
+
+/// Okumayı örten sürenin sonu ve boşluk toleransı.
+fn kapsam(yontem: Yontem, t: DateTime<Utc>) -> (DateTime<Utc>, Duration) {
+    match yontem {
+        Yontem::Periyot { cozunurluk } => (t + cozunurluk, cozunurluk / 2),
+        Yontem::Trapez { max_bosluk } => (t, max_bosluk),
+    }
+}
+
+/// [gun_bas, bitis) aralığını değerlendirir. `ornekler` sıralı olmalı ve
+/// yöntemin kenar payı kadar dış okuma içerebilir; `bitis` bugünse şimdiki zamandır.
+pub fn degerlendir(
+    ornekler: &[(DateTime<Utc>, f64)],
+    gun_bas: DateTime<Utc>,
+    bitis: DateTime<Utc>,
+    kurulu_mw: f64,
+    yontem: Yontem,
+) -> Degerlendirme {
+    let yontem = yontem.orneklere_gore(ornekler);
+    let n = ornekler.len();
+    let mut bayraklar: Vec<Vec<Bayrak>> = vec![Vec::new(); n];
+
+    // Tekil okuma kontrolleri
+    for (i, &(_, p)) in ornekler.iter().enumerate() {
+        if p < -NEGATIF_TOLERANSI * kurulu_mw {
+            bayraklar[i].push(Bayrak::Negatif);
+        }
+        if kurulu_mw > 0.0 && p > kurulu_mw * KAPASITE_TOLERANSI {
+            bayraklar[i].push(Bayrak::KapasiteUstu);
+        }
+        if kurulu_mw > 0.0 && i > 0 && i + 1 < n {
+            let (d1, d2) = (p - ornekler[i - 1].1, p - ornekler[i + 1].1);
+            let esik = SICRAMA_ORANI * kurulu_mw;
+            if d1.abs() > esik && d2.abs() > esik && d1.signum() == d2.signum() {
+                bayraklar[i].push(Bayrak::Sicrama);
+            }
+        }
+    }
+
+    // Donuk değer koşuları
+    let mut i = 0;
+    while i < n {
+        let mut j = i;
+        while j + 1 < n && ornekler[j + 1].1 == ornekler[i].1 {
+            j += 1;
+        }
+        if ornekler[i].1 != 0.0 && ornekler[j].0 - ornekler[i].0 >= DONUK_MIN_SURE {
+            for b in &mut bayraklar[i..=j] {
+                b.push(Bayrak::Donuk);
+            }
+        }
+        i = j + 1;
+    }
+
+    let gun_ici = |t: DateTime<Utc>| t >= gun_bas && t < bitis;
+    let mut sorunlar = Vec::new();
+
+    // Eksik aralıklar
+    let mut kapsanan = gun_bas;
+    for &(t, _) in ornekler.iter().filter(|(t, _)| *t < bitis) {
+        let (son, tolerans) = kapsam(yontem, t);
+        if t >= gun_bas && t - kapsanan > tolerans {
+            sorunlar.push(KaliteSorunu { tip: "eksik".into(), bas: kapsanan, bit: t, adet: 0 });
+        }
+        kapsanan = kapsanan.max(son);
+    }
+    let (_, tolerans) = kapsam(yontem, bitis);
+    if bitis - kapsanan > tolerans {
+        sorunlar.push(KaliteSorunu { tip: "eksik".into(), bas: kapsanan, bit: bitis, adet: 0 });
+    }
+
+    // Bayraklı okumaları ardışık aralıklara topla
+    for bayrak in Bayrak::HEPSI {
+        let mut acik: Option<KaliteSorunu> = None;
+        for (k, &(t, _)) in ornekler.iter().enumerate().filter(|(_, (t, _))| gun_ici(*t)) {
+            if bayraklar[k].contains(&bayrak) {
+                match &mut acik {
+                    Some(s) => {
+                        s.bit = t;
+                        s.adet += 1;
+                    }
+                    None => {
+                        acik = Some(KaliteSorunu { tip: bayrak.as_str().into(), bas: t, bit: t, adet: 1 })
+                    }
+                }
+            } else if let Some(s) = acik.take() {
+                sorunlar.push(s);
+            }
+        }
+        sorunlar.extend(acik);
+    }
+    sorunlar.sort_by_key(|s| s.bas);
+
+    // Kapsama: geçen süre içinde örtülen oran
+    let saat = ((bitis - gun_bas).num_seconds() as f64 / 3600.0).ceil() as usize;
+    let ortulen_sn: f64 = enerji::saatlik_enerji(ornekler, gun_bas, saat, yontem)
+        .iter()
+        .map(|e| e.kapsama_orani * 3600.0)
+        .sum();
+    let gecen_sn = (bitis - gun_bas).num_seconds().max(1) as f64;
+    let kapsama_orani = (ortulen_sn / gecen_sn).min(1.0);
+
+    let bayraklar: Vec<(DateTime<Utc>, Vec<Bayrak>)> = ornekler
+        .iter()
+        .zip(bayraklar)
+        .filter(|((t, _), _)| gun_ici(*t))
+        .map(|((t, _), b)| (*t, b))
+        .collect();
+    let n_olcum = bayraklar.len();
+    let n_bayrakli = bayraklar.iter().filter(|(_, b)| !b.is_empty()).count();
+    let skor = if n_olcum == 0 {
+        0.0
+    } else {
+        100.0 * kapsama_orani * (1.0 - n_bayrakli as f64 / n_olcum as f64)
+    };
+
+    Degerlendirme { bayraklar, sorunlar, kapsama_orani, n_olcum, n_bayrakli, skor }
+}
+
+/// Okumaları kalite moduna göre süzer. `bayrakli` → okuma en az bir bayrak taşıyor.
+pub fn uygula(
+    ornekler: Vec<(DateTime<Utc>, f64, bool)>,
+    kalite_modu: KaliteModu,
+) -> Vec<(DateTime<Utc>, f64)> {
+    match kalite_modu {
+        KaliteModu::Ham => ornekler.into_iter().map(|(t, p, _)| (t, p)).collect(),
+        KaliteModu::Haric => ornekler
+            .into_iter()
+            .filter(|(_, _, b)| !b)
+            .map(|(t, p, _)| (t, p))
+            .collect(),
+        KaliteModu::Doldur => {
+            let temiz: Vec<(DateTime<Utc>, f64)> = ornekler
+                .iter()
+                .filter(|(_, _, b)| !b)
+                .map(|&(t, p, _)| (t, p))
+                .collect();
+            ornekler
+                .into_iter()
+                .filter_map(|(t, p, bayrakli)| {
+                    if !bayrakli {
+                        return Some((t, p));
+                    }
+                    // t'yi çevreleyen temiz komşular arasında doğrusal değer
+                    let k = temiz.partition_point(|(tt, _)| *tt < t);
+                    let (t0, p0) = *temiz.get(k.checked_sub(1)?)?;
+                    let (t1, p1) = *temiz.get(k)?;
+                    let oran = (t - t0).num_milliseconds() as f64 / (t1 - t0).num_milliseconds() as f64;
+                    Some((t, p0 + (p1 - p0) * oran))
+                })
+                .collect()
+        }
+    }
+}
+
+/// Günü değerlendirir, okuma bayraklarını ve günlük skoru yazar.
+pub async fn gun_degerlendir(
+    pool: &PgPool,
+    santral_id: Uuid,
+    gun: NaiveDate,
+) -> Result<KaliteGun, sqlx::Error> {
+    let (kurulu_mw, cozunurluk) = db::santral_olcum_ayarlari(pool, santral_id).await?;
+    let yontem = Yontem::santral_icin(cozunurluk);
+
+    let gun_bas = piyasa_gunu::gun_baslangici(gun);
+    let gun_bit = piyasa_gunu::gun_baslangici(gun + Duration::days(1));
+    let bitis = gun_bit.min(Utc::now()).max(gun_bas);
+    let pay = yontem.kenar_payi().max(DONUK_MIN_SURE);
+
+    let ornekler: Vec<(DateTime<Utc>, f64)> =
+        db::olcum_ornekleri(pool, santral_id, gun_bas - pay, gun_bit + pay)
+            .await?
+            .into_iter()
+            .map(|(t, p, _)| (t, p))
+            .collect();
+
+    let d = degerlendir(&ornekler, gun_bas, bitis, kurulu_mw, yontem);
+    db::kalite_bayraklarini_yaz(pool, santral_id, &d.bayraklar).await?;
+    db::kalite_gun_kaydet(pool, santral_id, gun, &d).await
+}
+
+/// [start, end) günlerini değerlendirir (gelecek günler atlanır).
+pub async fn aralik_degerlendir(
+    pool: &PgPool,
+    santral_id: Uuid,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<Vec<KaliteGun>, sqlx::Error> {
+    let bugun = piyasa_gunu::bugun();
+    let mut out = Vec::new();
+    for gun in start.iter_days().take_while(|g| *g < end && *g <= bugun) {
+        out.push(gun_degerlendir(pool, santral_id, gun).await?);
+    }
+    Ok(out)
+}
+
+/// Değerlendirme işini arka planda başlatır.
+pub fn baslat(pool: PgPool, cfg: KaliteConfig) {
+    actix_web::rt::spawn(async move {
+        log::info!("Kalite değerlendirmesi başladı ({} dk, tur başına {} gün)", cfg.aralik_dakika, cfg.tur_gun);
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(cfg.aralik_dakika * 60));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            interval.tick().await;
+            match tur(&pool, cfg.tur_gun).await {
+                Ok(0) => {}
+                Ok(n) => log::info!("Kalite turu bitti: {n} gün"),
+                Err(e) => log::error!("Kalite turu hata: {e}"),
+            }
+        }
+    });
+}
+
+/// Tek değerlendirme turu; değerlendirilen gün sayısını döner.
+async fn tur(pool: &PgPool, limit: i64) -> Result<usize, sqlx::Error> {
+    let gunler = db::degerlendirilecek_kalite_gunleri(pool, limit).await?;
+    // Tek günün hatası turu durdurmaz; gün bir sonraki turda yeniden denenir
+    for &(santral_id, gun) in &gunler {
+        if let Err(e) = gun_degerlendir(pool, santral_id, gun).await {
+            log::warn!("Kalite değerlendirme hata (santral {santral_id}, gün {gun}): {e}");
+        }
+    }
+    Ok(gunler.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KURULU: f64 = 50.0;
+
+    fn gun() -> (DateTime<Utc>, DateTime<Utc>) {
+        let d = NaiveDate::from_ymd_opt(2025, 3, 10).unwrap();
+        (piyasa_gunu::gun_baslangici(d), piyasa_gunu::gun_baslangici(d + Duration::days(1)))
+    }
+
+    fn yontem() -> Yontem {
+        Yontem::Periyot { cozunurluk: Duration::minutes(15) }
+    }
+
+    /// Gün başından 15 dakikada bir okuma; komşular hep farklı, sıçramasız.
+    fn temiz_seri(bas: DateTime<Utc>) -> Vec<(DateTime<Utc>, f64)> {
+        (0..96).map(|i| (bas + Duration::minutes(15 * i), 5.0 + (i % 7) as f64)).collect()
+    }
+
+    fn bayraklari(d: &Degerlendirme, i: usize) -> &[Bayrak] {
+        &d.bayraklar[i].1
+    }
+
+    #[test]
+    fn temiz_gun_tam_skor() {
+        let (bas, bit) = gun();
+        let d = degerlendir(&temiz_seri(bas), bas, bit, KURULU, yontem());
+        assert!(d.sorunlar.is_empty(), "{:?}", d.sorunlar);
+        assert_eq!((d.n_olcum, d.n_bayrakli), (96, 0));
+        assert!((d.kapsama_orani - 1.0).abs() < 1e-9);
+        assert!((d.skor - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn negatif_kapasite_ustu_ve_sicrama() {
+        let (bas, bit) = gun();
+        let mut o = temiz_seri(bas);
+        o[10].1 = -1.0; // −%1 sınırının altında
+        o[20].1 = 60.0; // kurulu × 1.05 üstü; iki komşudan da sıçrıyor
+        o[30].1 = -0.2; // gece iç tüketimi kadar: bayraksız
+        let d = degerlendir(&o, bas, bit, KURULU, yontem());
+
+        assert_eq!(bayraklari(&d, 10), &[Bayrak::Negatif]);
+        assert_eq!(bayraklari(&d, 20), &[Bayrak::KapasiteUstu, Bayrak::Sicrama]);
+        assert!(bayraklari(&d, 30).is_empty());
+        assert_eq!(d.n_bayrakli, 2);
+        assert!((d.skor - 100.0 * (1.0 - 2.0 / 96.0)).abs() < 1e-9);
+
+        let tipler: Vec<&str> = d.sorunlar.iter().map(|s| s.tip.as_str()).collect();
+        assert_eq!(tipler, ["negatif", "kapasite_ustu", "sicrama"]);
+    }
+
+    #[test]
+    fn donuk_deger_bir_saat_surerse_bayraklanir() {
+        let (bas, bit) = gun();
+        let mut o = temiz_seri(bas);
+        // 40..=44: aynı değer 60 dk → donuk; 60..=63: 45 dk → değil
+        for x in &mut o[40..=44] {
+            x.1 = 12.5;
+        }
+        for x in &mut o[60..=63] {
+            x.1 = 13.5;
+        }
+        // gece sıfırları donuk sayılmaz
+        for x in &mut o[0..=8] {
+            x.1 = 0.0;
+        }
+        let d = degerlendir(&o, bas, bit, KURULU, yontem());
+
+        assert!((40..=44).all(|i| bayraklari(&d, i) == [Bayrak::Donuk]));
+        assert!((60..=63).all(|i| bayraklari(&d, i).is_empty()));
+        assert!((0..=8).all(|i| bayraklari(&d, i).is_empty()));
+        let donuk: Vec<&KaliteSorunu> = d.sorunlar.iter().filter(|s| s.tip == "donuk").collect();
+        assert_eq!(donuk.len(), 1);
+        assert_eq!((donuk[0].bas, donuk[0].bit, donuk[0].adet), (o[40].0, o[44].0, 5));
+    }
+
+    #[test]
+    fn eksik_aralik_ve_kapsama() {
+        let (bas, bit) = gun();
+        let o: Vec<_> = temiz_seri(bas)
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| !(40..48).contains(i))
+            .map(|(_, x)| x)
+            .collect();
+        let d = degerlendir(&o, bas, bit, KURULU, yontem());
+
+        assert_eq!(d.sorunlar.len(), 1);
+        let s = &d.sorunlar[0];
+        assert_eq!((s.tip.as_str(), s.adet), ("eksik", 0));
+        assert_eq!((s.bas, s.bit), (bas + Duration::hours(10), bas + Duration::hours(12)));
+        assert!((d.kapsama_orani - 88.0 / 96.0).abs() < 1e-9);
+        assert!((d.skor - 100.0 * 88.0 / 96.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn gun_sonu_eksigi_ve_bos_gun() {
+        let (bas, bit) = gun();
+        let o: Vec<_> = temiz_seri(bas).into_iter().take(48).collect();
+        let d = degerlendir(&o, bas, bit, KURULU, yontem());
+        assert_eq!(d.sorunlar.len(), 1);
+        assert_eq!((d.sorunlar[0].bas, d.sorunlar[0].bit), (bas + Duration::hours(12), bit));
+        assert!((d.kapsama_orani - 0.5).abs() < 1e-9);
+
+        let bos = degerlendir(&[], bas, bit, KURULU, yontem());
+        assert_eq!((bos.n_olcum, bos.skor), (0, 0.0));
+        assert_eq!(bos.sorunlar.len(), 1);
+    }
+
+    #[test]
+    fn gun_disi_okumalar_bayrak_listesine_girmez() {
+        let (bas, bit) = gun();
+        let mut o = vec![(bas - Duration::minutes(15), 5.0)];
+        o.extend(temiz_seri(bas));
+        o.push((bit, 6.0));
+        let d = degerlendir(&o, bas, bit, KURULU, yontem());
+        assert_eq!(d.n_olcum, 96);
+        assert!(d.bayraklar.iter().all(|(t, _)| *t >= bas && *t < bit));
+    }
+
+    fn bayrakli_seri() -> Vec<(DateTime<Utc>, f64, bool)> {
+        let (bas, _) = gun();
+        let t = |dk: i64| bas + Duration::minutes(dk);
+        vec![
+            (t(0), 90.0, true), // baştaki bayraklı: solda temiz komşu yok
+            (t(15), 2.0, false),
+            (t(30), 99.0, true),
+            (t(45), 4.0, false),
+            (t(60), 99.0, true),
+            (t(90), 99.0, true),
+            (t(120), 10.0, false),
+            (t(135), 99.0, true), // sondaki bayraklı: sağda temiz komşu yok
+        ]
+    }
+
+    #[test]
+    fn uygula_ham_hepsini_tutar() {
+        let o = uygula(bayrakli_seri(), KaliteModu::Ham);
+        assert_eq!(o.len(), 8);
+        assert_eq!(o[2].1, 99.0);
+    }
+
+    #[test]
+    fn uygula_haric_bayraklilari_atar() {
+        let o = uygula(bayrakli_seri(), KaliteModu::Haric);
+        let degerler: Vec<f64> = o.iter().map(|x| x.1).collect();
+        assert_eq!(degerler, [2.0, 4.0, 10.0]);
+    }
+
+    #[test]
+    fn uygula_doldur_temiz_komsulardan_dogrusal() {
+        let seri = bayrakli_seri();
+        let o = uygula(seri.clone(), KaliteModu::Doldur);
+        let zamanlar: Vec<_> = o.iter().map(|x| x.0).collect();
+        // uçtaki bayraklılar doldurulamaz, düşer
+        assert_eq!(zamanlar, seri[1..7].iter().map(|x| x.0).collect::<Vec<_>>());
+        let degerler: Vec<f64> = o.iter().map(|x| x.1).collect();
+        // 45 dk → 120 dk arası 4 → 10: 60 dk'da 4 + 6 × 15/75, 90 dk'da 4 + 6 × 45/75
+        let beklenen = [2.0, 3.0, 4.0, 4.0 + 6.0 * 0.2, 4.0 + 6.0 * 0.6, 10.0];
+        for (d, b) in degerler.iter().zip(beklenen) {
+            assert!((d - b).abs() < 1e-9, "{degerler:?}");
+        }
+    }
+
+    #[test]
+    fn kalite_modu_parse() {
+        assert_eq!(KaliteModu::parse("ham"), Some(KaliteModu::Ham));
+        assert_eq!(KaliteModu::parse("haric"), Some(KaliteModu::Haric));
+        assert_eq!(KaliteModu::parse("doldur"), Some(KaliteModu::Doldur));
+        assert_eq!(KaliteModu::parse("HAM"), None);
+    }
+}
@@ -0,0 +1,756 @@
+// backend/src/kgup.rs
+//
+// KGÜP plan çözünürlüğü, doğrulaması ve kapı kapanışı.
+//
+// Çözünürlük:
+// - Plan dizisi `cozunurluk_dk` (60 ya da 15) periyotlarının enerjisidir (MWh);
+//   indeks yerel gün başından itibaren periyot sırasıdır
+// - Dönüşüm: 60 → 15 saat enerjisini eşit böler, 15 → 60 dört çeyreği toplar
+//   (eksik çeyrek varsa saat boş kalır)
+// - Okuma uçları varsayılan olarak saatlik döner; eski istemciler etkilenmez
+//
+// Doğrulama (sunucu tarafı; form dışından gelen betik istekleri için de):
+// - Periyot sayısı teslim gününün yerel saat sayısı × (60 / çözünürlük) olmalı
+// - Değerler sonlu ve negatif olmayan MWh
+// - Periyot üst sınırı = kurulu güç × periyot süresi; planlı kesintinin örttüğü kısımda
+//   kesintinin kullanılabilir gücü geçerli (süre ağırlıklı, çakışmada en düşük)
+// - Hatalar periyot bazlı toplanır; istemci hepsini tek seferde düzeltebilsin
+//
+// Kapı kapanışı (gate closure):
+// - Gün öncesi: D günü planı D-1 günü yerel KGUP_GUN_ONCESI_KAPANIS saatinde kilitlenir
+// - Gün içi güncelleme açıksa kapanıştan sonra yalnızca teslim başlangıcına
+//   KGUP_GUN_ICI_KAPANIS_DK dakikadan fazla kalan periyotlar değiştirilebilir
+// - Kilitli periyodu değiştirmek `plan_kilit_as` yetkisi ve gerekçe ister
+// - Değişiklik son onaylı revizyona göre ölçülür (taslaklar sayılmaz); yazım, toplu
+//   aktarım ve onaylandi'ya geçiş aynı kontrolden geçer
+//
+// Onay akışı (revizyon başına durum):
+// - taslak → onaya_gonderildi → onaylandi → pys_gonderildi
+// - Yeni revizyon `plan_onayla` yetkisi olanın kaydında (taslak istenmedikçe)
+//   onaylı, diğerlerinde taslak başlar
+// - Sapma ve PYS dosyası son onaylı revizyonu kullanır; taslak sayılmaz
+// - pys_gonderildi dışındaki geçişler yalnızca güncel revizyona yapılır
+//
+// Ortam değişkenleri:
+//   KGUP_GUN_ONCESI_KAPANIS=17:00   (yerel saat, D-1)
+//   KGUP_GUN_ICI_GUNCELLEME=true
+//   KGUP_GUN_ICI_KAPANIS_DK=60
+
+use std::env;
+
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, NaiveTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::PlanliKesinti;
+use crate::piyasa_gunu;
+use crate::yetki::Yetki;
+
+/// Desteklenen çözünürlükler (dakika).
+pub const COZUNURLUKLER: [i32; 2] = [60, 15];
+/// Çözünürlük belirtilmezse (eski istemciler) saatlik.
+pub const VARSAYILAN_COZUNURLUK_DK: i32 = 60;
+
+/// Yuvarlama farkları hata sayılmasın.
+pub const LIMIT_TOLERANSI_MWH: f64 = 1e-6;
+
+pub fn cozunurluk_gecerli(dk: i32) -> bool {
+    COZUNURLUKLER.contains(&dk)
+}
+
+/// Teslim günündeki periyot sayısı.
+pub fn periyot_sayisi(gun: NaiveDate, cozunurluk_dk: i32) -> usize {
+    piyasa_gunu::saat_sayisi(gun) * (60 / cozunurluk_dk.clamp(1, 60)) as usize
+}
+
+/// `i`. periyodun başlangıcı (UTC).
+pub fn periyot_baslangici(gun: NaiveDate, cozunurluk_dk: i32, i: usize) -> DateTime<Utc> {
+    piyasa_gunu::gun_baslangici(gun) + Duration::minutes(cozunurluk_dk as i64 * i as i64)
+}
+
+/// Periyot değerlerini başka çözünürlüğe çevirir (enerji korunur).
+pub fn donustur(degerler: &[Option<f64>], kaynak_dk: i32, hedef_dk: i32) -> Vec<Option<f64>> {
+    if kaynak_dk == hedef_dk || kaynak_dk <= 0 || hedef_dk <= 0 {
+        return degerler.to_vec();
+    }
+    if kaynak_dk > hedef_dk {
+        let k = (kaynak_dk / hedef_dk) as usize;
+        degerler
+            .iter()
+            .flat_map(|v| std::iter::repeat_n(v.map(|x| x / k as f64), k))
+            .collect()
+    } else {
+        let k = (hedef_dk / kaynak_dk) as usize;
+        degerler
+            .chunks(k)
+            .map(|parca| {
+                if parca.len() < k {
+                    return None;
+                }
+                parca.iter().copied().sum::<Option<f64>>()
+            })
+            .collect()
+    }
+}
+
+/// Periyot bazlı (veya plan geneli, `saat` = None) doğrulama hatası.
+/// `saat` plan dizisindeki indekstir (çözünürlük 15 ise çeyrek saat sırası).
+#[derive(Serialize, Debug, Clone)]
+pub struct PlanHatasi {
+    pub saat: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bas_yerel: Option<DateTime<FixedOffset>>,
+    /// saat_sayisi | gecersiz_sayi | negatif | kapasite_ustu | kesinti_ustu
+    pub kod: &'static str,
+    pub mesaj: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deger_mwh: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit_mwh: Option<f64>,
+}
+
+/// Teslim gününün her periyodu için planlanabilecek en yüksek enerji (MWh).
+pub fn periyot_limitleri(
+    gun: NaiveDate,
+    cozunurluk_dk: i32,
+    kurulu_guc_mw: f64,
+    kesintiler: &[PlanliKesinti],
+) -> Vec<f64> {
+    let periyot = Duration::minutes(cozunurluk_dk as i64);
+    (0..periyot_sayisi(gun, cozunurluk_dk))
+        .map(|i| {
+            let bas = periyot_baslangici(gun, cozunurluk_dk, i);
+            periyot_limiti(bas, bas + periyot, kurulu_guc_mw, kesintiler)
+        })
+        .collect()
+}
+
+/// [bas, bit) aralığında kullanılabilir gücün süre ağırlıklı toplamı.
+fn periyot_limiti(
+    bas: DateTime<Utc>,
+    bit: DateTime<Utc>,
+    kurulu_guc_mw: f64,
+    kesintiler: &[PlanliKesinti],
+) -> f64 {
+    let mut sinirlar = vec![bas, bit];
+    for k in kesintiler {
+        for t in [k.baslangic, k.bitis] {
+            if t > bas && t < bit {
+                sinirlar.push(t);
+            }
+        }
+    }
+    sinirlar.sort();
+    sinirlar.dedup();
+
+    sinirlar
+        .windows(2)
+        .map(|w| {
+            let guc = kesintiler
+                .iter()
+                .filter(|k| k.baslangic <= w[0] && k.bitis >= w[1])
+                .map(|k| k.kullanilabilir_guc_mw)
+                .fold(kurulu_guc_mw, f64::min);
+            guc * (w[1] - w[0]).num_seconds() as f64 / 3600.0
+        })
+        .sum()
+}
+
+/// Hata mesajlarındaki periyot adı, 1 tabanlı: "7. saat" / "29. periyot (07:00)".
+fn periyot_adi(gun: NaiveDate, cozunurluk_dk: i32, i: usize) -> String {
+    if cozunurluk_dk == 60 {
+        return format!("{}. saat", i + 1);
+    }
+    let bas = piyasa_gunu::yerel(periyot_baslangici(gun, cozunurluk_dk, i));
+    format!("{}. periyot ({})", i + 1, bas.format("%H:%M"))
+}
+
+/// Planı doğrular; boş liste → geçerli.
+pub fn dogrula(
+    gun: NaiveDate,
+    cozunurluk_dk: i32,
+    saatlik_plan_mwh: &[f64],
+    kurulu_guc_mw: f64,
+    kesintiler: &[PlanliKesinti],
+) -> Vec<PlanHatasi> {
+    let limitler = periyot_limitleri(gun, cozunurluk_dk, kurulu_guc_mw, kesintiler);
+    let tam_kapasite = kurulu_guc_mw * cozunurluk_dk as f64 / 60.0;
+    let mut hatalar = Vec::new();
+
+    if saatlik_plan_mwh.len() != limitler.len() {
+        hatalar.push(PlanHatasi {
+            saat: None,
+            bas_yerel: None,
+            kod: "saat_sayisi",
+            mesaj: format!(
+                "{gun} teslim günü {cozunurluk_dk} dk çözünürlükte {} periyot; plan {} değer içeriyor.",
+                limitler.len(),
+                saatlik_plan_mwh.len()
+            ),
+            deger_mwh: None,
+            limit_mwh: None,
+        });
+    }
+
+    for (saat, &deger) in saatlik_plan_mwh.iter().enumerate() {
+        let ad = periyot_adi(gun, cozunurluk_dk, saat);
+        let hata = |kod, mesaj: String, limit_mwh| PlanHatasi {
+            saat: Some(saat),
+            bas_yerel: Some(piyasa_gunu::yerel(periyot_baslangici(gun, cozunurluk_dk, saat))),
+            kod,
+            mesaj,
+            deger_mwh: deger.is_finite().then_some(deger),
+            limit_mwh,
+        };
+        if !deger.is_finite() {
+            hatalar.push(hata("gecersiz_sayi", format!("{ad} sayı değil."), None));
+        } else if deger < 0.0 {
+            hatalar.push(hata("negatif", format!("{ad} negatif olamaz."), None));
+        } else if let Some(&limit) = limitler.get(saat)
+            && deger > limit + LIMIT_TOLERANSI_MWH
+        {
+            let kod = if limit + LIMIT_TOLERANSI_MWH < tam_kapasite {
+                "kesinti_ustu"
+            } else {
+                "kapasite_ustu"
+            };
+            let mesaj = format!("{ad} {deger} MWh, üst sınır {limit:.3} MWh.");
+            hatalar.push(hata(kod, mesaj, Some(limit)));
+        }
+    }
+    hatalar
+}
+
+/// Kapı kapanışı kuralları.
+#[derive(Debug, Clone)]
+pub struct KapanisKurallari {
+    pub gun_oncesi_kapanis: NaiveTime,
+    pub gun_ici_guncelleme: bool,
+    pub gun_ici_kapanis_dk: i64,
+}
+
+impl KapanisKurallari {
+    pub fn from_env() -> Self {
+        let gun_oncesi_kapanis = env::var("KGUP_GUN_ONCESI_KAPANIS")
+            .ok()
+            .and_then(|v| NaiveTime::parse_from_str(v.trim(), "%H:%M").ok())
+            .unwrap_or(NaiveTime::from_hms_opt(17, 0, 0).unwrap_or(NaiveTime::MIN));
+        let gun_ici_guncelleme = env::var("KGUP_GUN_ICI_GUNCELLEME")
+            .map(|v| matches!(v.trim(), "1" | "true" | "evet"))
+            .unwrap_or(true);
+        let gun_ici_kapanis_dk = env::var("KGUP_GUN_ICI_KAPANIS_DK")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|dk| *dk >= 0)
+            .unwrap_or(60);
+        Self { gun_oncesi_kapanis, gun_ici_guncelleme, gun_ici_kapanis_dk }
+    }
+
+    /// D günü planının gün öncesi kapanışı (D-1, yerel saat).
+    pub fn gun_oncesi_kapanisi(&self, gun: NaiveDate) -> DateTime<Utc> {
+        let onceki = gun.pred_opt().unwrap_or(gun);
+        piyasa_gunu::PIYASA_TZ
+            .from_local_datetime(&onceki.and_time(self.gun_oncesi_kapanis))
+            .earliest()
+            .map(|t| t.with_timezone(&Utc))
+            .unwrap_or_else(|| piyasa_gunu::gun_baslangici(gun))
+    }
+
+    /// Periyodun son değiştirilebileceği an. Gün içi güncelleme kapalıysa gün öncesi kapanış.
+    pub fn periyot_kapanisi(&self, gun: NaiveDate, cozunurluk_dk: i32, i: usize) -> DateTime<Utc> {
+        let gun_oncesi = self.gun_oncesi_kapanisi(gun);
+        if !self.gun_ici_guncelleme {
+            return gun_oncesi;
+        }
+        let teslim = periyot_baslangici(gun, cozunurluk_dk, i);
+        (teslim - Duration::minutes(self.gun_ici_kapanis_dk)).max(gun_oncesi)
+    }
+
+    /// `simdi` itibarıyla değiştirilemeyen periyotlar.
+    pub fn kapali_periyotlar(
+        &self,
+        gun: NaiveDate,
+        cozunurluk_dk: i32,
+        simdi: DateTime<Utc>,
+    ) -> Vec<usize> {
+        (0..periyot_sayisi(gun, cozunurluk_dk))
+            .filter(|&i| simdi >= self.periyot_kapanisi(gun, cozunurluk_dk, i))
+            .collect()
+    }
+
+    /// Güncel plana göre değişen ve kapısı kapanmış periyotlar.
+    /// `mevcut` yeni planın çözünürlüğüne çevrilmiş olmalı; None → plan yok,
+    /// kapanmış her periyot yeni değer sayılır.
+    pub fn kilitli_degisiklikler(
+        &self,
+        gun: NaiveDate,
+        cozunurluk_dk: i32,
+        mevcut: Option<&[Option<f64>]>,
+        yeni: &[f64],
+        simdi: DateTime<Utc>,
+    ) -> Vec<usize> {
+        self.kapali_periyotlar(gun, cozunurluk_dk, simdi)
+            .into_iter()
+            .filter(|&i| {
+                let eski = mevcut.and_then(|m| m.get(i).copied().flatten());
+                let yeni = yeni.get(i).copied();
+                match (eski, yeni) {
+                    (Some(a), Some(b)) => (a - b).abs() > LIMIT_TOLERANSI_MWH,
+                    (None, None) => false,
+                    _ => true,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Plan revizyonunun onay durumu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanDurumu {
+    Taslak,
+    OnayaGonderildi,
+    Onaylandi,
+    PysGonderildi,
+}
+
+impl PlanDurumu {
+    pub const HEPSI: [PlanDurumu; 4] = [
+        PlanDurumu::Taslak,
+        PlanDurumu::OnayaGonderildi,
+        PlanDurumu::Onaylandi,
+        PlanDurumu::PysGonderildi,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PlanDurumu::Taslak => "taslak",
+            PlanDurumu::OnayaGonderildi => "onaya_gonderildi",
+            PlanDurumu::Onaylandi => "onaylandi",
+            PlanDurumu::PysGonderildi => "pys_gonderildi",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<PlanDurumu> {
+        PlanDurumu::HEPSI.into_iter().find(|d| d.as_str() == s)
+    }
+
+    /// Sapma / dengesizlik hesaplarında sayılır mı.
+    pub fn onayli(&self) -> bool {
+        matches!(self, PlanDurumu::Onaylandi | PlanDurumu::PysGonderildi)
+    }
+
+    /// Bu duruma geçiş yalnızca planın güncel revizyonunda yapılabilir mi
+    /// (onaylı eski revizyon yine de PYS'ye gönderildi işaretlenebilir).
+    pub fn guncel_revizyon_ister(&self) -> bool {
+        *self != PlanDurumu::PysGonderildi
+    }
+
+    /// `self` → `hedef` geçişinin istediği yetki; geçiş tanımlı değilse None.
+    /// Geri çevirme (onaya_gonderildi → taslak) gönderen planlamacıya da açık.
+    pub fn gecis_yetkisi(&self, hedef: PlanDurumu) -> Option<Yetki> {
+        use PlanDurumu::*;
+        match (self, hedef) {
+            (Taslak, OnayaGonderildi) | (OnayaGonderildi, Taslak) => Some(Yetki::PlanYaz),
+            (Taslak, Onaylandi) | (OnayaGonderildi, Onaylandi) | (Onaylandi, PysGonderildi) => {
+                Some(Yetki::PlanOnayla)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    const KURULU: f64 = 50.0;
+
+    fn tarih(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    /// Sıradan gün (Türkiye 2016'dan beri yaz saati uygulamıyor).
+    fn gun() -> NaiveDate {
+        tarih(2025, 8, 2)
+    }
+
+    /// Yaz saatine geçiş (23 saat) ve dönüş (25 saat) günleri.
+    fn kisa_gun() -> NaiveDate {
+        tarih(2015, 3, 29)
+    }
+
+    fn uzun_gun() -> NaiveDate {
+        tarih(2015, 11, 8)
+    }
+
+    /// Yerel saatten (UTC+3) UTC.
+    fn yerel(gun: NaiveDate, saat: u32, dk: u32) -> DateTime<Utc> {
+        piyasa_gunu::PIYASA_TZ
+            .from_local_datetime(&gun.and_hms_opt(saat, dk, 0).unwrap())
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn kesinti(bas: DateTime<Utc>, bit: DateTime<Utc>, guc: f64) -> PlanliKesinti {
+        PlanliKesinti {
+            id: Uuid::nil(),
+            santral_id: Uuid::nil(),
+            baslangic: bas,
+            bitis: bit,
+            kullanilabilir_guc_mw: guc,
+            aciklama: None,
+            olusturan_id: None,
+            olusturma_tarihi: bas,
+        }
+    }
+
+    fn kodlar(h: &[PlanHatasi]) -> Vec<(Option<usize>, &'static str)> {
+        h.iter().map(|h| (h.saat, h.kod)).collect()
+    }
+
+    #[test]
+    fn gecerli_plan() {
+        assert!(dogrula(gun(), 60, &[KURULU; 24], KURULU, &[]).is_empty());
+        assert!(dogrula(gun(), 15, &[KURULU / 4.0; 96], KURULU, &[]).is_empty());
+    }
+
+    #[test]
+    fn periyot_sayisi_hatasi() {
+        let h = dogrula(gun(), 60, &[1.0; 23], KURULU, &[]);
+        assert_eq!(kodlar(&h), [(None, "saat_sayisi")]);
+        assert!(h[0].mesaj.contains("24 periyot; plan 23 değer"), "{}", h[0].mesaj);
+    }
+
+    #[test]
+    fn yaz_saati_gunleri() {
+        assert_eq!(periyot_limitleri(kisa_gun(), 60, KURULU, &[]).len(), 23);
+        assert_eq!(periyot_limitleri(uzun_gun(), 60, KURULU, &[]).len(), 25);
+        assert!(dogrula(kisa_gun(), 60, &[1.0; 23], KURULU, &[]).is_empty());
+        assert!(dogrula(uzun_gun(), 60, &[1.0; 25], KURULU, &[]).is_empty());
+        assert!(dogrula(kisa_gun(), 15, &[1.0; 92], KURULU, &[]).is_empty());
+        assert!(dogrula(uzun_gun(), 15, &[1.0; 100], KURULU, &[]).is_empty());
+        assert_eq!(kodlar(&dogrula(kisa_gun(), 60, &[1.0; 24], KURULU, &[])), [(None, "saat_sayisi")]);
+        assert_eq!(kodlar(&dogrula(uzun_gun(), 60, &[1.0; 24], KURULU, &[])), [(None, "saat_sayisi")]);
+    }
+
+    #[test]
+    fn periyot_hatalari_ve_mesajlari() {
+        let mut plan = [1.0; 24];
+        plan[0] = -0.5;
+        plan[2] = f64::NAN;
+        plan[6] = KURULU + 1.0;
+        let h = dogrula(gun(), 60, &plan, KURULU, &[]);
+        assert_eq!(kodlar(&h), [(Some(0), "negatif"), (Some(2), "gecersiz_sayi"), (Some(6), "kapasite_ustu")]);
+        // mesajlar 1 tabanlı
+        assert_eq!(h[0].mesaj, "1. saat negatif olamaz.");
+        assert_eq!(h[1].mesaj, "3. saat sayı değil.");
+        assert_eq!(h[2].mesaj, "7. saat 51 MWh, üst sınır 50.000 MWh.");
+        assert_eq!(h[1].deger_mwh, None);
+        assert_eq!(h[2].limit_mwh, Some(KURULU));
+        assert_eq!(h[2].bas_yerel.map(|t| t.format("%H:%M").to_string()).as_deref(), Some("06:00"));
+    }
+
+    #[test]
+    fn ceyrek_saat_mesaji() {
+        let mut plan = [1.0; 96];
+        plan[28] = KURULU;
+        let h = dogrula(gun(), 15, &plan, KURULU, &[]);
+        assert_eq!(kodlar(&h), [(Some(28), "kapasite_ustu")]);
+        assert_eq!(h[0].mesaj, "29. periyot (07:00) 50 MWh, üst sınır 12.500 MWh.");
+    }
+
+    #[test]
+    fn limit_toleransi() {
+        let mut plan = [KURULU; 24];
+        plan[3] = KURULU + LIMIT_TOLERANSI_MWH / 2.0;
+        assert!(dogrula(gun(), 60, &plan, KURULU, &[]).is_empty());
+        plan[3] = KURULU + LIMIT_TOLERANSI_MWH * 10.0;
+        assert_eq!(kodlar(&dogrula(gun(), 60, &plan, KURULU, &[])), [(Some(3), "kapasite_ustu")]);
+    }
+
+    #[test]
+    fn kesinti_sure_agirlikli_limit() {
+        // 10:30–11:15 arası 20 MW
+        let k = [kesinti(yerel(gun(), 10, 30), yerel(gun(), 11, 15), 20.0)];
+        let l = periyot_limitleri(gun(), 60, KURULU, &k);
+        assert!((l[9] - KURULU).abs() < 1e-9);
+        assert!((l[10] - (0.5 * KURULU + 0.5 * 20.0)).abs() < 1e-9);
+        assert!((l[11] - (0.25 * 20.0 + 0.75 * KURULU)).abs() < 1e-9);
+        assert!((l[12] - KURULU).abs() < 1e-9);
+
+        let l15 = periyot_limitleri(gun(), 15, KURULU, &k);
+        assert_eq!(l15.len(), 96);
+        assert!((l15[41] - KURULU / 4.0).abs() < 1e-9); // 10:15
+        assert!((l15[42] - 5.0).abs() < 1e-9); // 10:30
+        assert!((l15[44] - 5.0).abs() < 1e-9); // 11:00
+        assert!((l15[45] - KURULU / 4.0).abs() < 1e-9); // 11:15
+    }
+
+    #[test]
+    fn cakisan_kesintilerde_en_dusuk_guc() {
+        let k = [
+            kesinti(yerel(gun(), 10, 0), yerel(gun(), 12, 0), 30.0),
+            kesinti(yerel(gun(), 10, 30), yerel(gun(), 11, 0), 10.0),
+        ];
+        let l = periyot_limitleri(gun(), 60, KURULU, &k);
+        assert!((l[10] - (0.5 * 30.0 + 0.5 * 10.0)).abs() < 1e-9);
+        assert!((l[11] - 30.0).abs() < 1e-9);
+
+        // kurulu güçten yüksek "kesinti" limiti artırmaz
+        let yuksek = [kesinti(yerel(gun(), 0, 0), yerel(gun(), 23, 0), 80.0)];
+        assert!(periyot_limitleri(gun(), 60, KURULU, &yuksek).iter().all(|l| (l - KURULU).abs() < 1e-9));
+    }
+
+    #[test]
+    fn kesinti_ustu_kodu() {
+        let k = [kesinti(yerel(gun(), 10, 0), yerel(gun(), 11, 0), 20.0)];
+        let mut plan = [1.0; 24];
+        plan[10] = 25.0;
+        let h = dogrula(gun(), 60, &plan, KURULU, &k);
+        assert_eq!(kodlar(&h), [(Some(10), "kesinti_ustu")]);
+        assert_eq!(h[0].limit_mwh, Some(20.0));
+    }
+
+    #[test]
+    fn kesinti_yaz_saati_gununde() {
+        // 25 saatlik günde limit dizisi de 25; kesinti UTC aralığıyla eşleşir
+        let k = [kesinti(yerel(uzun_gun(), 12, 0), yerel(uzun_gun(), 13, 0), 0.0)];
+        let l = periyot_limitleri(uzun_gun(), 60, KURULU, &k);
+        assert_eq!(l.len(), 25);
+        // 03:00 tekrarı yüzünden yerel 12:00, gün başından 13. saat (indeks 13)
+        assert_eq!(l.iter().position(|x| *x == 0.0), Some(13));
+    }
+
+    #[test]
+    fn periyot_sayilari() {
+        assert_eq!(periyot_sayisi(gun(), 60), 24);
+        assert_eq!(periyot_sayisi(gun(), 15), 96);
+        assert_eq!(periyot_sayisi(kisa_gun(), 60), 23);
+        assert_eq!(periyot_sayisi(kisa_gun(), 15), 92);
+        assert_eq!(periyot_sayisi(uzun_gun(), 60), 25);
+        assert_eq!(periyot_sayisi(uzun_gun(), 15), 100);
+    }
+
+    #[test]
+    fn periyot_baslangiclari_yaz_saatinde_utc_ardisik() {
+        let g = uzun_gun();
+        let son = periyot_sayisi(g, 15) - 1;
+        assert_eq!(periyot_baslangici(g, 15, 0), piyasa_gunu::gun_baslangici(g));
+        assert_eq!(
+            periyot_baslangici(g, 15, son) + Duration::minutes(15),
+            piyasa_gunu::gun_baslangici(g + Duration::days(1))
+        );
+    }
+
+    fn toplam(v: &[Option<f64>]) -> f64 {
+        v.iter().flatten().sum()
+    }
+
+    #[test]
+    fn donustur_60_15_60_enerjiyi_korur() {
+        for g in [gun(), kisa_gun(), uzun_gun()] {
+            let saatlik: Vec<Option<f64>> = (0..periyot_sayisi(g, 60)).map(|i| Some(i as f64 * 1.25)).collect();
+            let ceyrek = donustur(&saatlik, 60, 15);
+            assert_eq!(ceyrek.len(), periyot_sayisi(g, 15), "{g}");
+            assert_eq!(ceyrek[4..8], [Some(0.3125); 4]);
+            assert!((toplam(&ceyrek) - toplam(&saatlik)).abs() < 1e-9);
+
+            let geri = donustur(&ceyrek, 15, 60);
+            assert_eq!(geri.len(), saatlik.len());
+            for (a, b) in geri.iter().zip(&saatlik) {
+                assert!((a.unwrap() - b.unwrap()).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn donustur_15_60_cekirdek_toplam() {
+        let ceyrek: Vec<Option<f64>> = (0..96).map(|i| Some(i as f64)).collect();
+        let saatlik = donustur(&ceyrek, 15, 60);
+        assert_eq!(saatlik.len(), 24);
+        assert_eq!(saatlik[0], Some(0.0 + 1.0 + 2.0 + 3.0));
+        assert!((toplam(&saatlik) - toplam(&ceyrek)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn donustur_eksik_ceyrek_ve_yarim_son_parca() {
+        let mut ceyrek = vec![Some(1.0); 10];
+        ceyrek[5] = None;
+        let saatlik = donustur(&ceyrek, 15, 60);
+        // [0..4) tam, [4..8) eksik çeyrek içeriyor, [8..10) yarım parça
+        assert_eq!(saatlik, [Some(4.0), None, None]);
+
+        // 60 → 15'te boş saat dört boş çeyrek olur
+        let ceyrek = donustur(&[None, Some(2.0)], 60, 15);
+        assert_eq!(ceyrek[..4], [None; 4]);
+        assert_eq!(ceyrek[4..], [Some(0.5); 4]);
+    }
+
+    #[test]
+    fn donustur_ayni_ya_da_gecersiz_cozunurluk() {
+        let v = vec![Some(1.0), None, Some(3.0)];
+        assert_eq!(donustur(&v, 60, 60), v);
+        assert_eq!(donustur(&v, 0, 15), v);
+        assert_eq!(donustur(&v, 60, -15), v);
+        assert!(donustur(&[], 15, 60).is_empty());
+    }
+
+    fn kurallar(gun_ici: bool) -> KapanisKurallari {
+        KapanisKurallari {
+            gun_oncesi_kapanis: NaiveTime::from_hms_opt(17, 0, 0).unwrap(),
+            gun_ici_guncelleme: gun_ici,
+            gun_ici_kapanis_dk: 60,
+        }
+    }
+
+    fn ilk(n: usize) -> Vec<usize> {
+        (0..n).collect()
+    }
+
+    #[test]
+    fn gun_oncesi_kapanisi_d_eksi_bir() {
+        let k = kurallar(true);
+        assert_eq!(k.gun_oncesi_kapanisi(gun()), yerel(tarih(2025, 8, 1), 17, 0));
+        assert_eq!(k.gun_oncesi_kapanisi(kisa_gun()), yerel(tarih(2015, 3, 28), 17, 0));
+        assert_eq!(k.gun_oncesi_kapanisi(uzun_gun()), yerel(tarih(2015, 11, 7), 17, 0));
+    }
+
+    #[test]
+    fn gun_oncesi_kapanistan_once_ve_sonra() {
+        let d1 = tarih(2025, 8, 1);
+        for gun_ici in [true, false] {
+            let k = kurallar(gun_ici);
+            assert!(k.kapali_periyotlar(gun(), 60, yerel(d1, 16, 59)).is_empty(), "{gun_ici}");
+        }
+
+        // gün içi kapalı: kapanışla bütün gün kilitlenir
+        assert_eq!(kurallar(false).kapali_periyotlar(gun(), 60, yerel(d1, 17, 0)), ilk(24));
+        assert_eq!(kurallar(false).kapali_periyotlar(gun(), 15, yerel(d1, 17, 0)), ilk(96));
+
+        // gün içi açık: ilk periyot teslimden 60 dk önce (D-1 23:00) kapanır
+        let k = kurallar(true);
+        assert!(k.kapali_periyotlar(gun(), 60, yerel(d1, 17, 0)).is_empty());
+        assert!(k.kapali_periyotlar(gun(), 60, yerel(d1, 22, 59)).is_empty());
+        assert_eq!(k.kapali_periyotlar(gun(), 60, yerel(d1, 23, 0)), [0]);
+    }
+
+    #[test]
+    fn gun_ici_kapanis_penceresi() {
+        let k = kurallar(true);
+        assert_eq!(k.periyot_kapanisi(gun(), 60, 10), yerel(gun(), 9, 0));
+        assert_eq!(k.kapali_periyotlar(gun(), 60, yerel(gun(), 9, 0)), ilk(11));
+        assert_eq!(k.kapali_periyotlar(gun(), 60, yerel(gun(), 9, 59)), ilk(11));
+        assert_eq!(k.kapali_periyotlar(gun(), 60, yerel(gun(), 10, 0)), ilk(12));
+
+        let sifir = KapanisKurallari { gun_ici_kapanis_dk: 0, ..kurallar(true) };
+        assert_eq!(sifir.kapali_periyotlar(gun(), 60, yerel(gun(), 9, 0)), ilk(10));
+
+        // pencere gün öncesi kapanışın gerisine düşmez
+        let genis = KapanisKurallari { gun_ici_kapanis_dk: 600, ..kurallar(true) };
+        assert_eq!(genis.periyot_kapanisi(gun(), 60, 0), genis.gun_oncesi_kapanisi(gun()));
+        assert_eq!(genis.periyot_kapanisi(gun(), 60, 12), yerel(gun(), 2, 0));
+    }
+
+    #[test]
+    fn gun_ici_guncelleme_kapali() {
+        let k = kurallar(false);
+        let kapanis = k.gun_oncesi_kapanisi(gun());
+        for i in 0..periyot_sayisi(gun(), 15) {
+            assert_eq!(k.periyot_kapanisi(gun(), 15, i), kapanis, "{i}");
+        }
+        // gün içinde her şey zaten kapalı
+        assert_eq!(k.kapali_periyotlar(gun(), 60, yerel(gun(), 9, 0)), ilk(24));
+    }
+
+    #[test]
+    fn ceyrek_saat_kapanisi() {
+        let k = kurallar(true);
+        assert_eq!(k.periyot_kapanisi(gun(), 15, 41), yerel(gun(), 9, 15));
+        assert_eq!(k.kapali_periyotlar(gun(), 15, yerel(gun(), 9, 0)), ilk(41));
+        assert_eq!(k.kapali_periyotlar(gun(), 15, yerel(gun(), 9, 14)), ilk(41));
+        assert_eq!(k.kapali_periyotlar(gun(), 15, yerel(gun(), 9, 15)), ilk(42));
+    }
+
+    #[test]
+    fn yaz_saati_gunlerinde_kapanis() {
+        let k = kurallar(true);
+        // 23 saatlik gün: 03:00 atlandığı için yerel 05:00'te bir periyot eksik kapanır
+        assert_eq!(k.kapali_periyotlar(kisa_gun(), 60, yerel(kisa_gun(), 5, 0)), ilk(6));
+        // 25 saatlik gün: 03:00 tekrarı yüzünden bir periyot fazla
+        assert_eq!(k.kapali_periyotlar(uzun_gun(), 60, yerel(uzun_gun(), 5, 0)), ilk(8));
+        assert_eq!(k.kapali_periyotlar(gun(), 60, yerel(gun(), 5, 0)), ilk(7));
+
+        let gun_sonu = |g: NaiveDate| piyasa_gunu::gun_baslangici(g + Duration::days(1));
+        assert_eq!(k.kapali_periyotlar(kisa_gun(), 15, gun_sonu(kisa_gun())).len(), 92);
+        assert_eq!(k.kapali_periyotlar(uzun_gun(), 15, gun_sonu(uzun_gun())).len(), 100);
+    }
+
+    #[test]
+    fn kilitli_degisiklikler_kapali_ve_degisen_periyotlar() {
+        let k = kurallar(true);
+        let simdi = yerel(gun(), 9, 0); // 0..11 kapalı
+        let mut mevcut = vec![Some(1.0); 24];
+        mevcut[2] = None;
+        let mut yeni = vec![1.0; 24];
+        yeni[0] = 1.0 + LIMIT_TOLERANSI_MWH / 2.0; // tolerans içinde
+        yeni[1] = 2.0;
+        yeni[20] = 5.0; // değişti ama kapısı açık
+
+        // (Some, Some) farkı ve (None, Some) yeni değer
+        assert_eq!(k.kilitli_degisiklikler(gun(), 60, Some(&mevcut), &yeni, simdi), [1, 2]);
+
+        // (Some, None): yeni planda olmayan kapalı periyotlar
+        assert_eq!(
+            k.kilitli_degisiklikler(gun(), 60, Some(&mevcut), &yeni[..8], simdi),
+            [1, 2, 8, 9, 10]
+        );
+
+        // plan yok: kapanmış her periyot yeni değer
+        assert_eq!(k.kilitli_degisiklikler(gun(), 60, None, &yeni, simdi), ilk(11));
+
+        // (None, None) değişiklik sayılmaz
+        let bos = vec![None; 24];
+        assert!(k.kilitli_degisiklikler(gun(), 60, Some(&bos), &[], simdi).is_empty());
+    }
+
+    #[test]
+    fn plan_durumu_gecis_tablosu() {
+        use PlanDurumu::*;
+        const YAZ: Option<Yetki> = Some(Yetki::PlanYaz);
+        const ONAY: Option<Yetki> = Some(Yetki::PlanOnayla);
+        // satır: mevcut, sütun: hedef (HEPSI sırası)
+        #[rustfmt::skip]
+        let tablo: [[Option<Yetki>; 4]; 4] = [
+            //  taslak  onaya_g.  onaylandi  pys_g.
+            [None,  YAZ,  ONAY, None], // taslak
+            [YAZ,   None, ONAY, None], // onaya_gonderildi
+            [None,  None, None, ONAY], // onaylandi
+            [None,  None, None, None], // pys_gonderildi
+        ];
+        for (i, a) in PlanDurumu::HEPSI.into_iter().enumerate() {
+            for (j, b) in PlanDurumu::HEPSI.into_iter().enumerate() {
+                assert_eq!(a.gecis_yetkisi(b), tablo[i][j], "{} → {}", a.as_str(), b.as_str());
+            }
+        }
+
+        // onaylı plan taslağa dönmez; PYS'ye gönderilmiş plandan çıkış yok
+        assert_eq!(Onaylandi.gecis_yetkisi(Taslak), None);
+        assert!(PlanDurumu::HEPSI.iter().all(|&b| PysGonderildi.gecis_yetkisi(b).is_none()));
+    }
+
+    #[test]
+    fn plan_durumu_revizyon_ve_onay() {
+        for d in PlanDurumu::HEPSI {
+            assert_eq!(d.guncel_revizyon_ister(), d != PlanDurumu::PysGonderildi, "{}", d.as_str());
+            assert_eq!(
+                d.onayli(),
+                matches!(d, PlanDurumu::Onaylandi | PlanDurumu::PysGonderildi),
+                "{}",
+                d.as_str()
+            );
+            assert_eq!(PlanDurumu::parse(d.as_str()), Some(d));
+        }
+        assert_eq!(PlanDurumu::parse("onayli"), None);
+    }
+}
@@ -6,6 +6,10 @@
 // - AuthConfig oku
 // - REST endpoint'ler
 // - WebSocket /ws/uretim (JWT zorunlu)
+// - Modbus TCP poller (MODBUS_POLLER_AKTIF=true ise)
+// - MQTT telemetri aboneliği (MQTT_AKTIF=true ise)
+// - EPİAŞ fiyat / üretim senkronu (EPIAS_AKTIF=true ise)
+// - KGÜP kapı kapanışı kuralları (KGUP_* ortam değişkenleri)
 
 use actix_cors::Cors;
 use actix_web::{middleware::Logger, web, App, HttpServer};
@@ -16,9 +20,24 @@ use std::env;
 pub mod auth;
 pub mod auth_mw;
 pub mod db;
+pub mod enerji;
+pub mod epias;
+#[cfg(test)]
+mod epias_mock;
 pub mod handlers;
+pub mod kalite;
+pub mod kgup;
 mod models;
+pub mod modbus;
+pub mod mqtt;
+pub mod olcum;
+pub mod piyasa;
+pub mod piyasa_gunu;
+pub mod plan_aktarim;
+pub mod pys;
+pub mod uzlastirma;
 mod ws;
+pub mod yetki;
 
 use crate::auth::AuthConfig;
 
@@ -46,6 +65,35 @@ async fn main() -> std::io::Result<()> {
     let auth_cfg =
         AuthConfig::from_env().expect("AuthConfig ortam değişkenleri okunamadı");
 
+    // Santral RTU okuma
+    let modbus_cfg = modbus::ModbusConfig::from_env();
+    if modbus_cfg.aktif {
+        modbus::baslat(pool.clone(), modbus_cfg);
+    }
+
+    // SCADA telemetri (MQTT)
+    let mqtt_cfg = mqtt::MqttConfig::from_env();
+    let mqtt_durum = web::Data::new(mqtt::MqttDurum::new(mqtt_cfg.aktif));
+    if mqtt_cfg.aktif {
+        mqtt::baslat(pool.clone(), mqtt_cfg, mqtt_durum.clone().into_inner());
+    }
+
+    // EPİAŞ Şeffaflık Platformu
+    let epias = web::Data::new(epias::Epias::new(epias::EpiasConfig::from_env()));
+    if epias.istemci.cfg().aktif {
+        epias::baslat(pool.clone(), epias.clone().into_inner());
+    }
+
+    // Ölçüm veri kalitesi değerlendirmesi
+    let kalite_cfg = kalite::KaliteConfig::from_env();
+    if kalite_cfg.aktif {
+        kalite::baslat(pool.clone(), kalite_cfg);
+    }
+
+    // KGÜP kapı kapanışı
+    let kapanis = web::Data::new(kgup::KapanisKurallari::from_env());
+    log::info!("KGÜP kapanış kuralları: {:?}", kapanis.get_ref());
+
     println!("🚀  http://127.0.0.1:8080");
 
     HttpServer::new(move || {
@@ -59,24 +107,87 @@ async fn main() -> std::io::Result<()> {
         App::new()
             .app_data(web::Data::new(pool.clone()))
             .app_data(web::Data::new(auth_cfg.clone()))
+            .app_data(mqtt_durum.clone())
+            .app_data(epias.clone())
+            .app_data(kapanis.clone())
             .wrap(cors)
             .wrap(Logger::new(
                 "%a \"%r\" %s %b \"%{Referer}i\" \"%{User-Agent}i\" %T s",
             ))
             // ---------- AUTH ----------
             .route("/auth/login", web::post().to(handlers::login_handler))
+            .route("/auth/refresh", web::post().to(handlers::refresh_handler))
+            .route("/auth/logout", web::post().to(handlers::logout_handler))
             .route("/auth/whoami", web::get().to(handlers::whoami))
+            .route("/auth/register", web::post().to(handlers::register_handler))
+            .route("/auth/davet", web::post().to(handlers::create_davet_handler))
+            .route("/auth/davet/kabul", web::post().to(handlers::accept_davet_handler))
+            // ---------- YÖNETİM ----------
+            .service(handlers::list_musteriler_handler)
+            .service(handlers::create_musteri_handler)
+            .service(handlers::rename_musteri_handler)
+            .service(handlers::set_musteri_aktif_handler)
+            .service(handlers::list_kullanicilar_handler)
+            .service(handlers::create_kullanici_handler)
+            .service(handlers::set_kullanici_aktif_handler)
+            .service(handlers::set_kullanici_rol_handler)
+            .service(handlers::reset_kullanici_sifre_handler)
+            .service(handlers::create_api_anahtari_handler)
+            .service(handlers::list_api_anahtarlari_handler)
+            .service(handlers::revoke_api_anahtari_handler)
             // ---------- SANTRAL CRUD & ANALİZ ----------
             .service(handlers::create_santral_handler)
             .service(handlers::get_all_santraller_handler)
             .service(handlers::delete_santral_handler)
             .service(handlers::update_santral_handler)
             .service(handlers::get_santral_by_id_handler)
+            .service(handlers::get_modbus_noktalari_handler)
+            .service(handlers::put_modbus_noktalari_handler)
+            .service(handlers::get_mqtt_eslemeleri_handler)
+            .service(handlers::put_mqtt_eslemeleri_handler)
+            .service(handlers::get_epias_eslemesi_handler)
+            .service(handlers::put_epias_eslemesi_handler)
+            .service(handlers::epias_uretim_handler)
+            // ---------- PİYASA FİYATLARI ----------
+            .service(handlers::piyasa_fiyat_eksikleri_handler)
+            .service(handlers::list_piyasa_fiyatlari_handler)
+            .service(handlers::piyasa_fiyat_aktar_handler)
+            .service(handlers::delete_piyasa_fiyati_handler)
+            // ---------- DENGESİZLİK KURALLARI ----------
+            .service(handlers::list_dengesizlik_kurallari_handler)
+            .service(handlers::create_dengesizlik_kurali_handler)
+            .service(handlers::list_santral_dengesizlik_kurallari_handler)
+            .service(handlers::put_santral_dengesizlik_kurali_handler)
+            .service(handlers::delete_santral_dengesizlik_kurali_handler)
+            .service(handlers::epias_durum_handler)
+            .service(handlers::epias_senkron_handler)
             // ---------- KGÜP & DENGESİZLİK ----------
             .service(handlers::dengesizlik_hesapla_handler)
+            .service(handlers::santral_dengesizlik_handler)
+            .service(handlers::santral_kupst_handler)
+            .service(handlers::kgup_plan_dogrula_handler)
             .service(handlers::create_or_update_kgup_plan_handler)
+            .service(handlers::get_kgup_plan_handler)
+            .service(handlers::list_kgup_planlari_handler)
+            .service(handlers::kgup_revizyonlari_handler)
+            .service(handlers::kgup_revizyon_farki_handler)
+            .service(handlers::kgup_revizyon_geri_yukle_handler)
+            .service(handlers::kgup_revizyon_durumu_handler)
+            .service(handlers::kgup_onay_gecmisi_handler)
+            .service(handlers::list_planli_kesintiler_handler)
+            .service(handlers::create_planli_kesinti_handler)
+            .service(handlers::delete_planli_kesinti_handler)
+            .service(handlers::kgup_portfoy_durumu_handler)
+            .service(handlers::kgup_kapanis_handler)
+            .service(handlers::kgup_pys_disa_aktar_handler)
+            .service(handlers::kgup_plan_aktar_handler)
             .service(handlers::sapma_gun_handler)
             .service(handlers::plan_gercek_tarihsel_handler)
+            .service(handlers::kalite_gunleri_handler)
+            .service(handlers::kalite_degerlendir_handler)
+            // ---------- ÖLÇÜM AKTARIMI ----------
+            .service(handlers::olcum_aktar_handler)
+            .service(handlers::mqtt_durum_handler)
             // ---------- WebSocket ----------
             .route("/ws/uretim", web::get().to(ws::ws_uretim_route))
     })
@@ -0,0 +1,326 @@
+// backend/src/modbus.rs
+//
+// Modbus TCP poller (santral RTU / inverter okuma).
+// - Register haritası santral başına `modbus_noktalari` tablosunda
+// - Her turda aktif noktalar (host, port, unit) grubuna ayrılır, grup başına tek bağlantı
+// - Santral gücü = noktaların (ham değer × olcek) toplamı, MW
+// - Sonuç `uretim_olcumleri`ne upsert edilir → /ws/uretim `uretim_tick` canlı veri taşır
+// - Bir noktası okunamayan santral o tur yazılmaz (eksik toplam gerçek üretim gibi görünmesin)
+//
+// Ortam değişkenleri:
+//   MODBUS_POLLER_AKTIF=true   (varsayılan kapalı)
+//   MODBUS_POLL_SANIYE=60      (ölçüm zamanı bu aralığa yuvarlanır)
+//   MODBUS_TIMEOUT_MS=3000     (bağlantı + okuma başına)
+//
+// Yerel deneme için herhangi bir Modbus TCP simülatörü yeterli
+// (ör. `diagslave -m tcp -p 5020`); noktayı host=127.0.0.1, port=5020 ile tanımla.
+
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use tokio::time::timeout;
+use tokio_modbus::prelude::*;
+use uuid::Uuid;
+
+use crate::db;
+use crate::models::ModbusNoktasi;
+use crate::olcum::OlcumSatiri;
+
+#[derive(Debug, Clone)]
+pub struct ModbusConfig {
+    pub aktif: bool,
+    pub poll_saniye: u64,
+    pub timeout_ms: u64,
+}
+
+impl ModbusConfig {
+    pub fn from_env() -> Self {
+        let aktif = env::var("MODBUS_POLLER_AKTIF")
+            .map(|v| matches!(v.trim(), "1" | "true" | "evet"))
+            .unwrap_or(false);
+        let poll_saniye = env::var("MODBUS_POLL_SANIYE")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|s| *s > 0)
+            .unwrap_or(60);
+        let timeout_ms = env::var("MODBUS_TIMEOUT_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(3000);
+        Self { aktif, poll_saniye, timeout_ms }
+    }
+}
+
+pub const VERI_TIPLERI: [&str; 5] = ["u16", "i16", "u32", "i32", "f32"];
+pub const REGISTER_TIPLERI: [&str; 2] = ["holding", "input"];
+pub const KELIME_SIRALARI: [&str; 2] = ["buyuk_once", "kucuk_once"];
+
+/// Bağlantı kurulamayan noktalar için saklanan genel hata metni.
+const BAGLANTI_HATASI: &str = "cihaza bağlanılamadı";
+
+/// Veri tipinin kapladığı register (16 bit kelime) sayısı.
+pub fn kelime_sayisi(veri_tipi: &str) -> u16 {
+    match veri_tipi {
+        "u32" | "i32" | "f32" => 2,
+        _ => 1,
+    }
+}
+
+/// Ham register'ları sayıya çevirir. `kelime_sirasi` yalnızca 32 bit tiplerde etkilidir.
+pub fn coz(veri_tipi: &str, kelime_sirasi: &str, regs: &[u16]) -> Result<f64, String> {
+    let gerek = kelime_sayisi(veri_tipi) as usize;
+    if regs.len() < gerek {
+        return Err(format!("{veri_tipi} için {gerek} register gerekli, {} geldi", regs.len()));
+    }
+    let u32_deger = || {
+        let (yuksek, dusuk) = match kelime_sirasi {
+            "kucuk_once" => (regs[1], regs[0]),
+            _ => (regs[0], regs[1]),
+        };
+        ((yuksek as u32) << 16) | dusuk as u32
+    };
+    let v = match veri_tipi {
+        "u16" => regs[0] as f64,
+        "i16" => regs[0] as i16 as f64,
+        "u32" => u32_deger() as f64,
+        "i32" => u32_deger() as i32 as f64,
+        "f32" => f32::from_bits(u32_deger()) as f64,
+        diger => return Err(format!("bilinmeyen veri tipi: {diger}")),
+    };
+    if !v.is_finite() {
+        return Err("okunan değer sayı değil".into());
+    }
+    Ok(v)
+}
+
+/// Poller'ı arka planda başlatır.
+pub fn baslat(pool: PgPool, cfg: ModbusConfig) {
+    actix_web::rt::spawn(async move {
+        log::info!(
+            "Modbus poller başladı (aralık {} sn, timeout {} ms)",
+            cfg.poll_saniye,
+            cfg.timeout_ms
+        );
+        let mut interval = tokio::time::interval(Duration::from_secs(cfg.poll_saniye));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            interval.tick().await;
+            if let Err(e) = tur(&pool, &cfg).await {
+                log::error!("Modbus tur hata: {e}");
+            }
+        }
+    });
+}
+
+/// Tek okuma turu.
+async fn tur(pool: &PgPool, cfg: &ModbusConfig) -> Result<(), sqlx::Error> {
+    let noktalar = db::list_aktif_modbus_noktalari(pool).await?;
+    if noktalar.is_empty() {
+        return Ok(());
+    }
+
+    let simdi = Utc::now();
+    let adim = cfg.poll_saniye as i64;
+    let zaman = DateTime::<Utc>::from_timestamp(simdi.timestamp() / adim * adim, 0).unwrap_or(simdi);
+
+    // Aynı cihazdaki noktalar tek bağlantıdan okunur
+    let mut gruplar: BTreeMap<(String, i32, i16), Vec<&ModbusNoktasi>> = BTreeMap::new();
+    for n in &noktalar {
+        gruplar.entry((n.host.clone(), n.port, n.unit_id)).or_default().push(n);
+    }
+
+    let sure = Duration::from_millis(cfg.timeout_ms);
+    let mut sonuclar: HashMap<Uuid, Result<f64, String>> = HashMap::new();
+    for ((host, port, unit), grup) in gruplar {
+        match cihaz_oku(&host, port, unit, &grup, sure).await {
+            Ok(degerler) => sonuclar.extend(degerler),
+            Err(hata) => {
+                // Ayrıntı yalnızca loga; `son_hata` API'den döner ve çözümleme / ret /
+                // zaman aşımı ayrımı iç ağ için port tarama ipucu olurdu.
+                log::warn!("Modbus bağlantı hata ({host}:{port}): {hata}");
+                for n in grup {
+                    sonuclar.insert(n.id, Err(BAGLANTI_HATASI.to_string()));
+                }
+            }
+        }
+    }
+
+    for n in &noktalar {
+        let hata = sonuclar[&n.id].as_ref().err().map(String::as_str);
+        if let Some(h) = hata {
+            log::warn!("Modbus okuma hata ({}:{} adres {}): {h}", n.host, n.port, n.adres);
+        }
+        db::modbus_durum_guncelle(pool, n.id, simdi, hata).await?;
+    }
+
+    let satirlar: Vec<OlcumSatiri> = santral_toplamlari(&noktalar, &sonuclar)
+        .into_iter()
+        .filter_map(|(santral_id, guc)| {
+            guc.map(|guc_mw| OlcumSatiri { santral_id, zaman_utc: zaman, guc_mw })
+        })
+        .collect();
+
+    if !satirlar.is_empty() {
+        db::upsert_olcumler(pool, &satirlar).await?;
+    }
+    Ok(())
+}
+
+/// Santral gücü = noktaların (ham × olcek) toplamı; hatalı noktası olan santral None.
+fn santral_toplamlari(
+    noktalar: &[ModbusNoktasi],
+    sonuclar: &HashMap<Uuid, Result<f64, String>>,
+) -> HashMap<Uuid, Option<f64>> {
+    let mut toplam: HashMap<Uuid, Option<f64>> = HashMap::new();
+    for n in noktalar {
+        let t = toplam.entry(n.santral_id).or_insert(Some(0.0));
+        *t = match (*t, &sonuclar[&n.id]) {
+            (Some(acc), Ok(v)) => Some(acc + v * n.olcek),
+            _ => None,
+        };
+    }
+    toplam
+}
+
+/// Bir cihaza bağlanıp gruptaki noktaları okur.
+/// Bağlantı hatası `Err`, nokta bazlı hatalar map içinde döner.
+async fn cihaz_oku(
+    host: &str,
+    port: i32,
+    unit: i16,
+    grup: &[&ModbusNoktasi],
+    sure: Duration,
+) -> Result<Vec<(Uuid, Result<f64, String>)>, String> {
+    let adres = timeout(sure, tokio::net::lookup_host((host, port as u16)))
+        .await
+        .map_err(|_| "adres çözümleme zaman aşımı".to_string())?
+        .map_err(|e| format!("adres çözülemedi: {e}"))?
+        .next()
+        .ok_or_else(|| "adres çözülemedi".to_string())?;
+
+    let mut ctx = timeout(sure, tcp::connect_slave(adres, Slave(unit as u8)))
+        .await
+        .map_err(|_| "bağlantı zaman aşımı".to_string())?
+        .map_err(|e| format!("bağlantı hatası: {e}"))?;
+
+    let mut out = Vec::with_capacity(grup.len());
+    for n in grup {
+        out.push((n.id, nokta_oku(&mut ctx, n, sure).await));
+    }
+    let _ = ctx.disconnect().await;
+    Ok(out)
+}
+
+async fn nokta_oku(
+    ctx: &mut client::Context,
+    n: &ModbusNoktasi,
+    sure: Duration,
+) -> Result<f64, String> {
+    let adres = n.adres as u16;
+    let adet = kelime_sayisi(&n.veri_tipi);
+    let okuma = match n.register_tipi.as_str() {
+        "input" => timeout(sure, ctx.read_input_registers(adres, adet)).await,
+        _ => timeout(sure, ctx.read_holding_registers(adres, adet)).await,
+    };
+    let regs = okuma
+        .map_err(|_| "okuma zaman aşımı".to_string())?
+        .map_err(|e| format!("okuma hatası: {e}"))?
+        .map_err(|e| format!("cihaz istisnası: {e}"))?;
+    coz(&n.veri_tipi, &n.kelime_sirasi, &regs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kelime_sayilari() {
+        assert_eq!(kelime_sayisi("u16"), 1);
+        assert_eq!(kelime_sayisi("i16"), 1);
+        for t in ["u32", "i32", "f32"] {
+            assert_eq!(kelime_sayisi(t), 2, "{t}");
+        }
+    }
+
+    #[test]
+    fn on_alti_bit() {
+        assert_eq!(coz("u16", "buyuk_once", &[0xFFFF]), Ok(65535.0));
+        assert_eq!(coz("i16", "buyuk_once", &[0xFFFF]), Ok(-1.0));
+        assert_eq!(coz("i16", "buyuk_once", &[0x8000]), Ok(-32768.0));
+        assert_eq!(coz("i16", "buyuk_once", &[0x7FFF]), Ok(32767.0));
+        // kelime sırası 16 bitte etkisiz
+        assert_eq!(coz("u16", "kucuk_once", &[1234]), Ok(1234.0));
+    }
+
+    #[test]
+    fn otuz_iki_bit_kelime_siralari() {
+        // 0x0001_0002 = 65538
+        assert_eq!(coz("u32", "buyuk_once", &[0x0001, 0x0002]), Ok(65538.0));
+        assert_eq!(coz("u32", "kucuk_once", &[0x0002, 0x0001]), Ok(65538.0));
+        assert_eq!(coz("u32", "buyuk_once", &[0xFFFF, 0xFFFF]), Ok(4294967295.0));
+
+        assert_eq!(coz("i32", "buyuk_once", &[0xFFFF, 0xFFFE]), Ok(-2.0));
+        assert_eq!(coz("i32", "kucuk_once", &[0xFFFE, 0xFFFF]), Ok(-2.0));
+        assert_eq!(coz("i32", "buyuk_once", &[0x8000, 0x0000]), Ok(i32::MIN as f64));
+        // yüksek kelime sıfırken işaret yok
+        assert_eq!(coz("i32", "buyuk_once", &[0x0000, 0xFFFF]), Ok(65535.0));
+    }
+
+    #[test]
+    fn f32_bilinen_bit_deseni() {
+        // 123.456f32 = 0x42F6E979
+        let v = coz("f32", "buyuk_once", &[0x42F6, 0xE979]).unwrap();
+        assert_eq!(v, 123.456f32 as f64);
+        assert_eq!(coz("f32", "kucuk_once", &[0xE979, 0x42F6]), Ok(v));
+        // -1.5f32 = 0xBFC00000
+        assert_eq!(coz("f32", "buyuk_once", &[0xBFC0, 0x0000]), Ok(-1.5));
+    }
+
+    #[test]
+    fn f32_nan_ve_sonsuz_reddedilir() {
+        assert!(coz("f32", "buyuk_once", &[0x7FC0, 0x0000]).is_err());
+        assert!(coz("f32", "buyuk_once", &[0x7F80, 0x0000]).is_err());
+    }
+
+    #[test]
+    fn eksik_register_ve_bilinmeyen_tip() {
+        assert!(coz("u32", "buyuk_once", &[1]).is_err());
+        assert!(coz("u16", "buyuk_once", &[]).is_err());
+        assert!(coz("f64", "buyuk_once", &[0, 0, 0, 0]).is_err());
+    }
+
+    #[test]
+    fn olcek_ve_santral_toplami() {
+        let nokta = |id: u128, santral: u128, olcek: f64| ModbusNoktasi {
+            id: Uuid::from_u128(id),
+            santral_id: Uuid::from_u128(santral),
+            host: "127.0.0.1".into(),
+            port: 502,
+            unit_id: 1,
+            register_tipi: "holding".into(),
+            adres: 0,
+            veri_tipi: "i32".into(),
+            kelime_sirasi: "buyuk_once".into(),
+            olcek,
+            aciklama: None,
+            aktif: true,
+            son_okuma_zamani: None,
+            son_hata: None,
+            olusturma_tarihi: Utc::now(),
+        };
+        let noktalar = [nokta(1, 10, 0.001), nokta(2, 10, 0.001), nokta(3, 20, 1.0), nokta(4, 20, 1.0)];
+        let sonuclar = HashMap::from([
+            (Uuid::from_u128(1), coz("i32", "buyuk_once", &[0x0000, 0x0BB8])), // 3000 kW
+            (Uuid::from_u128(2), coz("i32", "buyuk_once", &[0xFFFF, 0xFC18])), // -1000 kW
+            (Uuid::from_u128(3), Ok(4.0)),
+            (Uuid::from_u128(4), Err("okuma zaman aşımı".to_string())),
+        ]);
+        let t = santral_toplamlari(&noktalar, &sonuclar);
+        assert!((t[&Uuid::from_u128(10)].unwrap() - 2.0).abs() < 1e-12);
+        // bir noktası okunamayan santral yazılmaz
+        assert_eq!(t[&Uuid::from_u128(20)], None);
+    }
+}
@@ -1,5 +1,5 @@
 use bigdecimal::BigDecimal;
-use chrono::{DateTime, NaiveDate, Utc};
+use chrono::{DateTime, FixedOffset, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 use sqlx::FromRow;
@@ -15,6 +15,11 @@ pub struct Santral {
     pub koordinat_enlem: BigDecimal,
     pub koordinat_boylam: BigDecimal,
     pub musteri_id: Option<Uuid>,        // yeni
+    /// Ölçüm örnekleme aralığı (sn). NULL → zaman damgalarından trapez integrasyonu.
+    pub olcum_cozunurluk_sn: Option<i32>,
+    /// PYS tanımlayıcıları: UEVÇB EIC kodu ve PYS'deki UEVÇB adı.
+    pub eic_kodu: Option<String>,
+    pub uevcb_adi: Option<String>,
     pub olusturma_tarihi: DateTime<Utc>, // düzeltildi
 }
 
@@ -25,6 +30,125 @@ pub struct InputSantral {
     pub kurulu_guc_mw: BigDecimal,
     pub koordinat_enlem: BigDecimal,
     pub koordinat_boylam: BigDecimal,
+    #[serde(default)]
+    pub olcum_cozunurluk_sn: Option<i32>,
+    #[serde(default)]
+    pub eic_kodu: Option<String>,
+    #[serde(default)]
+    pub uevcb_adi: Option<String>,
+}
+
+// -------------------- MODBUS --------------------
+/// Santrale bağlı tek Modbus TCP okuma noktası.
+#[derive(Serialize, Debug, FromRow, Clone)]
+pub struct ModbusNoktasi {
+    pub id: Uuid,
+    pub santral_id: Uuid,
+    pub host: String,
+    pub port: i32,
+    pub unit_id: i16,
+    pub register_tipi: String, // "holding" | "input"
+    pub adres: i32,
+    pub veri_tipi: String,     // "u16" | "i16" | "u32" | "i32" | "f32"
+    pub kelime_sirasi: String, // "buyuk_once" | "kucuk_once"
+    pub olcek: f64,
+    pub aciklama: Option<String>,
+    pub aktif: bool,
+    pub son_okuma_zamani: Option<DateTime<Utc>>,
+    pub son_hata: Option<String>,
+    pub olusturma_tarihi: DateTime<Utc>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ModbusNoktasiInput {
+    pub host: String,
+    pub port: Option<i32>,
+    pub unit_id: Option<i16>,
+    pub register_tipi: Option<String>,
+    pub adres: i32,
+    pub veri_tipi: Option<String>,
+    pub kelime_sirasi: Option<String>,
+    pub olcek: Option<f64>,
+    pub aciklama: Option<String>,
+    pub aktif: Option<bool>,
+}
+
+// -------------------- MQTT --------------------
+/// MQTT konusunun santrale eşlemesi.
+#[derive(Serialize, Debug, FromRow, Clone)]
+pub struct MqttEslemesi {
+    pub id: Uuid,
+    pub santral_id: Uuid,
+    pub konu: String,
+    pub deger_yolu: Option<String>, // JSON Pointer, NULL → payload sayı
+    pub zaman_yolu: Option<String>, // JSON Pointer, NULL → alınma zamanı
+    pub olcek: f64,
+    pub aktif: bool,
+    pub olusturma_tarihi: DateTime<Utc>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct MqttEslemesiInput {
+    pub konu: String,
+    pub deger_yolu: Option<String>,
+    pub zaman_yolu: Option<String>,
+    pub olcek: Option<f64>,
+    pub aktif: Option<bool>,
+}
+
+// -------------------- EPİAŞ --------------------
+/// Santralin EPİAŞ Şeffaflık Platformu kimlikleri (bkz. `epias`).
+#[derive(Serialize, Debug, FromRow, Clone)]
+pub struct EpiasEslemesi {
+    pub santral_id: Uuid,
+    pub organizasyon_id: Option<i64>, // KGÜP (DPP) sorgusu, uevcb_id ile birlikte
+    pub uevcb_id: Option<i64>,
+    pub santral_kodu: Option<i64>,    // gerçek zamanlı üretim (powerPlantId)
+    pub aktif: bool,
+    pub guncelleme_tarihi: DateTime<Utc>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct EpiasEslemesiInput {
+    pub organizasyon_id: Option<i64>,
+    pub uevcb_id: Option<i64>,
+    pub santral_kodu: Option<i64>,
+    pub aktif: Option<bool>,
+}
+
+/// Yayımlanan saatlik KGÜP / gerçek zamanlı üretim.
+#[derive(Serialize, Debug, FromRow, Clone)]
+pub struct EpiasUretim {
+    pub santral_id: Uuid,
+    pub saat: DateTime<Utc>,
+    pub kgup_mwh: Option<f64>,
+    pub gercek_zamanli_mwh: Option<f64>,
+    pub guncelleme_tarihi: DateTime<Utc>,
+}
+
+/// Veri seti başına senkron imleci ve son deneme sonucu.
+#[derive(Serialize, Debug, FromRow, Clone)]
+pub struct EpiasSenkron {
+    pub anahtar: String,
+    pub son_saat: Option<DateTime<Utc>>, // bu saate kadar (hariç) alındı
+    pub son_deneme: DateTime<Utc>,
+    pub son_basari: Option<DateTime<Utc>>,
+    pub son_hata: Option<String>,
+    pub ardisik_hata: i32,
+}
+
+// -------------------- VERİ KALİTESİ --------------------
+/// Santral/gün kalite özeti (`sorunlar`: [{ tip, bas, bit, adet }]).
+#[derive(Serialize, Debug, FromRow, Clone)]
+pub struct KaliteGun {
+    pub santral_id: Uuid,
+    pub gun: NaiveDate,
+    pub skor: f64,          // 0..100
+    pub kapsama_orani: f64, // 0..1
+    pub n_olcum: i32,
+    pub n_bayrakli: i32,
+    pub sorunlar: JsonValue,
+    pub hesaplama_zamani: DateTime<Utc>,
 }
 
 // -------------------- DENGESİZLİK --------------------
@@ -32,8 +156,13 @@ pub struct InputSantral {
 pub struct DengesizlikInput {
     pub tahmini_uretim_mwh: f64,
     pub gerceklesen_uretim_mwh: f64,
-    pub ptf_tl: f64,
-    pub smf_tl: f64,
+    /// Teslim saati; PTF / SMF verilmezse kayıtlı piyasa fiyatlarından bulunur.
+    #[serde(default)]
+    pub saat: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub ptf_tl: Option<f64>,
+    #[serde(default)]
+    pub smf_tl: Option<f64>,
 }
 
 #[derive(Serialize, Debug)]
@@ -45,14 +174,219 @@ pub struct DengesizlikOutput {
     pub dengesizlik_miktari_mwh: f64,
     pub dengesizlik_tipi: String,
     pub dengesizlik_tutari_tl: f64,
+    /// Sapmanın tolerans bandında kalan (PTF ile fiyatlanan) kısmı.
+    pub tolerans_ici_mwh: f64,
+    /// Tolerans dışı kısmın birim fiyatı (TL/MWh).
+    pub birim_fiyat_tl: f64,
+    /// Uygulanan kural seti (bkz. `uzlastirma::KuralTakvimi`).
+    pub kural_kodu: Option<String>,
+    pub kural_surumu: Option<i32>,
     pub aciklama: String,
 }
 
+/// Kayıtlı plan / gerçekleşen / fiyattan periyot bazlı dengesizlik.
+#[derive(Serialize, Debug)]
+pub struct DengesizlikSaat {
+    pub ts_utc: DateTime<Utc>,
+    pub ts_yerel: DateTime<FixedOffset>,
+    pub plan_mwh: Option<f64>,
+    pub gercek_mwh: Option<f64>,
+    pub ptf_tl: Option<f64>,
+    pub smf_tl: Option<f64>,
+    pub kapsama_orani: f64,
+    /// Plan, gerçekleşen ve fiyatın hepsi varsa hesap sonucu.
+    pub sonuc: Option<DengesizlikOutput>,
+    /// Sapmanın PTF'den satışa göre kaybı: sapma × PTF − dengesizlik tutarı (≥ 0).
+    pub maliyet_tl: Option<f64>,
+    /// Hesaplanamayan periyot için sebep: plan_yok | olcum_yok | fiyat_yok | kural_yok
+    pub eksik: Option<&'static str>,
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct DengesizlikToplami {
+    pub plan_mwh: f64,
+    pub gercek_mwh: f64,
+    pub dengesizlik_mwh: f64,
+    pub pozitif_dengesizlik_mwh: f64,
+    pub negatif_dengesizlik_mwh: f64,
+    pub dengesizlik_tutari_tl: f64,
+    pub maliyet_tl: f64,
+    pub hesaplanan_periyot: usize,
+    pub eksik_periyot: usize,
+}
+
+#[derive(Serialize, Debug)]
+pub struct DengesizlikRaporu {
+    pub santral_id: Uuid,
+    pub start: NaiveDate,
+    pub end: NaiveDate, // exclusive
+    pub cozunurluk_dk: i32,
+    pub rows: Vec<DengesizlikSaat>,
+    /// Yalnızca hesaplanan periyotlar üzerinden.
+    pub toplam: DengesizlikToplami,
+}
+
+// -------------------- DENGESİZLİK KURALLARI --------------------
+/// Dengesizlik fiyatlama kural seti sürümü; bir sonraki sürümün başlangıcına kadar geçerli.
+#[derive(Serialize, Debug, FromRow, Clone)]
+pub struct DengesizlikKurali {
+    pub id: Uuid,
+    pub kod: String,
+    pub surum: i32,
+    pub ad: String,
+    pub gecerlilik_baslangic: NaiveDate, // teslim günü (dahil)
+    pub tolerans_orani: f64,
+    pub pozitif_k: f64,
+    pub negatif_k: f64,
+    pub yekdem_tolerans_orani: Option<f64>, // NULL → genel değer
+    pub yekdem_pozitif_k: Option<f64>,
+    pub yekdem_negatif_k: Option<f64>,
+    /// KÜPST: cezasız sapma bandı, birim fiyat katsayısı ve baz fiyat (max_ptf_smf | ptf | smf).
+    pub kupst_tolerans_orani: f64,
+    pub kupst_katsayi: f64,
+    pub kupst_fiyat_bazi: String,
+    pub yekdem_kupst_tolerans_orani: Option<f64>,
+    pub aciklama: Option<String>,
+    /// Geçmişe dönük sürümde zorunlu gerekçe; ileri tarihliyse None.
+    pub duzeltme_gerekcesi: Option<String>,
+    pub olusturan_id: Option<Uuid>,
+    pub olusturma_tarihi: DateTime<Utc>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct DengesizlikKuraliInput {
+    pub kod: String,
+    pub ad: String,
+    pub gecerlilik_baslangic: NaiveDate,
+    #[serde(default)]
+    pub tolerans_orani: f64,
+    #[serde(default)]
+    pub pozitif_k: f64,
+    #[serde(default)]
+    pub negatif_k: f64,
+    pub yekdem_tolerans_orani: Option<f64>,
+    pub yekdem_pozitif_k: Option<f64>,
+    pub yekdem_negatif_k: Option<f64>,
+    pub kupst_tolerans_orani: Option<f64>, // verilmezse uzlastirma::KupstKurali::default()
+    pub kupst_katsayi: Option<f64>,
+    pub kupst_fiyat_bazi: Option<String>,
+    pub yekdem_kupst_tolerans_orani: Option<f64>,
+    pub aciklama: Option<String>,
+    /// Başlangıç bugünden önceyse zorunlu (uzlaşmış dönemi yeniden fiyatlar).
+    pub duzeltme_gerekcesi: Option<String>,
+}
+
+/// Santralin dönemsel kural ataması.
+#[derive(Serialize, Debug, FromRow, Clone)]
+pub struct SantralDengesizlikKurali {
+    pub santral_id: Uuid,
+    pub gecerlilik_baslangic: NaiveDate,
+    pub kural_kodu: String,
+    pub yekdem: bool,
+    pub duzeltme_gerekcesi: Option<String>,
+    pub guncelleyen_id: Option<Uuid>,
+    pub guncelleme_tarihi: DateTime<Utc>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct SantralDengesizlikKuraliInput {
+    pub gecerlilik_baslangic: NaiveDate,
+    pub kural_kodu: String,
+    #[serde(default)]
+    pub yekdem: bool,
+    /// Başlangıç bugünden önceyse zorunlu (uzlaşmış dönemi yeniden fiyatlar).
+    pub duzeltme_gerekcesi: Option<String>,
+}
+
+// -------------------- KÜPST --------------------
+/// Saatlik KÜPST (bkz. `uzlastirma::KupstKurali`).
+#[derive(Serialize, Debug)]
+pub struct KupstSaat {
+    pub ts_utc: DateTime<Utc>,
+    pub ts_yerel: DateTime<FixedOffset>,
+    pub plan_mwh: Option<f64>,
+    pub gercek_mwh: Option<f64>,
+    pub ptf_tl: Option<f64>,
+    pub smf_tl: Option<f64>,
+    pub kapsama_orani: f64,
+    pub kural_kodu: Option<String>,
+    pub kural_surumu: Option<i32>,
+    /// gerçekleşen − KGÜP
+    pub sapma_mwh: Option<f64>,
+    pub tolerans_mwh: Option<f64>,
+    pub cezali_mwh: Option<f64>,
+    pub birim_fiyat_tl: Option<f64>,
+    pub tutar_tl: Option<f64>,
+    /// Hesaplanamayan saat için sebep: plan_yok | olcum_yok | fiyat_yok | kural_yok
+    pub eksik: Option<&'static str>,
+}
+
+#[derive(Serialize, Debug, Default, Clone)]
+pub struct KupstToplami {
+    pub plan_mwh: f64,
+    pub gercek_mwh: f64,
+    pub mutlak_sapma_mwh: f64,
+    pub cezali_mwh: f64,
+    pub tutar_tl: f64,
+    pub hesaplanan_saat: usize,
+    pub eksik_saat: usize,
+}
+
+/// Teslim ayı (Europe/Istanbul) toplamı.
+#[derive(Serialize, Debug)]
+pub struct KupstAy {
+    pub ay: String, // YYYY-MM
+    #[serde(flatten)]
+    pub toplam: KupstToplami,
+}
+
+#[derive(Serialize, Debug)]
+pub struct KupstRaporu {
+    pub santral_id: Uuid,
+    pub start: NaiveDate,
+    pub end: NaiveDate, // exclusive
+    pub rows: Vec<KupstSaat>,
+    pub aylar: Vec<KupstAy>,
+    /// Yalnızca hesaplanan saatler üzerinden.
+    pub toplam: KupstToplami,
+}
+
+// -------------------- PİYASA FİYATLARI --------------------
+/// Saatlik PTF / SMF / sistem yönü (bkz. `piyasa`).
+#[derive(Serialize, Debug, FromRow, Clone)]
+pub struct PiyasaFiyati {
+    pub saat: DateTime<Utc>,
+    pub ptf_tl: f64,
+    pub smf_tl: Option<f64>,
+    pub sistem_yonu: Option<String>,
+    pub kaynak: String,
+    pub guncelleyen_id: Option<Uuid>,
+    pub guncelleme_tarihi: DateTime<Utc>,
+}
+
 // -------------------- KGÜP --------------------
 #[derive(Deserialize, Debug)]
 pub struct KgupPlanInput {
     pub plan_tarihi: NaiveDate,
     pub saatlik_plan_mwh: Vec<f64>, // düzeltildi
+    /// 60 (varsayılan, 24 değer) ya da 15 (96 değer).
+    #[serde(default)]
+    pub cozunurluk_dk: Option<i32>,
+    /// Revizyon notu (ör. "gün içi düzeltme").
+    #[serde(default)]
+    pub aciklama: Option<String>,
+    /// Kapanmış saatleri değiştirmek için zorunlu gerekçe (plan_kilit_as yetkisi).
+    #[serde(default)]
+    pub kapanis_gerekcesi: Option<String>,
+    /// true → plan_onayla yetkisi olan da taslak kaydeder (varsayılan: onaylı).
+    #[serde(default)]
+    pub taslak: bool,
+}
+
+impl KgupPlanInput {
+    pub fn cozunurluk(&self) -> i32 {
+        self.cozunurluk_dk.unwrap_or(crate::kgup::VARSAYILAN_COZUNURLUK_DK)
+    }
 }
 
 #[derive(Serialize, Debug, FromRow, Clone)]
@@ -62,6 +396,250 @@ pub struct KgupPlan {
     pub plan_tarihi: NaiveDate,
     pub saatlik_plan_mwh: JsonValue,
     pub olusturma_tarihi: DateTime<Utc>, // düzeltildi
+    pub revizyon_no: i32,
+    pub guncelleme_tarihi: DateTime<Utc>,
+    pub cozunurluk_dk: i32,
+    /// Güncel revizyonun onay durumu (bkz. `kgup::PlanDurumu`).
+    pub durum: String,
+    /// Sapma / PYS için geçerli revizyon; None → onaylı revizyon yok.
+    pub onayli_revizyon_no: Option<i32>,
+}
+
+/// JSONB plan dizisini periyot değerlerine çevirir (sayı olmayan → None).
+pub fn saatlik_degerler(v: &JsonValue) -> Vec<Option<f64>> {
+    v.as_array()
+        .map(|a| a.iter().map(|x| x.as_f64()).collect())
+        .unwrap_or_default()
+}
+
+/// Okuma cevabı: JSON dizi ayrıştırılmış periyot değerleri, istenen çözünürlükte
+/// (indeks = yerel gün başından periyot sırası; 60 dk'da yerel saat).
+#[derive(Serialize, Debug, Clone)]
+pub struct KgupPlanDetay {
+    pub id: Uuid,
+    pub santral_id: Uuid,
+    pub plan_tarihi: NaiveDate,
+    pub cozunurluk_dk: i32,
+    /// Planın kaydedildiği çözünürlük.
+    pub kayit_cozunurluk_dk: i32,
+    pub saatlik_plan_mwh: Vec<Option<f64>>,
+    pub toplam_mwh: f64,
+    pub olusturma_tarihi: DateTime<Utc>,
+    pub revizyon_no: i32,
+    pub guncelleme_tarihi: DateTime<Utc>,
+    pub durum: String,
+    pub onayli_revizyon_no: Option<i32>,
+}
+
+impl KgupPlanDetay {
+    /// Planı `cozunurluk_dk` çözünürlüğünde sunar.
+    pub fn cozunurlukte(p: KgupPlan, cozunurluk_dk: i32) -> Self {
+        let saatlik =
+            crate::kgup::donustur(&saatlik_degerler(&p.saatlik_plan_mwh), p.cozunurluk_dk, cozunurluk_dk);
+        KgupPlanDetay {
+            id: p.id,
+            santral_id: p.santral_id,
+            plan_tarihi: p.plan_tarihi,
+            cozunurluk_dk,
+            kayit_cozunurluk_dk: p.cozunurluk_dk,
+            toplam_mwh: saatlik.iter().flatten().sum(),
+            saatlik_plan_mwh: saatlik,
+            olusturma_tarihi: p.olusturma_tarihi,
+            revizyon_no: p.revizyon_no,
+            guncelleme_tarihi: p.guncelleme_tarihi,
+            durum: p.durum,
+            onayli_revizyon_no: p.onayli_revizyon_no,
+        }
+    }
+}
+
+/// Plan revizyonu (geçmiş satırı).
+#[derive(Debug, FromRow, Clone)]
+pub struct KgupPlanRevizyonu {
+    pub revizyon_no: i32,
+    pub saatlik_plan_mwh: JsonValue,
+    pub yazan_kullanici_id: Option<Uuid>,
+    pub yazan_email: Option<String>,
+    pub aciklama: Option<String>,
+    pub kaynak: String, // kayit | geri_yukleme | aktarim
+    pub kapanis_gerekcesi: Option<String>,
+    pub olusturma_tarihi: DateTime<Utc>,
+    pub cozunurluk_dk: i32,
+    pub durum: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct KgupRevizyonDetay {
+    pub revizyon_no: i32,
+    pub guncel: bool,
+    pub cozunurluk_dk: i32,
+    pub saatlik_plan_mwh: Vec<Option<f64>>,
+    pub toplam_mwh: f64,
+    pub yazan_kullanici_id: Option<Uuid>,
+    pub yazan_email: Option<String>,
+    pub aciklama: Option<String>,
+    pub kaynak: String,
+    /// Dolu → kapı kapanışından sonra kilit aşılarak yazıldı.
+    pub kapanis_gerekcesi: Option<String>,
+    pub olusturma_tarihi: DateTime<Utc>,
+    pub durum: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct KgupRevizyonGecmisi {
+    pub santral_id: Uuid,
+    pub plan_tarihi: NaiveDate,
+    pub guncel_revizyon: i32,
+    pub onayli_revizyon_no: Option<i32>,
+    pub revizyonlar: Vec<KgupRevizyonDetay>, // yeniden eskiye
+}
+
+/// İki revizyon arasındaki periyot bazlı fark (b − a); `saat` periyot indeksidir.
+#[derive(Serialize, Debug)]
+pub struct KgupSaatFarki {
+    pub saat: usize,
+    pub ts_yerel: chrono::DateTime<chrono::FixedOffset>,
+    pub a_mwh: Option<f64>,
+    pub b_mwh: Option<f64>,
+    pub fark_mwh: Option<f64>,
+    pub degisti: bool,
+}
+
+#[derive(Serialize, Debug)]
+pub struct KgupRevizyonFarki {
+    pub santral_id: Uuid,
+    pub plan_tarihi: NaiveDate,
+    pub a: i32,
+    pub b: i32,
+    pub cozunurluk_dk: i32,
+    pub toplam_a_mwh: f64,
+    pub toplam_b_mwh: f64,
+    pub toplam_fark_mwh: f64,
+    pub degisen_saat_sayisi: usize,
+    pub saatler: Vec<KgupSaatFarki>,
+}
+
+/// Revizyon durum geçişi (onay akışı kaydı).
+#[derive(Serialize, Debug, FromRow)]
+pub struct KgupDurumGecisi {
+    pub revizyon_no: i32,
+    pub eski_durum: Option<String>,
+    pub yeni_durum: String,
+    pub kullanici_id: Option<Uuid>,
+    pub kullanici_email: Option<String>,
+    pub aciklama: Option<String>,
+    pub kapanis_gerekcesi: Option<String>,
+    pub olusturma_tarihi: DateTime<Utc>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct KgupDurumGecisiInput {
+    pub durum: String,
+    #[serde(default)]
+    pub aciklama: Option<String>,
+    /// Onaylanan revizyon kilitli periyotları değiştiriyorsa (`plan_kilit_as` ile).
+    #[serde(default)]
+    pub kapanis_gerekcesi: Option<String>,
+}
+
+/// Santralin son onaylı revizyonundaki planı (sapma / PYS).
+#[derive(Debug, FromRow)]
+pub struct OnayliKgupPlan {
+    pub plan_tarihi: NaiveDate,
+    pub revizyon_no: i32,
+    pub saatlik_plan_mwh: JsonValue,
+    pub cozunurluk_dk: i32,
+}
+
+/// Planlı kesinti / kapasite düşümü.
+#[derive(Serialize, Debug, FromRow, Clone)]
+pub struct PlanliKesinti {
+    pub id: Uuid,
+    pub santral_id: Uuid,
+    pub baslangic: DateTime<Utc>,
+    pub bitis: DateTime<Utc>,
+    pub kullanilabilir_guc_mw: f64,
+    pub aciklama: Option<String>,
+    pub olusturan_id: Option<Uuid>,
+    pub olusturma_tarihi: DateTime<Utc>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PlanliKesintiInput {
+    pub baslangic: DateTime<Utc>,
+    pub bitis: DateTime<Utc>,
+    /// Kesinti süresince kullanılabilir güç; varsayılan 0 (tam kesinti).
+    #[serde(default)]
+    pub kullanilabilir_guc_mw: f64,
+    pub aciklama: Option<String>,
+}
+
+/// Teslim günü için kapı kapanışı durumu.
+#[derive(Serialize, Debug)]
+pub struct KgupKapanisDurumu {
+    pub gun: NaiveDate,
+    pub gun_oncesi_kapanis: chrono::DateTime<chrono::FixedOffset>,
+    pub gun_oncesi_kapandi: bool,
+    pub cozunurluk_dk: i32,
+    pub gun_ici_guncelleme: bool,
+    pub gun_ici_kapanis_dk: i64,
+    pub kapali_saatler: Vec<usize>,
+    pub acik_saat_sayisi: usize,
+}
+
+/// Portföy görünümü: teslim günü için santral başına plan durumu.
+#[derive(Debug, FromRow)]
+pub struct KgupPlanDurumSatiri {
+    pub santral_id: Uuid,
+    pub santral_ad: String,
+    pub tip: String,
+    pub musteri_id: Option<Uuid>,
+    pub plan_id: Option<Uuid>,
+    pub saatlik_plan_mwh: Option<JsonValue>,
+    pub olusturma_tarihi: Option<DateTime<Utc>>,
+    pub revizyon_no: Option<i32>,
+    pub guncelleme_tarihi: Option<DateTime<Utc>>,
+    pub durum: Option<String>,
+    pub onayli_revizyon_no: Option<i32>,
+}
+
+/// PYS dışa aktarımı için santral kimlikleriyle birlikte plan satırı.
+#[derive(Debug, FromRow)]
+pub struct PysPlanSatiri {
+    pub santral_id: Uuid,
+    pub santral_ad: String,
+    pub eic_kodu: Option<String>,
+    pub uevcb_adi: Option<String>,
+    pub musteri_id: Option<Uuid>,
+    /// Son onaylı revizyon; None → planın onaylı revizyonu yok.
+    pub revizyon_no: Option<i32>,
+    pub saatlik_plan_mwh: Option<JsonValue>,
+    pub cozunurluk_dk: Option<i32>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct KgupPlanDurumu {
+    pub santral_id: Uuid,
+    pub santral_ad: String,
+    pub tip: String,
+    pub plan_var: bool,
+    pub plan_id: Option<Uuid>,
+    pub toplam_mwh: Option<f64>,
+    pub olusturma_tarihi: Option<DateTime<Utc>>,
+    pub revizyon_no: Option<i32>,
+    pub guncelleme_tarihi: Option<DateTime<Utc>>,
+    /// Güncel revizyonun onay durumu.
+    pub durum: Option<String>,
+    pub onayli_revizyon_no: Option<i32>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct KgupPortfoyResponse {
+    pub gun: NaiveDate,
+    pub santral_sayisi: usize,
+    pub plani_olan: usize,
+    pub plani_olmayan: usize,
+    pub santraller: Vec<KgupPlanDurumu>,
 }
 
 // -------------------- AUTH --------------------
@@ -78,6 +656,7 @@ pub struct Kullanici {
     pub id: Uuid,
     pub musteri_id: Uuid,
     pub email: String,
+    #[serde(skip_serializing)]
     pub sifre_hash: String,
     pub ad_soyad: Option<String>,
     pub rol: String,
@@ -85,14 +664,53 @@ pub struct Kullanici {
     pub olusturma_tarihi: DateTime<Utc>,
 }
 
+/// Müşteri içi davet kaydı (token özeti dışarı verilmez).
+#[derive(Debug, FromRow, Serialize)]
+pub struct Davet {
+    pub id: Uuid,
+    pub musteri_id: Uuid,
+    pub email: Option<String>,
+    pub rol: String,
+    pub son_gecerlilik: DateTime<Utc>,
+    pub olusturma_tarihi: DateTime<Utc>,
+}
+
+/// API anahtarı kaydı (özet dışarı verilmez).
+#[derive(Debug, FromRow, Serialize)]
+pub struct ApiAnahtari {
+    pub id: Uuid,
+    pub musteri_id: Uuid,
+    pub ad: String,
+    pub onek: String,
+    pub kapsam: String, // "olcum_yaz" | "analiz_oku"
+    pub santral_idler: Vec<Uuid>,
+    pub son_gecerlilik: Option<DateTime<Utc>>,
+    pub son_kullanim: Option<DateTime<Utc>>,
+    pub olusturan_id: Option<Uuid>,
+    pub iptal_tarihi: Option<DateTime<Utc>>,
+    pub olusturma_tarihi: DateTime<Utc>,
+}
+
+/// `api_anahtari_dogrula` sonucu; kapsam ham metin, `auth_mw` kimliğe çevirir.
+#[derive(Debug, FromRow)]
+pub struct DogrulananApiAnahtari {
+    pub id: Uuid,
+    pub musteri_id: Uuid,
+    pub kapsam: String,
+    pub santral_idler: Vec<Uuid>,
+}
+
 #[derive(Debug, serde::Serialize)]
 pub struct SapmaSaat {
-    pub saat: i32,                    // 0..23
+    pub saat: i32,                    // periyot indeksi: 0..23 (60 dk) / 0..95 (15 dk), yerel
     pub saat_ts: chrono::DateTime<chrono::Utc>,
+    pub saat_ts_yerel: chrono::DateTime<chrono::FixedOffset>,
     pub plan_mwh: Option<f64>,
     pub gercek_mwh: Option<f64>,
     pub sapma_mwh: Option<f64>,
     pub sapma_oran: Option<f64>,
+    pub kapsama_orani: f64,           // ölçümle örtülen süre / periyot süresi
+    pub n_olcum: i64,
 }
 
 /// Gün bazlı sapma cevabı (API response).
@@ -100,6 +718,9 @@ pub struct SapmaSaat {
 pub struct SapmaGunResponse {
     pub santral_id: uuid::Uuid,
     pub gun: chrono::NaiveDate,
+    pub cozunurluk_dk: i32,
+    /// Sapmada kullanılan son onaylı revizyon (None → onaylı plan yok).
+    pub plan_revizyon_no: Option<i32>,
     pub rows: Vec<SapmaSaat>,
     pub toplam_plan_mwh: Option<f64>,
     pub toplam_gercek_mwh: Option<f64>,
@@ -110,9 +731,11 @@ pub struct SapmaGunResponse {
 #[derive(Debug, serde::Serialize)]
 pub struct PlanGercekSaat {
     pub ts_utc: chrono::DateTime<chrono::Utc>,
+    pub ts_yerel: chrono::DateTime<chrono::FixedOffset>,
     pub plan_mwh: Option<f64>,
     pub gercek_mwh: Option<f64>,
     pub sapma_mwh: Option<f64>,
+    pub kapsama_orani: f64,
 }
 
 #[derive(Debug, serde::Serialize)]
@@ -120,6 +743,7 @@ pub struct PlanGercekResponse {
     pub santral_id: uuid::Uuid,
     pub start: chrono::NaiveDate,
     pub end: chrono::NaiveDate, // exclusive
+    pub cozunurluk_dk: i32,
     pub rows: Vec<PlanGercekSaat>,
     pub toplam_plan_mwh: Option<f64>,
     pub toplam_gercek_mwh: Option<f64>,
@@ -0,0 +1,466 @@
+// backend/src/mqtt.rs
+//
+// MQTT telemetri aboneliği (SCADA → uretim_olcumleri).
+// - MQTT_KONULAR filtrelerine abone olur, gelen mesajı kuyruğa atar
+// - Yazıcı görev konuyu `mqtt_eslemeleri` tablosuyla santrale eşler, payload'dan gücü çıkarır
+// - Ölçüm zamanı MQTT_ZAMAN_ADIMI_SANIYE'ye yuvarlanır; aynı adımda son değer geçerlidir
+// - Satırlar MQTT_BATCH_BOYUT dolunca ya da MQTT_BATCH_MS'de bir toplu upsert edilir
+// - Gecikme ve düşürülen mesaj sayaçları `MqttDurum`da tutulur (GET /api/admin/mqtt/durum)
+//
+// Ortam değişkenleri:
+//   MQTT_AKTIF=true              (varsayılan kapalı)
+//   MQTT_HOST=localhost  MQTT_PORT=1883  MQTT_CLIENT_ID=santral-backend
+//   MQTT_KULLANICI / MQTT_SIFRE  (opsiyonel)
+//   MQTT_KONULAR=santral/#       (virgülle ayrılmış filtreler)
+//   MQTT_ZAMAN_ADIMI_SANIYE=60
+//   MQTT_BATCH_BOYUT=500  MQTT_BATCH_MS=1000  MQTT_KUYRUK=10000
+//
+// Yerel deneme: `mosquitto -p 1883` + `mosquitto_pub -t santral/res1/guc -m '{"guc_kw": 1200}'`
+
+use std::collections::HashMap;
+use std::env;
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use rumqttc::{AsyncClient, Event, MqttOptions, Packet, QoS};
+use serde::Serialize;
+use sqlx::PgPool;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use crate::db;
+use crate::models::MqttEslemesi;
+use crate::olcum::OlcumSatiri;
+
+/// Eşleme tablosu bu aralıkla yeniden okunur.
+const ESLEME_YENILE: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone)]
+pub struct MqttConfig {
+    pub aktif: bool,
+    pub host: String,
+    pub port: u16,
+    pub client_id: String,
+    pub kullanici: Option<String>,
+    pub sifre: Option<String>,
+    pub konular: Vec<String>,
+    pub zaman_adimi_saniye: i64,
+    pub batch_boyut: usize,
+    pub batch_ms: u64,
+    pub kuyruk: usize,
+}
+
+fn env_sayi<T: std::str::FromStr>(ad: &str, varsayilan: T) -> T {
+    env::var(ad).ok().and_then(|v| v.trim().parse().ok()).unwrap_or(varsayilan)
+}
+
+impl MqttConfig {
+    pub fn from_env() -> Self {
+        let aktif = env::var("MQTT_AKTIF")
+            .map(|v| matches!(v.trim(), "1" | "true" | "evet"))
+            .unwrap_or(false);
+        let konular = env::var("MQTT_KONULAR")
+            .unwrap_or_else(|_| "santral/#".into())
+            .split(',')
+            .map(|k| k.trim().to_string())
+            .filter(|k| !k.is_empty())
+            .collect();
+        Self {
+            aktif,
+            host: env::var("MQTT_HOST").unwrap_or_else(|_| "localhost".into()),
+            port: env_sayi("MQTT_PORT", 1883),
+            client_id: env::var("MQTT_CLIENT_ID").unwrap_or_else(|_| "santral-backend".into()),
+            kullanici: env::var("MQTT_KULLANICI").ok(),
+            sifre: env::var("MQTT_SIFRE").ok(),
+            konular,
+            zaman_adimi_saniye: env_sayi("MQTT_ZAMAN_ADIMI_SANIYE", 60i64).max(1),
+            batch_boyut: env_sayi("MQTT_BATCH_BOYUT", 500usize).max(1),
+            batch_ms: env_sayi("MQTT_BATCH_MS", 1000u64).max(10),
+            kuyruk: env_sayi("MQTT_KUYRUK", 10_000usize).max(1),
+        }
+    }
+}
+
+/// Canlı sayaçlar (abone ve yazıcı görevler arasında paylaşılır).
+#[derive(Debug, Default)]
+pub struct MqttDurum {
+    aktif: bool,
+    bagli: AtomicBool,
+    alinan: AtomicU64,
+    yazilan: AtomicU64,
+    dusen_eslesmeyen: AtomicU64,
+    dusen_hatali: AtomicU64,
+    dusen_kuyruk: AtomicU64,
+    dusen_db: AtomicU64,
+    /// Son batch: mesajın alınmasından DB'ye yazılmasına kadar geçen en uzun süre.
+    kuyruk_gecikmesi_ms: AtomicI64,
+    /// Son batch: ölçüm zamanından DB'ye yazılmasına kadar geçen en uzun süre.
+    olcum_gecikmesi_ms: AtomicI64,
+    son_mesaj_unix_ms: AtomicI64,
+}
+
+/// `MqttDurum` anlık görüntüsü (API cevabı).
+#[derive(Debug, Serialize)]
+pub struct MqttDurumOzeti {
+    pub aktif: bool,
+    pub bagli: bool,
+    pub alinan: u64,
+    pub yazilan: u64,
+    pub dusen: DusenOzeti,
+    pub kuyruk_gecikmesi_ms: i64,
+    pub olcum_gecikmesi_ms: i64,
+    pub son_mesaj: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DusenOzeti {
+    pub eslesmeyen: u64,
+    pub hatali: u64,
+    pub kuyruk_dolu: u64,
+    pub db_hatasi: u64,
+}
+
+impl MqttDurum {
+    pub fn new(aktif: bool) -> Self {
+        Self { aktif, ..Default::default() }
+    }
+
+    pub fn ozet(&self) -> MqttDurumOzeti {
+        let son = self.son_mesaj_unix_ms.load(Ordering::Relaxed);
+        MqttDurumOzeti {
+            aktif: self.aktif,
+            bagli: self.bagli.load(Ordering::Relaxed),
+            alinan: self.alinan.load(Ordering::Relaxed),
+            yazilan: self.yazilan.load(Ordering::Relaxed),
+            dusen: DusenOzeti {
+                eslesmeyen: self.dusen_eslesmeyen.load(Ordering::Relaxed),
+                hatali: self.dusen_hatali.load(Ordering::Relaxed),
+                kuyruk_dolu: self.dusen_kuyruk.load(Ordering::Relaxed),
+                db_hatasi: self.dusen_db.load(Ordering::Relaxed),
+            },
+            kuyruk_gecikmesi_ms: self.kuyruk_gecikmesi_ms.load(Ordering::Relaxed),
+            olcum_gecikmesi_ms: self.olcum_gecikmesi_ms.load(Ordering::Relaxed),
+            son_mesaj: (son > 0).then(|| DateTime::from_timestamp_millis(son)).flatten(),
+        }
+    }
+}
+
+/// Kuyruktaki ham mesaj.
+struct Mesaj {
+    konu: String,
+    payload: Vec<u8>,
+    alinma: DateTime<Utc>,
+}
+
+/// Yazılmayı bekleyen satır; gecikme ölçümü için ilk alınma ve ham ölçüm zamanıyla.
+struct Bekleyen {
+    satir: OlcumSatiri,
+    ilk_alinma: DateTime<Utc>,
+    olcum_zamani: DateTime<Utc>,
+}
+
+/// Abone + yazıcı görevleri başlatır.
+pub fn baslat(pool: PgPool, cfg: MqttConfig, durum: Arc<MqttDurum>) {
+    let (tx, rx) = mpsc::channel(cfg.kuyruk);
+    actix_web::rt::spawn(abone(cfg.clone(), tx, durum.clone()));
+    actix_web::rt::spawn(yazici(pool, cfg, rx, durum));
+}
+
+async fn abone(cfg: MqttConfig, tx: mpsc::Sender<Mesaj>, durum: Arc<MqttDurum>) {
+    let mut opts = MqttOptions::new(&cfg.client_id, &cfg.host, cfg.port);
+    opts.set_keep_alive(Duration::from_secs(30));
+    if let Some(kullanici) = &cfg.kullanici {
+        opts.set_credentials(kullanici, cfg.sifre.clone().unwrap_or_default());
+    }
+
+    let (client, mut eventloop) = AsyncClient::new(opts, 64);
+    log::info!("MQTT abone başladı ({}:{}, {:?})", cfg.host, cfg.port, cfg.konular);
+
+    loop {
+        match eventloop.poll().await {
+            Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                durum.bagli.store(true, Ordering::Relaxed);
+                // Temiz oturumda abonelikler her bağlantıda yenilenmeli
+                for konu in &cfg.konular {
+                    if let Err(e) = client.try_subscribe(konu, QoS::AtLeastOnce) {
+                        log::error!("MQTT abonelik hata ({konu}): {e}");
+                    }
+                }
+            }
+            Ok(Event::Incoming(Packet::Publish(p))) => {
+                let alinma = Utc::now();
+                durum.alinan.fetch_add(1, Ordering::Relaxed);
+                durum.son_mesaj_unix_ms.store(alinma.timestamp_millis(), Ordering::Relaxed);
+                let mesaj = Mesaj { konu: p.topic, payload: p.payload.to_vec(), alinma };
+                if tx.try_send(mesaj).is_err() {
+                    durum.dusen_kuyruk.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+            Ok(_) => {}
+            Err(e) => {
+                durum.bagli.store(false, Ordering::Relaxed);
+                log::warn!("MQTT bağlantı hata: {e}; 5 sn sonra yeniden denenecek");
+                tokio::time::sleep(Duration::from_secs(5)).await;
+            }
+        }
+    }
+}
+
+async fn yazici(
+    pool: PgPool,
+    cfg: MqttConfig,
+    mut rx: mpsc::Receiver<Mesaj>,
+    durum: Arc<MqttDurum>,
+) {
+    let mut eslemeler: HashMap<String, MqttEslemesi> = HashMap::new();
+    let mut esleme_zamani: Option<tokio::time::Instant> = None;
+
+    let mut batch: HashMap<(Uuid, DateTime<Utc>), Bekleyen> = HashMap::new();
+    let mut flush = tokio::time::interval(Duration::from_millis(cfg.batch_ms));
+    flush.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        if esleme_zamani.is_none_or(|t| t.elapsed() >= ESLEME_YENILE) {
+            match db::list_aktif_mqtt_eslemeleri(&pool).await {
+                Ok(liste) => {
+                    eslemeler = liste.into_iter().map(|e| (e.konu.clone(), e)).collect();
+                    esleme_zamani = Some(tokio::time::Instant::now());
+                }
+                Err(e) => log::error!("MQTT eşleme okuma hata: {e}"),
+            }
+        }
+
+        tokio::select! {
+            mesaj = rx.recv() => {
+                let Some(m) = mesaj else { break };
+                let Some(esleme) = eslemeler.get(&m.konu) else {
+                    durum.dusen_eslesmeyen.fetch_add(1, Ordering::Relaxed);
+                    continue;
+                };
+                match coz(esleme, &m.payload, m.alinma, cfg.zaman_adimi_saniye) {
+                    Ok((satir, olcum_zamani)) => {
+                        let anahtar = (satir.santral_id, satir.zaman_utc);
+                        let ilk_alinma = batch.get(&anahtar).map_or(m.alinma, |b| b.ilk_alinma);
+                        batch.insert(anahtar, Bekleyen { satir, ilk_alinma, olcum_zamani });
+                    }
+                    Err(hata) => {
+                        durum.dusen_hatali.fetch_add(1, Ordering::Relaxed);
+                        log::debug!("MQTT payload reddedildi ({}): {hata}", m.konu);
+                    }
+                }
+                if batch.len() < cfg.batch_boyut {
+                    continue;
+                }
+            }
+            _ = flush.tick() => {}
+        }
+
+        if !batch.is_empty() {
+            yaz(&pool, std::mem::take(&mut batch), &durum).await;
+        }
+    }
+}
+
+async fn yaz(
+    pool: &PgPool,
+    batch: HashMap<(Uuid, DateTime<Utc>), Bekleyen>,
+    durum: &MqttDurum,
+) {
+    let adet = batch.len() as u64;
+    let mut en_eski_alinma = Utc::now();
+    let mut en_eski_olcum = Utc::now();
+    let satirlar: Vec<OlcumSatiri> = batch
+        .into_values()
+        .map(|b| {
+            en_eski_alinma = en_eski_alinma.min(b.ilk_alinma);
+            en_eski_olcum = en_eski_olcum.min(b.olcum_zamani);
+            b.satir
+        })
+        .collect();
+
+    match db::upsert_olcumler(pool, &satirlar).await {
+        Ok(_) => {
+            let simdi = Utc::now();
+            durum.yazilan.fetch_add(adet, Ordering::Relaxed);
+            durum
+                .kuyruk_gecikmesi_ms
+                .store((simdi - en_eski_alinma).num_milliseconds(), Ordering::Relaxed);
+            durum
+                .olcum_gecikmesi_ms
+                .store((simdi - en_eski_olcum).num_milliseconds(), Ordering::Relaxed);
+        }
+        Err(e) => {
+            durum.dusen_db.fetch_add(adet, Ordering::Relaxed);
+            log::error!("MQTT ölçüm yazma hata ({adet} satır): {e}");
+        }
+    }
+}
+
+/// Payload'ı eşlemeye göre ölçüm satırına çevirir; yanında yuvarlanmamış ölçüm zamanı döner.
+pub fn coz(
+    esleme: &MqttEslemesi,
+    payload: &[u8],
+    alinma: DateTime<Utc>,
+    zaman_adimi_saniye: i64,
+) -> Result<(OlcumSatiri, DateTime<Utc>), String> {
+    let metin = std::str::from_utf8(payload).map_err(|_| "payload UTF-8 değil".to_string())?;
+
+    let json = if esleme.deger_yolu.is_some() || esleme.zaman_yolu.is_some() {
+        Some(serde_json::from_str::<serde_json::Value>(metin).map_err(|e| format!("JSON değil: {e}"))?)
+    } else {
+        None
+    };
+    let alan = |yol: &str| {
+        json.as_ref()
+            .and_then(|j| j.pointer(yol))
+            .ok_or_else(|| format!("payload'da '{yol}' yok"))
+    };
+
+    let deger = match &esleme.deger_yolu {
+        Some(yol) => sayi(alan(yol)?)?,
+        None => metin.trim().parse::<f64>().map_err(|_| "payload sayı değil".to_string())?,
+    };
+    let guc_mw = deger * esleme.olcek;
+    if !guc_mw.is_finite() {
+        return Err("güç değeri sayı değil".into());
+    }
+
+    let zaman = match &esleme.zaman_yolu {
+        Some(yol) => zaman_coz(alan(yol)?)?,
+        None => alinma,
+    };
+    let adim = zaman_adimi_saniye;
+    let zaman_utc = DateTime::<Utc>::from_timestamp(zaman.timestamp().div_euclid(adim) * adim, 0)
+        .ok_or_else(|| "zaman aralık dışı".to_string())?;
+
+    Ok((OlcumSatiri { santral_id: esleme.santral_id, zaman_utc, guc_mw }, zaman))
+}
+
+fn sayi(v: &serde_json::Value) -> Result<f64, String> {
+    match v {
+        serde_json::Value::Number(n) => n.as_f64().ok_or_else(|| "sayı okunamadı".to_string()),
+        serde_json::Value::String(s) => s.trim().parse().map_err(|_| format!("'{s}' sayı değil")),
+        _ => Err("değer sayı değil".into()),
+    }
+}
+
+/// RFC 3339 metin ya da epoch (saniye; 1e12 üstü milisaniye sayılır).
+fn zaman_coz(v: &serde_json::Value) -> Result<DateTime<Utc>, String> {
+    match v {
+        serde_json::Value::String(s) => DateTime::parse_from_rfc3339(s.trim())
+            .map(|t| t.with_timezone(&Utc))
+            .map_err(|_| format!("'{s}' RFC 3339 zaman değil")),
+        serde_json::Value::Number(n) => {
+            let t = n.as_f64().ok_or_else(|| "zaman okunamadı".to_string())?;
+            let ms = if t.abs() >= 1e12 { t } else { t * 1000.0 };
+            DateTime::from_timestamp_millis(ms as i64).ok_or_else(|| "zaman aralık dışı".to_string())
+        }
+        _ => Err("zaman metin ya da sayı olmalı".into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn t(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn esleme(deger_yolu: Option<&str>, zaman_yolu: Option<&str>, olcek: f64) -> MqttEslemesi {
+        MqttEslemesi {
+            id: Uuid::nil(),
+            santral_id: Uuid::from_u128(7),
+            konu: "saha/1/guc".into(),
+            deger_yolu: deger_yolu.map(Into::into),
+            zaman_yolu: zaman_yolu.map(Into::into),
+            olcek,
+            aktif: true,
+            olusturma_tarihi: t("2025-01-01T00:00:00Z"),
+        }
+    }
+
+    #[test]
+    fn yalin_sayi_payload_alinma_zamaniyla() {
+        let alinma = t("2025-03-10T10:00:42Z");
+        let (s, ham) = coz(&esleme(None, None, 0.001), b" 2500.5\n", alinma, 60).unwrap();
+        assert_eq!(s.santral_id, Uuid::from_u128(7));
+        assert!((s.guc_mw - 2.5005).abs() < 1e-12);
+        assert_eq!(ham, alinma);
+        assert_eq!(s.zaman_utc, t("2025-03-10T10:00:00Z"));
+    }
+
+    #[test]
+    fn yalin_payload_sayi_degilse_hata() {
+        let e = esleme(None, None, 1.0);
+        assert!(coz(&e, b"{\"guc\": 1}", Utc::now(), 60).is_err());
+        assert!(coz(&e, &[0xff, 0xfe], Utc::now(), 60).is_err());
+    }
+
+    #[test]
+    fn json_pointer_ile_deger_ve_zaman() {
+        let e = esleme(Some("/olcum/guc_kw"), Some("/ts"), 0.001);
+        let p = br#"{"olcum": {"guc_kw": 1200}, "ts": "2025-03-10T13:07:31+03:00"}"#;
+        let (s, ham) = coz(&e, p, Utc::now(), 300).unwrap();
+        assert!((s.guc_mw - 1.2).abs() < 1e-12);
+        assert_eq!(ham, t("2025-03-10T10:07:31Z"));
+        assert_eq!(s.zaman_utc, t("2025-03-10T10:05:00Z"));
+    }
+
+    #[test]
+    fn json_eksik_alan_ya_da_bozuk_json_hata() {
+        let e = esleme(Some("/guc"), None, 1.0);
+        assert!(coz(&e, br#"{"baska": 1}"#, Utc::now(), 60).unwrap_err().contains("/guc"));
+        assert!(coz(&e, b"12.5", Utc::now(), 60).is_err());
+        assert!(coz(&e, b"{guc: 1", Utc::now(), 60).unwrap_err().starts_with("JSON değil"));
+    }
+
+    #[test]
+    fn sayi_metin_kabul_eder() {
+        assert_eq!(sayi(&json!(3.5)), Ok(3.5));
+        assert_eq!(sayi(&json!(" 42 ")), Ok(42.0));
+        assert!(sayi(&json!("yok")).is_err());
+        assert!(sayi(&json!(null)).is_err());
+        assert!(sayi(&json!([1])).is_err());
+    }
+
+    #[test]
+    fn epoch_saniye_ve_milisaniye() {
+        let beklenen = t("2025-03-10T10:00:00Z");
+        let sn = beklenen.timestamp();
+        assert_eq!(zaman_coz(&json!(sn)), Ok(beklenen));
+        assert_eq!(zaman_coz(&json!(sn * 1000)), Ok(beklenen));
+        // kesirli saniye milisaniyeye iner
+        assert_eq!(
+            zaman_coz(&json!(sn as f64 + 0.25)),
+            Ok(beklenen + chrono::Duration::milliseconds(250))
+        );
+        assert!(zaman_coz(&json!(true)).is_err());
+        assert!(zaman_coz(&json!("dün")).is_err());
+    }
+
+    #[test]
+    fn zaman_adima_asagi_yuvarlanir() {
+        let e = esleme(Some("/g"), Some("/ts"), 1.0);
+        for (ts, adim, beklenen) in [
+            ("2025-03-10T10:14:59Z", 900, "2025-03-10T10:00:00Z"),
+            ("2025-03-10T10:15:00Z", 900, "2025-03-10T10:15:00Z"),
+            ("2025-03-10T10:00:59Z", 60, "2025-03-10T10:00:00Z"),
+            // epoch öncesi: div_euclid aşağı yuvarlar, sıfıra doğru değil
+            ("1969-12-31T23:59:30Z", 60, "1969-12-31T23:59:00Z"),
+        ] {
+            let p = format!(r#"{{"g": 1, "ts": "{ts}"}}"#);
+            let (s, _) = coz(&e, p.as_bytes(), Utc::now(), adim).unwrap();
+            assert_eq!(s.zaman_utc, t(beklenen), "{ts} / {adim}");
+        }
+    }
+
+    #[test]
+    fn sonsuz_guc_reddedilir() {
+        let e = esleme(Some("/g"), None, f64::MAX);
+        assert!(coz(&e, br#"{"g": 10}"#, Utc::now(), 60).is_err());
+    }
+}
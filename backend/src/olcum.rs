@@ -0,0 +1,214 @@
+// backend/src/olcum.rs
+//
+// Üretim ölçümü aktarımı (uretim_olcumleri).
+// - JSON dizi ya da CSV gövdesini satırlara ayırır
+// - Satır bazlı doğrulama hatalarını toplar (bir satırın hatası diğerlerini durdurmaz)
+// - Aynı istekte tekrar eden (santral_id, zaman_utc) için son satır geçerlidir;
+//   yerine geçilen satırlar reddedilmez, uyarı olarak döner
+//
+// CSV başlığı zorunludur: santral_id,zaman_utc,guc_mw
+// zaman_utc RFC 3339 olmalı (ör. 2025-07-17T10:05:00Z).
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Tek istekte kabul edilen en fazla satır.
+pub const MAX_SATIR: usize = 50_000;
+
+/// Doğrulanmış ölçüm satırı.
+#[derive(Debug, Clone, Deserialize)]
+pub struct OlcumSatiri {
+    pub santral_id: Uuid,
+    pub zaman_utc: DateTime<Utc>,
+    pub guc_mw: f64,
+}
+
+/// Reddedilen satır. `satir` 1 tabanlıdır (CSV'de başlık hariç).
+#[derive(Debug, Clone, Serialize)]
+pub struct SatirHatasi {
+    pub satir: usize,
+    pub hata: String,
+}
+
+/// Aktarım özeti (API cevabı).
+#[derive(Debug, Default, Serialize)]
+pub struct AktarimOzeti {
+    pub toplam: usize,
+    pub eklenen: u64,
+    pub guncellenen: u64,
+    pub reddedilen: usize,
+    pub hatalar: Vec<SatirHatasi>,
+    /// Kabul edilen ama aynı istekteki sonraki bir satırla yerine geçilen satırlar.
+    pub uyarilar: Vec<SatirHatasi>,
+}
+
+/// Ayrıştırma sonucu: geçerli satırlar (satır numarasıyla) + hatalar + uyarılar.
+#[derive(Debug, Default)]
+pub struct Ayristirma {
+    pub satirlar: Vec<(usize, OlcumSatiri)>,
+    pub hatalar: Vec<SatirHatasi>,
+    pub uyarilar: Vec<SatirHatasi>,
+}
+
+impl Ayristirma {
+    pub fn toplam(&self) -> usize {
+        self.satirlar.len() + self.hatalar.len()
+    }
+
+    fn ekle(&mut self, satir: usize, sonuc: Result<OlcumSatiri, String>) {
+        match sonuc.and_then(dogrula) {
+            Ok(o) => self.satirlar.push((satir, o)),
+            Err(hata) => self.hatalar.push(SatirHatasi { satir, hata }),
+        }
+    }
+
+    /// Aynı (santral_id, zaman_utc) birden fazla geldiyse sonuncusu kalır, öncekiler
+    /// uyarıya taşınır (Postgres aynı satırı tek upsert'te iki kez güncelleyemez).
+    pub fn tekrarlari_ayikla(&mut self) {
+        let mut son: HashMap<(Uuid, DateTime<Utc>), usize> = HashMap::new();
+        for (i, (_, o)) in self.satirlar.iter().enumerate() {
+            son.insert((o.santral_id, o.zaman_utc), i);
+        }
+        let mut kalan = Vec::with_capacity(son.len());
+        for (i, (satir, o)) in std::mem::take(&mut self.satirlar).into_iter().enumerate() {
+            if son.get(&(o.santral_id, o.zaman_utc)) == Some(&i) {
+                kalan.push((satir, o));
+            } else {
+                self.uyarilar.push(SatirHatasi {
+                    satir,
+                    hata: "aynı santral/zaman bu istekte tekrar ediyor; son satır kullanıldı".into(),
+                });
+            }
+        }
+        self.satirlar = kalan;
+    }
+}
+
+fn dogrula(o: OlcumSatiri) -> Result<OlcumSatiri, String> {
+    if !o.guc_mw.is_finite() {
+        return Err("guc_mw sayı olmalı".into());
+    }
+    Ok(o)
+}
+
+/// JSON dizi gövdesi: [{ "santral_id": "...", "zaman_utc": "...", "guc_mw": 12.3 }, ...]
+pub fn parse_json(body: &[u8]) -> Result<Ayristirma, String> {
+    let values: Vec<serde_json::Value> =
+        serde_json::from_slice(body).map_err(|e| format!("JSON dizi bekleniyor: {e}"))?;
+    if values.len() > MAX_SATIR {
+        return Err(format!("en fazla {MAX_SATIR} satır gönderilebilir"));
+    }
+
+    let mut out = Ayristirma::default();
+    for (i, v) in values.into_iter().enumerate() {
+        out.ekle(i + 1, serde_json::from_value(v).map_err(|e| e.to_string()));
+    }
+    Ok(out)
+}
+
+/// CSV gövdesi (başlıklı): santral_id,zaman_utc,guc_mw
+pub fn parse_csv(body: &[u8]) -> Result<Ayristirma, String> {
+    let mut rdr = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(body);
+
+    let headers = rdr.headers().map_err(|e| format!("CSV başlığı okunamadı: {e}"))?;
+    for kolon in ["santral_id", "zaman_utc", "guc_mw"] {
+        if !headers.iter().any(|h| h == kolon) {
+            return Err(format!("CSV başlığında '{kolon}' kolonu yok"));
+        }
+    }
+
+    let mut out = Ayristirma::default();
+    for (i, rec) in rdr.deserialize::<OlcumSatiri>().enumerate() {
+        if i >= MAX_SATIR {
+            return Err(format!("en fazla {MAX_SATIR} satır gönderilebilir"));
+        }
+        out.ekle(i + 1, rec.map_err(|e| e.to_string()));
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const S1: &str = "00000000-0000-0000-0000-000000000001";
+    const S2: &str = "00000000-0000-0000-0000-000000000002";
+
+    #[test]
+    fn json_gecerli_ve_hatali_satirlar() {
+        let body = format!(
+            r#"[
+                {{"santral_id": "{S1}", "zaman_utc": "2025-07-17T10:05:00Z", "guc_mw": 12.5}},
+                {{"santral_id": "{S1}", "zaman_utc": "dün", "guc_mw": 1}},
+                {{"santral_id": "{S2}", "zaman_utc": "2025-07-17T13:05:00+03:00", "guc_mw": -0.2}},
+                {{"santral_id": "{S2}", "zaman_utc": "2025-07-17T10:10:00Z"}}
+            ]"#
+        );
+        let a = parse_json(body.as_bytes()).unwrap();
+        assert_eq!(a.toplam(), 4);
+        assert_eq!(a.satirlar.iter().map(|(i, _)| *i).collect::<Vec<_>>(), [1, 3]);
+        assert_eq!(a.satirlar[1].1.zaman_utc.to_rfc3339(), "2025-07-17T10:05:00+00:00");
+        assert_eq!(a.satirlar[1].1.guc_mw, -0.2);
+        assert_eq!(a.hatalar.iter().map(|h| h.satir).collect::<Vec<_>>(), [2, 4]);
+    }
+
+    #[test]
+    fn json_dizi_degilse_istek_reddedilir() {
+        assert!(parse_json(br#"{"santral_id": "x"}"#).is_err());
+        assert!(parse_json(b"[1,").is_err());
+    }
+
+    #[test]
+    fn csv_kolon_sirasi_serbest_bosluklar_kirpilir() {
+        let body = format!(
+            "guc_mw, santral_id ,zaman_utc\n 3.25 ,{S1}, 2025-07-17T10:05:00Z\nabc,{S1},2025-07-17T10:10:00Z\n"
+        );
+        let a = parse_csv(body.as_bytes()).unwrap();
+        assert_eq!(a.satirlar.len(), 1);
+        assert_eq!(a.satirlar[0].0, 1);
+        assert_eq!(a.satirlar[0].1.guc_mw, 3.25);
+        assert_eq!(a.hatalar.len(), 1);
+        assert_eq!(a.hatalar[0].satir, 2);
+    }
+
+    #[test]
+    fn csv_eksik_kolon() {
+        let e = parse_csv(b"santral_id,zaman_utc\nx,y\n").unwrap_err();
+        assert!(e.contains("guc_mw"), "{e}");
+    }
+
+    #[test]
+    fn sayi_olmayan_guc_reddedilir() {
+        let body = format!("santral_id,zaman_utc,guc_mw\n{S1},2025-07-17T10:05:00Z,NaN\n");
+        let a = parse_csv(body.as_bytes()).unwrap();
+        assert!(a.satirlar.is_empty());
+        assert_eq!(a.hatalar[0].hata, "guc_mw sayı olmalı");
+    }
+
+    #[test]
+    fn tekrarda_son_satir_kalir_oncekiler_uyari() {
+        let body = format!(
+            "santral_id,zaman_utc,guc_mw\n\
+             {S1},2025-07-17T10:05:00Z,1\n\
+             {S2},2025-07-17T10:05:00Z,2\n\
+             {S1},2025-07-17T13:05:00+03:00,3\n\
+             {S1},bozuk,4\n\
+             {S1},2025-07-17T10:05:00Z,5\n"
+        );
+        let mut a = parse_csv(body.as_bytes()).unwrap();
+        let toplam = a.toplam();
+        a.tekrarlari_ayikla();
+
+        assert_eq!(toplam, 5);
+        let kalan: Vec<(usize, f64)> = a.satirlar.iter().map(|(i, o)| (*i, o.guc_mw)).collect();
+        assert_eq!(kalan, [(2, 2.0), (5, 5.0)]);
+        // yerine geçilen satırlar reddedilmiş sayılmaz
+        assert_eq!(a.hatalar.iter().map(|h| h.satir).collect::<Vec<_>>(), [4]);
+        assert_eq!(a.uyarilar.iter().map(|h| h.satir).collect::<Vec<_>>(), [1, 3]);
+    }
+}
@@ -0,0 +1,430 @@
+// backend/src/piyasa.rs
+//
+// Saatlik piyasa fiyatları (piyasa_fiyatlari).
+// - JSON dizi ya da CSV gövdesini satırlara ayırır (ölçüm aktarımıyla aynı düzen)
+// - Satır hataları toplanır; aynı saat istekte tekrar ederse son satır geçerlidir,
+//   yerine geçilen satırlar uyarı olarak döner
+// - Finansal hesaplar fiyatı teslim saatine göre `FiyatTablosu` ile bulur
+// - Eksik raporu beklenen saatleri piyasa gününe göre (Europe/Istanbul) sayar
+//
+// CSV başlığı: saat,ptf_tl[,smf_tl][,sistem_yonu]
+// saat RFC 3339 ve saat başı olmalı (ör. 2025-08-01T00:00:00+03:00).
+// smf_tl / sistem_yonu boş bırakılırsa kayıtlı değer korunur (gün öncesinde yalnızca PTF bilinir).
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::PiyasaFiyati;
+use crate::olcum::SatirHatasi;
+use crate::piyasa_gunu;
+
+/// Tek istekte kabul edilen en fazla satır (~5,7 yıl saatlik).
+pub const MAX_SATIR: usize = 50_000;
+
+/// Sistem yönü: enerji açığı (YAL talimatı) / fazlası (YAT talimatı).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SistemYonu {
+    EnerjiAcigi,
+    EnerjiFazlasi,
+    Dengede,
+}
+
+impl SistemYonu {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SistemYonu::EnerjiAcigi => "enerji_acigi",
+            SistemYonu::EnerjiFazlasi => "enerji_fazlasi",
+            SistemYonu::Dengede => "dengede",
+        }
+    }
+}
+
+/// Doğrulanmış fiyat satırı.
+#[derive(Debug, Clone, Deserialize)]
+pub struct FiyatSatiri {
+    pub saat: DateTime<Utc>,
+    pub ptf_tl: f64,
+    #[serde(default)]
+    pub smf_tl: Option<f64>,
+    #[serde(default)]
+    pub sistem_yonu: Option<SistemYonu>,
+}
+
+#[derive(Debug, Default)]
+pub struct Ayristirma {
+    pub satirlar: Vec<(usize, FiyatSatiri)>,
+    pub hatalar: Vec<SatirHatasi>,
+    pub uyarilar: Vec<SatirHatasi>,
+}
+
+impl Ayristirma {
+    pub fn toplam(&self) -> usize {
+        self.satirlar.len() + self.hatalar.len()
+    }
+
+    fn ekle(&mut self, satir: usize, sonuc: Result<FiyatSatiri, String>) {
+        match sonuc.and_then(dogrula) {
+            Ok(f) => self.satirlar.push((satir, f)),
+            Err(hata) => self.hatalar.push(SatirHatasi { satir, hata }),
+        }
+    }
+
+    /// Aynı saat birden fazla geldiyse sonuncusu kalır, öncekiler uyarıya taşınır.
+    pub fn tekrarlari_ayikla(&mut self) {
+        let mut son: HashMap<DateTime<Utc>, usize> = HashMap::new();
+        for (i, (_, f)) in self.satirlar.iter().enumerate() {
+            son.insert(f.saat, i);
+        }
+        let mut kalan = Vec::with_capacity(son.len());
+        for (i, (satir, f)) in std::mem::take(&mut self.satirlar).into_iter().enumerate() {
+            if son.get(&f.saat) == Some(&i) {
+                kalan.push((satir, f));
+            } else {
+                self.uyarilar.push(SatirHatasi {
+                    satir,
+                    hata: "aynı saat bu istekte tekrar ediyor; son satır kullanıldı".into(),
+                });
+            }
+        }
+        self.satirlar = kalan;
+    }
+}
+
+fn dogrula(f: FiyatSatiri) -> Result<FiyatSatiri, String> {
+    if f.saat.minute() != 0 || f.saat.second() != 0 || f.saat.nanosecond() != 0 {
+        return Err("saat, saat başı olmalı".into());
+    }
+    if !f.ptf_tl.is_finite() || f.ptf_tl < 0.0 {
+        return Err("ptf_tl negatif olmayan bir sayı olmalı".into());
+    }
+    if f.smf_tl.is_some_and(|s| !s.is_finite() || s < 0.0) {
+        return Err("smf_tl negatif olmayan bir sayı olmalı".into());
+    }
+    Ok(f)
+}
+
+/// JSON dizi gövdesi: [{ "saat": "...", "ptf_tl": 2500.0, "smf_tl": 2700.0, "sistem_yonu": "enerji_acigi" }, ...]
+pub fn parse_json(body: &[u8]) -> Result<Ayristirma, String> {
+    let values: Vec<serde_json::Value> =
+        serde_json::from_slice(body).map_err(|e| format!("JSON dizi bekleniyor: {e}"))?;
+    if values.len() > MAX_SATIR {
+        return Err(format!("en fazla {MAX_SATIR} satır gönderilebilir"));
+    }
+
+    let mut out = Ayristirma::default();
+    for (i, v) in values.into_iter().enumerate() {
+        out.ekle(i + 1, serde_json::from_value(v).map_err(|e| e.to_string()));
+    }
+    Ok(out)
+}
+
+/// CSV gövdesi (başlıklı): saat,ptf_tl[,smf_tl][,sistem_yonu]
+pub fn parse_csv(body: &[u8]) -> Result<Ayristirma, String> {
+    let mut rdr = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(body);
+
+    let headers = rdr.headers().map_err(|e| format!("CSV başlığı okunamadı: {e}"))?;
+    for kolon in ["saat", "ptf_tl"] {
+        if !headers.iter().any(|h| h == kolon) {
+            return Err(format!("CSV başlığında '{kolon}' kolonu yok"));
+        }
+    }
+
+    let mut out = Ayristirma::default();
+    for (i, rec) in rdr.deserialize::<FiyatSatiri>().enumerate() {
+        if i >= MAX_SATIR {
+            return Err(format!("en fazla {MAX_SATIR} satır gönderilebilir"));
+        }
+        out.ekle(i + 1, rec.map_err(|e| e.to_string()));
+    }
+    Ok(out)
+}
+
+/// API cevabı: fiyat satırı + yerel saat.
+#[derive(Debug, Serialize)]
+pub struct FiyatDetay {
+    pub saat: DateTime<Utc>,
+    pub saat_yerel: DateTime<FixedOffset>,
+    pub ptf_tl: f64,
+    pub smf_tl: Option<f64>,
+    pub sistem_yonu: Option<String>,
+    pub kaynak: String,
+    pub guncelleme_tarihi: DateTime<Utc>,
+}
+
+impl From<PiyasaFiyati> for FiyatDetay {
+    fn from(f: PiyasaFiyati) -> Self {
+        FiyatDetay {
+            saat_yerel: piyasa_gunu::yerel(f.saat),
+            saat: f.saat,
+            ptf_tl: f.ptf_tl,
+            smf_tl: f.smf_tl,
+            sistem_yonu: f.sistem_yonu,
+            kaynak: f.kaynak,
+            guncelleme_tarihi: f.guncelleme_tarihi,
+        }
+    }
+}
+
+/// Teslim saatine göre fiyat arama.
+#[derive(Debug, Default)]
+pub struct FiyatTablosu {
+    saatler: HashMap<DateTime<Utc>, PiyasaFiyati>,
+}
+
+impl FiyatTablosu {
+    pub fn new(fiyatlar: Vec<PiyasaFiyati>) -> Self {
+        FiyatTablosu {
+            saatler: fiyatlar.into_iter().map(|f| (f.saat, f)).collect(),
+        }
+    }
+
+    /// `ts`nin içinde bulunduğu saatin fiyatı (15 dk periyotlar da saatin fiyatını alır).
+    pub fn saat(&self, ts: DateTime<Utc>) -> Option<&PiyasaFiyati> {
+        let saat_basi = ts.with_minute(0)?.with_second(0)?.with_nanosecond(0)?;
+        self.saatler.get(&saat_basi)
+    }
+}
+
+/// Eksik saat aralığı [bas, bit).
+#[derive(Debug, Serialize)]
+pub struct EksikAralik {
+    pub bas: DateTime<Utc>,
+    pub bas_yerel: DateTime<FixedOffset>,
+    pub bit: DateTime<Utc>,
+    pub bit_yerel: DateTime<FixedOffset>,
+    pub saat_sayisi: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EksikRaporu {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub beklenen_saat: usize,
+    pub ptf_olan_saat: usize,
+    pub smf_olan_saat: usize,
+    pub sistem_yonu_olan_saat: usize,
+    /// Hiç fiyatı olmayan saatler.
+    pub ptf_eksik: Vec<EksikAralik>,
+    /// PTF var, SMF ya da sistem yönü henüz yok.
+    pub smf_eksik: Vec<EksikAralik>,
+}
+
+/// [start, end) teslim günleri için beklenen saatlere göre eksikleri aralıklara toplar.
+pub fn eksik_raporu(start: NaiveDate, end: NaiveDate, fiyatlar: &[PiyasaFiyati]) -> EksikRaporu {
+    let bas = piyasa_gunu::gun_baslangici(start);
+    let bit = piyasa_gunu::gun_baslangici(end);
+    let saatler: Vec<DateTime<Utc>> = std::iter::successors(Some(bas), |t| Some(*t + Duration::hours(1)))
+        .take_while(|t| *t < bit)
+        .collect();
+
+    let aralikta = fiyatlar.iter().filter(|f| f.saat >= bas && f.saat < bit);
+    let ptf: HashSet<DateTime<Utc>> = fiyatlar.iter().map(|f| f.saat).collect();
+    let smf: HashSet<DateTime<Utc>> = fiyatlar
+        .iter()
+        .filter(|f| f.smf_tl.is_some() && f.sistem_yonu.is_some())
+        .map(|f| f.saat)
+        .collect();
+
+    EksikRaporu {
+        start,
+        end,
+        beklenen_saat: saatler.len(),
+        ptf_olan_saat: saatler.iter().filter(|t| ptf.contains(t)).count(),
+        smf_olan_saat: aralikta.clone().filter(|f| f.smf_tl.is_some()).count(),
+        sistem_yonu_olan_saat: aralikta.filter(|f| f.sistem_yonu.is_some()).count(),
+        ptf_eksik: araliklar(&saatler, |t| !ptf.contains(t)),
+        smf_eksik: araliklar(&saatler, |t| ptf.contains(t) && !smf.contains(t)),
+    }
+}
+
+fn araliklar(saatler: &[DateTime<Utc>], eksik: impl Fn(&DateTime<Utc>) -> bool) -> Vec<EksikAralik> {
+    let mut out: Vec<EksikAralik> = Vec::new();
+    for t in saatler.iter().filter(|t| eksik(t)) {
+        let bit = *t + Duration::hours(1);
+        match out.last_mut() {
+            Some(a) if a.bit == *t => {
+                a.bit = bit;
+                a.bit_yerel = piyasa_gunu::yerel(bit);
+                a.saat_sayisi += 1;
+            }
+            _ => out.push(EksikAralik {
+                bas: *t,
+                bas_yerel: piyasa_gunu::yerel(*t),
+                bit,
+                bit_yerel: piyasa_gunu::yerel(bit),
+                saat_sayisi: 1,
+            }),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn t(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn gun(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    fn fiyat(saat: DateTime<Utc>, smf: Option<f64>, yon: Option<&str>) -> PiyasaFiyati {
+        PiyasaFiyati {
+            saat,
+            ptf_tl: 2500.0,
+            smf_tl: smf,
+            sistem_yonu: yon.map(Into::into),
+            kaynak: "manuel".into(),
+            guncelleyen_id: None,
+            guncelleme_tarihi: saat,
+        }
+    }
+
+    /// Teslim gününün tüm saatleri için tam fiyat.
+    fn tam_gun(g: &str) -> Vec<PiyasaFiyati> {
+        let bas = piyasa_gunu::gun_baslangici(gun(g));
+        (0..piyasa_gunu::saat_sayisi(gun(g)) as i64)
+            .map(|h| fiyat(bas + Duration::hours(h), Some(2700.0), Some("enerji_acigi")))
+            .collect()
+    }
+
+    #[test]
+    fn json_satirlari_ve_opsiyonel_alanlar() {
+        let body = br#"[
+            {"saat": "2025-08-01T00:00:00+03:00", "ptf_tl": 2500, "smf_tl": 2700.5, "sistem_yonu": "enerji_fazlasi"},
+            {"saat": "2025-08-01T01:00:00+03:00", "ptf_tl": 2400},
+            {"saat": "2025-08-01T02:00:00+03:00", "ptf_tl": 2400, "sistem_yonu": "yukari"},
+            {"saat": "2025-08-01T03:00:00+03:00", "ptf_tl": -1}
+        ]"#;
+        let a = parse_json(body).unwrap();
+        assert_eq!(a.toplam(), 4);
+        assert_eq!(a.satirlar.len(), 2);
+        let (_, ilk) = &a.satirlar[0];
+        assert_eq!(ilk.saat, t("2025-07-31T21:00:00Z"));
+        assert_eq!(ilk.smf_tl, Some(2700.5));
+        assert_eq!(ilk.sistem_yonu, Some(SistemYonu::EnerjiFazlasi));
+        let (_, ikinci) = &a.satirlar[1];
+        assert_eq!((ikinci.smf_tl, ikinci.sistem_yonu), (None, None));
+        assert_eq!(a.hatalar.iter().map(|h| h.satir).collect::<Vec<_>>(), [3, 4]);
+    }
+
+    #[test]
+    fn csv_bos_smf_korunur_sayilir() {
+        let body = b"saat,ptf_tl,smf_tl,sistem_yonu\n\
+            2025-08-01T00:00:00+03:00,2500,,\n\
+            2025-08-01T01:00:00+03:00,2500,2600,dengede\n";
+        let a = parse_csv(body).unwrap();
+        assert!(a.hatalar.is_empty(), "{:?}", a.hatalar);
+        assert_eq!(a.satirlar[0].1.smf_tl, None);
+        assert_eq!(a.satirlar[0].1.sistem_yonu, None);
+        assert_eq!(a.satirlar[1].1.sistem_yonu, Some(SistemYonu::Dengede));
+    }
+
+    #[test]
+    fn csv_zorunlu_kolonlar() {
+        assert!(parse_csv(b"saat,smf_tl\n2025-08-01T00:00:00Z,1\n").unwrap_err().contains("ptf_tl"));
+        assert!(parse_csv(b"ptf_tl\n1\n").unwrap_err().contains("saat"));
+    }
+
+    #[test]
+    fn saat_basi_olmayan_ve_negatif_fiyat_reddedilir() {
+        let body = b"saat,ptf_tl,smf_tl\n\
+            2025-08-01T00:30:00+03:00,2500,\n\
+            2025-08-01T00:00:01Z,2500,\n\
+            2025-08-01T00:00:00.5Z,2500,\n\
+            2025-08-01T01:00:00+03:00,2500,-3\n\
+            2025-08-01T02:00:00+03:00,2500,0\n";
+        let a = parse_csv(body).unwrap();
+        assert_eq!(a.hatalar.iter().map(|h| h.satir).collect::<Vec<_>>(), [1, 2, 3, 4]);
+        assert_eq!(a.hatalar[0].hata, "saat, saat başı olmalı");
+        assert_eq!(a.satirlar.len(), 1);
+    }
+
+    #[test]
+    fn yarim_saat_ofsetli_saat_basi_reddedilir() {
+        // +05:30'da yerel saat başı UTC'de :30'dur; kontrol UTC'ye göre
+        let a = parse_json(br#"[{"saat": "2025-08-01T05:00:00+05:30", "ptf_tl": 1}]"#).unwrap();
+        assert_eq!(a.hatalar.len(), 1);
+    }
+
+    #[test]
+    fn tekrarda_son_satir_kalir_oncekiler_uyari() {
+        let body = b"saat,ptf_tl\n\
+            2025-08-01T00:00:00+03:00,1\n\
+            2025-07-31T21:00:00Z,2\n\
+            2025-08-01T01:00:00+03:00,3\n";
+        let mut a = parse_csv(body).unwrap();
+        a.tekrarlari_ayikla();
+        let kalan: Vec<(usize, f64)> = a.satirlar.iter().map(|(i, f)| (*i, f.ptf_tl)).collect();
+        assert_eq!(kalan, [(2, 2.0), (3, 3.0)]);
+        assert!(a.hatalar.is_empty());
+        assert_eq!(a.uyarilar.iter().map(|h| h.satir).collect::<Vec<_>>(), [1]);
+    }
+
+    #[test]
+    fn fiyat_tablosu_saat_icindeki_zamani_saat_basina_esler() {
+        let tablo = FiyatTablosu::new(vec![fiyat(t("2025-08-01T09:00:00Z"), None, None)]);
+        for ts in ["2025-08-01T09:00:00Z", "2025-08-01T09:15:00Z", "2025-08-01T09:59:59.999Z"] {
+            assert_eq!(tablo.saat(t(ts)).map(|f| f.saat), Some(t("2025-08-01T09:00:00Z")), "{ts}");
+        }
+        assert!(tablo.saat(t("2025-08-01T10:00:00Z")).is_none());
+        assert!(tablo.saat(t("2025-08-01T08:59:59Z")).is_none());
+    }
+
+    #[test]
+    fn eksik_raporu_tam_gun() {
+        let r = eksik_raporu(gun("2025-08-01"), gun("2025-08-02"), &tam_gun("2025-08-01"));
+        assert_eq!(r.beklenen_saat, 24);
+        assert_eq!((r.ptf_olan_saat, r.smf_olan_saat, r.sistem_yonu_olan_saat), (24, 24, 24));
+        assert!(r.ptf_eksik.is_empty() && r.smf_eksik.is_empty());
+    }
+
+    #[test]
+    fn eksik_raporu_yerel_gun_sinirlari_ve_araliklar() {
+        let mut fiyatlar = tam_gun("2025-08-01");
+        // yerel 05:00-07:59 fiyatsız, 20:00 yalnızca PTF, 21:00 SMF var yön yok
+        fiyatlar.retain(|f| !(5..8).contains(&piyasa_gunu::yerel(f.saat).hour()));
+        for f in &mut fiyatlar {
+            match piyasa_gunu::yerel(f.saat).hour() {
+                20 => (f.smf_tl, f.sistem_yonu) = (None, None),
+                21 => f.sistem_yonu = None,
+                _ => {}
+            }
+        }
+        // aralık dışı fiyatlar sayılmaz
+        fiyatlar.extend(tam_gun("2025-08-02"));
+
+        let r = eksik_raporu(gun("2025-08-01"), gun("2025-08-02"), &fiyatlar);
+        assert_eq!(r.beklenen_saat, 24);
+        assert_eq!(r.ptf_olan_saat, 21);
+        assert_eq!(r.smf_olan_saat, 20);
+        assert_eq!(r.sistem_yonu_olan_saat, 19);
+
+        assert_eq!(r.ptf_eksik.len(), 1);
+        let a = &r.ptf_eksik[0];
+        assert_eq!((a.bas, a.bit, a.saat_sayisi), (t("2025-08-01T02:00:00Z"), t("2025-08-01T05:00:00Z"), 3));
+        assert_eq!(a.bas_yerel.to_rfc3339(), "2025-08-01T05:00:00+03:00");
+
+        assert_eq!(r.smf_eksik.len(), 1);
+        let a = &r.smf_eksik[0];
+        assert_eq!((a.bas_yerel.hour(), a.bit_yerel.hour(), a.saat_sayisi), (20, 22, 2));
+    }
+
+    #[test]
+    fn eksik_raporu_yaz_saati_gecis_gunu() {
+        // 2015-03-29 İstanbul'da 23 saat (03:00 → 04:00)
+        let r = eksik_raporu(gun("2015-03-29"), gun("2015-03-30"), &[]);
+        assert_eq!(r.beklenen_saat, 23);
+        assert_eq!(r.ptf_eksik.len(), 1);
+        assert_eq!(r.ptf_eksik[0].saat_sayisi, 23);
+        assert_eq!(r.ptf_eksik[0].bas, t("2015-03-28T22:00:00Z"));
+    }
+}
@@ -0,0 +1,45 @@
+// backend/src/piyasa_gunu.rs
+//
+// Türkiye elektrik piyasası teslim günü (Europe/Istanbul).
+// - KGÜP planı, sapma, fiyat gibi tüm gün bazlı eşleşmeler yerel gün üzerinden yapılır
+// - Veritabanında zaman damgaları UTC (timestamptz); SQL tarafında aynı bölge adı
+//   `AT TIME ZONE 'Europe/Istanbul'` ile kullanılır (PIYASA_TZ_ADI)
+// - Plan dizisindeki indeks yerel gün başından periyot sırasıdır (60 dk → yerel saat)
+
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+pub const PIYASA_TZ: Tz = chrono_tz::Europe::Istanbul;
+/// SQL sorgularında kullanılan bölge adı.
+pub const PIYASA_TZ_ADI: &str = "Europe/Istanbul";
+
+/// Teslim gününün başlangıcı (yerel 00:00) UTC olarak.
+pub fn gun_baslangici(gun: NaiveDate) -> DateTime<Utc> {
+    PIYASA_TZ
+        .from_local_datetime(&gun.and_time(NaiveTime::MIN))
+        .earliest()
+        .map(|t| t.with_timezone(&Utc))
+        // Yerel gece yarısı yoksa (DST atlaması) UTC gece yarısına düş
+        .unwrap_or_else(|| gun.and_time(NaiveTime::MIN).and_utc())
+}
+
+/// Teslim günündeki saat sayısı (DST geçiş günlerinde 23/25).
+pub fn saat_sayisi(gun: NaiveDate) -> usize {
+    let sonraki = gun.succ_opt().unwrap_or(gun);
+    (gun_baslangici(sonraki) - gun_baslangici(gun)).num_hours().max(0) as usize
+}
+
+/// Zaman damgasının ait olduğu teslim günü.
+pub fn piyasa_gunu(ts: DateTime<Utc>) -> NaiveDate {
+    ts.with_timezone(&PIYASA_TZ).date_naive()
+}
+
+/// Bugünün teslim günü.
+pub fn bugun() -> NaiveDate {
+    piyasa_gunu(Utc::now())
+}
+
+/// Yerel saat etiketi (ör. 2025-07-20T00:00:00+03:00).
+pub fn yerel(ts: DateTime<Utc>) -> DateTime<FixedOffset> {
+    ts.with_timezone(&PIYASA_TZ).fixed_offset()
+}
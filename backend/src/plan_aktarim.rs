@@ -0,0 +1,501 @@
+// backend/src/plan_aktarim.rs
+//
+// Portföy KGÜP planlarının CSV / XLSX dosyasından toplu aktarımı.
+// - Dosyayı santral/gün planlarına ayırır; satır hataları toplanır
+// - Sahiplik, doğrulama, kapı kapanışı ve mevcut planla karşılaştırma handler'da
+// - Hata yoksa tüm planlar tek transaction'da yazılır (ya hepsi ya hiçbiri)
+//
+// Geniş biçim (santral başına satır, periyot başına kolon):
+//   santral_id,plan_tarihi,1,2,...,24      (15 dk çözünürlükte 1..96)
+// Uzun biçim (periyot başına satır):
+//   santral_id,plan_tarihi,periyot,miktar_mwh
+// - Kolon adları büyük/küçük harf duyarsız; periyot numaraları 1 tabanlı
+// - plan_tarihi YYYY-MM-DD ya da GG.AA.YYYY (XLSX tarih hücresi de olur)
+// - CSV ayracı ',' ya da ';' (başlıktan anlaşılır); ';' ile ondalık virgül kabul edilir
+// - XLSX'te ilk çalışma sayfası okunur
+//
+// `satir` dosyadaki satır numarasıdır (başlık satırı 1).
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::Cursor;
+
+use calamine::{Data, Reader, Xlsx};
+use chrono::NaiveDate;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::olcum::SatirHatasi;
+
+/// Tek dosyada kabul edilen en fazla santral/gün planı.
+pub const MAX_PLAN: usize = 2_000;
+/// Tek dosyada kabul edilen en fazla satır (uzun biçim 96 × MAX_PLAN'a kadar).
+pub const MAX_SATIR: usize = 200_000;
+
+/// Dosyadan okunan tek santral/gün planı.
+#[derive(Debug, Clone)]
+pub struct AktarilanPlan {
+    pub satir: usize,
+    pub santral_id: Uuid,
+    pub plan_tarihi: NaiveDate,
+    pub degerler: Vec<f64>,
+}
+
+#[derive(Debug, Default)]
+pub struct Ayristirma {
+    pub planlar: Vec<AktarilanPlan>,
+    pub hatalar: Vec<SatirHatasi>,
+}
+
+/// Önizleme / aktarım sonucunda plan başına karşılaştırma.
+#[derive(Debug, Serialize)]
+pub struct PlanKarsilastirmasi {
+    pub satir: usize,
+    pub santral_id: Uuid,
+    pub plan_tarihi: NaiveDate,
+    /// yeni | degisiyor | ayni
+    pub durum: &'static str,
+    pub mevcut_revizyon_no: Option<i32>,
+    pub mevcut_toplam_mwh: Option<f64>,
+    pub yeni_toplam_mwh: f64,
+    pub degisen_periyot_sayisi: usize,
+    /// Aktarımdan sonra planın revizyon numarası (yalnızca yazıldıysa).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revizyon_no: Option<i32>,
+}
+
+/// Aktarım özeti (API cevabı).
+#[derive(Debug, Serialize)]
+pub struct PlanAktarimOzeti {
+    pub onizleme: bool,
+    pub yazildi: bool,
+    pub cozunurluk_dk: i32,
+    pub plan_sayisi: usize,
+    pub yeni: usize,
+    pub degisen: usize,
+    pub ayni: usize,
+    pub reddedilen: usize,
+    pub planlar: Vec<PlanKarsilastirmasi>,
+    pub hatalar: Vec<SatirHatasi>,
+}
+
+type Tablo = Vec<(usize, Vec<String>)>;
+
+pub fn parse_csv(body: &[u8]) -> Result<Ayristirma, String> {
+    let ilk_satir = body.split(|&b| b == b'\n').next().unwrap_or_default();
+    let noktali_virgul = ilk_satir.contains(&b';');
+    let mut rdr = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .delimiter(if noktali_virgul { b';' } else { b',' })
+        .from_reader(body);
+
+    let mut tablo = Tablo::new();
+    for (i, rec) in rdr.records().enumerate() {
+        if i > MAX_SATIR {
+            return Err(format!("en fazla {MAX_SATIR} satır gönderilebilir"));
+        }
+        let rec = rec.map_err(|e| format!("CSV okunamadı (satır {}): {e}", i + 1))?;
+        let hucreler = rec
+            .iter()
+            .map(|h| if noktali_virgul { h.replace(',', ".") } else { h.to_string() })
+            .collect();
+        tablo.push((i + 1, hucreler));
+    }
+    tablodan(tablo)
+}
+
+pub fn parse_xlsx(body: &[u8]) -> Result<Ayristirma, String> {
+    let mut kitap = Xlsx::new(Cursor::new(body)).map_err(|e| format!("XLSX okunamadı: {e}"))?;
+    let sayfa = kitap
+        .worksheet_range_at(0)
+        .ok_or("XLSX çalışma sayfası yok")?
+        .map_err(|e| format!("XLSX sayfası okunamadı: {e}"))?;
+    let ilk_satir = sayfa.start().map(|(r, _)| r as usize).unwrap_or(0);
+
+    let mut tablo = Tablo::new();
+    for (i, satir) in sayfa.rows().enumerate() {
+        if i > MAX_SATIR {
+            return Err(format!("en fazla {MAX_SATIR} satır gönderilebilir"));
+        }
+        tablo.push((ilk_satir + i + 1, satir.iter().map(hucre_metni).collect()));
+    }
+    tablodan(tablo)
+}
+
+fn hucre_metni(h: &Data) -> String {
+    match h {
+        Data::Empty => String::new(),
+        Data::String(s) | Data::DateTimeIso(s) | Data::DurationIso(s) => s.trim().to_string(),
+        Data::Float(f) => f.to_string(),
+        Data::Int(i) => i.to_string(),
+        Data::Bool(b) => b.to_string(),
+        Data::DateTime(d) => d
+            .as_datetime()
+            .map(|t| t.date().format("%Y-%m-%d").to_string())
+            .unwrap_or_default(),
+        Data::Error(e) => format!("#{e:?}"),
+    }
+}
+
+fn tarih_oku(s: &str) -> Result<NaiveDate, String> {
+    let s = s.get(..10).unwrap_or(s); // XLSX ISO tarih-saat → tarih
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(s, "%d.%m.%Y"))
+        .map_err(|_| format!("plan_tarihi okunamadı: '{s}'"))
+}
+
+fn sayi_oku(s: &str, ne: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(v) if v.is_finite() => Ok(v),
+        _ => Err(format!("{ne} sayı değil: '{s}'")),
+    }
+}
+
+fn tablodan(tablo: Tablo) -> Result<Ayristirma, String> {
+    let mut satirlar = tablo
+        .into_iter()
+        .filter(|(_, h)| h.iter().any(|x| !x.is_empty()));
+    let (_, baslik) = satirlar.next().ok_or("dosya boş")?;
+    let baslik: Vec<String> = baslik.iter().map(|h| h.trim().to_lowercase()).collect();
+    let kolon = |ad: &str| baslik.iter().position(|h| h == ad);
+
+    let santral_k = kolon("santral_id").ok_or("başlıkta 'santral_id' kolonu yok")?;
+    let tarih_k = kolon("plan_tarihi").ok_or("başlıkta 'plan_tarihi' kolonu yok")?;
+    let satirlar: Tablo = satirlar.collect();
+
+    let mut out = match (kolon("periyot"), kolon("miktar_mwh")) {
+        (Some(periyot_k), Some(miktar_k)) => uzun(satirlar, santral_k, tarih_k, periyot_k, miktar_k),
+        (None, None) => {
+            let mut periyotlar: Vec<(usize, usize)> = Vec::new();
+            for (k, h) in baslik.iter().enumerate() {
+                if k == santral_k || k == tarih_k || h.is_empty() {
+                    continue;
+                }
+                match h.parse::<usize>() {
+                    Ok(no) if no >= 1 => periyotlar.push((no, k)),
+                    _ => return Err(format!("tanınmayan kolon: '{h}' (periyot kolonları 1, 2, ...)")),
+                }
+            }
+            periyotlar.sort();
+            if periyotlar.is_empty() {
+                return Err("periyot kolonu yok (1, 2, ... ya da periyot + miktar_mwh)".into());
+            }
+            if periyotlar.iter().enumerate().any(|(i, (no, _))| *no != i + 1) {
+                return Err("periyot kolonları 1'den başlayıp ardışık olmalı".into());
+            }
+            genis(satirlar, santral_k, tarih_k, &periyotlar)
+        }
+        _ => return Err("uzun biçimde 'periyot' ve 'miktar_mwh' kolonları birlikte olmalı".into()),
+    }?;
+
+    // Aynı santral/gün birden fazla kez geldiyse hepsi reddedilir (hangisinin geçerli olduğu belirsiz)
+    let mut sayac: HashMap<(Uuid, NaiveDate), usize> = HashMap::new();
+    for p in &out.planlar {
+        *sayac.entry((p.santral_id, p.plan_tarihi)).or_default() += 1;
+    }
+    let (planlar, tekrarlar): (Vec<_>, Vec<_>) = std::mem::take(&mut out.planlar)
+        .into_iter()
+        .partition(|p| sayac[&(p.santral_id, p.plan_tarihi)] == 1);
+    out.planlar = planlar;
+    out.hatalar.extend(tekrarlar.into_iter().map(|p| SatirHatasi {
+        satir: p.satir,
+        hata: format!("{} / {} dosyada birden fazla kez var", p.santral_id, p.plan_tarihi),
+    }));
+
+    if out.planlar.len() > MAX_PLAN {
+        return Err(format!("en fazla {MAX_PLAN} plan aktarılabilir"));
+    }
+    out.hatalar.sort_by_key(|h| h.satir);
+    Ok(out)
+}
+
+fn hucre(h: &[String], k: usize) -> &str {
+    h.get(k).map(String::as_str).unwrap_or("")
+}
+
+fn anahtar(h: &[String], santral_k: usize, tarih_k: usize) -> Result<(Uuid, NaiveDate), String> {
+    let santral_id = hucre(h, santral_k)
+        .parse::<Uuid>()
+        .map_err(|_| format!("santral_id geçersiz: '{}'", hucre(h, santral_k)))?;
+    Ok((santral_id, tarih_oku(hucre(h, tarih_k))?))
+}
+
+fn genis(
+    satirlar: Tablo,
+    santral_k: usize,
+    tarih_k: usize,
+    periyotlar: &[(usize, usize)],
+) -> Result<Ayristirma, String> {
+    let mut out = Ayristirma::default();
+    for (satir, h) in satirlar {
+        let sonuc = anahtar(&h, santral_k, tarih_k).and_then(|(santral_id, plan_tarihi)| {
+            // Sondaki boş kolonlar (ör. 25. saat) yok sayılır; aradaki boşluk hata
+            let dolu = periyotlar
+                .iter()
+                .rposition(|(_, k)| !hucre(&h, *k).is_empty())
+                .map_or(0, |i| i + 1);
+            let degerler = periyotlar[..dolu]
+                .iter()
+                .map(|(no, k)| match hucre(&h, *k) {
+                    "" => Err(format!("periyot {no} boş")),
+                    s => sayi_oku(s, &format!("periyot {no}")),
+                })
+                .collect::<Result<Vec<f64>, String>>()?;
+            Ok(AktarilanPlan { satir, santral_id, plan_tarihi, degerler })
+        });
+        match sonuc {
+            Ok(p) => out.planlar.push(p),
+            Err(hata) => out.hatalar.push(SatirHatasi { satir, hata }),
+        }
+    }
+    Ok(out)
+}
+
+/// Uzun biçimde bir santral/günün satırları.
+#[derive(Default)]
+struct UzunGrup {
+    ilk_satir: usize,
+    degerler: BTreeMap<usize, f64>,
+    tekrar: bool,
+}
+
+fn uzun(
+    satirlar: Tablo,
+    santral_k: usize,
+    tarih_k: usize,
+    periyot_k: usize,
+    miktar_k: usize,
+) -> Result<Ayristirma, String> {
+    let mut out = Ayristirma::default();
+    // Dosya sırası korunur
+    let mut sira: Vec<(Uuid, NaiveDate)> = Vec::new();
+    let mut gruplar: HashMap<(Uuid, NaiveDate), UzunGrup> = HashMap::new();
+
+    for (satir, h) in satirlar {
+        let sonuc = anahtar(&h, santral_k, tarih_k).and_then(|a| {
+            let no = match hucre(&h, periyot_k).parse::<usize>() {
+                Ok(no) if no >= 1 => no,
+                _ => return Err(format!("periyot geçersiz: '{}'", hucre(&h, periyot_k))),
+            };
+            Ok((a, no, sayi_oku(hucre(&h, miktar_k), "miktar_mwh")?))
+        });
+        let (a, no, deger) = match sonuc {
+            Ok(r) => r,
+            Err(hata) => {
+                out.hatalar.push(SatirHatasi { satir, hata });
+                continue;
+            }
+        };
+        let grup = gruplar.entry(a).or_insert_with(|| {
+            sira.push(a);
+            UzunGrup { ilk_satir: satir, ..Default::default() }
+        });
+        if grup.degerler.insert(no, deger).is_some() {
+            out.hatalar.push(SatirHatasi { satir, hata: format!("periyot {no} tekrar ediyor") });
+            grup.tekrar = true;
+        }
+    }
+
+    for a in sira {
+        let UzunGrup { ilk_satir: satir, degerler, tekrar } = gruplar.remove(&a).unwrap_or_default();
+        if tekrar {
+            continue; // hata tekrar eden satırda raporlandı
+        }
+        if let Some(eksik) = (1..=degerler.len()).find(|no| !degerler.contains_key(no)) {
+            out.hatalar.push(SatirHatasi {
+                satir,
+                hata: format!("{} / {}: periyot {eksik} eksik", a.0, a.1),
+            });
+            continue;
+        }
+        out.planlar.push(AktarilanPlan {
+            satir,
+            santral_id: a.0,
+            plan_tarihi: a.1,
+            degerler: degerler.into_values().collect(),
+        });
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const S1: &str = "11111111-1111-1111-1111-111111111111";
+    const S2: &str = "22222222-2222-2222-2222-222222222222";
+
+    fn tablo(satirlar: &[&str]) -> Tablo {
+        satirlar
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (i + 1, s.split(',').map(str::to_string).collect()))
+            .collect()
+    }
+
+    fn gun() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 8, 2).unwrap()
+    }
+
+    #[test]
+    fn genis_bicim() {
+        let a = tablodan(tablo(&[
+            "Santral_ID,PLAN_TARIHI,1,2,3",
+            &format!("{S1},2025-08-02,1.5,2,0"),
+            &format!("{S2},02.08.2025,3,4,5"),
+        ]))
+        .unwrap();
+        assert!(a.hatalar.is_empty(), "{:?}", a.hatalar);
+        assert_eq!(a.planlar.len(), 2);
+        assert_eq!(a.planlar[0].satir, 2);
+        assert_eq!(a.planlar[0].santral_id, S1.parse::<Uuid>().unwrap());
+        assert_eq!(a.planlar[0].plan_tarihi, gun());
+        assert_eq!(a.planlar[0].degerler, vec![1.5, 2.0, 0.0]);
+        assert_eq!(a.planlar[1].plan_tarihi, gun());
+        assert_eq!(a.planlar[1].degerler, vec![3.0, 4.0, 5.0]);
+    }
+
+    #[test]
+    fn genis_kolon_sirasi_serbest() {
+        let a = tablodan(tablo(&["2,plan_tarihi,1,santral_id", &format!("20,2025-08-02,10,{S1}")])).unwrap();
+        assert_eq!(a.planlar[0].degerler, vec![10.0, 20.0]);
+    }
+
+    #[test]
+    fn genis_satir_hatalari_toplanir() {
+        let a = tablodan(tablo(&[
+            "santral_id,plan_tarihi,1,2,3",
+            "yok,2025-08-02,1,2,3",
+            &format!("{S1},2025/08/02,1,2,3"),
+            &format!("{S1},2025-08-02,1,x,3"),
+            &format!("{S1},2025-08-03,1,,3"),
+            &format!("{S2},2025-08-02,1,2,3"),
+        ]))
+        .unwrap();
+        assert_eq!(a.planlar.len(), 1);
+        assert_eq!(a.planlar[0].satir, 6);
+        let satirlar: Vec<usize> = a.hatalar.iter().map(|h| h.satir).collect();
+        assert_eq!(satirlar, vec![2, 3, 4, 5]);
+        assert!(a.hatalar[0].hata.contains("santral_id geçersiz"));
+        assert!(a.hatalar[1].hata.contains("plan_tarihi okunamadı"));
+        assert!(a.hatalar[2].hata.contains("periyot 2 sayı değil"));
+        assert!(a.hatalar[3].hata.contains("periyot 2 boş"));
+    }
+
+    #[test]
+    fn genis_sondaki_bos_kolonlar_yok_sayilir() {
+        // 25. saat kolonu olan şablon, 24 saatlik günde boş bırakılır
+        let a = tablodan(tablo(&[
+            "santral_id,plan_tarihi,1,2,3,",
+            &format!("{S1},2025-08-02,1,2,,"),
+            &format!("{S2},2025-08-02,1,2,3,"),
+        ]))
+        .unwrap();
+        assert!(a.hatalar.is_empty(), "{:?}", a.hatalar);
+        assert_eq!(a.planlar[0].degerler, vec![1.0, 2.0]);
+        assert_eq!(a.planlar[1].degerler, vec![1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn genis_baslik_hatalari() {
+        let hata = |b: &str| tablodan(tablo(&[b, &format!("{S1},2025-08-02,1")])).unwrap_err();
+        assert!(hata("santral,plan_tarihi,1").contains("santral_id"));
+        assert!(hata("santral_id,tarih,1").contains("plan_tarihi"));
+        assert!(hata("santral_id,plan_tarihi,saat1").contains("tanınmayan kolon"));
+        assert!(hata("santral_id,plan_tarihi,0").contains("tanınmayan kolon"));
+        assert!(hata("santral_id,plan_tarihi,1,3").contains("ardışık"));
+        assert!(hata("santral_id,plan_tarihi").contains("periyot kolonu yok"));
+        assert!(hata("santral_id,plan_tarihi,periyot").contains("birlikte"));
+        assert_eq!(tablodan(tablo(&[",,"])).unwrap_err(), "dosya boş");
+    }
+
+    #[test]
+    fn uzun_bicim() {
+        let a = tablodan(tablo(&[
+            "santral_id,plan_tarihi,periyot,miktar_mwh",
+            &format!("{S2},2025-08-02,2,4"),
+            &format!("{S1},2025-08-02,2,2"),
+            &format!("{S2},2025-08-02,1,3"),
+            &format!("{S1},2025-08-02,1,1"),
+        ]))
+        .unwrap();
+        assert!(a.hatalar.is_empty(), "{:?}", a.hatalar);
+        // Dosya sırası korunur; satır grubun ilk satırıdır
+        assert_eq!(a.planlar.len(), 2);
+        assert_eq!(a.planlar[0].santral_id, S2.parse::<Uuid>().unwrap());
+        assert_eq!(a.planlar[0].satir, 2);
+        assert_eq!(a.planlar[0].degerler, vec![3.0, 4.0]);
+        assert_eq!(a.planlar[1].satir, 3);
+        assert_eq!(a.planlar[1].degerler, vec![1.0, 2.0]);
+    }
+
+    #[test]
+    fn uzun_hatalar() {
+        let a = tablodan(tablo(&[
+            "santral_id,plan_tarihi,periyot,miktar_mwh",
+            &format!("{S1},2025-08-02,1,1"),
+            &format!("{S1},2025-08-02,3,3"),
+            &format!("{S2},2025-08-02,1,1"),
+            &format!("{S2},2025-08-02,1,2"),
+            &format!("{S2},2025-08-03,0,1"),
+            &format!("{S2},2025-08-04,1,x"),
+        ]))
+        .unwrap();
+        assert!(a.planlar.is_empty(), "{:?}", a.planlar);
+        let hatalar: Vec<(usize, &str)> = a.hatalar.iter().map(|h| (h.satir, h.hata.as_str())).collect();
+        assert_eq!(hatalar.len(), 4, "{hatalar:?}");
+        assert_eq!(hatalar[0].0, 2);
+        assert!(hatalar[0].1.ends_with("periyot 2 eksik"));
+        assert_eq!(hatalar[1], (5, "periyot 1 tekrar ediyor"));
+        assert_eq!(hatalar[2], (6, "periyot geçersiz: '0'"));
+        assert_eq!(hatalar[3], (7, "miktar_mwh sayı değil: 'x'"));
+    }
+
+    #[test]
+    fn tekrar_eden_plan_reddedilir() {
+        let a = tablodan(tablo(&[
+            "santral_id,plan_tarihi,1,2",
+            &format!("{S1},2025-08-02,1,2"),
+            &format!("{S2},2025-08-02,1,2"),
+            &format!("{S1},02.08.2025,3,4"),
+        ]))
+        .unwrap();
+        // İkisi de reddedilir, diğer plan etkilenmez
+        assert_eq!(a.planlar.len(), 1);
+        assert_eq!(a.planlar[0].santral_id, S2.parse::<Uuid>().unwrap());
+        let satirlar: Vec<usize> = a.hatalar.iter().map(|h| h.satir).collect();
+        assert_eq!(satirlar, vec![2, 4]);
+        assert!(a.hatalar.iter().all(|h| h.hata.ends_with("dosyada birden fazla kez var")));
+    }
+
+    #[test]
+    fn csv_virgul() {
+        let body = format!("santral_id,plan_tarihi,1,2\n{S1},2025-08-02, 1.25 ,\"2\"\n");
+        let a = parse_csv(body.as_bytes()).unwrap();
+        assert!(a.hatalar.is_empty(), "{:?}", a.hatalar);
+        assert_eq!(a.planlar[0].satir, 2);
+        assert_eq!(a.planlar[0].degerler, vec![1.25, 2.0]);
+    }
+
+    #[test]
+    fn csv_noktali_virgul_ondalik_virgul() {
+        let body = format!("santral_id;plan_tarihi;1;2;3;\r\n{S1};02.08.2025;1,5;2,25;;\r\n");
+        let a = parse_csv(body.as_bytes()).unwrap();
+        assert!(a.hatalar.is_empty(), "{:?}", a.hatalar);
+        assert_eq!(a.planlar[0].plan_tarihi, gun());
+        assert_eq!(a.planlar[0].degerler, vec![1.5, 2.25]);
+
+        let body = format!("santral_id;plan_tarihi;periyot;miktar_mwh\n{S1};2025-08-02;1;0,75\n");
+        let a = parse_csv(body.as_bytes()).unwrap();
+        assert_eq!(a.planlar[0].degerler, vec![0.75]);
+    }
+
+    #[test]
+    fn csv_virgul_ayracinda_ondalik_virgul_kabul_edilmez() {
+        // ',' ayracında "1,5" iki hücredir; başlıkta karşılığı olmayan "5" okunmaz
+        let body = format!("santral_id,plan_tarihi,1\n{S1},2025-08-02,1,5\n");
+        let a = parse_csv(body.as_bytes()).unwrap();
+        assert_eq!(a.planlar[0].degerler, vec![1.0]);
+    }
+}
@@ -0,0 +1,202 @@
+// backend/src/pys.rs
+//
+// KGÜP planlarının PYS (Piyasa Yönetim Sistemi) yükleme dosyası biçimleri.
+//
+// Kimlikler:
+// - Santral `eic_kodu` (16 karakter, ENTSO-E EIC; son karakter kontrol hanesi)
+// - `uevcb_adi` (PYS'deki UEVÇB adı); boşsa santral adı kullanılır
+//
+// CSV (virgül ayraçlı, UTF-8, başlık satırlı):
+//   TARIH,SAAT,EIC,UEVCB,MIKTAR_MWH
+//   02.08.2025,00:00,40X000000000104H,GES-1,12.500
+// - TARIH gg.aa.yyyy teslim günü, SAAT periyodun yerel başlangıcı (SS:DD)
+// - Santral başına periyot sırasıyla bir satır; ondalık ayraç nokta, 3 hane
+//
+// XML:
+//   <KgupPlanlari teslimGunu="2025-08-02" cozunurlukDk="60">
+//     <Uevcb eic="..." ad="..." santralId="...">
+//       <Periyot no="1" baslangic="2025-08-02T00:00:00+03:00" miktarMwh="12.500"/>
+//     </Uevcb>
+//   </KgupPlanlari>
+//
+// Plan değerleri istenen çözünürlüğe `kgup::donustur` ile çevrilmiş olarak gelir.
+
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+use crate::kgup;
+use crate::piyasa_gunu;
+
+/// Dosyaya yazılacak tek santral planı (istenen çözünürlükte, eksiksiz).
+#[derive(Debug, Clone)]
+pub struct PysPlan {
+    pub santral_id: Uuid,
+    pub eic_kodu: String,
+    pub uevcb_adi: String,
+    pub degerler: Vec<f64>,
+}
+
+const EIC_KARAKTERLERI: &[u8; 37] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ-";
+
+/// 16 karakterlik EIC kodu biçimi ve kontrol hanesi (ENTSO-E ağırlıklı mod 37).
+/// Üçüncü karakter nesne tipidir (X, Y, Z, W, ...); harf olmalı.
+pub fn eic_gecerli(kod: &str) -> bool {
+    let b = kod.as_bytes();
+    if b.len() != 16 || !b[2].is_ascii_uppercase() {
+        return false;
+    }
+    let mut degerler = [0usize; 16];
+    for (i, c) in b.iter().enumerate() {
+        match EIC_KARAKTERLERI.iter().position(|x| x == c) {
+            Some(d) => degerler[i] = d,
+            None => return false,
+        }
+    }
+    // Kontrol hanesi '-' olamaz
+    if b[15] == b'-' {
+        return false;
+    }
+    let toplam: usize = degerler[..15].iter().enumerate().map(|(i, d)| d * (16 - i)).sum();
+    // 36 − ((toplam − 1) mod 37); toplam 0 olabilir, taşmasın diye −1 yerine +36
+    degerler[15] == 36 - ((toplam + 36) % 37)
+}
+
+fn miktar(mwh: f64) -> String {
+    format!("{mwh:.3}")
+}
+
+pub fn csv(gun: NaiveDate, cozunurluk_dk: i32, planlar: &[PysPlan]) -> Result<Vec<u8>, csv::Error> {
+    let mut w = csv::Writer::from_writer(Vec::new());
+    w.write_record(["TARIH", "SAAT", "EIC", "UEVCB", "MIKTAR_MWH"])?;
+    let tarih = gun.format("%d.%m.%Y").to_string();
+    for p in planlar {
+        for (i, v) in p.degerler.iter().enumerate() {
+            let saat = piyasa_gunu::yerel(kgup::periyot_baslangici(gun, cozunurluk_dk, i))
+                .format("%H:%M")
+                .to_string();
+            w.write_record([tarih.as_str(), &saat, &p.eic_kodu, &p.uevcb_adi, &miktar(*v)])?;
+        }
+    }
+    w.into_inner().map_err(|e| e.into_error().into())
+}
+
+fn xml_kacis(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+pub fn xml(gun: NaiveDate, cozunurluk_dk: i32, planlar: &[PysPlan]) -> String {
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str(&format!(
+        "<KgupPlanlari teslimGunu=\"{gun}\" cozunurlukDk=\"{cozunurluk_dk}\">\n"
+    ));
+    for p in planlar {
+        out.push_str(&format!(
+            "  <Uevcb eic=\"{}\" ad=\"{}\" santralId=\"{}\">\n",
+            xml_kacis(&p.eic_kodu),
+            xml_kacis(&p.uevcb_adi),
+            p.santral_id
+        ));
+        for (i, v) in p.degerler.iter().enumerate() {
+            let bas = piyasa_gunu::yerel(kgup::periyot_baslangici(gun, cozunurluk_dk, i));
+            out.push_str(&format!(
+                "    <Periyot no=\"{}\" baslangic=\"{}\" miktarMwh=\"{}\"/>\n",
+                i + 1,
+                bas.to_rfc3339(),
+                miktar(*v)
+            ));
+        }
+        out.push_str("  </Uevcb>\n");
+    }
+    out.push_str("</KgupPlanlari>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gun() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 8, 2).unwrap()
+    }
+
+    fn plan(ad: &str, degerler: Vec<f64>) -> PysPlan {
+        PysPlan {
+            santral_id: Uuid::nil(),
+            eic_kodu: "40X000000000104H".into(),
+            uevcb_adi: ad.into(),
+            degerler,
+        }
+    }
+
+    #[test]
+    fn eic_kontrol_hanesi() {
+        for kod in ["40X000000000104H", "10YTR-TEIAS----W", "10YDE-VE-------2", "10X1001A1001A094"] {
+            assert!(eic_gecerli(kod), "{kod}");
+        }
+        // yanlış kontrol hanesi
+        assert!(!eic_gecerli("40X000000000104G"));
+        assert!(!eic_gecerli("10YTR-TEIAS----X"));
+        // kontrol hanesi '-' olamaz
+        assert!(!eic_gecerli("40X00000000010--"));
+    }
+
+    #[test]
+    fn eic_bicimi() {
+        // toplamı 0 olan kod taşma yapmadan reddedilir (nesne tipi harf değil)
+        assert!(!eic_gecerli("0000000000000000"));
+        assert!(!eic_gecerli("40X000000000104"));
+        assert!(!eic_gecerli("40X000000000104HH"));
+        assert!(!eic_gecerli("40x000000000104H"));
+        assert!(!eic_gecerli("40X00000000010ÇH"));
+        assert!(!eic_gecerli(""));
+    }
+
+    #[test]
+    fn csv_saatlik() {
+        let degerler: Vec<f64> = (0..24).map(|i| i as f64 + 0.5).collect();
+        let out = String::from_utf8(csv(gun(), 60, &[plan("GES-1", degerler)]).unwrap()).unwrap();
+        let satirlar: Vec<&str> = out.lines().collect();
+        assert_eq!(satirlar.len(), 25);
+        assert_eq!(satirlar[0], "TARIH,SAAT,EIC,UEVCB,MIKTAR_MWH");
+        assert_eq!(satirlar[1], "02.08.2025,00:00,40X000000000104H,GES-1,0.500");
+        assert_eq!(satirlar[24], "02.08.2025,23:00,40X000000000104H,GES-1,23.500");
+    }
+
+    #[test]
+    fn csv_ceyrek_saat_ve_birden_fazla_santral() {
+        let planlar = [plan("GES-1", vec![1.0; 96]), plan("RES, Batı", vec![0.12345; 96])];
+        let out = String::from_utf8(csv(gun(), 15, &planlar).unwrap()).unwrap();
+        let satirlar: Vec<&str> = out.lines().collect();
+        assert_eq!(satirlar.len(), 1 + 2 * 96);
+        assert_eq!(satirlar[2], "02.08.2025,00:15,40X000000000104H,GES-1,1.000");
+        assert_eq!(satirlar[96], "02.08.2025,23:45,40X000000000104H,GES-1,1.000");
+        // virgül içeren ad tırnaklanır; miktar 3 haneye yuvarlanır
+        assert_eq!(satirlar[97], "02.08.2025,00:00,40X000000000104H,\"RES, Batı\",0.123");
+    }
+
+    #[test]
+    fn xml_periyotlar_ve_kacis() {
+        let out = xml(gun(), 60, &[plan("A&B <GES>", vec![2.0; 24])]);
+        assert!(out.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n"));
+        assert!(out.contains("<KgupPlanlari teslimGunu=\"2025-08-02\" cozunurlukDk=\"60\">"));
+        assert!(out.contains(&format!(
+            "<Uevcb eic=\"40X000000000104H\" ad=\"A&amp;B &lt;GES&gt;\" santralId=\"{}\">",
+            Uuid::nil()
+        )));
+        assert!(out.contains("<Periyot no=\"1\" baslangic=\"2025-08-02T00:00:00+03:00\" miktarMwh=\"2.000\"/>"));
+        assert!(out.contains("<Periyot no=\"24\" baslangic=\"2025-08-02T23:00:00+03:00\" miktarMwh=\"2.000\"/>"));
+        assert_eq!(out.matches("<Periyot ").count(), 24);
+        assert!(out.ends_with("  </Uevcb>\n</KgupPlanlari>\n"));
+    }
+}
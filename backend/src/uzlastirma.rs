@@ -0,0 +1,690 @@
+// backend/src/uzlastirma.rs
+//
+// Dengesizlik uzlaştırması (saf fonksiyonlar; DB / HTTP bağımlılığı yok).
+// - Dengesizlik = gerçekleşen − plan (MWh); pozitif: fazla üretim, negatif: eksik üretim
+// - Tolerans bandı: |sapma| ≤ tolerans_orani × plan kısmı PTF ile fiyatlanır
+// - Bant dışı kısım: pozitifte min(PTF, SMF) × (1 − k), negatifte max(PTF, SMF) × (1 + k).
+//   Katsayı fiyatın mutlak değeri üzerinden uygulanır; negatif fiyatlarda da ceza yönü korunur
+// - Tutar işaretlidir: pozitifte santrale ödenen (+), negatifte santralden alınan (−)
+// - Maliyet: sapmanın, üretimin PTF'den satılmasına göre kaybı = miktar × PTF − tutar.
+//   Kural gereği her zaman ≥ 0; dengede, bant içinde ve PTF = SMF (k = 0) iken 0
+//
+// KÜPST (Kesinleşmiş Üretim Planından Sapma Tutarı), dengesizlikten ayrı ve ek olarak:
+// - Cezalı miktar = max(|gerçekleşen − KGÜP| − tolerans × KGÜP, 0); iki yönde de uygulanır
+// - Birim fiyat = katsayı × baz fiyat (max(PTF, SMF) | PTF | SMF); negatif fiyatta 0
+//
+// Kural parametreleri dönemseldir (`dengesizlik_kurallari` sürümleri + santral atamaları);
+// `KuralTakvimi` bir teslim günü için geçerli sürümü çözer, böylece geçmiş dönemler
+// o gün geçerli kurallarla uzlaştırılır. Atama yoksa `standart` kodu kullanılır.
+//
+// Sapmayı fiyatlayan her uç (POST /api/hesapla/dengesizlik, GET /api/santral/{id}/dengesizlik,
+// GET /api/santral/{id}/kupst) bu modülü kullanır.
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::models::{DengesizlikKurali, DengesizlikOutput, SantralDengesizlikKurali};
+
+/// Atama yapılmamış santraller ve dönemler için kural kodu (migration ile gelir).
+pub const VARSAYILAN_KOD: &str = "standart";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Yon {
+    /// Fazla üretim (gerçekleşen > plan).
+    Pozitif,
+    /// Eksik üretim (gerçekleşen < plan).
+    Negatif,
+    Dengede,
+}
+
+impl Yon {
+    pub fn miktardan(miktar_mwh: f64) -> Yon {
+        if miktar_mwh > 0.0 {
+            Yon::Pozitif
+        } else if miktar_mwh < 0.0 {
+            Yon::Negatif
+        } else {
+            Yon::Dengede
+        }
+    }
+}
+
+/// Tek santral türü için çözülmüş fiyatlama parametreleri. `Default`: tolerans ve katsayı yok.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Kural {
+    /// Plana oranla PTF'den fiyatlanan sapma bandı (0..1).
+    pub tolerans_orani: f64,
+    /// Pozitif dengesizlik katsayısı: min(PTF, SMF) × (1 − k).
+    pub pozitif_k: f64,
+    /// Negatif dengesizlik katsayısı: max(PTF, SMF) × (1 + k).
+    pub negatif_k: f64,
+}
+
+impl Kural {
+    /// Yöne göre bant dışı birim fiyat (TL/MWh). Dengede tutar oluşmaz; PTF döner.
+    pub fn birim_fiyat(&self, yon: Yon, ptf_tl: f64, smf_tl: f64) -> f64 {
+        match yon {
+            Yon::Pozitif => {
+                let f = ptf_tl.min(smf_tl);
+                f - self.pozitif_k * f.abs()
+            }
+            Yon::Negatif => {
+                let f = ptf_tl.max(smf_tl);
+                f + self.negatif_k * f.abs()
+            }
+            Yon::Dengede => ptf_tl,
+        }
+    }
+
+    pub fn uzlastir(&self, plan_mwh: f64, gercek_mwh: f64, ptf_tl: f64, smf_tl: f64) -> Uzlasma {
+        let miktar_mwh = gercek_mwh - plan_mwh;
+        let yon = Yon::miktardan(miktar_mwh);
+        let bant = self.tolerans_orani * plan_mwh.abs();
+        let tolerans_ici_mwh = if bant > 0.0 { miktar_mwh.clamp(-bant, bant) } else { 0.0 };
+        let birim_fiyat_tl = self.birim_fiyat(yon, ptf_tl, smf_tl);
+        let tutar_tl = match yon {
+            Yon::Dengede => 0.0,
+            _ => tolerans_ici_mwh * ptf_tl + (miktar_mwh - tolerans_ici_mwh) * birim_fiyat_tl,
+        };
+        Uzlasma {
+            plan_mwh,
+            gercek_mwh,
+            ptf_tl,
+            smf_tl,
+            kural: *self,
+            miktar_mwh,
+            yon,
+            tolerans_ici_mwh,
+            birim_fiyat_tl,
+            tutar_tl,
+            maliyet_tl: miktar_mwh * ptf_tl - tutar_tl,
+        }
+    }
+}
+
+impl DengesizlikKurali {
+    /// Santral türüne göre parametreler; YEKDEM değeri NULL ise genel değer.
+    pub fn kural(&self, yekdem: bool) -> Kural {
+        let sec = |genel: f64, ozel: Option<f64>| if yekdem { ozel.unwrap_or(genel) } else { genel };
+        Kural {
+            tolerans_orani: sec(self.tolerans_orani, self.yekdem_tolerans_orani),
+            pozitif_k: sec(self.pozitif_k, self.yekdem_pozitif_k),
+            negatif_k: sec(self.negatif_k, self.yekdem_negatif_k),
+        }
+    }
+
+    /// KÜPST parametreleri; bilinmeyen baz fiyat (DB CHECK'e rağmen) varsayılana düşer.
+    pub fn kupst(&self, yekdem: bool) -> KupstKurali {
+        KupstKurali {
+            tolerans_orani: match (yekdem, self.yekdem_kupst_tolerans_orani) {
+                (true, Some(oran)) => oran,
+                _ => self.kupst_tolerans_orani,
+            },
+            katsayi: self.kupst_katsayi,
+            fiyat_bazi: FiyatBazi::parse(&self.kupst_fiyat_bazi).unwrap_or(FiyatBazi::MaxPtfSmf),
+        }
+    }
+}
+
+/// KÜPST birim fiyatının dayandığı fiyat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FiyatBazi {
+    MaxPtfSmf,
+    Ptf,
+    Smf,
+}
+
+impl FiyatBazi {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FiyatBazi::MaxPtfSmf => "max_ptf_smf",
+            FiyatBazi::Ptf => "ptf",
+            FiyatBazi::Smf => "smf",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<FiyatBazi> {
+        [FiyatBazi::MaxPtfSmf, FiyatBazi::Ptf, FiyatBazi::Smf]
+            .into_iter()
+            .find(|b| b.as_str() == s)
+    }
+
+    pub fn fiyat(&self, ptf_tl: f64, smf_tl: f64) -> f64 {
+        match self {
+            FiyatBazi::MaxPtfSmf => ptf_tl.max(smf_tl),
+            FiyatBazi::Ptf => ptf_tl,
+            FiyatBazi::Smf => smf_tl,
+        }
+    }
+}
+
+/// KÜPST parametreleri. `Default`: %5 tolerans, 0,03 × max(PTF, SMF) (migration varsayılanları).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KupstKurali {
+    /// KGÜP'e oranla cezasız sapma bandı (0..1).
+    pub tolerans_orani: f64,
+    pub katsayi: f64,
+    pub fiyat_bazi: FiyatBazi,
+}
+
+impl Default for KupstKurali {
+    fn default() -> Self {
+        Self { tolerans_orani: 0.05, katsayi: 0.03, fiyat_bazi: FiyatBazi::MaxPtfSmf }
+    }
+}
+
+/// Tek saatin KÜPST sonucu.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Kupst {
+    /// gerçekleşen − KGÜP (işaretli).
+    pub sapma_mwh: f64,
+    pub tolerans_mwh: f64,
+    pub cezali_mwh: f64,
+    pub birim_fiyat_tl: f64,
+    pub tutar_tl: f64,
+}
+
+impl KupstKurali {
+    pub fn hesapla(&self, plan_mwh: f64, gercek_mwh: f64, ptf_tl: f64, smf_tl: f64) -> Kupst {
+        let sapma_mwh = gercek_mwh - plan_mwh;
+        let tolerans_mwh = self.tolerans_orani * plan_mwh.abs();
+        let cezali_mwh = (sapma_mwh.abs() - tolerans_mwh).max(0.0);
+        let birim_fiyat_tl = (self.katsayi * self.fiyat_bazi.fiyat(ptf_tl, smf_tl)).max(0.0);
+        Kupst { sapma_mwh, tolerans_mwh, cezali_mwh, birim_fiyat_tl, tutar_tl: cezali_mwh * birim_fiyat_tl }
+    }
+}
+
+/// Tek periyodun uzlaştırma sonucu.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Uzlasma {
+    pub plan_mwh: f64,
+    pub gercek_mwh: f64,
+    pub ptf_tl: f64,
+    pub smf_tl: f64,
+    pub kural: Kural,
+    pub miktar_mwh: f64,
+    pub yon: Yon,
+    pub tolerans_ici_mwh: f64,
+    pub birim_fiyat_tl: f64,
+    pub tutar_tl: f64,
+    pub maliyet_tl: f64,
+}
+
+impl Uzlasma {
+    /// API cevabı (tip etiketi + açıklama metniyle); kural alanları boş.
+    pub fn cikti(&self) -> DengesizlikOutput {
+        let disi = (self.miktar_mwh - self.tolerans_ici_mwh).abs();
+        let (dengesizlik_tipi, mut aciklama) = match self.yon {
+            Yon::Pozitif => {
+                let fiyat = if self.kural.pozitif_k == 0.0 {
+                    format!("düşük olan {:.2} TL", self.birim_fiyat_tl)
+                } else {
+                    format!("düşük olanın %{:.1} eksiği {:.2} TL", self.kural.pozitif_k * 100.0, self.birim_fiyat_tl)
+                };
+                (
+                    "Pozitif Dengesizlik (Fazla Üretim)",
+                    format!("Sistem, fazla ürettiğiniz {disi:.2} MWh enerjiyi, {fiyat} fiyattan satın aldı."),
+                )
+            }
+            Yon::Negatif => {
+                let fiyat = if self.kural.negatif_k == 0.0 {
+                    format!("yüksek olan {:.2} TL", self.birim_fiyat_tl)
+                } else {
+                    format!("yüksek olanın %{:.1} fazlası {:.2} TL", self.kural.negatif_k * 100.0, self.birim_fiyat_tl)
+                };
+                (
+                    "Negatif Dengesizlik (Eksik Üretim)",
+                    format!("Sistem, eksik ürettiğiniz {disi:.2} MWh enerjiyi, {fiyat} fiyattan adınıza satın aldı."),
+                )
+            }
+            Yon::Dengede => ("Dengede", "Santral üretim tahmini ile tam dengededir.".to_string()),
+        };
+        if self.tolerans_ici_mwh != 0.0 {
+            let bant = format!(
+                "{:.2} MWh sapma tolerans içinde kaldığından PTF ({:.2} TL) ile uzlaştırıldı.",
+                self.tolerans_ici_mwh.abs(),
+                self.ptf_tl
+            );
+            aciklama = if disi == 0.0 { bant } else { format!("{bant} {aciklama}") };
+        }
+
+        DengesizlikOutput {
+            tahmini_uretim_mwh: self.plan_mwh,
+            gerceklesen_uretim_mwh: self.gercek_mwh,
+            ptf_tl: self.ptf_tl,
+            smf_tl: self.smf_tl,
+            dengesizlik_miktari_mwh: self.miktar_mwh,
+            dengesizlik_tipi: dengesizlik_tipi.to_string(),
+            dengesizlik_tutari_tl: self.tutar_tl,
+            tolerans_ici_mwh: self.tolerans_ici_mwh,
+            birim_fiyat_tl: self.birim_fiyat_tl,
+            kural_kodu: None,
+            kural_surumu: None,
+            aciklama,
+        }
+    }
+}
+
+/// Bir teslim günü için çözülmüş kural: sürüm kaydı + santral türüne göre parametreler.
+#[derive(Debug, Clone, Copy)]
+pub struct Cozum<'a> {
+    pub surum: &'a DengesizlikKurali,
+    pub kural: Kural,
+    pub kupst: KupstKurali,
+}
+
+impl Cozum<'_> {
+    pub fn uzlastir(&self, plan_mwh: f64, gercek_mwh: f64, ptf_tl: f64, smf_tl: f64) -> Uzlasma {
+        self.kural.uzlastir(plan_mwh, gercek_mwh, ptf_tl, smf_tl)
+    }
+
+    pub fn cikti(&self, u: &Uzlasma) -> DengesizlikOutput {
+        DengesizlikOutput {
+            kural_kodu: Some(self.surum.kod.clone()),
+            kural_surumu: Some(self.surum.surum),
+            ..u.cikti()
+        }
+    }
+}
+
+/// Dönemsel kural çözümü: önce santral ataması, sonra atanan kodun sürümü
+/// (ikisi de başlangıç günü ≤ teslim günü olan en son kayıt).
+#[derive(Debug, Clone)]
+pub struct KuralTakvimi {
+    surumler: Vec<DengesizlikKurali>,
+    /// (başlangıç, kural kodu, yekdem); başlangıca göre sıralı.
+    atamalar: Vec<(NaiveDate, String, bool)>,
+}
+
+impl KuralTakvimi {
+    pub fn new(mut surumler: Vec<DengesizlikKurali>, atamalar: &[SantralDengesizlikKurali]) -> Self {
+        surumler.sort_by(|a, b| (&a.kod, a.gecerlilik_baslangic).cmp(&(&b.kod, b.gecerlilik_baslangic)));
+        let mut atamalar: Vec<_> = atamalar
+            .iter()
+            .map(|a| (a.gecerlilik_baslangic, a.kural_kodu.clone(), a.yekdem))
+            .collect();
+        atamalar.sort_by_key(|a| a.0);
+        Self { surumler, atamalar }
+    }
+
+    /// Santralden bağımsız tek kod (ör. elle dengesizlik hesabı).
+    pub fn sabit(surumler: Vec<DengesizlikKurali>, kod: &str, yekdem: bool) -> Self {
+        let mut t = Self::new(surumler, &[]);
+        t.atamalar.push((NaiveDate::MIN, kod.to_string(), yekdem));
+        t
+    }
+
+    /// Teslim günü için geçerli kural; kod için o güne kadar başlamış sürüm yoksa None.
+    pub fn coz(&self, gun: NaiveDate) -> Option<Cozum<'_>> {
+        let (kod, yekdem) = self
+            .atamalar
+            .iter()
+            .rev()
+            .find(|a| a.0 <= gun)
+            .map_or((VARSAYILAN_KOD, false), |a| (a.1.as_str(), a.2));
+        let surum = self
+            .surumler
+            .iter()
+            .rev()
+            .find(|s| s.kod == kod && s.gecerlilik_baslangic <= gun)?;
+        Some(Cozum { surum, kural: surum.kural(yekdem), kupst: surum.kupst(yekdem) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use proptest::prelude::*;
+    use uuid::Uuid;
+
+    const EPS: f64 = 1e-6;
+
+    fn uzlastir(plan: f64, gercek: f64, ptf: f64, smf: f64) -> Uzlasma {
+        Kural::default().uzlastir(plan, gercek, ptf, smf)
+    }
+
+    fn gun(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    fn surum(kod: &str, surum: i32, bas: &str, tolerans: f64, k: f64, yekdem_tolerans: Option<f64>) -> DengesizlikKurali {
+        DengesizlikKurali {
+            id: Uuid::new_v4(),
+            kod: kod.to_string(),
+            surum,
+            ad: kod.to_string(),
+            gecerlilik_baslangic: gun(bas),
+            tolerans_orani: tolerans,
+            pozitif_k: k,
+            negatif_k: k,
+            yekdem_tolerans_orani: yekdem_tolerans,
+            yekdem_pozitif_k: None,
+            yekdem_negatif_k: None,
+            kupst_tolerans_orani: 0.05,
+            kupst_katsayi: 0.03,
+            kupst_fiyat_bazi: "max_ptf_smf".to_string(),
+            yekdem_kupst_tolerans_orani: yekdem_tolerans,
+            aciklama: None,
+            duzeltme_gerekcesi: None,
+            olusturan_id: None,
+            olusturma_tarihi: Utc::now(),
+        }
+    }
+
+    fn atama(bas: &str, kod: &str, yekdem: bool) -> SantralDengesizlikKurali {
+        SantralDengesizlikKurali {
+            santral_id: Uuid::nil(),
+            gecerlilik_baslangic: gun(bas),
+            kural_kodu: kod.to_string(),
+            yekdem,
+            duzeltme_gerekcesi: None,
+            guncelleyen_id: None,
+            guncelleme_tarihi: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn fazla_uretim_dusuk_fiyattan() {
+        let u = uzlastir(100.0, 110.0, 2500.0, 2800.0);
+        assert_eq!(u.yon, Yon::Pozitif);
+        assert_eq!(u.miktar_mwh, 10.0);
+        assert_eq!(u.birim_fiyat_tl, 2500.0);
+        assert_eq!(u.tutar_tl, 25_000.0);
+        assert_eq!(u.maliyet_tl, 0.0);
+
+        let u = uzlastir(100.0, 110.0, 2500.0, 2000.0);
+        assert_eq!(u.birim_fiyat_tl, 2000.0);
+        assert_eq!(u.tutar_tl, 20_000.0);
+        assert_eq!(u.maliyet_tl, 5_000.0);
+    }
+
+    #[test]
+    fn eksik_uretim_yuksek_fiyattan() {
+        let u = uzlastir(100.0, 90.0, 2500.0, 2800.0);
+        assert_eq!(u.yon, Yon::Negatif);
+        assert_eq!(u.miktar_mwh, -10.0);
+        assert_eq!(u.birim_fiyat_tl, 2800.0);
+        assert_eq!(u.tutar_tl, -28_000.0);
+        assert_eq!(u.maliyet_tl, 3_000.0);
+
+        let u = uzlastir(100.0, 90.0, 2500.0, 2000.0);
+        assert_eq!(u.birim_fiyat_tl, 2500.0);
+        assert_eq!(u.maliyet_tl, 0.0);
+    }
+
+    #[test]
+    fn dengede_tutar_yok() {
+        let u = uzlastir(50.0, 50.0, 2500.0, 9999.0);
+        assert_eq!(u.yon, Yon::Dengede);
+        assert_eq!(u.tutar_tl, 0.0);
+        assert_eq!(u.maliyet_tl, 0.0);
+        assert_eq!(u.cikti().dengesizlik_tipi, "Dengede");
+    }
+
+    #[test]
+    fn esit_fiyatta_maliyet_sifir() {
+        for gercek in [80.0, 100.0, 120.0] {
+            let u = uzlastir(100.0, gercek, 2500.0, 2500.0);
+            assert_eq!(u.birim_fiyat_tl, 2500.0);
+            assert_eq!(u.tutar_tl, (gercek - 100.0) * 2500.0);
+            assert_eq!(u.maliyet_tl, 0.0);
+        }
+    }
+
+    #[test]
+    fn negatif_fiyatlar() {
+        // Fazla üretim negatif fiyattan: santral öder
+        let u = uzlastir(10.0, 12.0, -100.0, 50.0);
+        assert_eq!(u.birim_fiyat_tl, -100.0);
+        assert_eq!(u.tutar_tl, -200.0);
+        assert_eq!(u.maliyet_tl, 0.0);
+
+        // Eksik üretim, ikisi de negatif: yüksek olan (sıfıra yakın) fiyat
+        let u = uzlastir(10.0, 8.0, -100.0, -40.0);
+        assert_eq!(u.birim_fiyat_tl, -40.0);
+        assert_eq!(u.tutar_tl, 80.0);
+        assert_eq!(u.maliyet_tl, 120.0);
+    }
+
+    #[test]
+    fn cikti_alanlari() {
+        let o = uzlastir(100.0, 90.0, 2500.0, 2800.0).cikti();
+        assert_eq!(o.tahmini_uretim_mwh, 100.0);
+        assert_eq!(o.gerceklesen_uretim_mwh, 90.0);
+        assert_eq!(o.dengesizlik_tipi, "Negatif Dengesizlik (Eksik Üretim)");
+        assert_eq!(o.dengesizlik_tutari_tl, -28_000.0);
+        assert_eq!(o.tolerans_ici_mwh, 0.0);
+        assert!(o.aciklama.contains("10.00 MWh"));
+    }
+
+    #[test]
+    fn tolerans_bandi_ptf_ile() {
+        let k = Kural { tolerans_orani: 0.1, ..Default::default() };
+
+        // 8 MWh sapma, bant 10 MWh: tamamı PTF
+        let u = k.uzlastir(100.0, 92.0, 2500.0, 2800.0);
+        assert_eq!(u.tolerans_ici_mwh, -8.0);
+        assert_eq!(u.tutar_tl, -20_000.0);
+        assert_eq!(u.maliyet_tl, 0.0);
+        assert!(u.cikti().aciklama.starts_with("8.00 MWh sapma tolerans içinde"));
+
+        // 15 MWh sapma: 10 MWh PTF, 5 MWh max(PTF, SMF)
+        let u = k.uzlastir(100.0, 85.0, 2500.0, 2800.0);
+        assert_eq!(u.tolerans_ici_mwh, -10.0);
+        assert_eq!(u.tutar_tl, -25_000.0 - 14_000.0);
+        assert_eq!(u.maliyet_tl, 1_500.0);
+        assert!(u.cikti().aciklama.contains("eksik ürettiğiniz 5.00 MWh"));
+    }
+
+    #[test]
+    fn katsayilar() {
+        let k = Kural { tolerans_orani: 0.0, pozitif_k: 0.03, negatif_k: 0.03 };
+
+        let u = k.uzlastir(100.0, 110.0, 2000.0, 2500.0);
+        assert!((u.birim_fiyat_tl - 1940.0).abs() < EPS);
+        assert!((u.maliyet_tl - 600.0).abs() < EPS);
+        assert!(u.cikti().aciklama.contains("%3.0 eksiği"));
+
+        let u = k.uzlastir(100.0, 90.0, 2000.0, 2500.0);
+        assert!((u.birim_fiyat_tl - 2575.0).abs() < EPS);
+        assert!((u.maliyet_tl - 5_750.0).abs() < EPS);
+
+        // Negatif fiyatta katsayı yine cezayı büyütür
+        let u = k.uzlastir(100.0, 110.0, -100.0, 50.0);
+        assert!((u.birim_fiyat_tl + 103.0).abs() < EPS);
+        assert!(u.maliyet_tl > 0.0);
+    }
+
+    #[test]
+    fn yekdem_parametreleri() {
+        let s = surum("epdk", 1, "2024-01-01", 0.05, 0.03, Some(0.2));
+        assert_eq!(s.kural(false).tolerans_orani, 0.05);
+        assert_eq!(s.kural(true).tolerans_orani, 0.2);
+        // NULL YEKDEM katsayısı → genel değer
+        assert_eq!(s.kural(true).pozitif_k, 0.03);
+    }
+
+    #[test]
+    fn takvim_surum_ve_atama() {
+        let surumler = vec![
+            surum("standart", 1, "2000-01-01", 0.0, 0.0, None),
+            surum("epdk", 2, "2025-03-01", 0.1, 0.05, None),
+            surum("epdk", 1, "2025-01-01", 0.0, 0.03, Some(0.1)),
+        ];
+        let t = KuralTakvimi::new(
+            surumler.clone(),
+            &[atama("2025-02-01", "epdk", true), atama("2024-12-01", "standart", false)],
+        );
+
+        // Atama öncesi: varsayılan kod
+        let c = t.coz(gun("2024-06-01")).unwrap();
+        assert_eq!((c.surum.kod.as_str(), c.surum.surum), ("standart", 1));
+
+        let c = t.coz(gun("2025-01-15")).unwrap();
+        assert_eq!(c.surum.kod, "standart");
+
+        // epdk ataması, v1 geçerli, YEKDEM toleransı
+        let c = t.coz(gun("2025-02-10")).unwrap();
+        assert_eq!((c.surum.kod.as_str(), c.surum.surum), ("epdk", 1));
+        assert_eq!(c.kural.tolerans_orani, 0.1);
+
+        // v2 başlangıcından itibaren
+        let c = t.coz(gun("2025-03-01")).unwrap();
+        assert_eq!(c.surum.surum, 2);
+        assert_eq!(c.kural.pozitif_k, 0.05);
+        assert_eq!(c.cikti(&c.uzlastir(1.0, 1.0, 1.0, 1.0)).kural_kodu.as_deref(), Some("epdk"));
+
+        // Kod için henüz başlamamış sürüm → None
+        let t = KuralTakvimi::sabit(surumler, "epdk", false);
+        assert!(t.coz(gun("2024-12-31")).is_none());
+        assert_eq!(t.coz(gun("2025-01-01")).unwrap().surum.surum, 1);
+    }
+
+    #[test]
+    fn kupst_tolerans_icinde_sifir() {
+        let k = KupstKurali::default();
+        for gercek in [95.0, 100.0, 105.0] {
+            let c = k.hesapla(100.0, gercek, 2500.0, 2800.0);
+            assert_eq!(c.cezali_mwh, 0.0);
+            assert_eq!(c.tutar_tl, 0.0);
+        }
+    }
+
+    #[test]
+    fn kupst_iki_yonde_bant_disi() {
+        let k = KupstKurali::default();
+
+        // Eksik üretim: 20 MWh sapma, 5 MWh bant → 15 MWh × 0,03 × 2800
+        let c = k.hesapla(100.0, 80.0, 2500.0, 2800.0);
+        assert_eq!(c.sapma_mwh, -20.0);
+        assert_eq!(c.cezali_mwh, 15.0);
+        assert!((c.birim_fiyat_tl - 84.0).abs() < EPS);
+        assert!((c.tutar_tl - 1_260.0).abs() < EPS);
+
+        // Fazla üretim aynı kuralla
+        let c = k.hesapla(100.0, 120.0, 2500.0, 2000.0);
+        assert_eq!(c.cezali_mwh, 15.0);
+        assert!((c.tutar_tl - 1_125.0).abs() < EPS);
+
+        // Plan yokken (0) sapmanın tamamı cezalı
+        let c = k.hesapla(0.0, 3.0, 2500.0, 2000.0);
+        assert_eq!(c.cezali_mwh, 3.0);
+    }
+
+    #[test]
+    fn kupst_fiyat_bazi_ve_negatif_fiyat() {
+        let ptf = KupstKurali { fiyat_bazi: FiyatBazi::Ptf, ..Default::default() };
+        assert!((ptf.hesapla(100.0, 80.0, 2500.0, 2800.0).birim_fiyat_tl - 75.0).abs() < EPS);
+        let smf = KupstKurali { fiyat_bazi: FiyatBazi::Smf, katsayi: 0.1, tolerans_orani: 0.0 };
+        assert!((smf.hesapla(100.0, 80.0, 2500.0, 2800.0).tutar_tl - 5_600.0).abs() < EPS);
+
+        let c = KupstKurali::default().hesapla(100.0, 80.0, -100.0, -50.0);
+        assert_eq!(c.birim_fiyat_tl, 0.0);
+        assert_eq!(c.tutar_tl, 0.0);
+
+        assert_eq!(FiyatBazi::parse("max_ptf_smf"), Some(FiyatBazi::MaxPtfSmf));
+        assert_eq!(FiyatBazi::parse("MAX"), None);
+    }
+
+    #[test]
+    fn kupst_yekdem_toleransi() {
+        let s = surum("epdk", 1, "2024-01-01", 0.0, 0.0, Some(0.2));
+        assert_eq!(s.kupst(false).tolerans_orani, 0.05);
+        assert_eq!(s.kupst(true).tolerans_orani, 0.2);
+        let s = surum("epdk", 1, "2024-01-01", 0.0, 0.0, None);
+        assert_eq!(s.kupst(true).tolerans_orani, 0.05);
+    }
+
+    fn miktar() -> impl Strategy<Value = f64> {
+        -1_000.0..1_000.0f64
+    }
+
+    fn fiyat() -> impl Strategy<Value = f64> {
+        -5_000.0..5_000.0f64
+    }
+
+    fn kural() -> impl Strategy<Value = Kural> {
+        (0.0..1.0f64, 0.0..1.0f64, 0.0..1.0f64)
+            .prop_map(|(tolerans_orani, pozitif_k, negatif_k)| Kural { tolerans_orani, pozitif_k, negatif_k })
+    }
+
+    fn kupst_kurali() -> impl Strategy<Value = KupstKurali> {
+        (0.0..1.0f64, 0.0..1.0f64, 0..3usize).prop_map(|(tolerans_orani, katsayi, b)| KupstKurali {
+            tolerans_orani,
+            katsayi,
+            fiyat_bazi: [FiyatBazi::MaxPtfSmf, FiyatBazi::Ptf, FiyatBazi::Smf][b],
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn maliyet_negatif_olmaz(k in kural(), p in miktar(), g in miktar(), ptf in fiyat(), smf in fiyat()) {
+            let u = k.uzlastir(p, g, ptf, smf);
+            prop_assert!(u.maliyet_tl >= -EPS * (1.0 + u.tutar_tl.abs()), "{u:?}");
+        }
+
+        #[test]
+        fn birim_fiyat_ptf_ya_da_smf(p in miktar(), g in miktar(), ptf in fiyat(), smf in fiyat()) {
+            let u = uzlastir(p, g, ptf, smf);
+            prop_assert!(u.birim_fiyat_tl == ptf || u.birim_fiyat_tl == smf);
+            match u.yon {
+                Yon::Pozitif => prop_assert!(u.birim_fiyat_tl <= ptf && u.birim_fiyat_tl <= smf),
+                Yon::Negatif => prop_assert!(u.birim_fiyat_tl >= ptf && u.birim_fiyat_tl >= smf),
+                Yon::Dengede => prop_assert_eq!(u.tutar_tl, 0.0),
+            }
+        }
+
+        #[test]
+        fn fiyat_sirasi_onemsiz(k in kural(), p in miktar(), g in miktar(), a in fiyat(), b in fiyat()) {
+            // min / max simetrik; tolerans bandı dışında PTF ile SMF yer değiştirince tutar değişmez
+            let k = Kural { tolerans_orani: 0.0, ..k };
+            let u = k.uzlastir(p, g, a, b);
+            let v = k.uzlastir(p, g, b, a);
+            prop_assert_eq!(u.tutar_tl, v.tutar_tl);
+        }
+
+        #[test]
+        fn pozitif_fiyatta_tutar_sapmayla_ayni_isaret(p in miktar(), g in miktar(), ptf in 0.01..5_000.0f64, smf in 0.01..5_000.0f64) {
+            let u = uzlastir(p, g, ptf, smf);
+            prop_assert_eq!(u.tutar_tl.signum() * u.miktar_mwh.abs().signum(), u.miktar_mwh.signum() * u.tutar_tl.abs().signum());
+            prop_assert!((u.tutar_tl - u.miktar_mwh * u.birim_fiyat_tl).abs() <= EPS * (1.0 + u.tutar_tl.abs()));
+        }
+
+        #[test]
+        fn tolerans_bandi_sinirli(k in kural(), p in miktar(), g in miktar(), ptf in fiyat(), smf in fiyat()) {
+            let u = k.uzlastir(p, g, ptf, smf);
+            prop_assert!(u.tolerans_ici_mwh.abs() <= k.tolerans_orani * p.abs() + EPS);
+            prop_assert!(u.tolerans_ici_mwh * u.miktar_mwh >= 0.0);
+            prop_assert!(u.tolerans_ici_mwh.abs() <= u.miktar_mwh.abs());
+        }
+
+        #[test]
+        fn tolerans_ve_katsayi_monoton(k in kural(), ek in 0.0..1.0f64, p in miktar(), g in miktar(), ptf in fiyat(), smf in fiyat()) {
+            // Bant genişledikçe maliyet artmaz; katsayı büyüdükçe azalmaz
+            let u = k.uzlastir(p, g, ptf, smf);
+            let genis = Kural { tolerans_orani: (k.tolerans_orani + ek).min(1.0), ..k };
+            prop_assert!(genis.uzlastir(p, g, ptf, smf).maliyet_tl <= u.maliyet_tl + EPS * (1.0 + u.maliyet_tl.abs()));
+            let sert = Kural { pozitif_k: k.pozitif_k + ek, negatif_k: k.negatif_k + ek, ..k };
+            prop_assert!(sert.uzlastir(p, g, ptf, smf).maliyet_tl >= u.maliyet_tl - EPS * (1.0 + u.maliyet_tl.abs()));
+        }
+
+        #[test]
+        fn kupst_sinirlari(k in kupst_kurali(), ek in 0.0..1.0f64, p in miktar(), g in miktar(), ptf in fiyat(), smf in fiyat()) {
+            let c = k.hesapla(p, g, ptf, smf);
+            prop_assert!(c.tutar_tl >= 0.0);
+            prop_assert!(c.cezali_mwh >= 0.0 && c.cezali_mwh <= c.sapma_mwh.abs());
+            // Sapma bant içindeyse ceza yok; bant genişledikçe tutar artmaz
+            if c.sapma_mwh.abs() <= c.tolerans_mwh {
+                prop_assert_eq!(c.tutar_tl, 0.0);
+            }
+            let genis = KupstKurali { tolerans_orani: k.tolerans_orani + ek, ..k };
+            prop_assert!(genis.hesapla(p, g, ptf, smf).tutar_tl <= c.tutar_tl + EPS);
+            // Yön simetrik: aynı büyüklükte fazla / eksik üretim aynı cezayı alır
+            let ters = k.hesapla(p, p - (g - p), ptf, smf);
+            prop_assert!((ters.tutar_tl - c.tutar_tl).abs() <= EPS * (1.0 + c.tutar_tl));
+        }
+    }
+}
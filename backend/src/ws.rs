@@ -25,7 +25,7 @@ use uuid::Uuid;
 use bigdecimal::ToPrimitive;
 use serde_json::json;
 
-use crate::auth::{self, AuthConfig};
+use crate::auth::{self, AuthConfig, Claims};
 use crate::db;
 use sqlx::PgPool;
 
@@ -36,14 +36,16 @@ const BROADCAST_INTERVAL: Duration   = Duration::from_secs(5); // üretim yayın
 
 pub struct UretimWs {
     pub musteri_id: Uuid,
+    oturum_id: Uuid,
     hb: Instant,
     pool: PgPool,
 }
 
 impl UretimWs {
-    pub fn new(musteri_id: Uuid, pool: PgPool) -> Self {
+    pub fn new(musteri_id: Uuid, oturum_id: Uuid, pool: PgPool) -> Self {
         Self {
             musteri_id,
+            oturum_id,
             hb: Instant::now(),
             pool,
         }
@@ -62,21 +64,35 @@ impl UretimWs {
     }
 
     /// Periyodik üretim yayını.
+    /// Her tick'te oturum da kontrol edilir; logout / pasifleştirme bağlantıyı keser.
     fn start_broadcast(&self, ctx: &mut ws::WebsocketContext<Self>) {
         ctx.run_interval(BROADCAST_INTERVAL, |act, ctx| {
             println!("[WS] broadcast tick -> mus={}", act.musteri_id);
 
             let pool = act.pool.clone();
             let mus  = act.musteri_id;
+            let sid  = act.oturum_id;
 
             // async DB çağrısını actor context'te future olarak sar
             ctx.spawn(
                 fut::wrap_future(async move {
-                    db::get_son_uretimler_by_musteri(&pool, mus).await
+                    match db::oturum_aktif(&pool, sid).await {
+                        Ok(true) => db::get_son_uretimler_by_musteri(&pool, mus).await.map(Some),
+                        Ok(false) => Ok(None),
+                        Err(e) => Err(e),
+                    }
                 })
                 .map(move |res, _act, ctx: &mut ws::WebsocketContext<UretimWs>| {
                     match res {
-                        Ok(rows) => {
+                        Ok(None) => {
+                            log::info!("[WS] mus={} oturum iptal edilmiş, kapanıyor.", mus);
+                            ctx.close(Some(ws::CloseReason {
+                                code: ws::CloseCode::Policy,
+                                description: Some("oturum sonlandırıldı".into()),
+                            }));
+                            ctx.stop();
+                        }
+                        Ok(Some(rows)) => {
                             println!("[WS] broadcast DB ok ({} rows) mus={}", rows.len(), mus);
 
                             let mut top_kurulu = 0f64;
@@ -171,9 +187,7 @@ impl StreamHandler<Result<WsMessage, ProtocolError>> for UretimWs {
 /// Query string'den ?token= al.
 fn extract_token_from_query(req: &HttpRequest) -> Option<String> {
     req.query_string().split('&').find_map(|kv| {
-        let mut it = kv.splitn(2, '=');
-        let k = it.next()?;
-        let v = it.next()?;
+        let (k, v) = kv.split_once('=')?;
         if k == "token" {
             Some(urlencoding::decode(v).ok()?.to_string())
         } else {
@@ -182,38 +196,28 @@ fn extract_token_from_query(req: &HttpRequest) -> Option<String> {
     })
 }
 
-/// Token'ı doğrula -> musteri_id dön.
-fn auth_from_req(req: &HttpRequest) -> Result<Uuid, String> {
+/// Token'ı doğrula -> claims dön.
+fn auth_from_req(req: &HttpRequest, cfg: &AuthConfig) -> Result<Claims, String> {
     // 1) query ?token=
     if let Some(tok) = extract_token_from_query(req) {
-        return decode_token_to_musteri(&tok);
+        return decode_token_to_claims(cfg, &tok);
     }
 
     // 2) Authorization: Bearer ...
-    if let Some(hv) = req.headers().get(actix_web::http::header::AUTHORIZATION) {
-        if let Ok(s) = hv.to_str() {
-            if let Some(rest) = s.strip_prefix("Bearer ") {
-                return decode_token_to_musteri(rest.trim());
-            }
-        }
+    if let Some(hv) = req.headers().get(actix_web::http::header::AUTHORIZATION)
+        && let Ok(s) = hv.to_str()
+        && let Some(rest) = s.strip_prefix("Bearer ")
+    {
+        return decode_token_to_claims(cfg, rest.trim());
     }
 
     Err("Token bulunamadı.".into())
 }
 
-/// JWT çöz → musteri_id.
-fn decode_token_to_musteri(token: &str) -> Result<Uuid, String> {
-    let secret = std::env::var("JWT_SECRET").map_err(|_| "JWT_SECRET env yok.".to_string())?;
-
-    // decode_jwt(cfg, token) -- secret lazım
-    let cfg = AuthConfig {
-        jwt_secret: secret,
-        jwt_exp_hours: 24, // decode için önemli değil
-    };
-
-    let token_data = auth::decode_jwt(&cfg, token).map_err(|e| format!("JWT hata: {e}"))?;
-    let claims = token_data.claims;
-    Ok(claims.mus) // mus zaten Uuid
+/// JWT çöz → claims (musteri_id + oturum_id).
+fn decode_token_to_claims(cfg: &AuthConfig, token: &str) -> Result<Claims, String> {
+    let token_data = auth::decode_jwt(cfg, token).map_err(|e| format!("JWT hata: {e}"))?;
+    Ok(token_data.claims)
 }
 
 /// WebSocket handshake handler.
@@ -222,17 +226,31 @@ pub async fn ws_uretim_route(
     req: HttpRequest,
     stream: web::Payload,
     pool: web::Data<PgPool>,
+    auth_cfg: web::Data<AuthConfig>,
 ) -> Result<HttpResponse, Error> {
     // token doğrula
-    let musteri_id = match auth_from_req(&req) {
-        Ok(id) => id,
+    let claims = match auth_from_req(&req, &auth_cfg) {
+        Ok(c) => c,
         Err(msg) => {
             println!("[WS] bağlantı reddedildi: {msg}");
             return Ok(HttpResponse::Unauthorized().body(msg));
         }
     };
 
+    // oturum iptal edilmiş mi?
+    match db::oturum_aktif(pool.get_ref(), claims.sid).await {
+        Ok(true) => {}
+        Ok(false) => {
+            log::info!("[WS] bağlantı reddedildi: oturum sonlandırılmış");
+            return Ok(HttpResponse::Unauthorized().body("Oturum sonlandırılmış."));
+        }
+        Err(e) => {
+            log::error!("[WS] oturum kontrol hata: {e}");
+            return Ok(HttpResponse::InternalServerError().finish());
+        }
+    }
+
     // actor başlat
-    let ws = UretimWs::new(musteri_id, pool.get_ref().clone());
+    let ws = UretimWs::new(claims.mus, claims.sid, pool.get_ref().clone());
     ws::start(ws, &req, stream)
 }
@@ -0,0 +1,303 @@
+// backend/src/yetki.rs
+//
+// Rol / yetki modeli.
+// - Rol: kullanıcıya atanan tek rol (DB'de `kullanicilar.rol`, JWT'de `rol`)
+// - Yetki: route'un istediği tekil izin
+// - Yetkili<Y>: handler imzasında yetkiyi beyan eden extractor
+//
+//   #[delete("/api/santral/{id}")]
+//   async fn sil(user: Yetkili<gerek::SantralSil>, ...) { ... }
+//
+// Yetki yoksa 403 + { "eksik_yetki": "santral_sil" } döner.
+//
+// IstemciYetkili<Y> aynı kontrolü API anahtarlarını da kabul ederek yapar
+// (bkz. auth_mw::Istemci).
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::Deref;
+
+use actix_web::{dev::Payload, error::InternalError, Error, FromRequest, HttpRequest, HttpResponse};
+use futures::future::LocalBoxFuture;
+use serde::{Deserialize, Serialize};
+
+use crate::auth_mw::{AuthenticatedUser, Istemci};
+
+/// Kullanıcı rolleri.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rol {
+    /// Platform yöneticisi — tüm müşteriler.
+    Admin,
+    /// Müşteri (portföy) yöneticisi — kendi müşterisinde her şey + kullanıcı yönetimi.
+    PortfoyAdmin,
+    /// KGÜP planı yazabilir.
+    Planlamaci,
+    /// Salt okunur analiz.
+    Analist,
+    /// Ölçüm aktaran makine hesabı.
+    VeriServisi,
+}
+
+/// Route bazlı tekil yetkiler.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Yetki {
+    SantralOku,
+    SantralYaz,
+    SantralSil,
+    PlanOku,
+    PlanYaz,
+    /// Kapı kapanışından sonra plan düzenleme (gerekçe zorunlu).
+    PlanKilitAs,
+    /// Plan revizyonunu onaylama ve PYS'ye gönderildi işaretleme.
+    PlanOnayla,
+    OlcumYaz,
+    /// Piyasa fiyatlarını (PTF/SMF) yükleme ve silme, dengesizlik kural sürümü ekleme;
+    /// fiyatlar ve kurallar tüm müşterilerce ortaktır.
+    FiyatYaz,
+    KullaniciYonet,
+    MusteriYonet,
+    ApiAnahtariYonet,
+    /// Müşteri sınırı olmadan tüm santrallere erişim.
+    TumMusteriler,
+}
+
+impl Rol {
+    pub const HEPSI: [Rol; 5] = [
+        Rol::Admin,
+        Rol::PortfoyAdmin,
+        Rol::Planlamaci,
+        Rol::Analist,
+        Rol::VeriServisi,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Rol::Admin => "admin",
+            Rol::PortfoyAdmin => "portfoy_admin",
+            Rol::Planlamaci => "planlamaci",
+            Rol::Analist => "analist",
+            Rol::VeriServisi => "veri_servisi",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Rol> {
+        Rol::HEPSI.into_iter().find(|r| r.as_str() == s)
+    }
+
+    /// Rolün sahip olduğu yetkiler.
+    pub fn yetkiler(&self) -> &'static [Yetki] {
+        use Yetki::*;
+        match self {
+            Rol::Admin => &[
+                SantralOku, SantralYaz, SantralSil, PlanOku, PlanYaz, PlanKilitAs, PlanOnayla,
+                OlcumYaz, FiyatYaz, KullaniciYonet, MusteriYonet, ApiAnahtariYonet, TumMusteriler,
+            ],
+            Rol::PortfoyAdmin => &[
+                SantralOku, SantralYaz, SantralSil, PlanOku, PlanYaz, PlanKilitAs, PlanOnayla,
+                OlcumYaz, KullaniciYonet, ApiAnahtariYonet,
+            ],
+            Rol::Planlamaci => &[SantralOku, PlanOku, PlanYaz],
+            Rol::Analist => &[SantralOku, PlanOku],
+            Rol::VeriServisi => &[SantralOku, OlcumYaz],
+        }
+    }
+
+    pub fn izinli(&self, yetki: Yetki) -> bool {
+        self.yetkiler().contains(&yetki)
+    }
+}
+
+impl fmt::Display for Rol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Yetki {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Yetki::SantralOku => "santral_oku",
+            Yetki::SantralYaz => "santral_yaz",
+            Yetki::SantralSil => "santral_sil",
+            Yetki::PlanOku => "plan_oku",
+            Yetki::PlanYaz => "plan_yaz",
+            Yetki::PlanKilitAs => "plan_kilit_as",
+            Yetki::PlanOnayla => "plan_onayla",
+            Yetki::OlcumYaz => "olcum_yaz",
+            Yetki::FiyatYaz => "fiyat_yaz",
+            Yetki::KullaniciYonet => "kullanici_yonet",
+            Yetki::MusteriYonet => "musteri_yonet",
+            Yetki::ApiAnahtariYonet => "api_anahtari_yonet",
+            Yetki::TumMusteriler => "tum_musteriler",
+        }
+    }
+}
+
+/// API anahtarı kapsamları.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiKapsam {
+    /// Yalnızca ölçüm aktarımı (SCADA gateway, script).
+    OlcumYaz,
+    /// Salt okunur analiz.
+    AnalizOku,
+}
+
+impl ApiKapsam {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiKapsam::OlcumYaz => "olcum_yaz",
+            ApiKapsam::AnalizOku => "analiz_oku",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<ApiKapsam> {
+        [ApiKapsam::OlcumYaz, ApiKapsam::AnalizOku]
+            .into_iter()
+            .find(|k| k.as_str() == s)
+    }
+
+    pub fn yetkiler(&self) -> &'static [Yetki] {
+        match self {
+            ApiKapsam::OlcumYaz => &[Yetki::OlcumYaz],
+            ApiKapsam::AnalizOku => &[Yetki::SantralOku, Yetki::PlanOku],
+        }
+    }
+}
+
+/// Eksik yetki için standart 403 cevabı.
+pub fn yetki_yok(yetki: Yetki) -> HttpResponse {
+    HttpResponse::Forbidden().json(serde_json::json!({
+        "status": "error",
+        "message": "Yetkin yok.",
+        "eksik_yetki": yetki,
+    }))
+}
+
+/// Route'un istediği yetkiyi tip seviyesinde taşır.
+pub trait YetkiGereksinimi {
+    const YETKI: Yetki;
+}
+
+/// `Yetkili<...>` için işaret tipleri.
+pub mod gerek {
+    use super::{Yetki, YetkiGereksinimi};
+
+    macro_rules! isaretler {
+        ($($ad:ident),* $(,)?) => {
+            $(
+                pub struct $ad;
+                impl YetkiGereksinimi for $ad {
+                    const YETKI: Yetki = Yetki::$ad;
+                }
+            )*
+        };
+    }
+
+    isaretler!(
+        SantralOku, SantralYaz, SantralSil, PlanOku, PlanYaz, PlanKilitAs, PlanOnayla, OlcumYaz,
+        FiyatYaz, KullaniciYonet, MusteriYonet, ApiAnahtariYonet,
+    );
+}
+
+/// Kimliği doğrulanmış ve `Y` yetkisine sahip kullanıcı.
+pub struct Yetkili<Y: YetkiGereksinimi> {
+    pub user: AuthenticatedUser,
+    _y: PhantomData<Y>,
+}
+
+impl<Y: YetkiGereksinimi> Deref for Yetkili<Y> {
+    type Target = AuthenticatedUser;
+
+    fn deref(&self) -> &Self::Target {
+        &self.user
+    }
+}
+
+impl<Y: YetkiGereksinimi + 'static> FromRequest for Yetkili<Y> {
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let fut = AuthenticatedUser::from_request(req, payload);
+        Box::pin(async move {
+            let user = fut.await?;
+            if !user.rol.izinli(Y::YETKI) {
+                return Err(InternalError::from_response(
+                    "yetki yok",
+                    yetki_yok(Y::YETKI),
+                )
+                .into());
+            }
+            Ok(Yetkili { user, _y: PhantomData })
+        })
+    }
+}
+
+/// Kullanıcı **veya** API anahtarıyla gelen, `Y` yetkisine sahip istemci.
+pub struct IstemciYetkili<Y: YetkiGereksinimi> {
+    pub istemci: Istemci,
+    _y: PhantomData<Y>,
+}
+
+impl<Y: YetkiGereksinimi> Deref for IstemciYetkili<Y> {
+    type Target = Istemci;
+
+    fn deref(&self) -> &Self::Target {
+        &self.istemci
+    }
+}
+
+impl<Y: YetkiGereksinimi + 'static> FromRequest for IstemciYetkili<Y> {
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let fut = Istemci::from_request(req, payload);
+        Box::pin(async move {
+            let istemci = fut.await?;
+            if !istemci.yetkili(Y::YETKI) {
+                return Err(InternalError::from_response(
+                    "yetki yok",
+                    yetki_yok(Y::YETKI),
+                )
+                .into());
+            }
+            Ok(IstemciYetkili { istemci, _y: PhantomData })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rol_yetki_matrisi() {
+        assert!(!Rol::Analist.izinli(Yetki::PlanYaz));
+        assert!(!Rol::Analist.izinli(Yetki::SantralSil));
+
+        assert!(Rol::Planlamaci.izinli(Yetki::PlanYaz));
+        assert!(!Rol::Planlamaci.izinli(Yetki::PlanOnayla));
+        assert!(!Rol::Planlamaci.izinli(Yetki::PlanKilitAs));
+
+        assert_eq!(Rol::VeriServisi.yetkiler(), [Yetki::SantralOku, Yetki::OlcumYaz]);
+
+        // fiyatlar ortak, müşteri sınırını yalnızca platform yöneticisi aşar
+        for rol in Rol::HEPSI {
+            for yetki in [Yetki::FiyatYaz, Yetki::TumMusteriler] {
+                assert_eq!(rol.izinli(yetki), rol == Rol::Admin, "{rol} {}", yetki.as_str());
+            }
+        }
+    }
+
+    #[test]
+    fn rol_parse() {
+        for rol in Rol::HEPSI {
+            assert_eq!(Rol::parse(rol.as_str()), Some(rol));
+        }
+        assert_eq!(Rol::parse("Admin"), None);
+    }
+}
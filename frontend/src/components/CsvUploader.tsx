@@ -31,11 +31,14 @@ export default function CsvUploader({ onTopluHesaplama }: Props) {
             skipEmptyLines: true,
             dynamicTyping: true,
             complete: async (results) => {
+                // Fiyat kolonları boşsa `saat` ile kayıtlı piyasa fiyatları kullanılır.
+                const fiyat = (v: any) => (typeof v === 'number' ? v : undefined);
                 const processedData: DengesizlikInput[] = results.data.map((row: any) => ({
                     tahmini_uretim_mwh: row.tahmini_uretim_mwh || 0,
                     gerceklesen_uretim_mwh: row.gerceklesen_uretim_mwh || 0,
-                    ptf_tl: row.ptf_tl || 0,
-                    smf_tl: row.smf_tl || 0,
+                    saat: typeof row.saat === 'string' && row.saat ? row.saat : undefined,
+                    ptf_tl: fiyat(row.ptf_tl),
+                    smf_tl: fiyat(row.smf_tl),
                 }));
                 
                 try {
@@ -55,7 +58,7 @@ export default function CsvUploader({ onTopluHesaplama }: Props) {
     return (
         <div className="p-6 bg-component-dark border border-border-dark rounded-lg">
             <h3 className="text-xl font-bold text-text-light mb-4">Saatlik Veri ile Toplu Analiz</h3>
-            <p className="text-sm text-text-dark mb-4">Saatlik üretim ve fiyat verilerinizi içeren bir CSV dosyası yükleyin. Başlıklar: `tahmini_uretim_mwh`, `gerceklesen_uretim_mwh`, `ptf_tl`, `smf_tl` olmalıdır.</p>
+            <p className="text-sm text-text-dark mb-4">Saatlik üretim ve fiyat verilerinizi içeren bir CSV dosyası yükleyin. Başlıklar: `tahmini_uretim_mwh`, `gerceklesen_uretim_mwh`, `ptf_tl`, `smf_tl` olmalıdır. Fiyatlar yerine `saat` (ör. 2025-08-01T14:00:00+03:00) verilirse kayıtlı PTF / SMF kullanılır.</p>
             <div className="flex items-center space-x-4">
                 <input type="file" accept=".csv" onChange={handleFileChange} className="block w-full text-sm text-text-dark file:mr-4 file:py-2 file:px-4 file:rounded-md file:border-0 file:text-sm file:font-semibold file:bg-base-dark file:text-brand-green hover:file:bg-brand-green/20"/>
                 <button onClick={handleFileUpload} disabled={!selectedFile || isLoading} className="whitespace-nowrap border border-brand-green text-brand-green font-bold py-2 px-4 rounded-md transition-all duration-300 hover:bg-brand-green hover:text-black disabled:opacity-50 disabled:cursor-not-allowed">
@@ -20,9 +20,20 @@ export default function DengesizlikForm({ onHesaplama }: Props) {
 
     const handleChange = (e: React.ChangeEvent<HTMLInputElement>) => {
         const { name, value } = e.target;
+        // Boş fiyat: teslim saatinin kayıtlı PTF / SMF'si kullanılır
+        if ((name === 'ptf_tl' || name === 'smf_tl') && value === '') {
+            setInput(prev => ({ ...prev, [name]: undefined }));
+            return;
+        }
         setInput(prev => ({ ...prev, [name]: parseFloat(value) || 0 }));
     };
 
+    // datetime-local → piyasa saati (Europe/Istanbul, UTC+3)
+    const handleSaatChange = (e: React.ChangeEvent<HTMLInputElement>) => {
+        const { value } = e.target;
+        setInput(prev => ({ ...prev, saat: value ? `${value}:00+03:00` : undefined }));
+    };
+
     // --- BU FONKSİYON GÜNCELLENDİ ---
     const handleSubmit = async (e: React.FormEvent) => {
         e.preventDefault();
@@ -57,13 +68,17 @@ export default function DengesizlikForm({ onHesaplama }: Props) {
                     <label htmlFor="gerceklesen_uretim_mwh" className="block text-sm font-medium text-text-dark">Gerçekleşen Üretim (MWh)</label>
                     <input type="number" step="any" name="gerceklesen_uretim_mwh" value={input.gerceklesen_uretim_mwh} onChange={handleChange} className="mt-1 block w-full bg-base-dark border-border-dark rounded-md text-text-light focus:ring-brand-green focus:border-brand-green"/>
                 </div>
+                <div className="md:col-span-2">
+                    <label htmlFor="saat" className="block text-sm font-medium text-text-dark">Teslim Saati (fiyat boşsa kayıtlı PTF / SMF kullanılır)</label>
+                    <input type="datetime-local" step={3600} name="saat" value={input.saat?.slice(0, 16) ?? ''} onChange={handleSaatChange} className="mt-1 block w-full bg-base-dark border-border-dark rounded-md text-text-light focus:ring-brand-green focus:border-brand-green"/>
+                </div>
                 <div>
                     <label htmlFor="ptf_tl" className="block text-sm font-medium text-text-dark">PTF (TL/MWh)</label>
-                    <input type="number" step="any" name="ptf_tl" value={input.ptf_tl} onChange={handleChange} className="mt-1 block w-full bg-base-dark border-border-dark rounded-md text-text-light focus:ring-brand-green focus:border-brand-green"/>
+                    <input type="number" step="any" name="ptf_tl" value={input.ptf_tl ?? ''} onChange={handleChange} className="mt-1 block w-full bg-base-dark border-border-dark rounded-md text-text-light focus:ring-brand-green focus:border-brand-green"/>
                 </div>
                 <div>
                     <label htmlFor="smf_tl" className="block text-sm font-medium text-text-dark">SMF (TL/MWh)</label>
-                    <input type="number" step="any" name="smf_tl" value={input.smf_tl} onChange={handleChange} className="mt-1 block w-full bg-base-dark border-border-dark rounded-md text-text-light focus:ring-brand-green focus:border-brand-green"/>
+                    <input type="number" step="any" name="smf_tl" value={input.smf_tl ?? ''} onChange={handleChange} className="mt-1 block w-full bg-base-dark border-border-dark rounded-md text-text-light focus:ring-brand-green focus:border-brand-green"/>
                 </div>
             </div>
             {/* DEĞİŞİKLİK 3: Butona 'disabled' özelliği ekliyoruz */}
@@ -1,13 +1,14 @@
 "use client";
 
 import React, { createContext, useCallback, useContext, useEffect, useState } from "react";
+import toast from "react-hot-toast";
 import type { AuthSession } from "@/types/auth";
-import { loadSession, saveSession, apiLogin } from "@/lib/api";
+import { loadSession, saveSession, apiLogin, apiLogout, SESSION_EVENT } from "@/lib/api";
 
 interface AuthState {
   session: AuthSession | null;
   login: (email: string, sifre: string) => Promise<boolean>;
-  logout: () => void;
+  logout: () => Promise<void>;
 }
 
 const AuthContext = createContext<AuthState | undefined>(undefined);
@@ -15,10 +16,13 @@ const AuthContext = createContext<AuthState | undefined>(undefined);
 export function AuthProvider({ children }: { children: React.ReactNode }) {
   const [session, setSession] = useState<AuthSession | null>(null);
 
-  // load once (browser)
+  // load once (browser), then follow token refreshes done by apiFetch
   useEffect(() => {
     const s = loadSession();
     if (s) setSession(s);
+    const sync = () => setSession(loadSession());
+    window.addEventListener(SESSION_EVENT, sync);
+    return () => window.removeEventListener(SESSION_EVENT, sync);
   }, []);
 
   const login = useCallback(async (email: string, sifre: string) => {
@@ -33,9 +37,17 @@ export function AuthProvider({ children }: { children: React.ReactNode }) {
     }
   }, []);
 
-  const logout = useCallback(() => {
+  const logout = useCallback(async () => {
+    const current = loadSession();
     setSession(null);
     saveSession(null);
+    try {
+      await apiLogout(current);
+    } catch (err) {
+      // local session is gone either way, but the server session may still be open
+      console.error("logout failed", err);
+      toast.error("Çıkış sunucuya iletilemedi; oturum sunucuda açık kalmış olabilir.");
+    }
   }, []);
 
   const value: AuthState = { session, login, logout };
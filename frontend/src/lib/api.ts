@@ -7,6 +7,9 @@ const API_BASE = process.env.NEXT_PUBLIC_API_URL ?? "http://127.0.0.1:8080";
  */
 const AUTH_KEY = "auth_session";
 
+/** Window event fired whenever the stored session changes (login, refresh, logout). */
+export const SESSION_EVENT = "auth_session_changed";
+
 /** Load session from localStorage (browser only). */
 export function loadSession(): AuthSession | null {
   if (typeof window === "undefined") return null;
@@ -27,11 +30,49 @@ export function saveSession(session: AuthSession | null) {
   } else {
     window.localStorage.removeItem(AUTH_KEY);
   }
+  window.dispatchEvent(new Event(SESSION_EVENT));
+}
+
+/** Single in-flight refresh: refresh tokens are one-time, parallel 401s must share it. */
+let refreshing: Promise<AuthSession | null> | null = null;
+
+/**
+ * Exchange the refresh token for a new access/refresh pair and store it.
+ * On failure the session is cleared (user has to log in again).
+ */
+export function refreshSession(session: AuthSession): Promise<AuthSession | null> {
+  if (!refreshing) {
+    refreshing = (async () => {
+      try {
+        const res = await fetch(`${API_BASE}/auth/refresh`, {
+          method: "POST",
+          headers: { "Content-Type": "application/json" },
+          body: JSON.stringify({ refresh_token: session.refresh_token }),
+        });
+        if (!res.ok) {
+          saveSession(null);
+          return null;
+        }
+        const next = toSession((await res.json()) as LoginResponse);
+        saveSession(next);
+        return next;
+      } catch {
+        return null;
+      } finally {
+        refreshing = null;
+      }
+    })();
+  }
+  return refreshing;
 }
 
 /**
  * Wrapper around fetch that automatically prefixes backend API URL
  * and attaches Authorization header if session present.
+ * Pass `session = null` to send the request without credentials.
+ *
+ * Access tokens are short-lived: on 401 the refresh token is used once
+ * and the request is retried with the new access token.
  */
 export async function apiFetch<T = unknown>(
   path: string,
@@ -40,14 +81,25 @@ export async function apiFetch<T = unknown>(
 ): Promise<T> {
   const url = path.startsWith("http") ? path : `${API_BASE}${path}`;
   const headers = new Headers(opts.headers ?? {});
-  const s = session ?? loadSession();
+  // Stored session wins: it carries the latest token after a refresh.
+  const s = session === null ? null : loadSession() ?? session;
   if (s?.token) {
     headers.set("Authorization", `Bearer ${s.token}`);
   }
   if (!headers.has("Content-Type") && !(opts.body instanceof FormData)) {
     headers.set("Content-Type", "application/json");
   }
-  const res = await fetch(url, { ...opts, headers });
+  let res = await fetch(url, { ...opts, headers });
+  if (res.status === 401 && s?.refresh_token) {
+    // another request may have rotated the tokens meanwhile; reusing the old
+    // refresh token would make the backend revoke the whole session
+    const latest = loadSession();
+    const next = latest && latest.token !== s.token ? latest : await refreshSession(s);
+    if (next) {
+      headers.set("Authorization", `Bearer ${next.token}`);
+      res = await fetch(url, { ...opts, headers });
+    }
+  }
   if (!res.ok) {
     // try to parse error json
     let msg = res.statusText;
@@ -65,17 +117,41 @@ export async function apiFetch<T = unknown>(
 /** Convenience: login */
 export interface LoginResponse {
   access_token: string;
+  refresh_token: string;
+  expires_in: number;
   user_id: string;
   musteri_id: string;
   rol: string;
 }
-export async function apiLogin(email: string, sifre: string): Promise<AuthSession> {
-  const body = JSON.stringify({ email, sifre });
-  const data = await apiFetch<LoginResponse>("/auth/login", { method: "POST", body }, null);
+
+function toSession(data: LoginResponse): AuthSession {
   return {
     token: data.access_token,
+    refresh_token: data.refresh_token,
     user_id: data.user_id,
     musteri_id: data.musteri_id,
     rol: data.rol as any,
   };
 }
+
+export async function apiLogin(email: string, sifre: string): Promise<AuthSession> {
+  const body = JSON.stringify({ email, sifre });
+  const data = await apiFetch<LoginResponse>("/auth/login", { method: "POST", body }, null);
+  return toSession(data);
+}
+
+/** Convenience: logout — revokes the server-side session (best effort). */
+export async function apiLogout(session: AuthSession | null): Promise<void> {
+  if (!session) return;
+  // plain fetch: an expired token must not trigger a refresh that resurrects the session.
+  // The refresh token in the body lets the backend revoke the session even then.
+  const res = await fetch(`${API_BASE}/auth/logout`, {
+    method: "POST",
+    headers: { "Content-Type": "application/json" },
+    body: JSON.stringify({ refresh_token: session.refresh_token }),
+  });
+  if (!res.ok) {
+    throw new Error(`Logout failed: ${res.status} ${await res.text()}`);
+  }
+}
+}
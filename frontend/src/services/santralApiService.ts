@@ -12,8 +12,10 @@ import { apiFetch } from '@/lib/api';
 export type DengesizlikInput = {
   tahmini_uretim_mwh: number;
   gerceklesen_uretim_mwh: number;
-  ptf_tl: number;
-  smf_tl: number;
+  /** Teslim saati (RFC 3339); fiyat verilmezse backend kayıtlı PTF / SMF'yi kullanır. */
+  saat?: string;
+  ptf_tl?: number;
+  smf_tl?: number;
 };
 
 export type DengesizlikOutput = {
@@ -24,6 +26,11 @@ export type DengesizlikOutput = {
   dengesizlik_miktari_mwh: number;
   dengesizlik_tipi: string;
   dengesizlik_tutari_tl: number;
+  /** Tolerans bandında kalıp PTF ile fiyatlanan sapma. */
+  tolerans_ici_mwh: number;
+  birim_fiyat_tl: number;
+  kural_kodu: string | null;
+  kural_surumu: number | null;
   aciklama: string;
 };
 
@@ -2,6 +2,7 @@ export type AuthRole = 'admin' | 'user';
 
 export interface AuthSession {
   token: string;
+  refresh_token: string;
   user_id: string;
   musteri_id: string;
   rol: AuthRole;